@@ -0,0 +1,268 @@
+//! User-defined command aliases from the `[aliases]` section of the sgit config,
+//! expanded before the command line is parsed.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use clap::CommandFactory;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Cli;
+use crate::config::Config;
+
+/// What an alias stands for: a command line as one string, or as separate words when
+/// an argument needs spaces without quoting.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum AliasValue {
+    Line(String),
+    Words(Vec<String>),
+}
+
+impl AliasValue {
+    pub fn words(&self) -> Result<Vec<String>> {
+        match self {
+            AliasValue::Line(line) => split_words(line),
+            AliasValue::Words(words) => Ok(words.clone()),
+        }
+    }
+
+    /// The expansion as it would be typed.
+    pub fn describe(&self) -> String {
+        match self {
+            AliasValue::Line(line) => line.clone(),
+            AliasValue::Words(words) => words
+                .iter()
+                .map(|word| {
+                    if word.contains(char::is_whitespace) {
+                        format!("\"{}\"", word)
+                    } else {
+                        word.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Whether `name` is one of sgit's own subcommands, which aliases cannot replace.
+pub fn is_builtin(name: &str) -> bool {
+    Cli::command().get_subcommands().any(|command| {
+        command.get_name() == name || command.get_all_aliases().any(|alias| alias == name)
+    }) || name == "help"
+}
+
+/// Where the subcommand is in `args`; global flags such as `--explain` may come first.
+pub fn subcommand_position(args: &[String]) -> Option<usize> {
+    args.iter()
+        .skip(1)
+        .position(|arg| !arg.starts_with('-'))
+        .map(|position| position + 1)
+}
+
+/// `args` (with the program name first) with the subcommand replaced by its alias
+/// expansion, repeatedly, so aliases can build on each other.
+pub fn expand_aliases(args: Vec<String>) -> Result<Vec<String>> {
+    let Some(position) = subcommand_position(&args) else {
+        return Ok(args);
+    };
+    if is_builtin(&args[position]) {
+        return Ok(args);
+    }
+    // With an unreadable config there are no aliases; clap reports the unknown command,
+    // and the next command that loads the config explains what is wrong with it.
+    let Ok(config) = Config::load() else {
+        return Ok(args);
+    };
+    expand_with(args, position, &config.aliases)
+}
+
+/// Expands the subcommand at `position` in `args` using `aliases`.
+fn expand_with(
+    mut args: Vec<String>,
+    position: usize,
+    aliases: &BTreeMap<String, AliasValue>,
+) -> Result<Vec<String>> {
+    let mut chain: Vec<String> = Vec::new();
+    while let Some(value) = aliases.get(&args[position]) {
+        let name = args[position].clone();
+        if chain.contains(&name) {
+            chain.push(name);
+            bail!(
+                "the alias '{}' never reaches a command: {}",
+                chain[0],
+                chain.join(" → ")
+            );
+        }
+        let words = value.words()?;
+        if words.is_empty() {
+            bail!(
+                "the alias '{}' is empty - give it a command in [aliases]",
+                name
+            );
+        }
+        chain.push(name);
+        args.splice(position..=position, words);
+        if is_builtin(&args[position]) {
+            break;
+        }
+    }
+    Ok(args)
+}
+
+/// Splits a command line into words the way a shell would for simple cases: on
+/// whitespace, with single or double quotes grouping words and `\` escaping a character.
+fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None | Some('"'), '\\') => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (_, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if let Some(open) = quote {
+        bail!("unclosed {} in the alias '{}'", open, line);
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn aliases(entries: &[(&str, &str)]) -> BTreeMap<String, AliasValue> {
+        entries
+            .iter()
+            .map(|(name, line)| (name.to_string(), AliasValue::Line(line.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(
+            split_words("  log\t--oneline   -5 ").unwrap(),
+            strings(&["log", "--oneline", "-5"])
+        );
+        assert!(split_words("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn quotes_group_words() {
+        assert_eq!(
+            split_words(r#"commit -m "fix the build" --author 'A B <a@b>'"#).unwrap(),
+            strings(&["commit", "-m", "fix the build", "--author", "A B <a@b>"])
+        );
+        assert_eq!(
+            split_words(r#"x"y z"w '' """#).unwrap(),
+            strings(&["xy zw", "", ""])
+        );
+    }
+
+    #[test]
+    fn backslashes_escape_outside_single_quotes() {
+        assert_eq!(
+            split_words(r#"a\ b "c\"d" 'e\f' g\\"#).unwrap(),
+            strings(&["a b", "c\"d", "e\\f", "g\\"])
+        );
+        // A trailing backslash has nothing to escape and is dropped.
+        assert_eq!(split_words(r"end\").unwrap(), strings(&["end"]));
+    }
+
+    #[test]
+    fn rejects_unclosed_quotes() {
+        let err = split_words(r#"commit -m "oops"#).unwrap_err();
+        assert!(err.to_string().contains("unclosed \""), "{}", err);
+        assert!(split_words("it's").is_err());
+    }
+
+    #[test]
+    fn describes_words_as_typed() {
+        let value = AliasValue::Words(strings(&["commit", "-m", "work in progress"]));
+        assert_eq!(value.describe(), r#"commit -m "work in progress""#);
+    }
+
+    #[test]
+    fn finds_the_subcommand_after_global_flags() {
+        assert_eq!(
+            subcommand_position(&strings(&["sgit", "--explain", "co", "-x"])),
+            Some(2)
+        );
+        assert_eq!(subcommand_position(&strings(&["sgit", "--version"])), None);
+    }
+
+    #[test]
+    fn expands_aliases_in_place() {
+        let aliases = aliases(&[("wip", "commit -m 'work in progress'")]);
+        assert_eq!(
+            expand_with(strings(&["sgit", "-q", "wip", "--amend"]), 2, &aliases).unwrap(),
+            strings(&["sgit", "-q", "commit", "-m", "work in progress", "--amend"])
+        );
+    }
+
+    #[test]
+    fn expands_aliases_that_build_on_each_other() {
+        let aliases = aliases(&[("up", "sy --all"), ("sy", "sync")]);
+        assert_eq!(
+            expand_with(strings(&["sgit", "up"]), 1, &aliases).unwrap(),
+            strings(&["sgit", "sync", "--all"])
+        );
+    }
+
+    #[test]
+    fn stops_at_builtin_commands() {
+        // `status` is sgit's own, so the alias of the same name is never followed.
+        let aliases = aliases(&[("st", "status"), ("status", "st")]);
+        assert_eq!(
+            expand_with(strings(&["sgit", "st"]), 1, &aliases).unwrap(),
+            strings(&["sgit", "status"])
+        );
+    }
+
+    #[test]
+    fn reports_alias_cycles() {
+        let aliases = aliases(&[("a", "b"), ("b", "c -v"), ("c", "a")]);
+        let err = expand_with(strings(&["sgit", "a"]), 1, &aliases).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the alias 'a' never reaches a command: a → b → c → a"
+        );
+    }
+
+    #[test]
+    fn reports_empty_aliases() {
+        let aliases = aliases(&[("nothing", "  ")]);
+        let err = expand_with(strings(&["sgit", "nothing"]), 1, &aliases).unwrap_err();
+        assert!(err.to_string().contains("'nothing' is empty"), "{}", err);
+    }
+}
@@ -0,0 +1,167 @@
+//! Checks, before a command runs, whether it can do anything useful in the repository's
+//! current state, so the user gets a targeted explanation (and, where possible, a
+//! one-keystroke fix) instead of a confusing git error halfway through.
+
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{bail, Result};
+use dialoguer::{Confirm, Input};
+
+use crate::cli::{BundleAction, FixupAction, SgitCommand, WipAction};
+use crate::commands::connect_remote;
+use crate::git::{run_git_quiet, run_git_silent};
+use crate::status::{get_porcelain_lines, get_remotes, resolve_commit};
+
+pub enum Gate {
+    /// Run the command as usual.
+    Proceed,
+    /// The fix already did what the command was for.
+    Handled,
+}
+
+pub fn check_capabilities(command: &SgitCommand) -> Result<Gate> {
+    let has_commits = resolve_commit("HEAD").is_ok();
+
+    if !has_commits {
+        match command {
+            SgitCommand::Branch { .. } => {
+                println!("This repository has no commits yet, so there is nothing to branch from.");
+                if offer_initial_commit()? {
+                    return Ok(Gate::Proceed);
+                }
+                bail!("make the first commit with 'sgit commit --all -m \"Initial commit\"', then create branches");
+            }
+            SgitCommand::Push { remote: None, .. } | SgitCommand::Sync { .. } => {
+                println!("This repository has no commits yet, so there is nothing to push.");
+                if !offer_initial_commit()? {
+                    bail!("make the first commit with 'sgit commit --all -m \"Initial commit\"', then push");
+                }
+            }
+            SgitCommand::Pull { remote: None, .. } => bail!(
+                "this repository is empty (no commits yet), so there is no branch to pull into\n  hint: to get a copy of an existing project use 'git clone <url>'; to start your own, make a first commit with 'sgit commit --all -m \"Initial commit\"'"
+            ),
+            _ if needs_commits(command) => bail!(
+                "this repository has no commits yet - make the first one with 'sgit commit --all -m \"Initial commit\"'"
+            ),
+            _ => {}
+        }
+    }
+
+    if needs_remote(command) && get_remotes()?.is_empty() {
+        if matches!(
+            command,
+            SgitCommand::Push { remote: None, .. } | SgitCommand::Sync { .. }
+        ) {
+            println!("This repository has no remote to push to yet.");
+            if offer_remote_and_push()? {
+                return Ok(Gate::Handled);
+            }
+        }
+        bail!("no remote configured - add one with 'sgit connect <url>'");
+    }
+
+    Ok(Gate::Proceed)
+}
+
+/// Commands that only make sense once there is at least one commit.
+fn needs_commits(command: &SgitCommand) -> bool {
+    matches!(
+        command,
+        SgitCommand::Log { .. }
+            | SgitCommand::Show { .. }
+            | SgitCommand::Blame { .. }
+            | SgitCommand::Find { .. }
+            | SgitCommand::Compare { .. }
+            | SgitCommand::Grep { history: true, .. }
+            | SgitCommand::Timeline { .. }
+            | SgitCommand::History { .. }
+            | SgitCommand::Reword { .. }
+            | SgitCommand::Squash { .. }
+            | SgitCommand::Split { .. }
+            | SgitCommand::Revert { .. }
+            | SgitCommand::UndoPush
+            | SgitCommand::MergeQueue { .. }
+            | SgitCommand::ExportBundle { .. }
+            | SgitCommand::Bundle {
+                action: BundleAction::Create { .. }
+            }
+            | SgitCommand::Ci { .. }
+            | SgitCommand::Attic { .. }
+            | SgitCommand::Fixup {
+                action: FixupAction::Apply
+            }
+    )
+}
+
+/// Commands that talk to a remote and cannot pick one themselves.
+fn needs_remote(command: &SgitCommand) -> bool {
+    matches!(
+        command,
+        SgitCommand::Push { remote: None, .. }
+            | SgitCommand::Pull { remote: None, .. }
+            | SgitCommand::Sync { remote: None, .. }
+            | SgitCommand::UndoPush
+            | SgitCommand::MergeQueue { remote: None }
+            | SgitCommand::Ci { .. }
+            | SgitCommand::Wip {
+                action: Some(WipAction::Push { .. } | WipAction::Clean { .. }),
+                ..
+            }
+    )
+}
+
+/// Offers to commit everything (or an empty commit) as the first commit.
+pub(crate) fn offer_initial_commit() -> Result<bool> {
+    if !stdin().is_terminal() {
+        return Ok(false);
+    }
+    let has_files = !get_porcelain_lines()?.is_empty();
+    let prompt = if has_files {
+        "Commit all current files as the initial commit now?"
+    } else {
+        "Create an empty initial commit now?"
+    };
+    if !Confirm::new()
+        .with_prompt(prompt)
+        .default(true)
+        .interact()?
+    {
+        return Ok(false);
+    }
+
+    let message: String = Input::new()
+        .with_prompt("Commit message")
+        .default("Initial commit".to_string())
+        .interact_text()?;
+    if has_files {
+        run_git_silent(&["add", "-A"])?;
+        run_git_quiet(&["commit", "-q", "-m", &message])?;
+    } else {
+        run_git_quiet(&["commit", "-q", "--allow-empty", "-m", &message])?;
+    }
+    println!("✓ Created the initial commit");
+    Ok(true)
+}
+
+/// Offers to add `origin` and push the current branch to it with upstream tracking.
+fn offer_remote_and_push() -> Result<bool> {
+    if !stdin().is_terminal() {
+        return Ok(false);
+    }
+    if !Confirm::new()
+        .with_prompt("Add a remote now?")
+        .default(true)
+        .interact()?
+    {
+        return Ok(false);
+    }
+    let url: String = Input::new()
+        .with_prompt("Remote URL (e.g. git@github.com:you/project.git)")
+        .interact_text()?;
+    let url = url.trim();
+    if url.is_empty() {
+        return Ok(false);
+    }
+    connect_remote("origin", url)?;
+    Ok(true)
+}
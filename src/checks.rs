@@ -0,0 +1,232 @@
+use std::fs;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command as StdCommand, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use dialoguer::console::Term;
+
+use crate::git::{git_output, run_git_silent};
+use crate::progress::{step, Phase};
+use crate::status::get_repo_root;
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const TICK: Duration = Duration::from_millis(100);
+const DEFAULT_WIDTH: usize = 80;
+const FAILURE_TAIL_LINES: usize = 20;
+const STASH_MESSAGE: &str = "sgit: unstaged changes, set aside while checks ran";
+
+pub struct CheckFailure {
+    pub command: String,
+    pub output: String,
+}
+
+impl CheckFailure {
+    pub fn print_summary(&self) {
+        eprintln!("✗ Check failed: {}", self.command);
+        let lines: Vec<&str> = self.output.lines().collect();
+        let start = lines.len().saturating_sub(FAILURE_TAIL_LINES);
+        if start > 0 {
+            eprintln!("  ... ({} earlier lines omitted)", start);
+        }
+        for line in &lines[start..] {
+            eprintln!("  {}", line);
+        }
+    }
+}
+
+/// Runs each check command in order, stopping at the first failure.
+pub fn run_checks(commands: &[String]) -> Result<Option<CheckFailure>> {
+    let total = commands.len();
+    let phase = Phase::start("checks", "run");
+    for (idx, command) in commands.iter().enumerate() {
+        println!("→ Running check {}/{}: {}", idx + 1, total, command);
+        step("checks", "run", command, "started");
+        let started = Instant::now();
+        let (success, output) = run_check(command)?;
+        let elapsed = started.elapsed().as_secs_f32();
+
+        if !success {
+            step("checks", "run", command, "failed");
+            phase.failed(&format!("check '{}' failed", command));
+            return Ok(Some(CheckFailure {
+                command: command.clone(),
+                output,
+            }));
+        }
+        step("checks", "run", command, "succeeded");
+        println!("✓ {} passed ({:.1}s)", command, elapsed);
+    }
+    phase.done();
+    Ok(None)
+}
+
+/// Like [`run_checks`], but against what is staged: unstaged changes and untracked files
+/// are stashed for the run and put back afterwards, so they cannot decide the result.
+pub fn run_staged_checks(commands: &[String]) -> Result<Option<CheckFailure>> {
+    let unstaged = !git_output(&["diff", "--name-only"])?.trim().is_empty()
+        || !git_output(&["ls-files", "--others", "--exclude-standard"])?
+            .trim()
+            .is_empty();
+    if !unstaged {
+        return run_checks(commands);
+    }
+    println!("→ Setting unstaged changes aside so the checks see only what is staged...");
+    println!("  (if this is interrupted, 'git stash pop --index' brings them back)");
+    run_git_silent(&[
+        "stash",
+        "push",
+        "--keep-index",
+        "--include-untracked",
+        "--quiet",
+        "-m",
+        STASH_MESSAGE,
+    ])?;
+    let stash = git_output(&["rev-parse", "--verify", "--quiet", "refs/stash"])?
+        .trim()
+        .to_string();
+    let result = run_checks(commands);
+    // Back to HEAD first, so the stash puts the index and working tree back exactly.
+    run_git_silent(&["reset", "--hard", "--quiet"])?;
+    let restored = clear_stashed_untracked(&stash)
+        .and_then(|_| run_git_silent(&["stash", "pop", "--index", "--quiet"]));
+    restored.with_context(|| {
+        format!(
+            "could not put your unstaged changes back - they are safe in the stash as \"{}\"; 'git stash pop --index' restores them",
+            STASH_MESSAGE
+        )
+    })?;
+    result
+}
+
+/// Removes whatever the checks left at the paths of the untracked files in `stash` (build
+/// output, lockfiles), since `git stash pop` refuses to overwrite them. `reset --hard`
+/// only takes care of tracked files.
+fn clear_stashed_untracked(stash: &str) -> Result<()> {
+    let untracked = format!("{}^3", stash);
+    // Without a third parent, no untracked files were stashed.
+    let Ok(paths) = git_output(&[
+        "ls-tree",
+        "-r",
+        "-z",
+        "--full-tree",
+        "--name-only",
+        &untracked,
+    ]) else {
+        return Ok(());
+    };
+    let root = PathBuf::from(get_repo_root()?);
+    for path in paths.split('\0').filter(|path| !path.is_empty()) {
+        let path = root.join(path);
+        let removed = match path.symlink_metadata() {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&path),
+            Ok(_) => fs::remove_file(&path),
+            Err(_) => continue,
+        };
+        removed.with_context(|| format!("could not remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Runs `command` through the shell, returning whether it passed and everything it
+/// printed. Its output streams while it runs: on a terminal the latest line shows next
+/// to the spinner, elsewhere every line is printed as it arrives.
+fn run_check(command: &str) -> Result<(bool, String)> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+
+    let mut child = StdCommand::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to start check '{}'", command))?;
+
+    let (sender, lines) = mpsc::channel();
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|s| forward_lines(s, sender.clone())),
+        child.stderr.take().map(|s| forward_lines(s, sender)),
+    ];
+
+    let live = std::io::stdout().is_terminal();
+    let width = Term::stdout()
+        .size_checked()
+        .map_or(DEFAULT_WIDTH, |(_, columns)| columns as usize);
+    let started = Instant::now();
+    let mut output = String::new();
+    let mut latest = String::new();
+    let mut tick = 0;
+    let mut record = |line: String, latest: &mut String| {
+        if !live {
+            println!("  │ {}", line);
+        }
+        output.push_str(&line);
+        output.push('\n');
+        *latest = line;
+    };
+    let status = loop {
+        match lines.recv_timeout(TICK) {
+            Ok(line) => record(line, &mut latest),
+            Err(RecvTimeoutError::Timeout) => {}
+            // Both streams are closed; the check is about to exit.
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(TICK),
+        }
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("failed to wait for check '{}'", command))?
+        {
+            break status;
+        }
+        if live && started.elapsed() >= TICK * tick {
+            let status = format!(
+                "  {} running ({}s)  ",
+                SPINNER[tick as usize % SPINNER.len()],
+                started.elapsed().as_secs()
+            );
+            let room = width.saturating_sub(status.chars().count() + 1);
+            let shown: String = latest
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .take(room)
+                .collect();
+            print!("\r\x1b[2K{}{}", status, shown);
+            let _ = std::io::stdout().flush();
+            tick += 1;
+        }
+    };
+
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+    for line in lines.try_iter() {
+        record(line, &mut latest);
+    }
+    if live {
+        print!("\r\x1b[2K");
+        let _ = std::io::stdout().flush();
+    }
+    Ok((status.success(), output))
+}
+
+fn forward_lines<R: Read + Send + 'static>(
+    stream: R,
+    sender: mpsc::Sender<String>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    })
+}
@@ -0,0 +1,89 @@
+//! Remembers which option the user picks in prompts they answer over and over (what to
+//! commit, the scope, whether to push) in `.git/sgit/choices.json`, so the usual answer
+//! can be preselected. Turned off with `remember = false` under `[prompts]` in `.sgit.toml`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::status::get_git_dir;
+
+/// Prompt name → option label → how often it was picked.
+type Counts = BTreeMap<String, BTreeMap<String, u32>>;
+
+fn choices_path() -> Option<PathBuf> {
+    let git_dir = get_git_dir().ok()?;
+    Some(PathBuf::from(git_dir).join("sgit").join("choices.json"))
+}
+
+fn enabled() -> bool {
+    Config::load()
+        .map(|config| config.prompts.remember)
+        .unwrap_or(true)
+}
+
+fn load() -> Counts {
+    choices_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// The index of the item picked most often for `prompt`, or `fallback` when there is
+/// no history (or remembering is off). Ties keep `fallback` if it is among them.
+pub fn usual_choice<S: AsRef<str>>(prompt: &str, items: &[S], fallback: usize) -> usize {
+    if !enabled() {
+        return fallback;
+    }
+    let counts = load();
+    let Some(picked) = counts.get(prompt) else {
+        return fallback;
+    };
+    let count_of = |idx: usize| {
+        items
+            .get(idx)
+            .and_then(|item| picked.get(item.as_ref()))
+            .copied()
+            .unwrap_or(0)
+    };
+    let best = (0..items.len()).max_by_key(|&idx| (count_of(idx), idx == fallback));
+    match best {
+        Some(idx) if count_of(idx) > 0 => idx,
+        _ => fallback,
+    }
+}
+
+/// Like [`usual_choice`] for a yes/no question.
+pub fn usual_answer(prompt: &str, fallback: bool) -> bool {
+    let items = ["no", "yes"];
+    usual_choice(prompt, &items, usize::from(fallback)) == 1
+}
+
+/// Records that `item` was picked for `prompt`. Best effort: a failure to save only
+/// means the next default is less smart.
+pub fn remember_choice(prompt: &str, item: &str) {
+    if !enabled() {
+        return;
+    }
+    let Some(path) = choices_path() else {
+        return;
+    };
+    let mut counts = load();
+    *counts
+        .entry(prompt.to_string())
+        .or_default()
+        .entry(item.to_string())
+        .or_default() += 1;
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(contents) = serde_json::to_string_pretty(&counts) {
+        let _ = fs::write(path, contents);
+    }
+}
+
+/// Like [`remember_choice`] for a yes/no question.
+pub fn remember_answer(prompt: &str, answer: bool) {
+    remember_choice(prompt, if answer { "yes" } else { "no" });
+}
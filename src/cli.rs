@@ -8,17 +8,100 @@ use clap::{Parser, Subcommand};
     propagate_version = true
 )]
 pub struct Cli {
+    /// Run as if sgit was started in DIR (may be repeated; each is relative to the last)
+    #[arg(short = 'C', value_name = "DIR")]
+    pub directory: Vec<std::path::PathBuf>,
+
     #[arg(long, global = true)]
     pub explain: bool,
 
+    /// Emit machine-readable progress events as JSON lines on stderr
+    #[arg(long, global = true)]
+    pub progress_json: bool,
+
     #[command(subcommand)]
     pub command: Option<SgitCommand>,
 }
 
 #[derive(Subcommand)]
 pub enum SgitCommand {
-    Init,
+    /// Start a new project: git, a .gitignore, the first branch, a first commit, a remote
+    Init {
+        /// Directory to create the repository in (defaults to the current one)
+        #[arg(value_name = "DIR")]
+        directory: Option<String>,
+        /// .gitignore templates to write, e.g. `--ignore rust,os` (asked when omitted)
+        #[arg(long, value_name = "TEMPLATE", value_delimiter = ',')]
+        ignore: Vec<String>,
+        /// Name of the first branch (defaults to git's init.defaultBranch)
+        #[arg(short, long, value_name = "NAME")]
+        branch: Option<String>,
+        /// Create an initial commit (with the .gitignore, if any)
+        #[arg(long)]
+        commit: bool,
+        /// Remote URL to add as origin
+        #[arg(long, value_name = "URL")]
+        remote: Option<String>,
+        /// Ask nothing; only do what the other flags say
+        #[arg(short, long)]
+        yes: bool,
+        /// Create a bare repository (no working files) for others to push to and pull from
+        #[arg(long, conflicts_with_all = ["ignore", "commit", "remote"])]
+        bare: bool,
+    },
+    /// Turn an existing directory into a repository with a clean first commit
+    Import {
+        /// Remote URL to add as origin and push to
+        #[arg(long, value_name = "URL")]
+        remote: Option<String>,
+        /// Create one commit per top-level directory
+        #[arg(long)]
+        split: bool,
+    },
+    /// Save the files of a branch, tag, or commit as a zip or tar.gz, without history
+    Export {
+        /// Branch, tag, or commit (pick interactively when omitted)
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+        /// zip or tar.gz (defaults to the --output extension, or zip)
+        #[arg(long)]
+        format: Option<String>,
+        /// File to write (defaults to <repo>-<ref>.<format>)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+        /// Only this directory, from the top of the repository
+        #[arg(long, value_name = "DIR")]
+        path: Option<String>,
+    },
+    /// Carry branches between machines without a network: create, verify, and fetch bundles
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+    /// Old name for 'sgit bundle create'
+    #[command(hide = true)]
+    ExportBundle {
+        /// Branch, tag, or `base..branch` range (pick interactively when omitted)
+        #[arg(value_name = "RANGE")]
+        target: Option<String>,
+        /// File to write (defaults to <repo>-<branch>.bundle)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Old name for 'sgit bundle verify' and 'sgit bundle fetch'
+    #[command(hide = true)]
+    ImportBundle {
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// Local branch to import into (asked interactively when omitted)
+        #[arg(short, long)]
+        branch: Option<String>,
+        /// Only check that the bundle is intact and applies to this repository
+        #[arg(long)]
+        verify: bool,
+    },
     Stage {
+        /// Paths to stage; `-` reads NUL- or newline-separated paths from stdin
         #[arg(value_name = "PATH")]
         targets: Vec<String>,
         #[arg(long)]
@@ -27,14 +110,33 @@ pub enum SgitCommand {
         tracked: bool,
     },
     Unstage {
+        /// Paths to unstage; `-` reads NUL- or newline-separated paths from stdin
         #[arg(value_name = "PATH")]
         targets: Vec<String>,
         #[arg(long)]
         all: bool,
     },
+    /// Add patterns or a bundled template to .gitignore (pick untracked files when omitted)
+    Ignore {
+        /// Patterns to add, e.g. `"*.log"` or `build/`
+        #[arg(value_name = "PATTERN")]
+        patterns: Vec<String>,
+        /// Add a bundled template's rules: rust, node, python, go, java, c, os
+        #[arg(short, long, value_name = "LANG", value_delimiter = ',')]
+        template: Vec<String>,
+        /// Explain which ignore file and rule keep PATH out of `sgit status`
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["patterns", "template"])]
+        why: Option<String>,
+    },
     Status {
         #[arg(long)]
         short: bool,
+        /// Show plain `git status` output instead of sgit's summary
+        #[arg(long, conflicts_with = "short")]
+        raw: bool,
+        /// Keep the view open and redraw it whenever files change
+        #[arg(long, conflicts_with_all = ["short", "raw"])]
+        watch: bool,
     },
     Commit {
         #[arg(short, long, value_name = "MSG")]
@@ -51,15 +153,184 @@ pub enum SgitCommand {
         amend: bool,
         #[arg(long)]
         no_verify: bool,
+        /// Run the checks configured in .sgit.toml and only commit if they pass
+        #[arg(long)]
+        when_green: bool,
+        /// Record the changes as a fixup of an earlier unpushed commit (pick interactively if no ref)
+        #[arg(long, value_name = "REF", num_args = 0..=1, default_missing_value = "")]
+        fixup: Option<String>,
+        /// Skip the summary confirmation and commit right away
+        #[arg(short, long)]
+        yes: bool,
+        /// Commit even if the message breaks the [lint] rules in .sgit.toml
+        #[arg(long)]
+        no_lint: bool,
+        /// Write the message in your editor, starting from --message or the commit template
+        #[arg(short, long)]
+        edit: bool,
+        /// Sign the commit with your GPG or SSH key (see 'sgit signing setup')
+        #[arg(short = 'S', long)]
+        sign: bool,
+        /// With --amend, keep the last commit's message
+        #[arg(long, requires = "amend", conflicts_with_all = ["message", "edit"])]
+        no_edit: bool,
+    },
+    /// Fold fixup commits into the commits they fix
+    Fixup {
+        #[command(subcommand)]
+        action: FixupAction,
     },
     Log {
         #[arg(long)]
         short: bool,
+        /// Only commits by this author (name or email, partial matches work)
+        #[arg(long, value_name = "NAME")]
+        author: Option<String>,
+        /// Only commits newer than this date (e.g. "2 weeks ago", 2024-05-01)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        /// Only commits older than this date
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+        /// Only commits that touched this file or directory
+        #[arg(long, value_name = "PATH")]
+        path: Option<String>,
+        /// Only commits whose message contains this text (case-insensitive)
+        #[arg(long, value_name = "TEXT")]
+        grep: Option<String>,
+        /// Mark each commit as signed and verified, signed but unverifiable, or unsigned
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Take a short hands-on lesson in a throwaway practice repository
+    Learn {
+        /// branches, merging, or undo (pick from a list when omitted)
+        topic: Option<String>,
+    },
+    /// Search commits by message, author, or code, then show/checkout/cherry-pick/revert one
+    Find {
+        /// Text to search for (asked interactively when omitted)
+        term: Option<String>,
+    },
+    /// Search tracked files for text, or find the commits that added or removed it
+    Grep {
+        pattern: String,
+        /// Only search these files or directories
+        #[arg(value_name = "PATH")]
+        paths: Vec<String>,
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Treat the pattern as a regular expression instead of plain text
+        #[arg(short = 'E', long)]
+        regex: bool,
+        /// Search history: list the commits where the text was added or removed
+        #[arg(long)]
+        history: bool,
+    },
+    /// Show who last changed each line of a file, and when
+    Blame {
+        path: String,
+        /// Only these lines, e.g. 10-20 or 10,+5
+        #[arg(short = 'L', long, value_name = "RANGE")]
+        lines: Option<String>,
+    },
+    /// Show one commit's details, message, and changes
+    Show {
+        /// Commit, branch, or tag (pick from recent commits when omitted)
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+    },
+    /// Print a compact repository summary for shell prompts (silent outside a repo)
+    Prompt,
+    /// Explain what the previous sgit command did, what failed, and how to fix it
+    ExplainLast,
+    /// Narrate how a branch moved (commits, resets, rebases) using the reflog
+    Timeline {
+        #[arg(short, long)]
+        branch: Option<String>,
+        /// Only look at today's movement (the default)
+        #[arg(long, conflicts_with = "since")]
+        today: bool,
+        /// Start of the window, e.g. "2 hours ago", "yesterday", or a date
+        #[arg(long, value_name = "TIME")]
+        since: Option<String>,
+    },
+    /// Rewrite recent history with a guided editor
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Change the message of one or more earlier commits
+    Reword {
+        /// Commit to reword (pick one or several unpushed commits if omitted)
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+        #[arg(short, long, value_name = "MSG")]
+        message: Option<String>,
+        /// Edit the full message in your editor instead of the one-line prompt
+        #[arg(short, long, conflicts_with = "message")]
+        edit: bool,
+        /// Reword even if the commit was already pushed
+        #[arg(long)]
+        force: bool,
+    },
+    /// Combine the last N commits into a single commit
+    Squash {
+        /// Number of commits to squash (pick interactively if omitted)
+        #[arg(value_name = "N")]
+        count: Option<usize>,
+        #[arg(short, long, value_name = "MSG")]
+        message: Option<String>,
+        /// Squash even if some of the commits were already pushed
+        #[arg(long)]
+        force: bool,
+    },
+    /// Undo the last commit and re-commit its changes as several smaller commits
+    Split {
+        /// Split even if the commit was already pushed
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show what is in one branch but not the other, in both directions
+    Compare {
+        /// First branch, tag, or commit (pick interactively when omitted)
+        a: Option<String>,
+        /// Second branch, tag, or commit (pick interactively when omitted)
+        b: Option<String>,
+        /// Also print the full diff without asking
+        #[arg(long)]
+        diff: bool,
     },
     Diff {
-        path: Option<String>,
+        /// A path, two refs to compare, or two refs and a path
+        #[arg(value_name = "REF_OR_PATH", num_args = 0..=3)]
+        targets: Vec<String>,
         #[arg(long)]
         staged: bool,
+        /// Dim lines that were moved rather than added or removed
+        #[arg(long)]
+        highlight_moved: bool,
+        /// Highlight changed words instead of whole lines (handy for prose and docs)
+        #[arg(long)]
+        words: bool,
+        /// Only summarize how many lines changed in each file
+        #[arg(long, conflicts_with_all = ["words", "highlight_moved"])]
+        stat: bool,
+        /// Open the changes in your diff tool (picked from installed tools if none is set)
+        #[arg(long, conflicts_with_all = ["words", "highlight_moved", "stat"])]
+        tool: bool,
+        /// Print git's plain diff output instead of the highlighted view
+        #[arg(long)]
+        plain: bool,
+        /// Compare the working tree with a branch, tag, or commit (picked if omitted)
+        #[arg(long, value_name = "REF", num_args = 0..=1, default_missing_value = "")]
+        against: Option<String>,
+        /// Start of the comparison (defaults to HEAD when only --to is given)
+        #[arg(long, value_name = "REF")]
+        from: Option<String>,
+        /// End of the comparison (defaults to the working tree)
+        #[arg(long, value_name = "REF")]
+        to: Option<String>,
     },
     Reset {
         #[arg(long)]
@@ -73,20 +344,651 @@ pub enum SgitCommand {
         #[arg(long)]
         untracked: bool,
     },
+    /// Create a new commit that undoes an earlier one
+    Revert {
+        /// Commit to revert (pick interactively if omitted)
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+        /// Apply the revert to the working tree and index without committing
+        #[arg(long)]
+        no_commit: bool,
+        /// Parent number to keep when reverting a merge commit
+        #[arg(short, long, value_name = "PARENT")]
+        mainline: Option<usize>,
+    },
+    /// Merge a branch into the current one (picked when omitted)
+    Merge {
+        branch: Option<String>,
+        /// Stage the branch's changes as one change and commit it with a message listing
+        /// the squashed commits
+        #[arg(long)]
+        squash: bool,
+        /// The message for the squashed commit (opens in your editor otherwise)
+        #[arg(short, long, requires = "squash")]
+        message: Option<String>,
+        /// Don't ask for confirmation (of the squashed commit, or of --abort)
+        #[arg(short = 'y', long)]
+        yes: bool,
+        /// Go back to before a merge that stopped on conflicts, showing what is discarded
+        #[arg(long, conflicts_with_all = ["branch", "squash"])]
+        abort: bool,
+    },
+    /// Continue the paused merge, rebase, cherry-pick, or revert
+    Continue,
+    /// Abort the paused merge, rebase, cherry-pick, or revert
+    Abort,
     Branch {
         #[arg(short, long)]
         create: Option<String>,
+        /// Show which branches and tags contain a commit (pick one when no commit is given)
+        #[arg(
+            long,
+            value_name = "COMMIT",
+            num_args = 0..=1,
+            default_missing_value = "",
+            conflicts_with = "create"
+        )]
+        contains: Option<String>,
+        /// List the commits on this branch that are not in any tag yet
+        #[arg(long, conflicts_with_all = ["create", "contains"])]
+        untagged: bool,
+    },
+    /// Connect this repository to a new, empty remote: add it and push with tracking
+    Connect {
+        /// The remote repository's URL (asked when omitted)
+        #[arg(value_name = "URL")]
+        url: Option<String>,
+        /// Name for the remote
+        #[arg(long, default_value = "origin")]
+        name: String,
     },
     Push {
         remote: Option<String>,
         branch: Option<String>,
+        /// Push the current branch to every remote, e.g. a mirror next to the main one
+        /// (the default with [sync] push_all_remotes = true)
+        #[arg(long, conflicts_with_all = ["remote", "branch"])]
+        all_remotes: bool,
+        /// Replace the remote branch after rewriting history (with --force-with-lease, so
+        /// commits you have not seen are never overwritten)
+        #[arg(long, conflicts_with = "all_remotes")]
+        force: bool,
+    },
+    /// Register repositories, or find them under a directory, and list their state
+    Repos {
+        #[command(subcommand)]
+        action: Option<ReposAction>,
+    },
+    /// Check, pull, push, or sync several repositories at once (status when no action)
+    Workspace {
+        #[command(subcommand)]
+        action: Option<WorkspaceAction>,
+    },
+    /// Mirror every branch and tag to a backup remote (set up on first use)
+    Backup {
+        #[command(subcommand)]
+        action: Option<BackupAction>,
+    },
+    /// Undo commits you already pushed, by reverting or rolling the remote back
+    UndoPush,
+    /// Download new commits, branches, and tags without touching your branches, and list
+    /// what changed
+    Fetch {
+        /// The remote to fetch (the current branch's, when omitted)
+        remote: Option<String>,
+        /// Fetch every remote
+        #[arg(long, conflicts_with = "remote")]
+        all: bool,
+        /// Remove remote branches that were deleted on the remote
+        #[arg(long)]
+        prune: bool,
     },
     Pull {
         remote: Option<String>,
         branch: Option<String>,
+        /// Replay your commits on top of the remote ones instead of merging (uncommitted
+        /// changes are set aside meanwhile)
+        #[arg(long, conflicts_with = "merge")]
+        rebase: bool,
+        /// Merge the remote commits, even when [sync] mode or pull.rebase says rebase
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Fetch, pull, and push in the background at an interval (status when no action)
+    Autosync {
+        #[command(subcommand)]
+        action: Option<AutosyncAction>,
     },
     Sync {
         remote: Option<String>,
         branch: Option<String>,
     },
+    /// Let 'sgit sync' keep this branch rebased onto the default branch
+    /// Land HEAD on the default branch: rebase onto the latest tip, run checks, then push
+    MergeQueue {
+        /// Remote to land on (defaults to the branch's remote, else the only one, else asks)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+    AutoRebase {
+        #[command(subcommand)]
+        action: Option<AutoRebaseAction>,
+    },
+    /// Show CI results from GitHub or GitLab
+    Ci {
+        #[command(subcommand)]
+        action: CiAction,
+    },
+    /// Park everything in a WIP commit, or share a snapshot without committing
+    #[command(args_conflicts_with_subcommands = true)]
+    Wip {
+        #[command(subcommand)]
+        action: Option<WipAction>,
+        /// Use this instead of the generated message (after "WIP: ")
+        #[arg(short, long, value_name = "MSG")]
+        message: Option<String>,
+    },
+    /// Take the last WIP commit apart again, leaving its changes staged
+    Unwip,
+    /// Keep the repository small and fast: pack objects, prune, and index history
+    Maintenance {
+        #[command(subcommand)]
+        action: Option<MaintenanceAction>,
+    },
+    /// Show how big the repository is and which files in its history take the space
+    Size {
+        /// How many of the largest files to list
+        #[arg(long, default_value_t = 10, value_name = "N")]
+        top: usize,
+    },
+    /// Check out only the directories you work in, for big repositories
+    Sparse {
+        #[command(subcommand)]
+        action: Option<SparseAction>,
+    },
+    /// Add, update, inspect, and remove submodules: repositories inside this one
+    Submodule {
+        #[command(subcommand)]
+        action: Option<SubmoduleAction>,
+    },
+    /// Copy another repository into a directory here, and pull or push its changes later
+    Subtree {
+        #[command(subcommand)]
+        action: Option<SubtreeAction>,
+    },
+    /// Check out other branches in directories of their own, to work on several at once
+    Worktree {
+        #[command(subcommand)]
+        action: Option<WorktreeAction>,
+    },
+    /// Commit your work in progress to sgit/watch/<branch> as you save, until Ctrl-C
+    Watch {
+        /// Save every SECONDS (when something changed) instead of each time you save
+        #[arg(long, value_name = "SECONDS")]
+        interval: Option<u64>,
+    },
+    /// Check that the working tree matches a ref exactly (exit 1 if not, 2 on error)
+    VerifyTree {
+        /// Branch, tag, or commit to compare with (default HEAD)
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Walk through the files with merge conflicts one by one, or resolve them in your
+    /// merge tool
+    Conflicts {
+        #[command(subcommand)]
+        action: Option<ConflictsAction>,
+        /// Open the conflicted files in your merge tool (picked if none is set)
+        #[arg(long)]
+        tool: bool,
+    },
+    /// Park and restore whole working-tree states under a name (list when no action)
+    Attic {
+        #[command(subcommand)]
+        action: Option<AtticAction>,
+    },
+    /// Manage the allowed-signers file used to verify SSH-signed commits
+    Signers {
+        #[command(subcommand)]
+        action: Option<SignersAction>,
+    },
+    /// Set the name and email git records on your commits
+    Setup,
+    /// Switch this repository between identity profiles from your sgit config (list when no action)
+    Profile {
+        #[command(subcommand)]
+        action: Option<ProfileAction>,
+    },
+    /// Check that git, your identity, the remote, hooks, and line endings are set up well
+    Doctor,
+    /// List, enable, disable, and install git hooks (lists them when no action)
+    Hooks {
+        #[command(subcommand)]
+        action: Option<HooksAction>,
+    },
+    /// List the command aliases defined under [aliases] in your sgit config
+    Alias {
+        #[command(subcommand)]
+        action: Option<AliasAction>,
+    },
+    /// View and change sgit's settings and common git ones (a menu when no action)
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// Set up GPG or SSH commit signing, or show how it is configured (no action)
+    Signing {
+        #[command(subcommand)]
+        action: Option<SigningAction>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FixupAction {
+    /// Run an autosquash rebase over your unpushed commits
+    Apply,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// Keep, squash, reword, drop, or reorder recent commits
+    Edit {
+        /// How many recent commits to show (default 10)
+        #[arg(short = 'n', long)]
+        count: Option<usize>,
+        /// Edit even if some of the commits were already pushed
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AutoRebaseAction {
+    /// Turn auto-rebase on for the current branch
+    On,
+    /// Turn auto-rebase off for the current branch
+    Off,
+    /// Show whether auto-rebase is on for the current branch
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum CiAction {
+    /// Show the checks for a commit (default: the branch's open pull request, else HEAD, or
+    /// its upstream if HEAD is unpushed)
+    Status {
+        #[arg(value_name = "REF")]
+        target: Option<String>,
+        /// Keep polling until every check has finished
+        #[arg(long)]
+        watch: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WipAction {
+    /// Push a snapshot of your working tree to wip/<user>/<branch>
+    Push {
+        #[arg(long)]
+        remote: Option<String>,
+        #[arg(short, long, value_name = "MSG")]
+        message: Option<String>,
+    },
+    /// Delete the pushed wip ref for the current branch
+    Clean {
+        #[arg(long)]
+        remote: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MaintenanceAction {
+    /// Tidy up now (what plain 'sgit maintenance' does)
+    Run {
+        /// Recompress everything into one pack: slower, smallest result
+        #[arg(long)]
+        full: bool,
+    },
+    /// Show the repository's size, packs, and whether background maintenance is on
+    Status,
+    /// Have git tidy this repository up in the background from now on
+    Schedule,
+    /// Stop the background maintenance for this repository
+    Unschedule,
+}
+
+#[derive(Subcommand)]
+pub enum SparseAction {
+    /// Show whether sparse checkout is on and which directories are checked out
+    Status,
+    /// Tick the top-level directories to check out, from a list
+    Pick,
+    /// Check out more directories (turns sparse checkout on with just these if it is off)
+    Add {
+        #[arg(required = true, value_name = "DIR")]
+        paths: Vec<String>,
+    },
+    /// Stop checking out these directories
+    Remove {
+        #[arg(required = true, value_name = "DIR")]
+        paths: Vec<String>,
+    },
+    /// Turn sparse checkout off and check every file out again
+    Disable,
+}
+
+#[derive(Subcommand)]
+pub enum SubmoduleAction {
+    /// Show each submodule, nested ones too, and whether it is at the recorded commit
+    Status,
+    /// Clone a repository into this one as a submodule
+    Add {
+        url: String,
+        /// Where to put it (defaults to the repository's name)
+        path: Option<String>,
+        /// Branch for 'git submodule update --remote' to follow
+        #[arg(long)]
+        branch: Option<String>,
+    },
+    /// Clone missing submodules and check out the recorded commit in each, recursively
+    #[command(alias = "init")]
+    Update {
+        /// Update even submodules with commits this repository does not record
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove a submodule completely: its files, settings, and clone
+    Remove {
+        path: String,
+        /// Remove it even with uncommitted changes inside
+        #[arg(long)]
+        force: bool,
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SubtreeAction {
+    /// List the subtrees set up in .sgit.toml and when each was last pulled
+    List,
+    /// Copy a repository into a directory and remember where it came from (asks for
+    /// what is not given)
+    Add {
+        /// Directory to put it in, e.g. vendor/lib
+        prefix: Option<String>,
+        /// URL or remote name of the repository
+        remote: Option<String>,
+        /// Branch to follow (defaults to the repository's default branch)
+        branch: Option<String>,
+        /// Bring in every commit of its history instead of one squashed commit
+        #[arg(long)]
+        full_history: bool,
+    },
+    /// Bring in the new commits of a subtree's repository
+    Pull {
+        /// Directory of the subtree (not needed when there is only one)
+        prefix: Option<String>,
+    },
+    /// Send the commits made here to a subtree back to its repository
+    Push {
+        /// Directory of the subtree (not needed when there is only one)
+        prefix: Option<String>,
+        /// Push to this branch instead of the one it follows
+        #[arg(long)]
+        branch: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleAction {
+    /// Write branches or tags to one file (pick branches when none are named)
+    Create {
+        #[arg(value_name = "REF")]
+        refs: Vec<String>,
+        /// Every local branch
+        #[arg(long, conflicts_with = "refs")]
+        all: bool,
+        /// Only commits after this tag or commit, which the receiver already has
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+        /// File to write (defaults to <repo>.bundle, or <repo>-<branch>.bundle for one)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Check that a received bundle is intact and this repository can use it
+    Verify {
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+    /// Bring branches from a bundle into local branches of the same names (or clone it)
+    Fetch {
+        #[arg(value_name = "FILE")]
+        file: String,
+        /// Only these branches (pick them when the bundle has several)
+        #[arg(value_name = "BRANCH")]
+        branches: Vec<String>,
+        /// Local branch to fetch the one chosen branch into
+        #[arg(long, value_name = "BRANCH")]
+        into: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorktreeAction {
+    /// List the worktrees, their branches, and whether they have uncommitted changes
+    List,
+    /// Check out a branch in a new worktree (picked from a list when not given)
+    Add {
+        /// Local or remote branch, or the name of a new branch to create from HEAD
+        branch: Option<String>,
+        /// Directory for it (defaults to <repo>-<branch> next to the main worktree)
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Remove a worktree, by branch or path (picked from a list when not given)
+    Remove {
+        worktree: Option<String>,
+        /// Remove it even with uncommitted changes or a lock
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AtticAction {
+    /// Save staged, unstaged, and untracked changes under NAME and clean the working tree
+    Put {
+        name: String,
+        /// Leave the working tree as it is
+        #[arg(long)]
+        keep: bool,
+    },
+    /// List parked entries with their size and age
+    List,
+    /// Bring an entry's changes back into the working tree
+    Restore {
+        name: Option<String>,
+        /// Delete the entry once it is restored
+        #[arg(long)]
+        drop: bool,
+    },
+    /// Show what restoring an entry would change
+    Diff { name: Option<String> },
+    /// Delete an entry
+    Drop { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+pub enum SignersAction {
+    /// List trusted signers and their keys
+    List,
+    /// Trust a signer's key, pasted directly or fetched from the forge
+    Add {
+        email: Option<String>,
+        /// Public key, or path to a .pub file
+        #[arg(long)]
+        key: Option<String>,
+        /// Forge username to fetch SSH signing keys for
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Stop trusting a signer
+    Remove { email: Option<String> },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List the profiles in your sgit config, marking the one this repository uses
+    List,
+    /// Apply a profile's name, email, signing key, and remote host to this repository
+    Use { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+pub enum HooksAction {
+    /// Show the installed hooks and whether each one runs
+    List,
+    /// Turn a disabled hook back on (pick one when no name)
+    Enable { name: Option<String> },
+    /// Stop a hook from running without deleting it (pick one when no name)
+    Disable { name: Option<String> },
+    /// Install a hook from sgit's templates (pick one when no template)
+    Install {
+        template: Option<String>,
+        /// Write it to a directory in the repository the team shares (default .githooks)
+        /// and point core.hooksPath at it
+        #[arg(long, value_name = "DIR", num_args = 0..=1, default_missing_value = ".githooks")]
+        shared: Option<String>,
+        /// Replace a hook sgit did not write, keeping it as <hook>.backup
+        #[arg(long)]
+        force: bool,
+        /// Set one of the template's parameters, e.g. --set max_subject=50 (the others are
+        /// asked for, or take their defaults)
+        #[arg(long, value_name = "NAME=VALUE")]
+        set: Vec<String>,
+    },
+    /// Run `pre-commit install` so git runs the checks in .pre-commit-config.yaml
+    InstallPrecommit,
+}
+
+#[derive(Subcommand)]
+pub enum ConflictsAction {
+    /// Show each conflicted region of a file with the two sides next to each other
+    Show { file: String },
+}
+
+#[derive(Subcommand)]
+pub enum ReposAction {
+    /// Show each registered repository's branch, changes, and distance from its upstream
+    List,
+    /// Register the repository at PATH (the current one when omitted)
+    Add { path: Option<String> },
+    /// Unregister a repository, by name or path
+    Remove { repo: String },
+    /// Register every repository found under DIR (the current directory when omitted)
+    Scan {
+        dir: Option<String>,
+        /// How many directory levels down to look
+        #[arg(long, default_value_t = 3)]
+        depth: usize,
+    },
 }
+
+#[derive(Subcommand)]
+pub enum AutosyncAction {
+    /// Start syncing this repository in the background
+    Start {
+        /// How often: 30s, 10m, 1h, or a number of minutes
+        #[arg(long, default_value = "10m")]
+        interval: String,
+    },
+    /// Stop the background sync
+    Stop,
+    /// Show whether it runs and what it did lately
+    Status,
+    /// The background process itself
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceAction {
+    /// Show each repository's branch, uncommitted changes, and distance from its upstream
+    Status,
+    /// Fetch each repository and fast-forward its branch to the upstream
+    Pull,
+    /// Push each repository's branch when it is ahead of its upstream
+    Push,
+    /// Pull, then push, each repository
+    Sync,
+}
+
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// Choose or add the remote backups go to
+    Setup,
+}
+
+#[derive(Subcommand)]
+pub enum AliasAction {
+    /// Show each alias and what it expands to
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Show every setting with its current value
+    List,
+    /// Print one setting's value
+    Get { key: String },
+    /// Change a setting after checking the value
+    Set {
+        key: String,
+        value: String,
+        /// Write git settings to this repository instead of your global config
+        #[arg(long)]
+        local: bool,
+        /// Write sgit settings to your user config instead of .sgit.toml
+        #[arg(long, conflicts_with = "local")]
+        user: bool,
+    },
+    /// Remove a setting so its default applies again
+    Unset {
+        key: String,
+        /// Remove a git setting from this repository instead of your global config
+        #[arg(long)]
+        local: bool,
+        /// Remove an sgit setting from your user config instead of .sgit.toml
+        #[arg(long, conflicts_with = "local")]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SigningAction {
+    /// Show the signing format, key, and whether commits are signed by default
+    Status,
+    /// Pick an SSH or GPG key, configure git to sign with it, and test a signature
+    Setup,
+}
+
+/// Returned by commands whose exit status carries meaning (e.g. "differences found"),
+/// after they have already reported everything; `main` exits with the code silently.
+#[derive(Debug)]
+pub struct ExitCode(pub i32);
+
+impl std::fmt::Display for ExitCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit code {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitCode {}
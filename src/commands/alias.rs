@@ -0,0 +1,39 @@
+use anyhow::Result;
+
+use crate::alias::is_builtin;
+use crate::cli::AliasAction;
+use crate::config::Config;
+
+pub fn run_alias(action: Option<AliasAction>) -> Result<()> {
+    match action.unwrap_or(AliasAction::List) {
+        AliasAction::List => list_aliases(),
+    }
+}
+
+fn list_aliases() -> Result<()> {
+    let aliases = Config::load()?.aliases;
+    if aliases.is_empty() {
+        println!("No aliases defined yet. Add them under [aliases] in .sgit.toml or ~/.config/sgit/config.toml:");
+        println!();
+        println!("  [aliases]");
+        println!("  s = \"status --short\"");
+        println!("  ship = \"commit --all --push\"");
+        return Ok(());
+    }
+    let width = aliases.keys().map(|name| name.len()).max().unwrap_or(0);
+    for (name, value) in &aliases {
+        let note = if is_builtin(name) {
+            "  (ignored: sgit has a command with this name)"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<width$} = {}{}",
+            name,
+            value.describe(),
+            note,
+            width = width
+        );
+    }
+    Ok(())
+}
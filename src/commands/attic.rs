@@ -0,0 +1,291 @@
+use std::io::{stdout, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::cli::AtticAction;
+use crate::git::{git_cmd, run_git, run_git_quiet, run_git_silent};
+use crate::journal::{self, JournalEntry};
+use crate::snapshot::create_stash_commit;
+use crate::status::{get_current_branch, get_porcelain_lines, resolve_commit};
+
+const ATTIC_REF_PREFIX: &str = "refs/sgit/attic/";
+
+struct AtticEntry {
+    name: String,
+    hash: String,
+    date: String,
+    message: String,
+}
+
+pub fn run_attic(action: Option<AtticAction>) -> Result<()> {
+    match action.unwrap_or(AtticAction::List) {
+        AtticAction::Put { name, keep } => attic_put(&name, keep),
+        AtticAction::List => attic_list(),
+        AtticAction::Restore { name, drop } => attic_restore(name, drop),
+        AtticAction::Diff { name } => attic_diff(name),
+        AtticAction::Drop { name } => attic_drop(name),
+    }
+}
+
+fn attic_put(name: &str, keep: bool) -> Result<()> {
+    let ref_name = attic_ref(name)?;
+    if resolve_commit(&ref_name).is_ok() {
+        bail!(
+            "an attic entry named '{}' already exists - pick another name or 'sgit attic drop {}' first",
+            name,
+            name
+        );
+    }
+
+    let branch = get_current_branch()?;
+    let message = if branch.is_empty() {
+        format!("sgit attic: {}", name)
+    } else {
+        format!("sgit attic: {} (on {})", name, branch)
+    };
+    let Some(commit) = create_stash_commit(&message)? else {
+        println!("Nothing to park - there are no staged, unstaged, or untracked changes.");
+        return Ok(());
+    };
+    run_git_silent(&["update-ref", &ref_name, &commit])?;
+
+    if keep {
+        println!(
+            "✓ Parked your changes as '{}' (working tree left as is)",
+            name
+        );
+    } else {
+        run_git_silent(&["reset", "--hard", "--quiet"])?;
+        run_git_silent(&["clean", "-fd", "--quiet"])?;
+        println!(
+            "✓ Parked your changes as '{}'; the working tree is clean",
+            name
+        );
+    }
+    println!("  Bring them back with 'sgit attic restore {}'.", name);
+    Ok(())
+}
+
+fn attic_list() -> Result<()> {
+    let entries = read_entries()?;
+    if entries.is_empty() {
+        println!("The attic is empty. Park your changes with 'sgit attic put <name>'.");
+        return Ok(());
+    }
+    let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+    for entry in &entries {
+        println!(
+            "  {:<width$}  {:<16}  {}",
+            entry.name,
+            entry.date,
+            describe_size(&entry.hash)?,
+            width = width
+        );
+        if let Some(origin) = entry
+            .message
+            .strip_prefix("sgit attic: ")
+            .and_then(|rest| rest.split_once(" (on "))
+        {
+            println!(
+                "  {:<width$}  parked on {}",
+                "",
+                origin.1.trim_end_matches(')'),
+                width = width
+            );
+        }
+    }
+    Ok(())
+}
+
+fn attic_restore(name: Option<String>, drop: bool) -> Result<()> {
+    let Some(entry) = choose_entry(name, "Which entry do you want to restore?")? else {
+        return Ok(());
+    };
+    if !get_porcelain_lines()?.is_empty() {
+        bail!("you have uncommitted changes - park them first with 'sgit attic put <name>' or commit them");
+    }
+
+    println!("→ Restoring '{}'...", entry.name);
+    // `--index` also brings back what was staged; fall back when the index no longer fits.
+    if run_git_quiet(&["stash", "apply", "--index", "--quiet", &entry.hash]).is_err() {
+        run_git_quiet(&["stash", "apply", "--quiet", &entry.hash])?;
+        println!("  (the staged/unstaged split could not be restored; everything is unstaged)");
+    }
+    println!("✓ Restored '{}'", entry.name);
+
+    if drop {
+        remove_entry(&entry)?;
+    } else {
+        println!(
+            "  The entry is still in the attic; remove it with 'sgit attic drop {}'.",
+            entry.name
+        );
+    }
+    Ok(())
+}
+
+fn attic_diff(name: Option<String>) -> Result<()> {
+    let Some(entry) = choose_entry(name, "Which entry do you want to compare with?")? else {
+        return Ok(());
+    };
+    let mut args = vec!["diff"];
+    if stdout().is_terminal() {
+        args.push("--color=always");
+    }
+    // Read as "what restoring this entry would change in your working tree".
+    args.push("-R");
+    args.push(&entry.hash);
+    run_git(&args)?;
+
+    let untracked = untracked_files(&entry.hash)?;
+    if !untracked.is_empty() {
+        println!();
+        println!("Untracked files in '{}':", entry.name);
+        for file in untracked {
+            println!("  {}", file);
+        }
+    }
+    Ok(())
+}
+
+fn attic_drop(name: Option<String>) -> Result<()> {
+    let Some(entry) = choose_entry(name, "Which entry do you want to delete?")? else {
+        return Ok(());
+    };
+    let confirm = Confirm::new()
+        .with_prompt(format!("Delete the attic entry '{}'?", entry.name))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("Aborted.");
+        return Ok(());
+    }
+    remove_entry(&entry)
+}
+
+fn remove_entry(entry: &AtticEntry) -> Result<()> {
+    let ref_name = format!("{}{}", ATTIC_REF_PREFIX, entry.name);
+    run_git_silent(&["update-ref", "-d", &ref_name])?;
+    journal::record(
+        &JournalEntry::new("attic", format!("dropped attic entry '{}'", entry.name))
+            .with_undo(format!("git update-ref {} {}", ref_name, entry.hash)),
+    )?;
+    println!("✓ Removed '{}' from the attic", entry.name);
+    Ok(())
+}
+
+fn choose_entry(name: Option<String>, prompt: &str) -> Result<Option<AtticEntry>> {
+    let mut entries = read_entries()?;
+    if let Some(name) = name {
+        return match entries.iter().position(|e| e.name == name) {
+            Some(idx) => Ok(Some(entries.swap_remove(idx))),
+            None => bail!(
+                "no attic entry named '{}' - 'sgit attic list' shows them",
+                name
+            ),
+        };
+    }
+    if entries.is_empty() {
+        println!("The attic is empty.");
+        return Ok(None);
+    }
+    let labels: Vec<String> = entries
+        .iter()
+        .map(|e| format!("{}  ({})", e.name, e.date))
+        .collect();
+    let selection = Select::new()
+        .with_prompt(prompt)
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    Ok(Some(entries.swap_remove(selection)))
+}
+
+/// Attic entries, newest first.
+fn read_entries() -> Result<Vec<AtticEntry>> {
+    let output = git_cmd()
+        .args([
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--format=%(refname)%00%(objectname)%00%(committerdate:relative)%00%(subject)",
+            ATTIC_REF_PREFIX,
+        ])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let name = fields.next()?.strip_prefix(ATTIC_REF_PREFIX)?.to_string();
+            Some(AtticEntry {
+                name,
+                hash: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                message: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+fn attic_ref(name: &str) -> Result<String> {
+    let ref_name = format!("{}{}", ATTIC_REF_PREFIX, name);
+    let valid = git_cmd()
+        .args(["check-ref-format", &ref_name])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    if !valid {
+        bail!(
+            "'{}' cannot be used as a name - use letters, digits, '-', '_', or '/'",
+            name
+        );
+    }
+    Ok(ref_name)
+}
+
+/// e.g. "3 files, +10 -2, 1 untracked".
+fn describe_size(hash: &str) -> Result<String> {
+    // Against the index commit's parent (HEAD at the time), so staged work counts too.
+    let output = git_cmd()
+        .args(["diff", "--numstat", &format!("{}^1", hash), hash])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    let mut files = 0;
+    let mut added = 0;
+    let mut removed = 0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split('\t');
+        files += 1;
+        added += fields
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .unwrap_or(0);
+        removed += fields
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .unwrap_or(0);
+    }
+    let untracked = untracked_files(hash)?.len();
+    let mut parts = vec![format!("{} file(s), +{} -{}", files, added, removed)];
+    if untracked > 0 {
+        parts.push(format!("{} untracked", untracked));
+    }
+    Ok(parts.join(", "))
+}
+
+fn untracked_files(hash: &str) -> Result<Vec<String>> {
+    let untracked = format!("{}^3", hash);
+    if resolve_commit(&untracked).is_err() {
+        return Ok(Vec::new());
+    }
+    let output = git_cmd()
+        .args(["ls-tree", "-r", "--name-only", &untracked])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
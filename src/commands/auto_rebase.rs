@@ -0,0 +1,93 @@
+use anyhow::{bail, Result};
+
+use crate::cli::AutoRebaseAction;
+use crate::git::{run_git_quiet_with_env, run_git_silent};
+use crate::progress::Phase;
+use crate::status::{get_config_value, get_current_branch, get_default_branch, is_ancestor};
+
+pub fn run_auto_rebase(action: Option<AutoRebaseAction>) -> Result<()> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        bail!("HEAD is detached - switch to a branch first");
+    }
+    let key = config_key(&branch);
+
+    match action.unwrap_or(AutoRebaseAction::Status) {
+        AutoRebaseAction::On => {
+            run_git_silent(&["config", "--local", key.as_str(), "true"])?;
+            println!(
+                "✓ 'sgit sync' on '{}' now rebases it onto the default branch",
+                branch
+            );
+            println!(
+                "  When the branch was already pushed, sync asks before updating it with --force-with-lease."
+            );
+        }
+        AutoRebaseAction::Off => {
+            // Exit code 5 means the key was not set, which is fine here.
+            let _ = run_git_silent(&["config", "--local", "--unset", key.as_str()]);
+            println!("✓ Auto-rebase turned off for '{}'", branch);
+        }
+        AutoRebaseAction::Status => {
+            if is_enabled(&branch) {
+                println!("Auto-rebase is on for '{}'.", branch);
+            } else {
+                println!("Auto-rebase is off for '{}'.", branch);
+                println!("  Turn it on with 'sgit auto-rebase on'.");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn config_key(branch: &str) -> String {
+    format!("branch.{}.sgitAutoRebase", branch)
+}
+
+fn is_enabled(branch: &str) -> bool {
+    get_config_value(&config_key(branch)).is_some_and(|v| v == "true")
+}
+
+/// Rebases the current branch onto `<remote>/<default>` when the branch opted in.
+/// Returns whether history was rewritten, so the caller knows a plain push will be rejected.
+pub(crate) fn auto_rebase_onto_default(remote: &str) -> Result<bool> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() || !is_enabled(&branch) {
+        return Ok(false);
+    }
+    let Some(default) = get_default_branch(remote) else {
+        eprintln!(
+            "⚠ Auto-rebase skipped: could not find the default branch of '{}'",
+            remote
+        );
+        return Ok(false);
+    };
+    if default == branch {
+        return Ok(false);
+    }
+
+    let onto = format!("{}/{}", remote, default);
+    if is_ancestor(&onto, "HEAD") {
+        println!("✓ '{}' already contains the latest {}", branch, onto);
+        return Ok(false);
+    }
+
+    println!("→ Rebasing '{}' onto {}...", branch, onto);
+    let phase = Phase::start("sync", "rebase");
+    if let Err(e) = run_git_quiet_with_env(
+        &["rebase", "--autostash", onto.as_str()],
+        &[("GIT_EDITOR", "true")],
+    ) {
+        phase.failed(&e.to_string());
+        eprintln!("✗ Auto-rebase stopped on conflicts with {}", onto);
+        eprintln!("  1. Edit the conflicting files (marked with <<<<<<<)");
+        eprintln!("  2. Run 'sgit stage .' to stage the resolved files");
+        eprintln!(
+            "  3. Run 'sgit continue' (or 'sgit abort' to undo the rebase), then 'sgit sync' again"
+        );
+        return Err(e);
+    }
+    phase.done();
+    println!("✓ Rebased onto {}", onto);
+    Ok(true)
+}
@@ -0,0 +1,339 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::AutosyncAction;
+use crate::commands::workspace::{sync_repo, Outcome};
+use crate::status::{get_git_dir, get_operation_in_progress, get_repo_root};
+
+/// The background sync rewrites its lock this often while it waits; a lock left alone
+/// for longer than a slow sync could take belongs to a process that died.
+const HEARTBEAT: Duration = Duration::from_secs(5);
+const STALE_AFTER: Duration = Duration::from_secs(120);
+
+const SHORTEST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lines of the log `sgit autosync status` shows.
+const STATUS_LOG_LINES: usize = 8;
+
+#[derive(Serialize, Deserialize)]
+struct Lock {
+    pid: u32,
+    interval_secs: u64,
+}
+
+pub fn run_autosync(action: Option<AutosyncAction>) -> Result<()> {
+    match action.unwrap_or(AutosyncAction::Status) {
+        AutosyncAction::Start { interval } => start(&interval),
+        AutosyncAction::Stop => stop(),
+        AutosyncAction::Status => status(),
+        AutosyncAction::Run { interval } => run_loop(Duration::from_secs(interval)),
+    }
+}
+
+fn sgit_dir() -> Result<PathBuf> {
+    Ok(Path::new(&get_git_dir()?).join("sgit"))
+}
+
+fn lock_path() -> Result<PathBuf> {
+    Ok(sgit_dir()?.join("autosync.lock"))
+}
+
+fn log_path() -> Result<PathBuf> {
+    Ok(sgit_dir()?.join("autosync.log"))
+}
+
+/// The lock of a background sync that is still alive.
+fn running_lock() -> Result<Option<Lock>> {
+    let path = lock_path()?;
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let fresh = fs::metadata(&path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < STALE_AFTER);
+    Ok(serde_json::from_str(&contents).ok().filter(|_| fresh))
+}
+
+/// `30s`, `10m`, `2h`, or a plain number of minutes.
+fn parse_interval(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((idx, _)) => text.split_at(idx),
+        None => (text, "m"),
+    };
+    let Ok(number) = number.parse::<u64>() else {
+        bail!(
+            "cannot read the interval '{}' - use e.g. 30s, 10m, or 1h",
+            text
+        );
+    };
+    let secs = match unit.trim() {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => bail!(
+            "cannot read the interval '{}' - use e.g. 30s, 10m, or 1h",
+            text
+        ),
+    };
+    let interval = Duration::from_secs(secs);
+    if interval < SHORTEST_INTERVAL {
+        bail!("sync at most every {} seconds", SHORTEST_INTERVAL.as_secs());
+    }
+    Ok(interval)
+}
+
+fn describe_interval(secs: u64) -> String {
+    match secs {
+        secs if secs.is_multiple_of(3600) => format!("{}h", secs / 3600),
+        secs if secs.is_multiple_of(60) => format!("{}m", secs / 60),
+        secs => format!("{}s", secs),
+    }
+}
+
+fn start(interval: &str) -> Result<()> {
+    let interval = parse_interval(interval)?;
+    if let Some(lock) = running_lock()? {
+        bail!(
+            "autosync is already running here (process {}, every {})\n  hint: 'sgit autosync stop' stops it",
+            lock.pid,
+            describe_interval(lock.interval_secs)
+        );
+    }
+    fs::create_dir_all(sgit_dir()?).context("failed to create .git/sgit")?;
+    let exe = env::current_exe().context("failed to locate the sgit binary")?;
+    let mut command = StdCommand::new(exe);
+    command
+        .args([
+            "autosync",
+            "run",
+            "--interval",
+            &interval.as_secs().to_string(),
+        ])
+        .current_dir(get_repo_root()?)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    detach(&mut command);
+    let child = command
+        .spawn()
+        .context("failed to start the background sync")?;
+
+    // The new process writes the lock once it is up.
+    for _ in 0..20 {
+        if running_lock()?.is_some_and(|lock| lock.pid == child.id()) {
+            println!(
+                "✓ Autosync started: fetching, pulling, and pushing every {} in the background",
+                describe_interval(interval.as_secs())
+            );
+            println!("  It only fast-forwards and pushes; anything that needs you is logged to");
+            println!(
+                "  {} and shown as a desktop notification where the system supports it.",
+                log_path()?.display()
+            );
+            println!(
+                "  'sgit autosync status' shows how it is doing, 'sgit autosync stop' ends it."
+            );
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    bail!(
+        "the background sync did not start - see {}",
+        log_path()?.display()
+    )
+}
+
+/// Keeps the background process running after the terminal that started it closes, and
+/// out of reach of its Ctrl-C.
+#[cfg(unix)]
+fn detach(command: &mut StdCommand) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(command: &mut StdCommand) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_command: &mut StdCommand) {}
+
+/// Removing the lock is the signal to stop: the background process checks it every
+/// second and exits once it is gone.
+fn stop() -> Result<()> {
+    let Some(lock) = running_lock()? else {
+        let _ = fs::remove_file(lock_path()?);
+        println!("Autosync is not running in this repository.");
+        return Ok(());
+    };
+    fs::remove_file(lock_path()?).context("failed to remove the autosync lock")?;
+    println!("✓ Autosync stopped (process {})", lock.pid);
+    Ok(())
+}
+
+fn status() -> Result<()> {
+    match running_lock()? {
+        Some(lock) => println!(
+            "Autosync is running (process {}, every {})",
+            lock.pid,
+            describe_interval(lock.interval_secs)
+        ),
+        None => println!("Autosync is not running - 'sgit autosync start' starts it."),
+    }
+    let log = fs::read_to_string(log_path()?).unwrap_or_default();
+    let lines: Vec<&str> = log.lines().collect();
+    if !lines.is_empty() {
+        println!();
+        println!("Recent activity:");
+        for line in &lines[lines.len().saturating_sub(STATUS_LOG_LINES)..] {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// The background process: syncs every `interval` while its lock is in place.
+fn run_loop(interval: Duration) -> Result<()> {
+    let pid = std::process::id();
+    let lock = serde_json::to_string(&Lock {
+        pid,
+        interval_secs: interval.as_secs(),
+    })?;
+    fs::write(lock_path()?, &lock).context("failed to write the autosync lock")?;
+    log(&format!(
+        "started (process {}, every {})",
+        pid,
+        describe_interval(interval.as_secs())
+    ));
+
+    let root = PathBuf::from(get_repo_root()?);
+    let mut last_problem: Option<String> = None;
+    'outer: loop {
+        sync_once(&root, &mut last_problem);
+        let mut waited = Duration::ZERO;
+        while waited < interval {
+            thread::sleep(Duration::from_secs(1));
+            waited += Duration::from_secs(1);
+            let ours = fs::read_to_string(lock_path()?)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Lock>(&contents).ok())
+                .is_some_and(|lock| lock.pid == pid);
+            if !ours {
+                break 'outer;
+            }
+            // Never creates the lock, so a stop in between is not undone.
+            if waited.as_secs().is_multiple_of(HEARTBEAT.as_secs())
+                && OpenOptions::new()
+                    .write(true)
+                    .truncate(true)
+                    .open(lock_path()?)
+                    .and_then(|mut file| file.write_all(lock.as_bytes()))
+                    .is_err()
+            {
+                break 'outer;
+            }
+        }
+    }
+    log("stopped");
+    Ok(())
+}
+
+fn sync_once(root: &Path, last_problem: &mut Option<String>) {
+    if let Ok(Some(operation)) = get_operation_in_progress() {
+        log(&format!("skipped: a {} is in progress", operation.name()));
+        return;
+    }
+    let problem = match sync_repo(root) {
+        Ok(Outcome::Done(text)) => {
+            // Quiet when there was nothing to do, so the log shows what happened.
+            if text != "up to date" {
+                log(&text);
+            }
+            None
+        }
+        Ok(Outcome::Skipped(reason)) => {
+            log(&format!("skipped: {}", reason));
+            None
+        }
+        Ok(Outcome::Failed(reason)) => Some(reason),
+        Err(e) => Some(e.to_string()),
+    };
+    if let Some(ref problem) = problem {
+        log(&format!("needs you: {}", problem));
+        // Said once, not again at every interval while it lasts.
+        if last_problem.as_ref() != Some(problem) {
+            notify(&format!("sgit autosync in {}", root.display()), problem);
+        }
+    }
+    *last_problem = problem;
+}
+
+fn log(message: &str) {
+    let Ok(path) = log_path() else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{}  {}", timestamp(), message);
+    }
+}
+
+/// The current time as `2024-05-01 14:03:09 UTC`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Howard Hinnant's days-to-civil conversion.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+/// A desktop notification, where the system has a way to show one; the log has it
+/// either way.
+fn notify(title: &str, message: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", message, title);
+        let mut command = StdCommand::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(windows) {
+        return;
+    } else {
+        let mut command = StdCommand::new("notify-send");
+        command.args([title, message]);
+        command
+    };
+    let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
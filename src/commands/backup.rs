@@ -0,0 +1,250 @@
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::cli::BackupAction;
+use crate::git::{git_cmd, git_failure, run_git_silent};
+use crate::progress::Phase;
+use crate::status::{get_config_value, get_remotes, get_repo_root};
+
+/// Local git config key naming the remote `sgit backup` pushes to.
+const BACKUP_REMOTE_KEY: &str = "sgit.backupRemote";
+
+/// Branches, tags, notes, and sgit's own refs such as the attic, each copied as it is.
+const MIRRORED_REFS: [&str; 4] = [
+    "+refs/heads/*:refs/heads/*",
+    "+refs/tags/*:refs/tags/*",
+    "+refs/notes/*:refs/notes/*",
+    "+refs/sgit/*:refs/sgit/*",
+];
+
+pub fn run_backup(action: Option<BackupAction>) -> Result<()> {
+    match action {
+        Some(BackupAction::Setup) => setup_backup(),
+        None => match get_config_value(BACKUP_REMOTE_KEY) {
+            Some(remote) => back_up(&remote),
+            None if stdin().is_terminal() => {
+                println!("No backup remote is set up for this repository yet.");
+                setup_backup()
+            }
+            None => bail!("no backup remote is set up yet\n  hint: run 'sgit backup setup' once"),
+        },
+    }
+}
+
+/// Pushes every branch, tag, and other ref to `remote`, making it an exact copy: refs
+/// deleted here are deleted there too.
+fn back_up(remote: &str) -> Result<()> {
+    let Some(url) = get_config_value(&format!("remote.{}.url", remote)) else {
+        bail!(
+            "the backup remote '{}' no longer exists\n  hint: pick another with 'sgit backup setup'",
+            remote
+        );
+    };
+    println!("→ Backing up to {} ({})...", remote, url);
+    // What `--mirror` would push, except remote-tracking refs: those include the backup
+    // remote's own, which would change with every backup.
+    let mut args = vec!["push", "--prune", "--porcelain", remote];
+    args.extend(MIRRORED_REFS);
+    let phase = Phase::start("backup", "push");
+    let output = git_cmd()
+        .args(&args)
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    if !output.status.success() {
+        let err = git_failure(&args, &String::from_utf8_lossy(&output.stderr));
+        phase.failed(&err.to_string());
+        return Err(err);
+    }
+    phase.done();
+
+    // Each ref is reported as `<flag>\t<from>:<to>\t<summary>`.
+    let (mut created, mut updated, mut deleted, mut unchanged) = (0, 0, 0, 0);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines().filter(|line| line.contains('\t')) {
+        match line.chars().next() {
+            Some('*') => created += 1,
+            Some(' ') | Some('+') => updated += 1,
+            Some('-') => deleted += 1,
+            Some('=') => unchanged += 1,
+            _ => {}
+        }
+    }
+    if created + updated + deleted == 0 {
+        println!("✓ The backup was already up to date ({} refs)", unchanged);
+    } else {
+        println!(
+            "✓ Backed up: {} new, {} updated, {} deleted, {} unchanged",
+            created, updated, deleted, unchanged
+        );
+    }
+    Ok(())
+}
+
+/// Asks for the remote to back up to, adding it when it is new, and remembers it.
+fn setup_backup() -> Result<()> {
+    if !stdin().is_terminal() {
+        bail!("'sgit backup setup' asks questions, so it needs a terminal");
+    }
+    let remotes = get_remotes()?;
+    let current = get_config_value(BACKUP_REMOTE_KEY);
+    let mut items: Vec<String> = remotes
+        .iter()
+        .map(|name| {
+            let url = get_config_value(&format!("remote.{}.url", name)).unwrap_or_default();
+            format!("{}  ({})", name, url)
+        })
+        .collect();
+    items.push("A new remote - enter its URL".to_string());
+    let default = current
+        .as_ref()
+        .and_then(|current| remotes.iter().position(|name| name == current))
+        .unwrap_or(items.len() - 1);
+    let choice = Select::new()
+        .with_prompt("Back up to which remote?")
+        .items(&items)
+        .default(default)
+        .interact()?;
+
+    let remote = match remotes.get(choice) {
+        Some(name) => {
+            if is_tracked_remote(name) && !confirm_shared_remote(name)? {
+                println!("Nothing changed.");
+                return Ok(());
+            }
+            name.clone()
+        }
+        None => match add_backup_remote(&remotes)? {
+            Some(name) => name,
+            None => {
+                println!("Nothing changed.");
+                return Ok(());
+            }
+        },
+    };
+
+    run_git_silent(&["config", "--local", BACKUP_REMOTE_KEY, &remote])?;
+    println!("✓ 'sgit backup' now mirrors this repository to {}", remote);
+    print_schedule_hint()?;
+    if Confirm::new()
+        .with_prompt("Back up now?")
+        .default(true)
+        .interact()?
+    {
+        back_up(&remote)?;
+    }
+    Ok(())
+}
+
+/// Whether a branch pulls from `remote`, which makes it a shared remote rather than a
+/// backup.
+fn is_tracked_remote(remote: &str) -> bool {
+    let output = git_cmd()
+        .args(["config", "--get-regexp", r"^branch\..*\.remote$"])
+        .output();
+    output.is_ok_and(|output| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(remote))
+    })
+}
+
+fn confirm_shared_remote(remote: &str) -> Result<bool> {
+    println!(
+        "⚠ Your branches pull from {}. A backup makes it an exact copy of this repository,",
+        remote
+    );
+    println!("  deleting every branch and tag on it that you do not have here.");
+    Ok(Confirm::new()
+        .with_prompt(format!("Use {} for backups anyway?", remote))
+        .default(false)
+        .interact()?)
+}
+
+/// Asks for a name and URL, checks the remote answers, and adds it. `None` when the user
+/// backs out.
+fn add_backup_remote(remotes: &[String]) -> Result<Option<String>> {
+    let name: String = Input::new()
+        .with_prompt("Name for the remote")
+        .default("backup".to_string())
+        .validate_with(|name: &String| -> std::result::Result<(), String> {
+            let name = name.trim();
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                Err("use a name without spaces".to_string())
+            } else if remotes.iter().any(|remote| remote == name) {
+                Err(format!(
+                    "'{}' already exists - pick it from the list instead",
+                    name
+                ))
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    let name = name.trim().to_string();
+    let url: String = Input::new()
+        .with_prompt(
+            "Its URL (e.g. git@backup.example.com:you/project.git or /mnt/nas/project.git)",
+        )
+        .interact_text()?;
+    let url = url.trim();
+    if url.is_empty() {
+        bail!("the remote URL cannot be empty");
+    }
+
+    println!("→ Checking {}...", url);
+    let output = git_cmd()
+        .args(["ls-remote", url])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .map(|line| line.trim_start_matches("fatal: ").trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or("git ls-remote failed");
+        bail!(
+            "cannot reach {}: {}\n  hint: check the URL, that the repository exists (create an empty one first), and that you have access",
+            url,
+            reason
+        );
+    }
+    let existing = String::from_utf8_lossy(&output.stdout).lines().count();
+    if existing > 0 {
+        println!(
+            "⚠ {} already has {} refs; backing up replaces them with this repository's.",
+            url, existing
+        );
+        if !Confirm::new()
+            .with_prompt("Use it anyway?")
+            .default(false)
+            .interact()?
+        {
+            return Ok(None);
+        }
+    }
+    run_git_silent(&["remote", "add", &name, url])?;
+    println!("✓ Added the remote {}", name);
+    Ok(Some(name))
+}
+
+fn print_schedule_hint() -> Result<()> {
+    let root = get_repo_root()?;
+    println!();
+    println!("To back up on a schedule, run 'sgit backup' from your scheduler, e.g.");
+    if cfg!(windows) {
+        println!(
+            "  schtasks /create /sc daily /tn \"sgit backup\" /tr \"cmd /c cd /d \\\"{}\\\" && sgit backup\"",
+            root
+        );
+    } else {
+        println!(
+            "  crontab -e, then add: 0 * * * * cd '{}' && sgit backup",
+            root
+        );
+    }
+    println!();
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::io::{stdin, stdout, IsTerminal};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::Input;
+
+use crate::commands::show::run_show;
+use crate::git::git_cmd;
+
+const UNCOMMITTED: &str = "0000000000000000000000000000000000000000";
+const SUMMARY_WIDTH: usize = 28;
+
+struct BlameCommit {
+    author: String,
+    time: u64,
+    summary: String,
+}
+
+struct BlameLine {
+    hash: String,
+    number: usize,
+    text: String,
+}
+
+pub fn run_blame(path: String, lines: Option<String>) -> Result<()> {
+    if !Path::new(&path).is_file() {
+        bail!("{} is not a file", path);
+    }
+    let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+    if let Some(range) = &lines {
+        args.push("-L".to_string());
+        args.push(parse_line_range(range)?);
+    }
+    args.push("--".to_string());
+    args.push(path.clone());
+
+    let output = git_cmd()
+        .args(&args)
+        .output()
+        .context("running git blame")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no such path") {
+            bail!(
+                "{} is not tracked yet - commit it first to see who changed what",
+                path
+            );
+        }
+        bail!("git blame failed: {}", stderr.trim());
+    }
+    let (commits, blamed) = parse_porcelain(&String::from_utf8_lossy(&output.stdout));
+    if blamed.is_empty() {
+        println!("{} is empty.", path);
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let author_width = commits
+        .values()
+        .map(|c| c.author.chars().count().min(16))
+        .max()
+        .unwrap_or(0);
+    let number_width = blamed
+        .last()
+        .map(|l| l.number.to_string().len())
+        .unwrap_or(1);
+    for line in &blamed {
+        let commit = &commits[&line.hash];
+        let (short, date) = if line.hash == UNCOMMITTED {
+            ("-------", "not committed".to_string())
+        } else {
+            (
+                &line.hash[..7],
+                relative_time(now.saturating_sub(commit.time)),
+            )
+        };
+        println!(
+            "{} {:<aw$} {:<14} {:<sw$} {:>nw$} │ {}",
+            short,
+            truncate(&commit.author, 16),
+            date,
+            truncate(&commit.summary, SUMMARY_WIDTH),
+            line.number,
+            line.text,
+            aw = author_width,
+            sw = SUMMARY_WIDTH,
+            nw = number_width,
+        );
+    }
+
+    if stdin().is_terminal() && stdout().is_terminal() {
+        offer_commit_view(&blamed)?;
+    }
+    Ok(())
+}
+
+/// Lets the user open the commit behind a line, until they leave the prompt empty.
+fn offer_commit_view(blamed: &[BlameLine]) -> Result<()> {
+    loop {
+        println!();
+        let answer: String = Input::new()
+            .with_prompt("Line number to see its commit (Enter to quit)")
+            .allow_empty(true)
+            .interact_text()?;
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(());
+        }
+        let Some(line) = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| blamed.iter().find(|l| l.number == n))
+        else {
+            println!("No line {} in the output above.", answer);
+            continue;
+        };
+        if line.hash == UNCOMMITTED {
+            println!("Line {} has not been committed yet.", line.number);
+            continue;
+        }
+        println!();
+        run_show(Some(line.hash.clone()))?;
+    }
+}
+
+/// Accepts `10-20`, `10,20`, `10` (to the end), or `10,+5`, returning git's `-L` form.
+fn parse_line_range(range: &str) -> Result<String> {
+    let range = range.trim();
+    let (start, end) = match range.split_once(['-', ',']) {
+        Some((start, end)) => (start.trim(), Some(end.trim())),
+        None => (range, None),
+    };
+    let valid_number = |s: &str| s.parse::<usize>().is_ok_and(|n| n > 0);
+    if !valid_number(start) {
+        bail!("'{}' is not a line range - use e.g. 10-20", range);
+    }
+    match end {
+        None | Some("") => Ok(format!("{},", start)),
+        Some(end) if valid_number(end.trim_start_matches('+')) => Ok(format!("{},{}", start, end)),
+        Some(_) => bail!("'{}' is not a line range - use e.g. 10-20", range),
+    }
+}
+
+fn parse_porcelain(output: &str) -> (HashMap<String, BlameCommit>, Vec<BlameLine>) {
+    let mut commits: HashMap<String, BlameCommit> = HashMap::new();
+    let mut blamed = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+
+    for line in output.lines() {
+        if let Some(text) = line.strip_prefix('\t') {
+            if let Some((hash, number)) = current.take() {
+                blamed.push(BlameLine {
+                    hash,
+                    number,
+                    text: text.to_string(),
+                });
+            }
+            continue;
+        }
+        let Some((hash, _)) = &current else {
+            // `<hash> <original line> <final line> [<group size>]` starts each entry.
+            let mut fields = line.split(' ');
+            let hash = fields.next().unwrap_or_default().to_string();
+            let number = fields.nth(1).and_then(|n| n.parse().ok()).unwrap_or(0);
+            commits.entry(hash.clone()).or_insert_with(|| BlameCommit {
+                author: String::new(),
+                time: 0,
+                summary: String::new(),
+            });
+            current = Some((hash, number));
+            continue;
+        };
+        // Commit details follow only the first entry for each commit.
+        let Some(commit) = commits.get_mut(hash) else {
+            continue;
+        };
+        if let Some(author) = line.strip_prefix("author ") {
+            commit.author = author.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            commit.time = time.parse().unwrap_or(0);
+        } else if let Some(summary) = line.strip_prefix("summary ") {
+            commit.summary = summary.to_string();
+        }
+    }
+    // git reports edits in the working tree as "Not Committed Yet" with a made-up summary.
+    if let Some(commit) = commits.get_mut(UNCOMMITTED) {
+        commit.author = "you".to_string();
+        commit.summary = String::new();
+    }
+    (commits, blamed)
+}
+
+pub(crate) fn relative_time(secs: u64) -> String {
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+    format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(width - 1).collect();
+        format!("{}…", cut)
+    }
+}
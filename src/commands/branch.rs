@@ -1,6 +1,7 @@
 use anyhow::{bail, Result};
-use dialoguer::{Input, Select};
+use dialoguer::Input;
 
+use crate::commands::picker::{Picked, RefKind, RefPicker};
 use crate::git::run_git_silent;
 use crate::status::{get_branches, get_current_branch};
 
@@ -18,46 +19,58 @@ pub fn create_branch(branch_name: &str) -> Result<()> {
 }
 
 pub fn run_branch_interactive() -> Result<()> {
-    let branches = get_branches()?;
-    let current = get_current_branch().unwrap_or_default();
-
-    let mut display_branches: Vec<String> = branches
-        .iter()
-        .map(|b| {
-            if b == &current {
-                format!("{} (current)", b)
+    let picked = RefPicker::new(
+        "Select a branch to checkout (type to search)",
+        &[RefKind::Branch, RefKind::RemoteBranch],
+    )
+    .extra_item("Create new branch...")
+    .interact()?;
+
+    match picked {
+        Some(Picked::Ref(branch)) if branch.kind == RefKind::RemoteBranch => {
+            checkout_remote_branch(&branch.name)?;
+        }
+        Some(Picked::Ref(branch)) => {
+            if branch.name == get_current_branch().unwrap_or_default() {
+                println!("Already on branch '{}'.", branch.name);
             } else {
-                b.clone()
+                run_git_silent(&["checkout", branch.name.as_str()])?;
+                println!("✓ Switched to branch '{}'", branch.name);
             }
-        })
-        .collect();
-    display_branches.push("Create new branch...".to_string());
-
-    let selection = Select::new()
-        .with_prompt("Select a branch to checkout")
-        .items(&display_branches)
-        .default(0)
-        .interact()?;
+        }
+        Some(Picked::Extra(0)) | None => {
+            let branch_name: String = Input::new().with_prompt("New branch name").interact()?;
 
-    if selection == branches.len() {
-        let branch_name: String = Input::new().with_prompt("New branch name").interact()?;
+            if branch_name.is_empty() {
+                bail!("branch name cannot be empty");
+            }
 
-        if branch_name.is_empty() {
-            bail!("branch name cannot be empty");
+            let normalized_name = branch_name.trim().replace(' ', "-");
+            run_git_silent(&["checkout", "-b", &normalized_name])?;
+            println!("✓ Created and switched to branch '{}'", normalized_name);
         }
+        Some(Picked::Extra(_)) => {}
+    }
 
-        let normalized_name = branch_name.trim().replace(' ', "-");
-        run_git_silent(&["checkout", "-b", &normalized_name])?;
-        println!("✓ Created and switched to branch '{}'", normalized_name);
+    Ok(())
+}
+
+/// Switches to the local branch for `remote_branch` (e.g. `origin/feature`), creating a
+/// tracking branch when there is none yet.
+fn checkout_remote_branch(remote_branch: &str) -> Result<()> {
+    let local = remote_branch
+        .split_once('/')
+        .map(|(_, name)| name)
+        .unwrap_or(remote_branch);
+    if get_branches()?.iter().any(|b| b == local) {
+        run_git_silent(&["checkout", local])?;
+        println!("✓ Switched to branch '{}'", local);
     } else {
-        let selected_branch = &branches[selection];
-        if selected_branch == &current {
-            println!("Already on branch '{}'.", selected_branch);
-        } else {
-            run_git_silent(&["checkout", selected_branch])?;
-            println!("✓ Switched to branch '{}'", selected_branch);
-        }
+        run_git_silent(&["checkout", "-b", local, "--track", remote_branch])?;
+        println!(
+            "✓ Created '{}' tracking {} and switched to it",
+            local, remote_branch
+        );
     }
-
     Ok(())
 }
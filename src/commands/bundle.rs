@@ -0,0 +1,402 @@
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Input, MultiSelect, Select};
+
+use crate::cli::BundleAction;
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::format::format_size;
+use crate::git::{check_in_repo, git_cmd, run_git_quiet, run_git_silent};
+use crate::status::{get_branches, get_current_branch, get_repo_root, is_ancestor, resolve_commit};
+use crate::workspace::repo_name;
+
+/// Lists the refs in a bundle and whether it needs commits the receiver must already have.
+fn print_contents(file: &str) -> Result<()> {
+    for (_, name) in list_heads(file)? {
+        println!("  contains {}", name);
+    }
+    let prerequisites = prerequisites(file)?;
+    if prerequisites.is_empty() {
+        println!("  Self-contained: it can be imported into an empty directory.");
+    } else {
+        println!(
+            "  The receiving repository must already have {} commit(s), e.g. {}.",
+            prerequisites.len(),
+            &prerequisites[0][..7.min(prerequisites[0].len())]
+        );
+    }
+    Ok(())
+}
+
+/// Fails unless the bundle is intact and this repository has every commit it builds on.
+fn verify_applies(file: &str) -> Result<()> {
+    if let Err(err) = run_git_silent(&["bundle", "verify", "--quiet", file]) {
+        let missing = prerequisites(file)?
+            .into_iter()
+            .filter(|hash| resolve_commit(hash).is_err())
+            .count();
+        if missing > 0 {
+            bail!(
+                "this repository lacks {} commit(s) the bundle builds on - import an earlier bundle or fetch from the original remote first",
+                missing
+            );
+        }
+        return Err(err.context(format!("{} failed verification", file)));
+    }
+    Ok(())
+}
+
+/// Points `local` at the fetched `hash`: creates the branch, fast-forwards it, or, when
+/// the two have diverged, parks the bundle's version at `bundle/<short_source>`.
+fn update_branch(local: &str, hash: &str, short_source: &str) -> Result<()> {
+    if !get_branches()?.iter().any(|branch| branch == local) {
+        run_git_silent(&["branch", local, hash])?;
+        println!("✓ Created branch '{}' at {}", local, &hash[..7]);
+    } else if is_ancestor(hash, local) {
+        println!("✓ '{}' already contains everything in the bundle", local);
+    } else if is_ancestor(local, hash) {
+        if get_current_branch().unwrap_or_default() == local {
+            run_git_quiet(&["merge", "--ff-only", "--quiet", hash])?;
+        } else {
+            run_git_silent(&["branch", "-f", local, hash])?;
+        }
+        println!("✓ Fast-forwarded '{}' to {}", local, &hash[..7]);
+    } else {
+        let parked = format!("refs/remotes/bundle/{}", short_source);
+        run_git_silent(&["update-ref", &parked, hash])?;
+        println!(
+            "⚠ '{}' and the bundle have diverged; the bundle's version is at bundle/{}.",
+            local, short_source
+        );
+        println!(
+            "  Compare with 'sgit diff --against bundle/{}' and merge it when ready.",
+            short_source
+        );
+    }
+    Ok(())
+}
+
+fn clone_bundle(file: &str) -> Result<()> {
+    let stem = Path::new(file)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("bundle")
+        .to_string();
+    let dir: String = Input::new()
+        .with_prompt("Not in a repository - clone the bundle into directory")
+        .default(stem)
+        .interact_text()?;
+    if Path::new(&dir).exists() {
+        bail!("{} already exists - choose another directory", dir);
+    }
+    println!("→ Cloning {} into {}...", file, dir);
+    run_git_quiet(&["clone", "--quiet", file, &dir])
+        .context("the bundle could not be cloned - it may only contain changes on top of an existing repository")?;
+    println!("✓ Cloned into {}", dir);
+    Ok(())
+}
+
+/// `sgit bundle`: branches (and tags) in one file, for machines that cannot reach each
+/// other.
+pub fn run_bundle(action: BundleAction) -> Result<()> {
+    match action {
+        BundleAction::Create {
+            refs,
+            all,
+            since,
+            output,
+        } => create(refs, all, since, output),
+        BundleAction::Verify { file } => verify(&file),
+        BundleAction::Fetch {
+            file,
+            branches,
+            into,
+        } => fetch(&file, &branches, into),
+    }
+}
+
+fn create(
+    refs: Vec<String>,
+    all: bool,
+    since: Option<String>,
+    output: Option<String>,
+) -> Result<()> {
+    check_in_repo()?;
+    let local = get_branches()?;
+    if local.is_empty() {
+        bail!("there are no branches to bundle yet - make a commit first");
+    }
+    let interactive = refs.is_empty() && !all && stdin().is_terminal();
+    let refs = if all {
+        local.clone()
+    } else if !refs.is_empty() {
+        refs
+    } else if interactive {
+        pick_branches(&local)?
+    } else {
+        bail!("name the branches to bundle, or pass --all");
+    };
+    if refs.is_empty() {
+        println!("No branches picked - nothing to bundle.");
+        return Ok(());
+    }
+    let mut full_refs = Vec::new();
+    for name in &refs {
+        if local.contains(name) {
+            full_refs.push(format!("refs/heads/{}", name));
+        } else if resolve_ref(&format!("refs/tags/{}", name)).is_some() {
+            full_refs.push(format!("refs/tags/{}", name));
+        } else {
+            bail!(
+                "'{}' is not a local branch or tag\n  hint: 'sgit branch' lists the branches",
+                name
+            );
+        }
+    }
+    let since = match since {
+        Some(since) => Some(since),
+        None if interactive => pick_since()?,
+        None => None,
+    };
+    if let Some(since) = &since {
+        resolve_commit(since)?;
+    }
+
+    let file = match (output, refs.as_slice()) {
+        (Some(file), _) => file,
+        (None, [only]) => format!(
+            "{}-{}.bundle",
+            repo_name(Path::new(&get_repo_root()?)),
+            only.replace('/', "-")
+        ),
+        (None, _) => format!("{}.bundle", repo_name(Path::new(&get_repo_root()?))),
+    };
+    if Path::new(&file).exists() {
+        bail!(
+            "{} already exists - pass --output to choose another file",
+            file
+        );
+    }
+
+    let mut args = vec!["bundle", "create", file.as_str()];
+    args.extend(full_refs.iter().map(String::as_str));
+    // With HEAD in it, cloning the bundle checks out the current branch.
+    if refs.contains(&get_current_branch().unwrap_or_default()) {
+        args.push("HEAD");
+    }
+    let exclude = since.as_ref().map(|since| format!("^{}", since));
+    if let Some(exclude) = &exclude {
+        args.push(exclude);
+    }
+    println!(
+        "→ Writing {}{} to {}...",
+        refs.join(", "),
+        since
+            .as_ref()
+            .map(|since| format!(" (commits since {})", since))
+            .unwrap_or_default(),
+        file
+    );
+    run_git_silent(&args).with_context(|| match &since {
+        Some(since) => format!(
+            "could not write {} - is there anything new since {}?",
+            file, since
+        ),
+        None => format!("could not write {}", file),
+    })?;
+    run_git_silent(&["bundle", "verify", "--quiet", &file])
+        .context("the bundle was written but does not verify - try creating it again")?;
+
+    let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+    println!("✓ Wrote {} ({})", file, format_size(size));
+    print_contents(&file)?;
+    println!(
+        "  Carry it over, then run 'sgit bundle fetch {}' there.",
+        file
+    );
+    Ok(())
+}
+
+fn pick_branches(local: &[String]) -> Result<Vec<String>> {
+    let current = get_current_branch().unwrap_or_default();
+    let defaults: Vec<bool> = local.iter().map(|branch| *branch == current).collect();
+    let picked = MultiSelect::new()
+        .with_prompt("Branches to bundle (space to tick, enter to write the file)")
+        .items(local)
+        .defaults(&defaults)
+        .interact()?;
+    Ok(picked.into_iter().map(|idx| local[idx].clone()).collect())
+}
+
+/// Asks whether the bundle needs the whole history, or only what the receiver lacks.
+fn pick_since() -> Result<Option<String>> {
+    let scope = Select::new()
+        .with_prompt("How much history?")
+        .items(&[
+            "All of it (works for an empty repository)",
+            "Only commits since a tag or commit the other side already has",
+        ])
+        .default(0)
+        .interact()?;
+    if scope == 0 {
+        return Ok(None);
+    }
+    match RefPicker::new(
+        "The other side already has (type to search)",
+        &[RefKind::Tag, RefKind::Commit],
+    )
+    .interact()?
+    {
+        Some(Picked::Ref(base)) => Ok(Some(base.name)),
+        _ => Ok(None),
+    }
+}
+
+/// The refs in `file`, failing when it is missing or not a bundle.
+fn bundle_heads(file: &str) -> Result<Vec<(String, String)>> {
+    if !Path::new(file).is_file() {
+        bail!("{} does not exist", file);
+    }
+    let heads = list_heads(file)?;
+    if heads.is_empty() {
+        bail!("{} is not a git bundle, or contains no branches", file);
+    }
+    Ok(heads)
+}
+
+fn verify(file: &str) -> Result<()> {
+    bundle_heads(file)?;
+    verify_applies(file)?;
+    println!("✓ {} is intact and applies to this repository", file);
+    print_contents(file)?;
+    println!("  'sgit bundle fetch {}' brings its branches in.", file);
+    Ok(())
+}
+
+/// Fetches the chosen branches and tags of a bundle into local ones of the same names
+/// (or, with `into`, the one branch into that branch), or clones the bundle when run
+/// outside a repository.
+fn fetch(file: &str, names: &[String], into: Option<String>) -> Result<()> {
+    let heads = bundle_heads(file)?;
+    if check_in_repo().is_err() {
+        return clone_bundle(file);
+    }
+    verify_applies(file)?;
+
+    let chosen: Vec<(String, String)> = if !names.is_empty() {
+        let mut chosen = Vec::new();
+        for name in names {
+            match heads.iter().find(|(_, head)| short_ref(head) == name) {
+                Some(head) => chosen.push(head.clone()),
+                None => bail!(
+                    "'{}' is not in {}\n  hint: 'sgit bundle verify {}' lists what is",
+                    name,
+                    file,
+                    file
+                ),
+            }
+        }
+        chosen
+    } else if heads.len() == 1 || !stdin().is_terminal() {
+        heads
+    } else {
+        let labels: Vec<&str> = heads.iter().map(|(_, name)| short_ref(name)).collect();
+        let picked = MultiSelect::new()
+            .with_prompt("What to fetch (space to tick, enter to fetch)")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()?;
+        picked.into_iter().map(|idx| heads[idx].clone()).collect()
+    };
+    if chosen.is_empty() {
+        println!("Nothing picked - nothing fetched.");
+        return Ok(());
+    }
+    if into.is_some() && (chosen.len() > 1 || !chosen[0].1.starts_with("refs/heads/")) {
+        bail!(
+            "--into takes one branch - name it: 'sgit bundle fetch {} <branch> --into <name>'",
+            file
+        );
+    }
+
+    let names: Vec<&str> = chosen.iter().map(|(_, name)| short_ref(name)).collect();
+    println!("→ Fetching {} from the bundle...", names.join(", "));
+    let mut args = vec!["fetch", "--quiet", file];
+    args.extend(chosen.iter().map(|(_, name)| name.as_str()));
+    run_git_quiet(&args)?;
+    for (hash, name) in &chosen {
+        let short = short_ref(name);
+        if name.starts_with("refs/tags/") {
+            update_tag(short, hash)?;
+        } else {
+            update_branch(into.as_deref().unwrap_or(short), hash, short)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates tag `name` at `hash`; a tag of that name elsewhere is left alone.
+fn update_tag(name: &str, hash: &str) -> Result<()> {
+    let tag = format!("refs/tags/{}", name);
+    match resolve_ref(&tag) {
+        None => {
+            run_git_silent(&["update-ref", &tag, hash])?;
+            println!("✓ Created tag '{}' at {}", name, &hash[..7]);
+        }
+        Some(existing) if existing == hash => println!("✓ Tag '{}' is already here", name),
+        Some(_) => println!(
+            "⚠ Tag '{}' already points somewhere else here; left it as it is.",
+            name
+        ),
+    }
+    Ok(())
+}
+
+fn resolve_ref(name: &str) -> Option<String> {
+    let output = git_cmd()
+        .args(["rev-parse", "--verify", "--quiet", name])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns `(hash, refname)` for every ref stored in the bundle.
+fn list_heads(file: &str) -> Result<Vec<(String, String)>> {
+    let output = git_cmd()
+        .args(["bundle", "list-heads", file])
+        .output()
+        .context("running git bundle list-heads")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let (hash, name) = line.split_once(' ')?;
+            (name != "HEAD").then(|| (hash.to_string(), name.to_string()))
+        })
+        .collect())
+}
+
+/// Commits the bundle builds on, read from the `-<hash>` lines of its header.
+fn prerequisites(file: &str) -> Result<Vec<String>> {
+    let reader = BufReader::new(File::open(file).with_context(|| format!("opening {}", file))?);
+    // The header ends at the first empty line; the pack data follows.
+    Ok(reader
+        .split(b'\n')
+        .map_while(|line| line.ok())
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| String::from_utf8(line).ok())
+        .filter_map(|line| line.strip_prefix('-').map(str::to_string))
+        .filter_map(|rest| rest.split_whitespace().next().map(str::to_string))
+        .collect())
+}
+
+fn short_ref(name: &str) -> &str {
+    name.strip_prefix("refs/heads/")
+        .or_else(|| name.strip_prefix("refs/tags/"))
+        .unwrap_or(name)
+}
@@ -0,0 +1,142 @@
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::cli::CiAction;
+use crate::forge::{CheckRun, CheckState, Forge, ForgeKind};
+use crate::status::{get_current_branch, get_unpushed_commits, get_upstream, resolve_commit};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+pub fn run_ci(action: CiAction) -> Result<()> {
+    match action {
+        CiAction::Status { target, watch } => run_ci_status(target, watch),
+    }
+}
+
+fn run_ci_status(target: Option<String>, watch: bool) -> Result<()> {
+    let forge = Forge::detect()?;
+    let (sha, label) = match target {
+        Some(target) => (resolve_commit(&target)?, target),
+        None => match open_pr_head(&forge)? {
+            Some(head) => head,
+            None => commit_to_check()?,
+        },
+    };
+    println!("CI for {} on {}", label, forge.host);
+
+    loop {
+        let runs = forge.fetch_check_runs(&sha)?;
+        if runs.is_empty() {
+            println!("  No checks reported for this commit (yet).");
+            if !watch {
+                return Ok(());
+            }
+        } else {
+            print_runs(&runs);
+        }
+
+        let pending = runs
+            .iter()
+            .filter(|r| r.state == CheckState::Pending)
+            .count();
+        let failed = runs
+            .iter()
+            .filter(|r| r.state == CheckState::Failure)
+            .count();
+        if watch && (pending > 0 || runs.is_empty()) {
+            println!(
+                "… {} check(s) still running - checking again in {}s (Ctrl-C to stop)",
+                pending,
+                WATCH_INTERVAL.as_secs()
+            );
+            thread::sleep(WATCH_INTERVAL);
+            println!();
+            continue;
+        }
+
+        if failed > 0 {
+            bail!("{} check(s) failed", failed);
+        }
+        if pending > 0 {
+            println!(
+                "… {} check(s) still running - use --watch to wait for them",
+                pending
+            );
+        } else {
+            println!("✓ All checks passed");
+        }
+        return Ok(());
+    }
+}
+
+/// The head commit of the current branch's open pull request, plus a label for it.
+fn open_pr_head(forge: &Forge) -> Result<Option<(String, String)>> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        return Ok(None);
+    }
+    let Some(pr) = forge.find_open_pr(&branch)? else {
+        return Ok(None);
+    };
+    let (kind, number) = match forge.kind {
+        ForgeKind::GitHub => ("pull request", format!("#{}", pr.number)),
+        ForgeKind::GitLab => ("merge request", format!("!{}", pr.number)),
+    };
+    println!("Open {} {}: {}", kind, number, pr.title);
+    println!("  {}", pr.url);
+    if resolve_commit("HEAD").is_ok_and(|head| head != pr.head_sha) {
+        println!(
+            "  Your HEAD is not what the {} has; push to update it.",
+            kind
+        );
+    }
+    let label = format!(
+        "the head of {} ({})",
+        number,
+        &pr.head_sha[..7.min(pr.head_sha.len())]
+    );
+    Ok(Some((pr.head_sha, label)))
+}
+
+/// The commit whose checks to show when the branch has no open pull request, plus a
+/// label for it. When HEAD hasn't been pushed, CI can't have run on it, so the upstream
+/// tip is used instead.
+fn commit_to_check() -> Result<(String, String)> {
+    let head = resolve_commit("HEAD")?;
+    if get_unpushed_commits()?.contains(&head)
+        && let Some(upstream) = get_upstream()
+    {
+        println!("HEAD is not pushed yet - showing {} instead.", upstream);
+        let sha = resolve_commit(&upstream)?;
+        return Ok((sha, upstream));
+    }
+    let label = format!("HEAD ({})", &head[..7]);
+    Ok((head, label))
+}
+
+fn print_runs(runs: &[CheckRun]) {
+    let width = runs.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for run in runs {
+        let (icon, state) = match run.state {
+            CheckState::Success => ("✓", "passed"),
+            CheckState::Failure => ("✗", "failed"),
+            CheckState::Pending => ("…", "running"),
+            CheckState::Skipped => ("-", "skipped"),
+        };
+        let duration = run
+            .duration_secs
+            .map(|secs| format!("{}m{:02}s", secs / 60, secs % 60))
+            .unwrap_or_default();
+        println!(
+            "  {} {:<width$}  {:<7}  {:>7}  {}",
+            icon,
+            run.name,
+            state,
+            duration,
+            run.url.as_deref().unwrap_or(""),
+            width = width
+        );
+    }
+}
@@ -1,32 +1,118 @@
-use std::process::Command as StdCommand;
+use std::collections::BTreeSet;
+use std::io::{stdin, stdout, IsTerminal};
+use std::path::Path;
 
-use anyhow::{bail, Result};
-use dialoguer::{Confirm, Input, Select};
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, FuzzySelect, Input, MultiSelect, Select};
 
-use crate::git::{run_git_quiet, run_git_silent};
-use crate::status::{get_current_branch, get_repo_root, PorcelainStatus};
+use crate::checks::run_staged_checks;
+use crate::choices::{remember_answer, remember_choice, usual_answer, usual_choice};
+use crate::commands::branch::create_branch;
+use crate::commands::file_picker::{select_files, Preview};
+use crate::commands::fixup::resolve_fixup_target;
+use crate::commands::setup::ensure_identity;
+use crate::config::Config;
+use crate::conventional::{
+    check_scope, format_header, suggest_scope, with_gitmoji, COMMIT_TYPES, GITMOJIS,
+};
+use crate::format::format_size;
+use crate::git::{
+    git_cmd, git_command, git_failure, git_output, run_git_in_dir_silent, run_git_quiet,
+    run_git_silent,
+};
+use crate::hooks::{has_precommit_config, hooks_dir, installed_hooks, precommit_installed};
+use crate::lfs;
+use crate::lint;
+use crate::message::{commit_template, edit_in_editor, strip_comments};
+use crate::status::{
+    get_ahead_behind, get_commit_message, get_config_value, get_current_branch,
+    get_porcelain_lines, get_repo_root, get_staged_blobs, get_staged_files, get_upstream,
+    resolve_commit, PorcelainStatus, EMPTY_TREE,
+};
+use crate::whitespace::{self, Issue};
 
-pub fn run_commit(
-    message: Option<String>,
-    all: bool,
-    staged: bool,
-    unstaged: bool,
-    push: bool,
-    amend: bool,
-    no_verify: bool,
-) -> Result<()> {
-    let is_interactive = message.is_none() && !all && !staged && !unstaged;
+/// How many recent contributors the co-author picker lists.
+const CO_AUTHOR_LIMIT: usize = 30;
+
+pub struct CommitOptions {
+    pub message: Option<String>,
+    pub all: bool,
+    pub staged: bool,
+    pub unstaged: bool,
+    pub push: bool,
+    pub amend: bool,
+    pub no_verify: bool,
+    pub when_green: bool,
+    /// `Some("")` means "pick the target interactively".
+    pub fixup: Option<String>,
+    /// Commit without asking to confirm the summary.
+    pub yes: bool,
+    /// Skip the `[lint]` checks on the message.
+    pub no_lint: bool,
+    /// Write the message in the editor, starting from `message` or the commit template.
+    pub edit: bool,
+    /// Sign the commit (also on when `[commit] sign` is set).
+    pub sign: bool,
+    /// Amend without changing the message.
+    pub no_edit: bool,
+}
+
+pub fn run_commit(opts: CommitOptions) -> Result<()> {
+    let CommitOptions {
+        message,
+        all,
+        staged,
+        unstaged,
+        push,
+        amend,
+        no_verify,
+        when_green,
+        fixup,
+        yes,
+        no_lint,
+        edit,
+        sign,
+        no_edit,
+    } = opts;
+
+    if no_edit && (!amend || message.is_some() || edit) {
+        bail!("--no-edit only works with --amend, and not together with --message or --edit");
+    }
+    if fixup.is_some() && (amend || message.is_some() || edit) {
+        bail!("--fixup cannot be combined with --amend, --message, or --edit");
+    }
+    let fixup_target = match fixup {
+        Some(ref target) => Some(resolve_fixup_target(target)?),
+        None => None,
+    };
+
+    ensure_identity()?;
+    let config = Config::load()?;
+    if !leave_protected_branch(&config)? {
+        println!("Aborted.");
+        return Ok(());
+    }
+    if !amend && !check_behind_upstream()? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let is_interactive =
+        message.is_none() && fixup_target.is_none() && !no_edit && !all && !staged && !unstaged;
+    let mut keep_message = no_edit;
     let (all, staged, unstaged, commit_msg, push, custom_files) = if is_interactive {
+        let scope_items = [
+            "Staged changes",
+            "Unstaged changes",
+            "All changes",
+            "Custom",
+        ];
         let scope = Select::new()
             .with_prompt("What would you like to commit?")
-            .items(&[
-                "Staged changes",
-                "Unstaged changes",
-                "All changes",
-                "Custom",
-            ])
-            .default(0)
+            .items(&scope_items)
+            .default(usual_choice("commit.what", &scope_items, 0))
             .interact()?;
+        remember_choice("commit.what", scope_items[scope]);
 
         let (all, staged, unstaged) = match scope {
             0 => (false, true, false),
@@ -44,10 +130,8 @@ pub fn run_commit(
                 return Ok(());
             }
             let files_owned: Vec<String> = files.iter().map(|s| s.to_string()).collect();
-            let selected = dialoguer::MultiSelect::new()
-                .with_prompt("Select files to stage")
-                .items(&files_owned)
-                .interact()?;
+            let selected =
+                select_files("Select files to stage", &files_owned, Preview::Uncommitted)?;
 
             if selected.is_empty() {
                 println!("No files selected.");
@@ -59,27 +143,47 @@ pub fn run_commit(
             }
         }
 
-        let msg: String = Input::new().with_prompt("Commit message").interact()?;
+        if amend && !keep_message {
+            keep_message = prompt_keep_message()?;
+        }
+        let msg = if keep_message {
+            get_commit_message("HEAD")?
+        } else {
+            write_message(&config, edit, all, unstaged, &custom_files)?
+        };
         let should_push = Confirm::new()
             .with_prompt("Push after committing?")
-            .default(false)
+            .default(usual_answer("commit.push", false))
             .interact()?;
+        remember_answer("commit.push", should_push);
         (all, staged, unstaged, msg, should_push, custom_files)
     } else {
-        let msg = message.unwrap_or_default();
+        let msg = if keep_message {
+            get_commit_message("HEAD")?
+        } else if edit {
+            compose_in_editor(message.as_deref(), commit_template()?.as_deref())?
+        } else {
+            message.unwrap_or_default()
+        };
         (all, staged, unstaged, msg, push, Vec::new())
     };
 
-    if commit_msg.trim().is_empty() {
+    if fixup_target.is_none() && commit_msg.trim().is_empty() {
         bail!("commit message cannot be empty");
     }
+    if fixup_target.is_none() && !keep_message {
+        check_scope(&commit_msg, &config.commit.scopes)?;
+        if !no_lint {
+            lint::enforce(&commit_msg, &config.lint)?;
+        }
+    }
 
     if staged && (all || unstaged) {
         bail!("cannot combine --staged with --all or --unstaged");
     }
 
     if amend && !no_verify {
-        let has_commits = StdCommand::new("git")
+        let has_commits = git_cmd()
             .args(["log", "--oneline", "-n", "1"])
             .output()
             .ok()
@@ -101,6 +205,12 @@ pub fn run_commit(
         }
     }
 
+    // What was staged before sgit added anything, so declining the summary puts it back.
+    let index_before = if all || unstaged || !custom_files.is_empty() {
+        git_output(&["write-tree"]).ok()
+    } else {
+        None
+    };
     if all {
         run_git_silent(&["add", "-A"])?;
         println!("→ Staged all files");
@@ -112,10 +222,57 @@ pub fn run_commit(
         let mut args = vec!["add".to_string()];
         args.extend(custom_files.iter().cloned());
         let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-        crate::git::run_git_in_dir_silent(&args_refs, &repo_root)?;
+        run_git_in_dir_silent(&args_refs, &repo_root)?;
         println!("→ Staged {} file(s)", custom_files.len());
     }
 
+    suggest_lfs(yes)?;
+    let too_large = find_oversized(config.commit.max_file_size_mb)?;
+    if !too_large.is_empty() {
+        if let Some(tree) = &index_before {
+            run_git_silent(&["read-tree", tree.trim()])?;
+        }
+        print_oversized(&too_large, config.commit.max_file_size_mb);
+        bail!(
+            "commit blocked - files over {} MB are staged",
+            config.commit.max_file_size_mb
+        );
+    }
+    if !no_verify {
+        check_whitespace(yes)?;
+        if has_precommit_config() && !precommit_installed() {
+            println!("⚠ The pre-commit checks in .pre-commit-config.yaml are not installed, so they do not run - 'sgit hooks install-precommit'");
+        }
+    }
+
+    if when_green {
+        if config.checks.commands.is_empty() {
+            bail!(
+                "no checks configured - add a [checks] commands list to .sgit.toml to use --when-green"
+            );
+        }
+        if let Some(failure) = run_staged_checks(&config.checks.commands)? {
+            failure.print_summary();
+            bail!("checks failed - commit not created, your changes are still staged");
+        }
+        println!("✓ All checks passed");
+    }
+
+    let summary_message = match fixup_target {
+        Some(ref hash) => format!(
+            "fixup! {}",
+            git_output(&["log", "-1", "--format=%s", hash])?.trim()
+        ),
+        None => commit_msg.clone(),
+    };
+    if !confirm_summary(&summary_message, amend, yes)? {
+        if let Some(tree) = index_before {
+            run_git_silent(&["read-tree", tree.trim()])?;
+        }
+        println!("Aborted - nothing was committed.");
+        return Ok(());
+    }
+
     print!("→ Committing");
     if amend {
         print!(" (amend)");
@@ -129,11 +286,24 @@ pub fn run_commit(
     if no_verify {
         commit_args.push("--no-verify");
     }
-    commit_args.push("-m");
-    commit_args.push(commit_msg.as_str());
+    if sign || config.commit.sign {
+        commit_args.push("-S");
+    }
+    let fixup_arg = fixup_target.map(|hash| format!("--fixup={}", hash));
+    if let Some(ref fixup_arg) = fixup_arg {
+        commit_args.push(fixup_arg.as_str());
+    } else if keep_message {
+        commit_args.push("--no-edit");
+    } else {
+        commit_args.push("-m");
+        commit_args.push(commit_msg.as_str());
+    }
 
-    run_git_quiet(&commit_args)?;
+    create_commit(&commit_args, no_verify)?;
     println!("✓ Commit created");
+    if fixup_arg.is_some() {
+        println!("  Run 'sgit fixup apply' to fold it into its target before pushing.");
+    }
 
     if push {
         print!("→ Pushing");
@@ -149,3 +319,587 @@ pub fn run_commit(
     println!("Done.");
     Ok(())
 }
+
+/// The hooks `git commit` runs that can stop it.
+const COMMIT_HOOKS: &[&str] = &["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// Runs `git commit` with `args`. When a hook stops the commit, its output is shown as it
+/// wrote it instead of as a git error.
+fn create_commit(args: &[&str], no_verify: bool) -> Result<()> {
+    let output = git_command(args, None)
+        .0
+        .output()
+        .context("failed to execute git commit - is git installed?")?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // The hooks ran and passed when git got as far as writing the commit (e.g. signing).
+    if no_verify || stderr.contains("failed to write commit object") || !rejected_by_hook(args) {
+        return Err(git_failure(args, &stderr));
+    }
+
+    // git sends hooks' output to stderr.
+    eprint!("{}", String::from_utf8_lossy(&output.stdout));
+    eprint!("{}", stderr);
+    let hooks: Vec<_> = installed_hooks(&hooks_dir()?)?
+        .into_iter()
+        .filter(|hook| {
+            hook.enabled && hook.executable && COMMIT_HOOKS.contains(&hook.name.as_str())
+        })
+        .collect();
+    if hooks.iter().any(|hook| hook.precommit) {
+        eprintln!("  pre-commit may have fixed some files itself - check 'sgit status' and stage the fixes.");
+    }
+    let which = match hooks.as_slice() {
+        [hook] => format!("the {} hook", hook.name),
+        _ => "a commit hook".to_string(),
+    };
+    bail!(
+        "{} stopped the commit - fix what it reported above and commit again (--no-verify skips hooks)",
+        which
+    );
+}
+
+/// Whether git makes the commit when its hooks do not run.
+fn rejected_by_hook(args: &[&str]) -> bool {
+    let mut dry_run = vec!["commit", "--dry-run", "--no-verify"];
+    dry_run.extend(args.iter().skip(1));
+    git_command(&dry_run, None)
+        .0
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Lists whitespace and line-ending problems in the staged files and, on a terminal
+/// without `--yes`, offers to fix the staged copies.
+fn check_whitespace(yes: bool) -> Result<()> {
+    let files = whitespace::check_staged()?;
+    if files.is_empty() {
+        return Ok(());
+    }
+    println!("⚠ Whitespace problems in {} staged file(s):", files.len());
+    for file in &files {
+        println!("  {}", file.path);
+        for issue in &file.issues {
+            println!("    {}", issue.describe());
+        }
+    }
+    if !files
+        .iter()
+        .any(|file| file.issues.iter().any(Issue::is_fixable))
+    {
+        return Ok(());
+    }
+    if yes || !stdin().is_terminal() {
+        println!("  Committing anyway - run 'sgit commit' in a terminal to have them fixed.");
+        return Ok(());
+    }
+    let fix = Confirm::new()
+        .with_prompt("Fix them in the staged files before committing?")
+        .default(true)
+        .interact()?;
+    if !fix {
+        return Ok(());
+    }
+    let untouched = whitespace::fix_staged(&files)?;
+    println!("✓ Fixed the staged files");
+    for path in untouched {
+        println!(
+            "  {} has unstaged changes, so only its staged copy was fixed",
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Staged files bigger than `limit_mb` megabytes (none when the limit is 0).
+fn find_oversized(limit_mb: u64) -> Result<Vec<(String, u64)>> {
+    if limit_mb == 0 {
+        return Ok(Vec::new());
+    }
+    let limit = limit_mb.saturating_mul(1024 * 1024);
+    Ok(get_staged_blobs()?
+        .into_iter()
+        .filter(|(_, size)| *size > limit)
+        .collect())
+}
+
+fn print_oversized(files: &[(String, u64)], limit_mb: u64) {
+    eprintln!(
+        "✗ {} staged file(s) are bigger than the {} MB limit:",
+        files.len(),
+        limit_mb
+    );
+    for (path, size) in files {
+        eprintln!("  {} ({})", path, format_size(*size));
+    }
+    eprintln!(
+        "  Pushing a commit like this fails on most hosts (GitHub rejects files over 100 MB),"
+    );
+    eprintln!("  and once it is in the history, removing it means rewriting every later commit.");
+    eprintln!("  Instead:");
+    eprintln!("    - leave it out: 'sgit unstage <file>', then 'sgit ignore <file>'");
+    if lfs::is_installed() {
+        eprintln!("    - store it in Git LFS: 'git lfs track \"<pattern>\"', then stage it again");
+    } else {
+        eprintln!("    - store it in Git LFS (https://git-lfs.com)");
+    }
+    eprintln!("    - if the file really belongs in git, raise the limit: 'sgit config set commit.max_file_size_mb <MB>'");
+}
+
+/// Points out staged files that belong in Git LFS and, when the user agrees, tracks their
+/// types with LFS and restages them as LFS pointers.
+fn suggest_lfs(yes: bool) -> Result<()> {
+    let candidates = lfs::candidates(&get_staged_blobs()?)?;
+    if candidates.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "⚠ {} staged file(s) look like they belong in Git LFS:",
+        candidates.len()
+    );
+    for (path, size) in &candidates {
+        println!("  {} ({})", path, format_size(*size));
+    }
+    println!("  Binaries like these make every clone bigger with each version you commit.");
+    if !lfs::is_installed() {
+        println!("  Install Git LFS (https://git-lfs.com) to keep them out of the history.");
+        return Ok(());
+    }
+    let patterns: Vec<String> = candidates
+        .iter()
+        .map(|(path, _)| lfs::track_pattern(path))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if yes || !stdin().is_terminal() {
+        let quoted: Vec<String> = patterns.iter().map(|p| format!("\"{}\"", p)).collect();
+        println!(
+            "  To store them in LFS: 'git lfs track {}'",
+            quoted.join(" ")
+        );
+        return Ok(());
+    }
+    let track = Confirm::new()
+        .with_prompt(format!(
+            "Track {} with Git LFS before committing?",
+            patterns.join(", ")
+        ))
+        .default(true)
+        .interact()?;
+    if !track {
+        return Ok(());
+    }
+    lfs::track_patterns(&patterns)?;
+    let repo_root = get_repo_root()?;
+    run_git_in_dir_silent(&["add", "--", ".gitattributes"], &repo_root)?;
+    // The files are staged already; renormalizing runs them through the LFS filter.
+    let mut args = vec!["add", "--renormalize", "--"];
+    args.extend(candidates.iter().map(|(path, _)| path.as_str()));
+    run_git_in_dir_silent(&args, &repo_root)?;
+    println!(
+        "✓ Tracking {} with Git LFS - the files are staged as LFS pointers",
+        patterns.join(", ")
+    );
+    Ok(())
+}
+
+/// Opens the editor on `message`, or else the commit template, the way `git commit` does.
+fn compose_in_editor(message: Option<&str>, template: Option<&str>) -> Result<String> {
+    let start = match (message, template) {
+        (Some(message), _) => format!("{}\n", message.trim_end()),
+        (None, Some(template)) => template.to_string(),
+        (None, None) => String::new(),
+    };
+    let contents = format!(
+        "{}\n# Write the commit message. Lines starting with '#' are ignored;\n# an empty message aborts the commit.\n",
+        start
+    );
+    let Some(edited) = edit_in_editor("COMMIT_EDITMSG", &contents)? else {
+        bail!("empty commit message - nothing was committed");
+    };
+    if message.is_none() && template.is_some_and(|t| strip_comments(t) == edited) {
+        bail!("the commit template was left unchanged - nothing was committed");
+    }
+    Ok(edited)
+}
+
+/// Shows the branch, files, diffstat, and message of the commit about to be made and,
+/// on a terminal without `--yes`, asks to go ahead. Returns `false` if the user declines.
+fn confirm_summary(message: &str, amend: bool, yes: bool) -> Result<bool> {
+    // An amend replaces HEAD, so its contents are everything since HEAD's parent.
+    let base = if amend {
+        resolve_commit("HEAD^").ok()
+    } else {
+        resolve_commit("HEAD").ok()
+    }
+    .unwrap_or_else(|| EMPTY_TREE.to_string());
+
+    let files = git_output(&["diff", "--cached", "--name-only", &base])?;
+    let file_count = files.lines().filter(|line| !line.is_empty()).count();
+    if file_count == 0 && !amend {
+        bail!("nothing is staged - stage files first, or use --all to commit everything");
+    }
+
+    let branch = get_current_branch().unwrap_or_default();
+    println!();
+    if branch.is_empty() {
+        println!("About to commit on a detached HEAD:");
+    } else if amend {
+        println!("About to amend the last commit on {}:", branch);
+    } else {
+        println!("About to commit on {}:", branch);
+    }
+    println!("  {} file(s)", file_count);
+    let color = if stdout().is_terminal() {
+        "--color=always"
+    } else {
+        "--no-color"
+    };
+    let stat = git_output(&["diff", "--cached", "--stat", color, &base])?;
+    for line in stat.lines() {
+        println!("  {}", line);
+    }
+    println!();
+    for line in message.lines() {
+        println!("  │ {}", line);
+    }
+    println!();
+
+    if yes || !stdin().is_terminal() {
+        return Ok(true);
+    }
+    Ok(Confirm::new()
+        .with_prompt("Create this commit?")
+        .default(true)
+        .interact()?)
+}
+
+/// Refuses to commit on a branch the sgit config protects, offering (on a terminal) to
+/// move the changes to a new branch first. Returns `false` if the user cancels.
+fn leave_protected_branch(config: &Config) -> Result<bool> {
+    let branch = get_current_branch()?;
+    // The very first commit has nowhere else to go.
+    if branch.is_empty()
+        || !config.branches.is_protected(&branch)
+        || resolve_commit("HEAD").is_err()
+    {
+        return Ok(true);
+    }
+    if !stdin().is_terminal() {
+        bail!(
+            "'{}' is a protected branch, so commits go on a branch of their own\n  hint: 'sgit branch --create <name>' keeps your changes and switches to a new branch",
+            branch
+        );
+    }
+    println!(
+        "'{}' is a protected branch - changes reach it through reviews.",
+        branch
+    );
+    let name: String = Input::new()
+        .with_prompt("Name a new branch for this commit (empty to cancel)")
+        .allow_empty(true)
+        .interact_text()?;
+    if name.trim().is_empty() {
+        return Ok(false);
+    }
+    create_branch(&name)?;
+    Ok(true)
+}
+
+/// Warns when the upstream (as of the last fetch) has commits this branch lacks, and
+/// offers to pull them in first. Returns `false` if the user cancels the commit.
+fn check_behind_upstream() -> Result<bool> {
+    let Some(upstream) = get_upstream() else {
+        return Ok(true);
+    };
+    let Some((_, behind)) = get_ahead_behind(&upstream) else {
+        return Ok(true);
+    };
+    if behind == 0 {
+        return Ok(true);
+    }
+
+    eprintln!(
+        "⚠ {} has {} newer commit(s) - you may want to pull/rebase first to avoid a rejected push.",
+        upstream, behind
+    );
+    if !stdin().is_terminal() {
+        return Ok(true);
+    }
+    let choice = Select::new()
+        .with_prompt("What would you like to do?")
+        .items(&[
+            "Pull them in first (rebase, keeps your changes)",
+            "Commit anyway",
+            "Cancel",
+        ])
+        .default(0)
+        .interact()?;
+    match choice {
+        0 => {
+            pull_before_commit(&upstream)?;
+            Ok(true)
+        }
+        1 => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+/// Rebases onto the upstream with the working tree and index stashed, so what you
+/// staged stays staged.
+fn pull_before_commit(upstream: &str) -> Result<()> {
+    let dirty = !get_porcelain_lines()?.is_empty();
+    if dirty {
+        run_git_silent(&[
+            "stash",
+            "push",
+            "--include-untracked",
+            "-m",
+            "sgit: changes set aside while pulling before commit",
+        ])?;
+    }
+
+    println!("→ Pulling {}...", upstream);
+    if let Err(err) = run_git_quiet(&["pull", "--rebase"]) {
+        if dirty {
+            eprintln!("  Your uncommitted changes are safe in the stash - restore them with 'git stash pop --index' once the pull is sorted out.");
+        }
+        return Err(err);
+    }
+    if dirty {
+        run_git_quiet(&["stash", "pop", "--index"]).context(
+            "your changes are still in the stash - resolve the conflicts and run 'git stash drop'",
+        )?;
+    }
+    println!("✓ Up to date with {}", upstream);
+    Ok(())
+}
+
+/// Asks for the message of a new commit: the gitmoji, template, or type-and-scope steps
+/// the repository is set up for, then the co-authors.
+fn write_message(
+    config: &Config,
+    edit: bool,
+    all: bool,
+    unstaged: bool,
+    custom_files: &[String],
+) -> Result<String> {
+    let gitmoji = if config.commit.gitmoji {
+        prompt_gitmoji()?
+    } else {
+        None
+    };
+    let template = commit_template()?;
+    // A template with more than a subject line needs the editor to be filled in.
+    let multi_line = template
+        .as_deref()
+        .is_some_and(|t| strip_comments(t).lines().count() > 1);
+    let msg = if edit || multi_line {
+        compose_in_editor(None, template.as_deref())?
+    } else if config.commit.scopes.is_empty() && !config.lint.conventional {
+        let subject = template.as_deref().map(strip_comments).unwrap_or_default();
+        let msg: String = Input::new()
+            .with_prompt("Commit message")
+            .with_initial_text(subject.as_str())
+            .interact_text()?;
+        if !subject.trim().is_empty() && msg.trim() == subject.trim() {
+            bail!("the commit template was left unchanged - nothing was committed");
+        }
+        msg
+    } else {
+        let files = files_to_commit(all, unstaged, custom_files)?;
+        let suggested = suggest_scope(&files, Path::new(&get_repo_root()?));
+        prompt_conventional_message(&config.commit.scopes, suggested)?
+    };
+    let msg = match gitmoji {
+        Some(emoji) => with_gitmoji(&msg, emoji),
+        None => msg,
+    };
+    prompt_co_authors(msg)
+}
+
+/// For an interactive amend: keep the last commit's message or write a new one.
+fn prompt_keep_message() -> Result<bool> {
+    let current = get_commit_message("HEAD")?;
+    let subject = current.lines().next().unwrap_or("");
+    let items = [
+        format!("Keep the existing message (\"{}\")", subject),
+        "Write a new message".to_string(),
+    ];
+    let selection = Select::new()
+        .with_prompt("Commit message")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(selection == 0)
+}
+
+/// Asks for the gitmoji to start the message with; `None` for "no emoji".
+fn prompt_gitmoji() -> Result<Option<&'static str>> {
+    let mut labels: Vec<String> = GITMOJIS
+        .iter()
+        .map(|(emoji, code, about)| format!("{}  {}  {}", emoji, about, code))
+        .collect();
+    labels.push("(no emoji)".to_string());
+    let mut codes: Vec<&str> = GITMOJIS.iter().map(|(_, code, _)| *code).collect();
+    codes.push("none");
+    let selection = FuzzySelect::new()
+        .with_prompt("Gitmoji (type to search)")
+        .items(&labels)
+        .default(usual_choice("commit.gitmoji", &codes, 0))
+        .interact()?;
+    remember_choice("commit.gitmoji", codes[selection]);
+    Ok(GITMOJIS.get(selection).map(|(emoji, _, _)| *emoji))
+}
+
+/// Offers the people who committed here in the last year as co-authors and appends a
+/// `Co-authored-by:` trailer for each one picked.
+fn prompt_co_authors(message: String) -> Result<String> {
+    let output = git_cmd()
+        .args(["shortlog", "-sne", "--since=1.year", "HEAD"])
+        .output()
+        .context("running git shortlog")?;
+    let me = get_config_value("user.email")
+        .unwrap_or_default()
+        .to_lowercase();
+    let contributors: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t').map(|(_, who)| who.to_string()))
+        .filter(|who| !who.contains("[bot]"))
+        .filter(|who| me.is_empty() || !who.to_lowercase().contains(&format!("<{}>", me)))
+        .take(CO_AUTHOR_LIMIT)
+        .collect();
+    if contributors.is_empty() {
+        return Ok(message);
+    }
+
+    let wanted = Confirm::new()
+        .with_prompt("Add co-authors?")
+        .default(usual_answer("commit.co_authors", false))
+        .interact()?;
+    remember_answer("commit.co_authors", wanted);
+    if !wanted {
+        return Ok(message);
+    }
+    let picked = MultiSelect::new()
+        .with_prompt("Co-authors, most active first (space to pick, enter to confirm)")
+        .items(&contributors)
+        .interact()?;
+    if picked.is_empty() {
+        return Ok(message);
+    }
+
+    let trailers: Vec<String> = picked
+        .iter()
+        .map(|&idx| format!("Co-authored-by: {}", contributors[idx]))
+        .collect();
+    // Join an existing trailer block instead of starting a second one.
+    let last_paragraph = message.trim_end().rsplit("\n\n").next().unwrap_or("");
+    let has_trailers = message.trim_end().contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+        });
+    let separator = if has_trailers { "\n" } else { "\n\n" };
+    Ok(format!(
+        "{}{}{}",
+        message.trim_end(),
+        separator,
+        trailers.join("\n")
+    ))
+}
+
+/// The files the commit will contain once the chosen changes are staged.
+fn files_to_commit(all: bool, unstaged: bool, custom_files: &[String]) -> Result<Vec<String>> {
+    let status = PorcelainStatus::parse()?;
+    if all {
+        return Ok(status
+            .all_uncommitted_files()
+            .into_iter()
+            .map(str::to_string)
+            .collect());
+    }
+    let mut files = get_staged_files()?;
+    if unstaged {
+        files.extend(status.unstaged_files().into_iter().map(str::to_string));
+    }
+    files.extend(custom_files.iter().cloned());
+    // Renames are listed as `old -> new`.
+    for file in &mut files {
+        if let Some((_, new)) = file.split_once(" -> ") {
+            *file = new.to_string();
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Builds a `type(scope): description` message, offering `suggested` (worked out from
+/// the changed paths) as the scope. With a scope list in .sgit.toml the scope is picked
+/// from it; otherwise it is typed, starting from the suggestion.
+fn prompt_conventional_message(scopes: &[String], suggested: Option<String>) -> Result<String> {
+    let type_labels: Vec<String> = COMMIT_TYPES
+        .iter()
+        .map(|(kind, about)| format!("{:<9} {}", kind, about))
+        .collect();
+    let type_names: Vec<&str> = COMMIT_TYPES.iter().map(|(kind, _)| *kind).collect();
+    let kind = Select::new()
+        .with_prompt("Type of change")
+        .items(&type_labels)
+        .default(usual_choice("commit.type", &type_names, 0))
+        .interact()?;
+    remember_choice("commit.type", type_names[kind]);
+
+    let scope = if scopes.is_empty() {
+        let prompt = match suggested {
+            Some(_) => "Scope (suggested from the changed files; edit, or clear for none)",
+            None => "Scope (leave empty for none)",
+        };
+        let typed: String = Input::new()
+            .with_prompt(prompt)
+            .with_initial_text(suggested.unwrap_or_default())
+            .allow_empty(true)
+            .interact_text()?;
+        Some(typed.trim().to_string()).filter(|scope| !scope.is_empty())
+    } else {
+        let mut scope_items = vec!["(no scope)".to_string()];
+        scope_items.extend(scopes.iter().cloned());
+        // A suggestion from the list beats the usual answer; it fits this commit.
+        let default = suggested
+            .and_then(|s| scope_items.iter().position(|item| *item == s))
+            .unwrap_or_else(|| usual_choice("commit.scope", &scope_items, 0));
+        let prompt = if default > 0 {
+            format!(
+                "Scope (type to search; '{}' fits the changed files)",
+                scope_items[default]
+            )
+        } else {
+            "Scope (type to search)".to_string()
+        };
+        let scope = FuzzySelect::new()
+            .with_prompt(prompt)
+            .items(&scope_items)
+            .default(default)
+            .interact()?;
+        remember_choice("commit.scope", &scope_items[scope]);
+        (scope > 0).then(|| scope_items[scope].clone())
+    };
+
+    let description: String = Input::new()
+        .with_prompt("Short description")
+        .interact_text()?;
+    let breaking = Confirm::new()
+        .with_prompt("Is this a breaking change?")
+        .default(false)
+        .interact()?;
+
+    Ok(format_header(
+        COMMIT_TYPES[kind].0,
+        scope.as_deref(),
+        breaking,
+        &description,
+    ))
+}
@@ -0,0 +1,98 @@
+use std::io::{stdin, stdout, IsTerminal};
+
+use anyhow::{bail, Result};
+use dialoguer::Confirm;
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::run_git;
+use crate::status::{get_commits, resolve_commit, CommitSummary};
+
+const REF_KINDS: &[RefKind] = &[RefKind::Branch, RefKind::RemoteBranch, RefKind::Tag];
+
+pub fn run_compare(a: Option<String>, b: Option<String>, diff: bool) -> Result<()> {
+    let a = match a {
+        Some(a) => a,
+        None => match pick("First branch (usually yours)")? {
+            Some(a) => a,
+            None => return Ok(()),
+        },
+    };
+    let b = match b {
+        Some(b) => b,
+        None => match pick(&format!("Compare {} with (usually main)", a))? {
+            Some(b) => b,
+            None => return Ok(()),
+        },
+    };
+    let hash_a = resolve_commit(&a)?;
+    let hash_b = resolve_commit(&b)?;
+    if hash_a == hash_b {
+        println!(
+            "{} and {} point at the same commit - nothing to compare.",
+            a, b
+        );
+        return Ok(());
+    }
+
+    let only_a = get_commits(&[&format!("{}..{}", b, a)])?;
+    let only_b = get_commits(&[&format!("{}..{}", a, b)])?;
+    print_side(&a, &b, &only_a);
+    println!();
+    print_side(&b, &a, &only_b);
+
+    // Three dots: what `a` changed since it split off from `b`.
+    let range = format!("{}...{}", b, a);
+    println!();
+    if only_a.is_empty() {
+        println!("{} has no changes of its own compared to {}.", a, b);
+        return Ok(());
+    }
+    println!("Files changed in {} since it split from {}:", a, b);
+    run_git(&["diff", "--stat", &range])?;
+
+    let show_diff = diff
+        || (stdin().is_terminal()
+            && Confirm::new()
+                .with_prompt("Show the full diff?")
+                .default(false)
+                .interact()?);
+    if show_diff {
+        let mut args = vec!["diff"];
+        if stdout().is_terminal() {
+            args.push("--color=always");
+        }
+        args.push(&range);
+        run_git(&args)?;
+    }
+    Ok(())
+}
+
+fn print_side(side: &str, other: &str, commits: &[CommitSummary]) {
+    if commits.is_empty() {
+        println!("Nothing in {} that isn't in {}.", side, other);
+        return;
+    }
+    println!(
+        "{} commit(s) in {} that aren't in {}:",
+        commits.len(),
+        side,
+        other
+    );
+    for commit in commits {
+        println!("  {}", commit.label());
+    }
+}
+
+fn pick(prompt: &str) -> Result<Option<String>> {
+    if !stdin().is_terminal() {
+        bail!("pass both refs, e.g. 'sgit compare my-feature main'");
+    }
+    let prompt = format!("{} (type to search)", prompt);
+    match RefPicker::new(&prompt, REF_KINDS).interact()? {
+        Some(Picked::Ref(picked)) => Ok(Some(picked.name)),
+        _ => {
+            println!("No branches or tags to compare.");
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,584 @@
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Input, Select};
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+use crate::cli::ConfigAction;
+use crate::config::{user_config_path, Config, REPO_CONFIG_FILE};
+use crate::git::{check_in_repo, git_cmd, run_git_silent};
+use crate::status::{get_config_value, get_repo_root};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Bool,
+    Number,
+    Text,
+    /// Comma-separated on the command line, an array in TOML.
+    List,
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Clone, Copy)]
+enum Store {
+    /// A `section.name` key in `.sgit.toml`.
+    Sgit,
+    /// A git config key, written to the global config unless `--local` is passed.
+    Git(&'static str),
+}
+
+/// Where sgit settings are written.
+#[derive(Clone, Copy)]
+pub(super) enum Layer {
+    /// The repository's `.sgit.toml`, whose settings win.
+    Repo,
+    /// `~/.config/sgit/config.toml`, for every repository.
+    User,
+}
+
+impl Layer {
+    fn path(self) -> Result<PathBuf> {
+        match self {
+            Layer::Repo => Ok(Path::new(&get_repo_root()?).join(REPO_CONFIG_FILE)),
+            Layer::User => user_config_path()
+                .context("cannot locate your user config - set HOME or XDG_CONFIG_HOME"),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Layer::Repo => REPO_CONFIG_FILE,
+            Layer::User => "your user config",
+        }
+    }
+}
+
+struct Setting {
+    key: &'static str,
+    kind: Kind,
+    store: Store,
+    about: &'static str,
+}
+
+const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "commit.gitmoji",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "start interactive commits with a gitmoji picker",
+    },
+    Setting {
+        key: "commit.sign",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "sign every commit made with sgit commit",
+    },
+    Setting {
+        key: "commit.scopes",
+        kind: Kind::List,
+        store: Store::Sgit,
+        about: "allowed Conventional Commit scopes (empty allows any)",
+    },
+    Setting {
+        key: "commit.max_file_size_mb",
+        kind: Kind::Number,
+        store: Store::Sgit,
+        about: "largest file sgit commit accepts, in MB (0 turns it off)",
+    },
+    Setting {
+        key: "checks.commands",
+        kind: Kind::List,
+        store: Store::Sgit,
+        about: "commands sgit runs before a push",
+    },
+    Setting {
+        key: "prompts.remember",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "preselect the answer you usually pick",
+    },
+    Setting {
+        key: "lint.level",
+        kind: Kind::Choice(&["off", "warn", "block"]),
+        store: Store::Sgit,
+        about: "what happens when a commit message breaks the rules",
+    },
+    Setting {
+        key: "lint.max_subject_length",
+        kind: Kind::Number,
+        store: Store::Sgit,
+        about: "longest allowed subject line (0 turns it off)",
+    },
+    Setting {
+        key: "lint.no_trailing_period",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "flag subjects that end with a period",
+    },
+    Setting {
+        key: "lint.imperative",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "flag subjects like \"Added ...\" instead of \"Add ...\"",
+    },
+    Setting {
+        key: "lint.conventional",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "require type(scope): description subjects",
+    },
+    Setting {
+        key: "branches.protected",
+        kind: Kind::List,
+        store: Store::Sgit,
+        about: "branches sgit will not commit or push to directly (release/* style patterns)",
+    },
+    Setting {
+        key: "sync.mode",
+        kind: Kind::Choice(&["merge", "rebase"]),
+        store: Store::Sgit,
+        about: "how sgit pull and sgit sync bring in remote commits",
+    },
+    Setting {
+        key: "sync.push_all_remotes",
+        kind: Kind::Bool,
+        store: Store::Sgit,
+        about: "make sgit push push the current branch to every remote",
+    },
+    Setting {
+        key: "default-branch",
+        kind: Kind::Text,
+        store: Store::Git("init.defaultBranch"),
+        about: "branch name new repositories start on",
+    },
+    Setting {
+        key: "pull.rebase",
+        kind: Kind::Choice(&["false", "true", "merges"]),
+        store: Store::Git("pull.rebase"),
+        about: "rebase your commits on pull instead of merging",
+    },
+    Setting {
+        key: "editor",
+        kind: Kind::Text,
+        store: Store::Git("core.editor"),
+        about: "editor git opens for messages",
+    },
+];
+
+pub fn run_config(action: Option<ConfigAction>) -> Result<()> {
+    match action {
+        Some(ConfigAction::List) => list_settings(),
+        Some(ConfigAction::Get { key }) => {
+            let setting = find_setting(&key)?;
+            match current_value(setting)? {
+                Some(value) => println!("{}", value),
+                None => bail!("{} is not set", setting.key),
+            }
+            Ok(())
+        }
+        Some(ConfigAction::Set {
+            key,
+            value,
+            local,
+            user,
+        }) => {
+            let setting = find_setting(&key)?;
+            check_scope_flags(setting, local, user)?;
+            set_setting(setting, &value, local, layer_for(user))
+        }
+        Some(ConfigAction::Unset { key, local, user }) => {
+            let setting = find_setting(&key)?;
+            check_scope_flags(setting, local, user)?;
+            unset_setting(setting, local, layer_for(user))
+        }
+        None if stdin().is_terminal() => config_menu(),
+        None => list_settings(),
+    }
+}
+
+fn layer_for(user: bool) -> Layer {
+    if user {
+        Layer::User
+    } else {
+        Layer::Repo
+    }
+}
+
+fn check_scope_flags(setting: &Setting, local: bool, user: bool) -> Result<()> {
+    match setting.store {
+        Store::Sgit if local => bail!(
+            "--local is for git settings; {} is written to {} (or your user config with --user)",
+            setting.key,
+            REPO_CONFIG_FILE
+        ),
+        Store::Git(_) if user => bail!(
+            "--user is for sgit settings; {} goes in your global git config (or this repository's with --local)",
+            setting.key
+        ),
+        _ => Ok(()),
+    }
+}
+
+fn find_setting(key: &str) -> Result<&'static Setting> {
+    SETTINGS
+        .iter()
+        .find(|setting| {
+            setting.key == key || matches!(setting.store, Store::Git(git_key) if git_key == key)
+        })
+        .ok_or_else(|| {
+            let known: Vec<&str> = SETTINGS.iter().map(|setting| setting.key).collect();
+            anyhow::anyhow!(
+                "unknown setting '{}' - sgit knows: {}",
+                key,
+                known.join(", ")
+            )
+        })
+}
+
+fn list_settings() -> Result<()> {
+    let in_repo = check_in_repo().is_ok();
+    let repo = if in_repo {
+        read_document(Layer::Repo)?
+    } else {
+        DocumentMut::new()
+    };
+    let user = read_document(Layer::User)?;
+    println!("sgit settings:");
+    for setting in SETTINGS.iter().filter(|s| matches!(s.store, Store::Sgit)) {
+        let value = current_value(setting)?
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "(none)".to_string());
+        let origin = if lookup(&repo, setting.key).is_some() {
+            REPO_CONFIG_FILE
+        } else if lookup(&user, setting.key).is_some() {
+            "user config"
+        } else {
+            "default"
+        };
+        println!("  {:<24} {}  ({})", setting.key, value, origin);
+    }
+    println!();
+    println!("git settings:");
+    for setting in SETTINGS.iter().filter(|s| matches!(s.store, Store::Git(_))) {
+        let value = current_value(setting)?.unwrap_or_else(|| "(not set)".to_string());
+        println!("  {:<24} {}", setting.key, value);
+    }
+    if !in_repo {
+        println!();
+        println!(
+            "Run this inside a repository to include its {} as well.",
+            REPO_CONFIG_FILE
+        );
+    }
+    Ok(())
+}
+
+/// Pick a setting, change it, and come back to the list until the user is done. sgit
+/// settings go to `.sgit.toml` inside a repository and to the user config outside one.
+fn config_menu() -> Result<()> {
+    let layer = layer_for(check_in_repo().is_err());
+    let settings: Vec<&Setting> = SETTINGS.iter().collect();
+    let mut position = 0;
+    loop {
+        let mut labels = Vec::new();
+        for setting in &settings {
+            let value = current_value(setting)?.unwrap_or_else(|| "(not set)".to_string());
+            labels.push(format!(
+                "{:<24} {:<16} {}",
+                setting.key, value, setting.about
+            ));
+        }
+        labels.push("Done".to_string());
+        let Some(selection) = Select::new()
+            .with_prompt("Which setting do you want to change?")
+            .items(&labels)
+            .default(position)
+            .interact_opt()?
+        else {
+            return Ok(());
+        };
+        if selection == settings.len() {
+            return Ok(());
+        }
+        position = selection;
+        let setting = settings[selection];
+        let current = current_value(setting)?.unwrap_or_default();
+        let value = match setting.kind {
+            Kind::Bool => choose(setting.key, &["true", "false"], &current)?,
+            Kind::Choice(choices) => choose(setting.key, choices, &current)?,
+            Kind::List | Kind::Number | Kind::Text => Input::<String>::new()
+                .with_prompt(match setting.kind {
+                    Kind::List => format!("{} (comma-separated)", setting.key),
+                    _ => setting.key.to_string(),
+                })
+                .with_initial_text(current)
+                .allow_empty(matches!(setting.kind, Kind::List))
+                .interact_text()?,
+        };
+        if let Err(err) = set_setting(setting, &value, false, layer) {
+            eprintln!("error: {}", err);
+        }
+    }
+}
+
+fn choose(key: &str, choices: &[&str], current: &str) -> Result<String> {
+    let default = choices
+        .iter()
+        .position(|choice| *choice == current)
+        .unwrap_or(0);
+    let selection = Select::new()
+        .with_prompt(key)
+        .items(choices)
+        .default(default)
+        .interact()?;
+    Ok(choices[selection].to_string())
+}
+
+/// The value in effect: for sgit settings that includes the defaults, shown the way
+/// `sgit config set` takes them.
+fn current_value(setting: &Setting) -> Result<Option<String>> {
+    match setting.store {
+        Store::Git(git_key) => Ok(get_config_value(git_key)),
+        Store::Sgit => {
+            let effective = toml::Value::try_from(Config::load()?)
+                .context("failed to read the current sgit settings")?;
+            let (section, name) = split_key(setting.key);
+            Ok(effective
+                .get(section)
+                .and_then(|table| table.get(name))
+                .map(display_value))
+        }
+    }
+}
+
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// A value as `sgit config set` takes it.
+fn describe_item(item: &Item) -> String {
+    match item.as_value() {
+        Some(toml_edit::Value::String(text)) => text.value().clone(),
+        Some(toml_edit::Value::Array(items)) => items
+            .iter()
+            .map(|item| item.as_str().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(other) => other.to_string().trim().to_string(),
+        None => String::new(),
+    }
+}
+
+fn set_setting(setting: &Setting, value: &str, local: bool, layer: Layer) -> Result<()> {
+    let value = value.trim();
+    match setting.store {
+        Store::Git(git_key) => {
+            validate_git_value(setting, value)?;
+            let scope = if local { "--local" } else { "--global" };
+            run_git_silent(&["config", scope, git_key, value])?;
+            println!("✓ Set {} = {} ({} git config)", git_key, value, &scope[2..]);
+        }
+        Store::Sgit => {
+            let item = parse_value(setting, value)?;
+            let mut document = read_document(layer)?;
+            let (section, name) = split_key(setting.key);
+            if !document.contains_key(section) {
+                document[section] = Item::Table(Table::new());
+            }
+            document[section][name] = item;
+            write_document(layer, &document)?;
+            println!(
+                "✓ Set {} = {} in {}",
+                setting.key,
+                describe_item(&document[section][name]),
+                layer.name()
+            );
+            if let Layer::User = layer
+                && check_in_repo().is_ok()
+                && lookup(&read_document(Layer::Repo)?, setting.key).is_some()
+            {
+                println!(
+                    "  This repository's {} also sets it, and wins here: {}",
+                    REPO_CONFIG_FILE,
+                    current_value(setting)?.unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unset_setting(setting: &Setting, local: bool, layer: Layer) -> Result<()> {
+    match setting.store {
+        Store::Git(git_key) => {
+            let scope = if local { "--local" } else { "--global" };
+            let set_there = git_cmd()
+                .args(["config", scope, "--get", git_key])
+                .output()
+                .context("failed to execute git - is git installed?")?
+                .status
+                .success();
+            if !set_there {
+                println!("{} is not set in the {} git config", git_key, &scope[2..]);
+                return Ok(());
+            }
+            run_git_silent(&["config", scope, "--unset", git_key])?;
+            println!("✓ Removed {} from the {} git config", git_key, &scope[2..]);
+        }
+        Store::Sgit => {
+            let mut document = read_document(layer)?;
+            let (section, name) = split_key(setting.key);
+            let removed = document
+                .get_mut(section)
+                .and_then(Item::as_table_like_mut)
+                .and_then(|table| table.remove(name))
+                .is_some();
+            if !removed {
+                println!("{} is not set in {}", setting.key, layer.name());
+                return Ok(());
+            }
+            if document
+                .get(section)
+                .and_then(Item::as_table_like)
+                .is_some_and(|table| table.is_empty())
+            {
+                document.remove(section);
+            }
+            write_document(layer, &document)?;
+            println!("✓ Removed {} from {}", setting.key, layer.name());
+        }
+    }
+    Ok(())
+}
+
+/// Turns what the user typed into the TOML value the setting expects.
+fn parse_value(setting: &Setting, value: &str) -> Result<Item> {
+    Ok(match setting.kind {
+        Kind::Bool => match value.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => toml_edit::value(true),
+            "false" | "no" | "off" | "0" => toml_edit::value(false),
+            _ => bail!("{} is either true or false, not '{}'", setting.key, value),
+        },
+        Kind::Number => {
+            let number: i64 = value
+                .parse()
+                .ok()
+                .filter(|number| *number >= 0)
+                .with_context(|| {
+                    format!("{} must be a whole number, not '{}'", setting.key, value)
+                })?;
+            toml_edit::value(number)
+        }
+        Kind::Choice(choices) => {
+            if !choices.contains(&value) {
+                bail!(
+                    "{} must be one of {}, not '{}'",
+                    setting.key,
+                    choices.join(", "),
+                    value
+                );
+            }
+            toml_edit::value(value)
+        }
+        Kind::Text => toml_edit::value(value),
+        Kind::List => {
+            let items: Array = value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .collect();
+            toml_edit::value(items)
+        }
+    })
+}
+
+fn validate_git_value(setting: &Setting, value: &str) -> Result<()> {
+    if value.is_empty() {
+        bail!(
+            "{} cannot be empty - use 'sgit config unset {}' to remove it",
+            setting.key,
+            setting.key
+        );
+    }
+    if let Kind::Choice(choices) = setting.kind
+        && !choices.contains(&value)
+    {
+        bail!(
+            "{} must be one of {}, not '{}'",
+            setting.key,
+            choices.join(", "),
+            value
+        );
+    }
+    match setting.key {
+        "default-branch" => {
+            let valid = git_cmd()
+                .args(["check-ref-format", "--branch", value])
+                .output()
+                .context("failed to execute git - is git installed?")?
+                .status
+                .success();
+            if !valid {
+                bail!("'{}' is not a valid branch name", value);
+            }
+        }
+        "editor" => {
+            let program = value.split_whitespace().next().unwrap_or(value);
+            let found = StdCommand::new("sh")
+                .args(["-c", "command -v \"$1\"", "sh", program])
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false);
+            if !found {
+                bail!("'{}' was not found on your PATH", program);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn split_key(key: &str) -> (&str, &str) {
+    key.split_once('.').unwrap_or(("", key))
+}
+
+fn lookup<'a>(document: &'a DocumentMut, key: &str) -> Option<&'a Item> {
+    let (section, name) = split_key(key);
+    document.get(section)?.get(name)
+}
+
+pub(super) fn read_document(layer: Layer) -> Result<DocumentMut> {
+    let path = layer.path()?;
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    contents
+        .parse()
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Writes the document back, keeping the user's comments and layout, once sgit can
+/// still load it.
+pub(super) fn write_document(layer: Layer, document: &DocumentMut) -> Result<()> {
+    let contents = document.to_string();
+    toml::from_str::<Config>(&contents)
+        .with_context(|| format!("the change would leave {} unreadable", layer.name()))?;
+    let path = layer.path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,419 @@
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::console::Term;
+use dialoguer::{Confirm, Select};
+
+use crate::cli::ConflictsAction;
+use crate::commands::operation::run_continue;
+use crate::commands::tool::{launch_tool, ToolKind};
+use crate::git::{git_cmd, run_git_in_dir_silent};
+use crate::message::open_in_editor;
+use crate::status::{
+    get_conflicted_files, get_current_branch, get_operation_in_progress, get_repo_root,
+    RepoOperation,
+};
+
+/// Used when the terminal's width is unknown.
+const DEFAULT_WIDTH: usize = 100;
+
+pub fn run_conflicts(action: Option<ConflictsAction>, tool: bool) -> Result<()> {
+    if let Some(ConflictsAction::Show { file }) = action {
+        if present_sides(Path::new("."), &file)? == (false, false) {
+            bail!(
+                "{} is not conflicted - 'sgit conflicts' lists the files that are",
+                file
+            );
+        }
+        let sides = sides(get_operation_in_progress()?);
+        return show_conflicts(Path::new(&file), &file, &sides);
+    }
+    let files = get_conflicted_files()?;
+    if files.is_empty() {
+        println!("No conflicted files.");
+        return Ok(());
+    }
+
+    if tool {
+        println!(
+            "→ Opening {} conflicted file(s) in your merge tool...",
+            files.len()
+        );
+        launch_tool(ToolKind::Merge, &[])?;
+        let remaining = get_conflicted_files()?;
+        if !remaining.is_empty() {
+            println!("{} file(s) are still conflicted:", remaining.len());
+            for file in &remaining {
+                println!("  {}", file);
+            }
+            return Ok(());
+        }
+        println!("✓ All conflicts resolved and staged");
+    } else if !stdin().is_terminal() {
+        println!("{} conflicted file(s):", files.len());
+        for file in &files {
+            println!("  {}", file);
+        }
+        println!();
+        println!(
+            "  Run 'sgit conflicts' in a terminal to resolve them one by one, or 'sgit conflicts --tool' to use a merge tool."
+        );
+        return Ok(());
+    } else {
+        let remaining = resolve_each(&files)?;
+        if !remaining.is_empty() {
+            println!("{} file(s) are still conflicted:", remaining.len());
+            for file in &remaining {
+                println!("  {}", file);
+            }
+            println!("  Run 'sgit conflicts' again when you are ready for them.");
+            return Ok(());
+        }
+        println!("✓ All conflicts resolved and staged");
+    }
+
+    match get_operation_in_progress()? {
+        Some(operation) if operation != RepoOperation::Bisect && stdin().is_terminal() => {
+            let finish = Confirm::new()
+                .with_prompt(format!("Finish the {} now?", operation.name()))
+                .default(true)
+                .interact()?;
+            if finish {
+                return run_continue();
+            }
+            println!("  Finish it later with 'sgit continue'.");
+        }
+        Some(operation) => println!("  Finish the {} with 'sgit continue'.", operation.name()),
+        None => println!("  Commit the result with 'sgit commit'."),
+    }
+    Ok(())
+}
+
+/// Where each side of a conflict came from, in words.
+struct Sides {
+    ours: String,
+    theirs: String,
+}
+
+/// A rebase replays your commits onto the other branch, so there "ours" is that branch
+/// and "theirs" is your own commit.
+fn sides(operation: Option<RepoOperation>) -> Sides {
+    let branch = get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
+    let (ours, theirs) = match operation {
+        Some(RepoOperation::Rebase) => (
+            "the branch you are rebasing onto".to_string(),
+            "your commit being replayed".to_string(),
+        ),
+        Some(RepoOperation::Merge) => (
+            format!("your branch ({})", branch),
+            "the branch being merged in".to_string(),
+        ),
+        Some(RepoOperation::CherryPick) => (
+            format!("your branch ({})", branch),
+            "the commit being cherry-picked".to_string(),
+        ),
+        Some(RepoOperation::Revert) => (
+            format!("your branch ({})", branch),
+            "the commit being reverted".to_string(),
+        ),
+        Some(RepoOperation::Am) => (format!("your branch ({})", branch), "the patch".to_string()),
+        _ => (
+            format!("your branch ({})", branch),
+            "the incoming changes".to_string(),
+        ),
+    };
+    Sides { ours, theirs }
+}
+
+enum Choice {
+    KeepOurs,
+    KeepTheirs,
+    Edit,
+    View,
+    Skip,
+}
+
+/// Walks through `files` (paths from the repository root) one at a time, returning the
+/// ones left conflicted.
+fn resolve_each(files: &[String]) -> Result<Vec<String>> {
+    let root = get_repo_root()?;
+    let root = Path::new(&root);
+    let sides = sides(get_operation_in_progress()?);
+    let mut skipped = Vec::new();
+    for (idx, path) in files.iter().enumerate() {
+        let (in_ours, in_theirs) = present_sides(root, path)?;
+        let what = match (in_ours, in_theirs) {
+            (true, true) => "changed on both sides".to_string(),
+            (false, _) => format!("deleted by {}, changed by {}", sides.ours, sides.theirs),
+            (_, false) => format!("deleted by {}, changed by {}", sides.theirs, sides.ours),
+        };
+        println!();
+        println!("{} ({} of {}) - {}", path, idx + 1, files.len(), what);
+
+        let choices = [
+            (Choice::KeepOurs, keep_label(&sides.ours, in_ours)),
+            (Choice::KeepTheirs, keep_label(&sides.theirs, in_theirs)),
+            (Choice::Edit, "Open in editor".to_string()),
+            (Choice::View, "View both sides".to_string()),
+            (Choice::Skip, "Skip for now".to_string()),
+        ];
+        let labels: Vec<&str> = choices.iter().map(|(_, label)| label.as_str()).collect();
+        loop {
+            let selection = Select::new()
+                .with_prompt(format!("How should {} be resolved?", path))
+                .items(&labels)
+                .default(0)
+                .interact()?;
+            match choices[selection].0 {
+                Choice::KeepOurs => {
+                    keep_side(root, path, "--ours", in_ours)?;
+                    println!("✓ {}: kept {}", path, sides.ours);
+                }
+                Choice::KeepTheirs => {
+                    keep_side(root, path, "--theirs", in_theirs)?;
+                    println!("✓ {}: kept {}", path, sides.theirs);
+                }
+                Choice::Edit => {
+                    let file = root.join(path);
+                    if !open_in_editor(&file)? {
+                        println!("⚠ The editor exited with an error");
+                        continue;
+                    }
+                    if has_conflict_markers(&file) {
+                        println!("⚠ {} still has conflict markers (<<<<<<<)", path);
+                        continue;
+                    }
+                    let resolved = Confirm::new()
+                        .with_prompt(format!("Mark {} as resolved?", path))
+                        .default(true)
+                        .interact()?;
+                    if !resolved {
+                        continue;
+                    }
+                    run_git_in_dir_silent(&["add", "--", path], root)?;
+                    println!("✓ {}: resolved and staged", path);
+                }
+                Choice::View => {
+                    show_conflicts(&root.join(path), path, &sides)?;
+                    continue;
+                }
+                Choice::Skip => skipped.push(path.clone()),
+            }
+            break;
+        }
+    }
+    Ok(skipped)
+}
+
+fn keep_label(side: &str, present: bool) -> String {
+    if present {
+        format!("Keep {}", side)
+    } else {
+        format!("Keep {}, which deletes the file", side)
+    }
+}
+
+/// Whether the file exists on our side and on theirs of the conflict; `path` is relative
+/// to `dir`.
+fn present_sides(dir: &Path, path: &str) -> Result<(bool, bool)> {
+    let output = git_cmd()
+        .args(["ls-files", "-u", "-z", "--", path])
+        .current_dir(dir)
+        .output()
+        .context("running git ls-files")?;
+    // Entries are `<mode> <hash> <stage>\t<path>`; stage 2 is ours, 3 is theirs.
+    let stages: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter_map(|entry| {
+            entry
+                .split('\t')
+                .next()?
+                .split(' ')
+                .nth(2)
+                .map(str::to_string)
+        })
+        .collect();
+    Ok((
+        stages.iter().any(|stage| stage == "2"),
+        stages.iter().any(|stage| stage == "3"),
+    ))
+}
+
+/// Resolves `path` to one side (`--ours` or `--theirs`), deleting it when that side did.
+fn keep_side(root: &Path, path: &str, side: &str, present: bool) -> Result<()> {
+    if present {
+        run_git_in_dir_silent(&["checkout", side, "--", path], root)?;
+        run_git_in_dir_silent(&["add", "--", path], root)
+    } else {
+        run_git_in_dir_silent(&["rm", "--quiet", "--", path], root)
+    }
+}
+
+fn has_conflict_markers(file: &Path) -> bool {
+    fs::read(file).is_ok_and(|contents| {
+        String::from_utf8_lossy(&contents)
+            .lines()
+            .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+    })
+}
+
+/// One `<<<<<<<` ... `>>>>>>>` region of a conflicted file.
+struct Region {
+    /// Of the `<<<<<<<` marker, from 1.
+    line: usize,
+    /// What git wrote after the markers: usually `HEAD` and a branch, or a commit.
+    ours_label: String,
+    theirs_label: String,
+    ours: Vec<String>,
+    /// The common ancestor's lines, with `merge.conflictStyle` set to `diff3` or `zdiff3`.
+    base: usize,
+    theirs: Vec<String>,
+}
+
+fn parse_regions(contents: &str) -> Vec<Region> {
+    enum Part {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut regions: Vec<Region> = Vec::new();
+    let mut part = Part::Outside;
+    for (idx, line) in contents.lines().enumerate() {
+        let marker = |prefix: &str| {
+            (line == prefix || line.starts_with(&format!("{} ", prefix)))
+                .then(|| line[prefix.len()..].trim().to_string())
+        };
+        match part {
+            Part::Outside => {
+                if let Some(label) = marker("<<<<<<<") {
+                    regions.push(Region {
+                        line: idx + 1,
+                        ours_label: label,
+                        theirs_label: String::new(),
+                        ours: Vec::new(),
+                        base: 0,
+                        theirs: Vec::new(),
+                    });
+                    part = Part::Ours;
+                }
+                continue;
+            }
+            _ if marker("|||||||").is_some() => part = Part::Base,
+            _ if marker("=======").is_some() => part = Part::Theirs,
+            _ => {}
+        }
+        let Some(region) = regions.last_mut() else {
+            continue;
+        };
+        if let Some(label) = marker(">>>>>>>") {
+            region.theirs_label = label;
+            part = Part::Outside;
+            continue;
+        }
+        if line.starts_with("|||||||") || line.starts_with("=======") {
+            continue;
+        }
+        match part {
+            Part::Ours => region.ours.push(line.to_string()),
+            Part::Base => region.base += 1,
+            Part::Theirs => region.theirs.push(line.to_string()),
+            Part::Outside => {}
+        }
+    }
+    regions
+}
+
+/// Prints each conflicted region of `file` with the two sides next to each other, each
+/// headed by where it came from.
+fn show_conflicts(file: &Path, name: &str, sides: &Sides) -> Result<()> {
+    let contents = match fs::read(file) {
+        Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            println!(
+                "{} was deleted on one side, so there are no lines to compare.",
+                name
+            );
+            return Ok(());
+        }
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", file.display())),
+    };
+    let regions = parse_regions(&contents);
+    if regions.is_empty() {
+        println!(
+            "{} has no conflict markers - it may be binary, deleted on one side, or already edited.",
+            name
+        );
+        return Ok(());
+    }
+
+    let width = Term::stdout()
+        .size_checked()
+        .map_or(DEFAULT_WIDTH, |(_, columns)| columns as usize);
+    // Two columns with ` │ ` between them.
+    let column = (width.saturating_sub(3) / 2).max(20);
+    println!();
+    println!(
+        "{}: {} conflict{}",
+        name,
+        regions.len(),
+        if regions.len() == 1 { "" } else { "s" }
+    );
+    for (idx, region) in regions.iter().enumerate() {
+        println!();
+        println!(
+            "Conflict {} of {}, line {}",
+            idx + 1,
+            regions.len(),
+            region.line
+        );
+        print_row(
+            &side_heading("ours", &sides.ours, &region.ours_label),
+            &side_heading("theirs", &sides.theirs, &region.theirs_label),
+            column,
+        );
+        println!("{}┼{}", "─".repeat(column + 1), "─".repeat(column + 1));
+        let rows = region.ours.len().max(region.theirs.len()).max(1);
+        for row in 0..rows {
+            let cell = |lines: &[String]| match lines.get(row) {
+                Some(line) => line.replace('\t', "    "),
+                None if row == 0 => "(nothing - these lines are removed)".to_string(),
+                None => String::new(),
+            };
+            print_row(&cell(&region.ours), &cell(&region.theirs), column);
+        }
+        if region.base > 0 {
+            println!(
+                "  ({} line(s) of the common ancestor not shown)",
+                region.base
+            );
+        }
+    }
+    println!();
+    Ok(())
+}
+
+fn side_heading(side: &str, from: &str, label: &str) -> String {
+    if label.is_empty() {
+        format!("{}: {}", side, from)
+    } else {
+        format!("{}: {} - {}", side, from, label)
+    }
+}
+
+fn print_row(left: &str, right: &str, column: usize) {
+    let row = format!("{} │ {}", fit(left, column), fit(right, column));
+    println!("{}", row.trim_end());
+}
+
+/// `text` cut or padded to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let cut: String = text.chars().take(width - 1).collect();
+        format!("{}…", cut)
+    } else {
+        format!("{:<width$}", text, width = width)
+    }
+}
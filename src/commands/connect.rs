@@ -0,0 +1,111 @@
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{anyhow, bail, Result};
+use dialoguer::{Confirm, Input};
+
+use crate::capabilities::offer_initial_commit;
+use crate::git::{git_complaint, git_output, run_git_quiet, run_git_silent};
+use crate::status::{get_config_value, get_current_branch, resolve_commit};
+
+/// Connects this repository to a newly created remote: asks for its URL when not given,
+/// checks the remote answers and is empty, adds it, and pushes with upstream tracking.
+pub fn run_connect(url: Option<String>, name: String) -> Result<()> {
+    if let Some(existing) = get_config_value(&format!("remote.{}.url", name)) {
+        bail!(
+            "'{}' already points at {}\n  hint: change it with 'git remote set-url {} <url>', or pick another name with --name",
+            name,
+            existing,
+            name
+        );
+    }
+    let url = match url {
+        Some(url) => url,
+        None => {
+            if !stdin().is_terminal() {
+                bail!("give the remote's URL: 'sgit connect <url>'");
+            }
+            println!(
+                "First create an empty repository on your git host (on GitHub: New repository),"
+            );
+            println!("without a README, license, or .gitignore - sgit pushes yours to it.");
+            Input::new()
+                .with_prompt("Its URL (e.g. git@github.com:you/project.git)")
+                .interact_text()?
+        }
+    };
+    connect_remote(&name, url.trim())
+}
+
+/// Adds `url` as the remote `name` and pushes the current branch to it with upstream
+/// tracking, making the first commit first if the user wants one.
+pub fn connect_remote(name: &str, url: &str) -> Result<()> {
+    if url.is_empty() {
+        bail!("the remote URL cannot be empty");
+    }
+    println!("→ Checking {}...", url);
+    let heads = git_output(&["ls-remote", "--heads", url]).map_err(|err| {
+        anyhow!(
+            "cannot reach {}: {}\n  hint: check the URL for typos, that the repository exists, and that you have access (for SSH URLs, 'ssh -T git@<host>' tests your key)",
+            url,
+            git_complaint(&err)
+        )
+    })?;
+    let remote_branches: Vec<String> = heads
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter_map(|reference| reference.strip_prefix("refs/heads/"))
+        .map(str::to_string)
+        .collect();
+
+    run_git_silent(&["remote", "add", name, url])?;
+    println!("✓ Added remote '{}'", name);
+
+    if !remote_branches.is_empty() {
+        // Pushing now would be rejected: the remote has history this repository lacks.
+        run_git_quiet(&["fetch", name])?;
+        let branch = get_current_branch().unwrap_or_default();
+        println!(
+            "The remote already has commits (branches: {}), so nothing was pushed.",
+            remote_branches.join(", ")
+        );
+        let target = if remote_branches.contains(&branch) {
+            branch.clone()
+        } else {
+            remote_branches[0].clone()
+        };
+        println!(
+            "  To build on them: 'git pull --rebase {} {}', then 'sgit push'.",
+            name, target
+        );
+        println!("  If it was meant to be empty, create a fresh repository and run 'git remote remove {}'.", name);
+        return Ok(());
+    }
+
+    if resolve_commit("HEAD").is_err() {
+        println!("This repository has no commits yet, so there is nothing to push.");
+        if !offer_initial_commit()? {
+            println!("  Make the first commit with 'sgit commit', then 'sgit push' publishes it.");
+            return Ok(());
+        }
+    }
+    let branch = get_current_branch().unwrap_or_default();
+    if branch.is_empty() {
+        bail!("HEAD is detached, so there is no branch to push - switch to one with 'sgit branch', then 'sgit push'");
+    }
+    if stdin().is_terminal()
+        && !Confirm::new()
+            .with_prompt(format!("Push {} to {} now?", branch, name))
+            .default(true)
+            .interact()?
+    {
+        println!("  'sgit push' publishes it when you are ready.");
+        return Ok(());
+    }
+    println!("→ Pushing {} to {}...", branch, name);
+    run_git_quiet(&["push", "-u", name, &branch])?;
+    println!(
+        "✓ Pushed {} and set it to track {}/{} - next time plain 'sgit push' is enough",
+        branch, name, branch
+    );
+    Ok(())
+}
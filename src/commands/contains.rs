@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::git_cmd;
+use crate::status::{get_commits, get_current_branch, resolve_commit};
+
+/// Shows which branches and tags contain a commit. An empty target opens the picker.
+pub fn run_contains(target: String) -> Result<()> {
+    let rev = if target.is_empty() {
+        let Some(Picked::Ref(commit)) = RefPicker::new(
+            "Which commit are you looking for? (type to search)",
+            &[RefKind::Commit, RefKind::Tag],
+        )
+        .interact()?
+        else {
+            println!("No commits to look up.");
+            return Ok(());
+        };
+        commit.name
+    } else {
+        target
+    };
+    let hash = resolve_commit(&rev)?;
+    if let Some(commit) = get_commits(&["-n", "1", &hash])?.first() {
+        println!("Commit {}", commit.label());
+    }
+    println!();
+
+    let current = get_current_branch().unwrap_or_default();
+    let branches: Vec<String> = refs_containing(&hash, "refs/heads", "-committerdate")?
+        .into_iter()
+        .map(|b| {
+            if b == current {
+                format!("{} (current)", b)
+            } else {
+                b
+            }
+        })
+        .collect();
+    let remote_branches = refs_containing(&hash, "refs/remotes", "-committerdate")?;
+    // Oldest first, so the first tag is the release that shipped the commit.
+    let tags = refs_containing(&hash, "refs/tags", "creatordate")?;
+
+    print_group("Branches", &branches);
+    print_group("Remote branches", &remote_branches);
+    print_group("Tags", &tags);
+    println!();
+
+    match tags.first() {
+        Some(first) => println!("✓ First released in {}", first),
+        None if remote_branches.is_empty() => {
+            println!("Not in any tag, and not pushed to any remote branch yet.")
+        }
+        None => println!("Not in any tag yet - it will ship with the next release."),
+    }
+    Ok(())
+}
+
+/// Lists the commits on the current branch that no tag contains yet.
+pub fn run_untagged() -> Result<()> {
+    let commits = get_commits(&["HEAD", "--not", "--tags"])?;
+    let latest_tag = latest_tag();
+    let branch = get_current_branch().unwrap_or_default();
+    let branch = if branch.is_empty() { "HEAD" } else { &branch };
+
+    if commits.is_empty() {
+        match latest_tag {
+            Some(tag) => println!("✓ Everything on {} is tagged (latest: {}).", branch, tag),
+            None => println!("No commits on {} yet.", branch),
+        }
+        return Ok(());
+    }
+
+    match latest_tag {
+        Some(tag) => println!(
+            "{} commit(s) on {} are not in any tag yet (latest tag: {}):",
+            commits.len(),
+            branch,
+            tag
+        ),
+        None => println!(
+            "{} commit(s) on {}, and the repository has no tags yet:",
+            commits.len(),
+            branch
+        ),
+    }
+    for commit in &commits {
+        println!("  {}", commit.label());
+    }
+    Ok(())
+}
+
+fn print_group(title: &str, names: &[String]) {
+    if names.is_empty() {
+        println!("{}: none", title);
+    } else {
+        println!("{} ({}): {}", title, names.len(), names.join(", "));
+    }
+}
+
+fn refs_containing(hash: &str, namespace: &str, sort: &str) -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args([
+            "for-each-ref",
+            "--contains",
+            hash,
+            &format!("--sort={}", sort),
+            "--format=%(refname:short)%1f%(symref)",
+            namespace,
+        ])
+        .output()
+        .context("running git for-each-ref")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, symref) = line.split_once('\x1f')?;
+            // Skip `origin/HEAD`-style aliases.
+            symref.is_empty().then(|| name.to_string())
+        })
+        .collect())
+}
+
+fn latest_tag() -> Option<String> {
+    let output = git_cmd()
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .ok()?;
+    let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !tag.is_empty()).then_some(tag)
+}
@@ -0,0 +1,198 @@
+use std::io::{stdout, IsTerminal};
+
+use anyhow::{bail, Result};
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::commands::tool::{launch_tool, ToolKind};
+use crate::git::{git_cmd, git_output, run_git};
+use crate::render::DiffRenderer;
+use crate::status::resolve_commit;
+
+pub struct DiffOptions {
+    /// `[path]`, `<ref1> <ref2>`, or `<ref1> <ref2> <path>`.
+    pub targets: Vec<String>,
+    pub staged: bool,
+    pub highlight_moved: bool,
+    pub words: bool,
+    pub stat: bool,
+    /// Open the diff in the configured (or a picked) external diff tool.
+    pub tool: bool,
+    /// Print git's own output instead of the highlighted rendering.
+    pub plain: bool,
+    /// `Some("")` means "pick the ref interactively".
+    pub against: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+pub fn run_diff(opts: DiffOptions) -> Result<()> {
+    let DiffOptions {
+        targets,
+        staged,
+        highlight_moved,
+        words,
+        stat,
+        tool,
+        plain,
+        against,
+        from,
+        to,
+    } = opts;
+
+    let (path, refs) = match targets.as_slice() {
+        [] => (None, None),
+        [path] => (Some(path.clone()), None),
+        [a, b] => {
+            if resolve_commit(b).is_err() && resolve_commit(a).is_ok() {
+                bail!(
+                    "'{}' is not a branch, tag, or commit\n  hint: to diff one path against a ref use 'sgit diff {} --against {}'",
+                    b,
+                    b,
+                    a
+                );
+            }
+            (None, Some((a.clone(), b.clone())))
+        }
+        [a, b, path] => (Some(path.clone()), Some((a.clone(), b.clone()))),
+        _ => bail!("pass at most two refs and a path, e.g. 'sgit diff main my-feature src/'"),
+    };
+    if refs.is_some() && (from.is_some() || to.is_some()) {
+        bail!("give the refs either as arguments or with --from/--to, not both");
+    }
+    let range = match (refs, from, to) {
+        (Some((a, b)), _, _) => {
+            check_ref(&a, "first ref")?;
+            check_ref(&b, "second ref")?;
+            Some((a, Some(b)))
+        }
+        (None, from, to) if from.is_some() || to.is_some() => {
+            let from = from.unwrap_or_else(|| "HEAD".to_string());
+            check_ref(&from, "--from")?;
+            if let Some(ref to) = to {
+                check_ref(to, "--to")?;
+            }
+            Some((from, to))
+        }
+        _ => None,
+    };
+    if range.is_some() && (staged || against.is_some()) {
+        bail!("comparing two refs cannot be combined with --staged or --against");
+    }
+
+    let against = match against {
+        Some(target) if target.is_empty() => match pick_against()? {
+            Some(target) => Some(target),
+            None => return Ok(()),
+        },
+        Some(target) => {
+            resolve_commit(&target)?;
+            Some(target)
+        }
+        None => None,
+    };
+    if against.is_some() && staged {
+        bail!("--against cannot be combined with --staged");
+    }
+
+    let mut args = vec!["diff"];
+    if staged {
+        args.push("--staged");
+    }
+    if let Some(ref target) = against {
+        args.push(target.as_str());
+    }
+    if let Some((ref from, ref to)) = range {
+        args.push(from.as_str());
+        if let Some(to) = to {
+            args.push(to.as_str());
+        }
+    }
+    if highlight_moved {
+        // Moved blocks are dimmed (zebra-striped at block edges) so genuine
+        // additions and deletions stand out; re-indented moves still count.
+        args.push("--color-moved=dimmed-zebra");
+        args.push("--color-moved-ws=allow-indentation-change");
+        // Moves are only visible through color, and git sees a pipe here.
+        if stdout().is_terminal() {
+            args.push("--color=always");
+        }
+    }
+    if words {
+        // Color marks the changed words on a terminal; pipes get git's
+        // `[-old-]{+new+}` markers instead.
+        if stdout().is_terminal() {
+            args.push("--word-diff=color");
+            args.push("--color=always");
+        } else {
+            args.push("--word-diff=plain");
+        }
+    }
+    if stat {
+        args.push("--stat");
+    }
+    if let Some(ref path) = path {
+        args.push("--");
+        args.push(path.as_str());
+    }
+    if tool {
+        return launch_tool(ToolKind::Diff, &args[1..]);
+    }
+    // The other views rely on git's own coloring.
+    if !plain && !stat && !words && !highlight_moved && stdout().is_terminal() {
+        args.insert(1, "--no-color");
+        args.insert(2, "--no-ext-diff");
+        let diff = git_output(&args)?;
+        return DiffRenderer::new().print(&diff);
+    }
+    run_git(&args)
+}
+
+fn pick_against() -> Result<Option<String>> {
+    let picked = RefPicker::new(
+        "Compare your working tree against (type to search)",
+        &[
+            RefKind::Branch,
+            RefKind::RemoteBranch,
+            RefKind::Tag,
+            RefKind::Commit,
+        ],
+    )
+    .interact()?;
+    match picked {
+        Some(Picked::Ref(target)) => Ok(Some(target.name)),
+        _ => {
+            println!("No branches, tags, or commits to compare against.");
+            Ok(None)
+        }
+    }
+}
+
+/// Validates a ref given to the diff, pointing at a same-named remote branch when the
+/// user has no local branch of that name.
+fn check_ref(name: &str, role: &str) -> Result<()> {
+    if resolve_commit(name).is_ok() {
+        return Ok(());
+    }
+    let output = git_cmd()
+        .args([
+            "for-each-ref",
+            "--format=%(refname:short)",
+            &format!("refs/remotes/*/{}", name),
+        ])
+        .output();
+    if let Ok(output) = output
+        && let Some(remote_branch) = String::from_utf8_lossy(&output.stdout).lines().next()
+    {
+        bail!(
+            "'{}' ({}) is not a local branch - did you mean '{}'?",
+            name,
+            role,
+            remote_branch
+        );
+    }
+    bail!(
+        "'{}' ({}) is not a branch, tag, or commit\n  hint: 'sgit branch' lists branches and 'sgit log' shows commits",
+        name,
+        role
+    )
+}
@@ -0,0 +1,417 @@
+use std::io::{stdin, IsTerminal};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use dialoguer::MultiSelect;
+
+use crate::cli::ExitCode;
+use crate::commands::setup::run_setup;
+use crate::git::{
+    check_in_repo, git_cmd, git_program, git_supports, git_version, run_git_silent, MIN_GIT_VERSION,
+};
+use crate::hooks::{
+    has_precommit_config, hooks_dir, installed_hooks, make_executable, precommit_installed,
+};
+use crate::remote::{configured_remote, Direction};
+use crate::status::{get_config_value, get_current_branch, get_remotes, get_upstream};
+
+/// The newest feature sgit uses, `--pathspec-from-file`, arrived in git 2.26; older
+/// versions get fallbacks.
+const RECOMMENDED_GIT_VERSION: (u32, u32) = (2, 26);
+
+/// How long to wait for a remote before calling it unreachable.
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum Outcome {
+    Pass,
+    Warn,
+    Fail,
+}
+
+enum Fix {
+    RunSetup,
+    SetConfig {
+        /// `--global`, or `--local` when the repository overrides the global setting.
+        scope: &'static str,
+        key: &'static str,
+        value: String,
+    },
+    SetUpstream {
+        upstream: String,
+    },
+    MakeExecutable(PathBuf),
+}
+
+impl Fix {
+    fn describe(&self) -> String {
+        match self {
+            Fix::RunSetup => "sgit setup".to_string(),
+            Fix::SetConfig { scope, key, value } => {
+                format!("git config {} {} {}", scope, key, value)
+            }
+            Fix::SetUpstream { upstream } => format!("git branch --set-upstream-to={}", upstream),
+            Fix::MakeExecutable(path) => format!("chmod +x {}", path.display()),
+        }
+    }
+
+    fn apply(&self) -> Result<()> {
+        match self {
+            Fix::RunSetup => run_setup(),
+            Fix::SetConfig { scope, key, value } => run_git_silent(&["config", scope, key, value]),
+            Fix::SetUpstream { upstream } => {
+                run_git_silent(&["branch", &format!("--set-upstream-to={}", upstream)])
+            }
+            Fix::MakeExecutable(path) => make_executable(path),
+        }
+    }
+}
+
+struct Check {
+    outcome: Outcome,
+    summary: String,
+    fix: Option<Fix>,
+}
+
+impl Check {
+    fn pass(summary: impl Into<String>) -> Self {
+        Self {
+            outcome: Outcome::Pass,
+            summary: summary.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(summary: impl Into<String>, fix: Option<Fix>) -> Self {
+        Self {
+            outcome: Outcome::Warn,
+            summary: summary.into(),
+            fix,
+        }
+    }
+
+    fn fail(summary: impl Into<String>, fix: Option<Fix>) -> Self {
+        Self {
+            outcome: Outcome::Fail,
+            summary: summary.into(),
+            fix,
+        }
+    }
+}
+
+pub fn run_doctor() -> Result<()> {
+    let mut checks = vec![check_git_version()];
+    checks.push(check_identity());
+    if check_in_repo().is_ok() {
+        checks.extend(check_remote());
+        checks.push(check_upstream());
+        checks.extend(check_hooks()?);
+    }
+    checks.push(check_line_endings());
+
+    for check in &checks {
+        let mark = match check.outcome {
+            Outcome::Pass => "✓",
+            Outcome::Warn => "⚠",
+            Outcome::Fail => "✗",
+        };
+        println!("{} {}", mark, check.summary);
+        if let Some(fix) = &check.fix {
+            println!("    fix: {}", fix.describe());
+        }
+    }
+
+    // Which check each offered fix belongs to.
+    let fixable: Vec<usize> = (0..checks.len())
+        .filter(|&index| checks[index].fix.is_some())
+        .collect();
+    let is_failure = |check: &Check| matches!(check.outcome, Outcome::Fail);
+    if fixable.is_empty() {
+        println!();
+        let failures = checks.iter().filter(|check| is_failure(check)).count();
+        if failures == 0 {
+            println!("Everything looks healthy.");
+            return Ok(());
+        }
+        println!("{} problem(s) need your attention.", failures);
+        return Err(ExitCode(1).into());
+    }
+    if !stdin().is_terminal() {
+        println!();
+        println!("Run 'sgit doctor' in a terminal to apply the fixes above.");
+        return Err(ExitCode(1).into());
+    }
+
+    println!();
+    let labels: Vec<String> = fixable
+        .iter()
+        .filter_map(|&index| checks[index].fix.as_ref().map(Fix::describe))
+        .collect();
+    let defaults = vec![true; labels.len()];
+    let chosen = MultiSelect::new()
+        .with_prompt("Apply these fixes? (space toggles, enter confirms)")
+        .items(&labels)
+        .defaults(&defaults)
+        .interact()?;
+    let mut repaired = Vec::new();
+    for choice in chosen {
+        let index = fixable[choice];
+        let Some(fix) = &checks[index].fix else {
+            continue;
+        };
+        match fix.apply() {
+            Ok(()) => {
+                println!("✓ {}", labels[choice]);
+                repaired.push(index);
+            }
+            Err(err) => eprintln!("✗ {}: {}", labels[choice], err),
+        }
+    }
+    let still_failing =
+        (0..checks.len()).any(|index| is_failure(&checks[index]) && !repaired.contains(&index));
+    if still_failing {
+        return Err(ExitCode(1).into());
+    }
+    Ok(())
+}
+
+fn check_git_version() -> Check {
+    if git_cmd().arg("--version").output().is_err() {
+        return Check::fail(
+            "git is not installed (or not on your PATH) - install it from https://git-scm.com/downloads",
+            None,
+        );
+    }
+    let Some(version) = git_version() else {
+        return Check::warn("could not tell which version of git this is", None);
+    };
+    if version < MIN_GIT_VERSION {
+        return Check::fail(
+            format!(
+                "git {} is older than {}.{}, which sgit needs - upgrade it from https://git-scm.com/downloads",
+                version, MIN_GIT_VERSION.major, MIN_GIT_VERSION.minor
+            ),
+            None,
+        );
+    }
+    if !git_supports(RECOMMENDED_GIT_VERSION.0, RECOMMENDED_GIT_VERSION.1) {
+        return Check::warn(
+            format!(
+                "git {} works, but sgit has to use older commands in places; {}.{} or newer is recommended",
+                version, RECOMMENDED_GIT_VERSION.0, RECOMMENDED_GIT_VERSION.1
+            ),
+            None,
+        );
+    }
+    if git_program() != "git" {
+        return Check::pass(format!(
+            "git {} ({})",
+            version,
+            git_program().to_string_lossy()
+        ));
+    }
+    Check::pass(format!("git {}", version))
+}
+
+fn check_identity() -> Check {
+    match (
+        get_config_value("user.name"),
+        get_config_value("user.email"),
+    ) {
+        (Some(name), Some(email)) => Check::pass(format!("Committing as {} <{}>", name, email)),
+        (None, None) => Check::fail(
+            "git does not know your name and email, so you cannot commit",
+            Some(Fix::RunSetup),
+        ),
+        (None, Some(_)) => Check::fail("user.name is not set", Some(Fix::RunSetup)),
+        (Some(_), None) => Check::fail("user.email is not set", Some(Fix::RunSetup)),
+    }
+}
+
+fn check_remote() -> Option<Check> {
+    let remote = match configured_remote(Direction::Fetch) {
+        Some(resolved) => resolved.name,
+        None => {
+            let remotes = get_remotes().ok()?;
+            match remotes.as_slice() {
+                [] => {
+                    return Some(Check::warn(
+                        "no remote configured - run 'sgit push' to add one",
+                        None,
+                    ))
+                }
+                [only] => only.clone(),
+                _ => remotes.iter().find(|name| *name == "origin")?.clone(),
+            }
+        }
+    };
+    Some(match reach_remote(&remote) {
+        Ok(()) => Check::pass(format!("Remote '{}' is reachable", remote)),
+        Err(reason) => Check::fail(format!("cannot reach '{}': {}", remote, reason), None),
+    })
+}
+
+/// Lists the remote's branches without prompting for credentials, giving up after
+/// `REMOTE_TIMEOUT`.
+fn reach_remote(remote: &str) -> Result<(), String> {
+    let mut child = git_cmd()
+        .args(["ls-remote", "--heads", remote])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", ssh_batch_command())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(_)) => {
+                let output = child.wait_with_output().map_err(|err| err.to_string())?;
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr
+                    .lines()
+                    .map(|line| line.trim_start_matches("fatal: ").trim())
+                    .find(|line| !line.is_empty())
+                    .unwrap_or("git ls-remote failed");
+                return Err(reason.to_string());
+            }
+            Ok(None) if started.elapsed() > REMOTE_TIMEOUT => {
+                let _ = child.kill();
+                return Err(format!(
+                    "no answer within {} seconds",
+                    REMOTE_TIMEOUT.as_secs()
+                ));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+}
+
+/// The user's SSH command with password prompts turned off, so an unreachable remote
+/// fails instead of waiting for input.
+fn ssh_batch_command() -> String {
+    let ssh = get_config_value("core.sshCommand").unwrap_or_else(|| "ssh".to_string());
+    format!("{} -o BatchMode=yes", ssh)
+}
+
+fn check_upstream() -> Check {
+    let branch = get_current_branch().unwrap_or_default();
+    if branch.is_empty() {
+        return Check::warn("HEAD is detached, so there is no branch to track", None);
+    }
+    if let Some(upstream) = get_upstream() {
+        return Check::pass(format!("'{}' tracks {}", branch, upstream));
+    }
+    // A remote branch of the same name is almost certainly the one to track.
+    let candidate = get_remotes()
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|remote| {
+            let upstream = format!("{}/{}", remote, branch);
+            run_git_silent(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/remotes/{}", upstream),
+            ])
+            .ok()
+            .map(|_| upstream)
+        });
+    match candidate {
+        Some(upstream) => Check::warn(
+            format!(
+                "'{}' has no upstream, so pull and push need to be told where to go",
+                branch
+            ),
+            Some(Fix::SetUpstream { upstream }),
+        ),
+        None => Check::warn(
+            format!(
+                "'{}' has no upstream yet - 'sgit push' publishes it and sets one",
+                branch
+            ),
+            None,
+        ),
+    }
+}
+
+/// Hooks git would skip because they are not executable.
+fn check_hooks() -> Result<Vec<Check>> {
+    let hooks = installed_hooks(&hooks_dir()?)?;
+    let mut checks = Vec::new();
+    let mut healthy = 0;
+    for hook in hooks.into_iter().filter(|hook| hook.enabled) {
+        if hook.executable {
+            healthy += 1;
+            continue;
+        }
+        checks.push(Check::fail(
+            format!(
+                "the {} hook is not executable, so git silently skips it",
+                hook.name
+            ),
+            Some(Fix::MakeExecutable(hook.path)),
+        ));
+    }
+    if healthy > 0 && checks.is_empty() {
+        checks.push(Check::pass(format!(
+            "{} hook(s) installed and executable",
+            healthy
+        )));
+    }
+    if has_precommit_config() && !precommit_installed() {
+        checks.push(Check::warn(
+            "the checks in .pre-commit-config.yaml never run because pre-commit is not installed as a hook - run 'sgit hooks install-precommit'",
+            None,
+        ));
+    }
+    Ok(checks)
+}
+
+fn config_scope(key: &str) -> &'static str {
+    let local = git_cmd()
+        .args(["config", "--local", "--get", key])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if local {
+        "--local"
+    } else {
+        "--global"
+    }
+}
+
+/// `core.autocrlf` should convert line endings on Windows and leave them alone (or only
+/// fix CRLFs on the way in) everywhere else.
+fn check_line_endings() -> Check {
+    let autocrlf = get_config_value("core.autocrlf").map(|value| value.to_lowercase());
+    if cfg!(windows) {
+        return match autocrlf.as_deref() {
+            Some("true") => Check::pass("Line endings: core.autocrlf = true"),
+            _ => Check::warn(
+                "core.autocrlf is not true, so commits may mix Windows and Unix line endings",
+                Some(Fix::SetConfig {
+                    scope: config_scope("core.autocrlf"),
+                    key: "core.autocrlf",
+                    value: "true".to_string(),
+                }),
+            ),
+        };
+    }
+    match autocrlf.as_deref() {
+        Some("true") => Check::warn(
+            "core.autocrlf = true turns files into Windows line endings on checkout",
+            Some(Fix::SetConfig {
+                scope: config_scope("core.autocrlf"),
+                key: "core.autocrlf",
+                value: "input".to_string(),
+            }),
+        ),
+        Some(value) => Check::pass(format!("Line endings: core.autocrlf = {}", value)),
+        None => Check::pass("Line endings are left as they are (core.autocrlf unset)"),
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::commands::blame::relative_time;
+use crate::journal::{read_last_invocation, read_recent};
+use crate::status::get_operation_in_progress;
+
+/// Replays the previous sgit invocation: the git commands it ran and what they printed,
+/// what failed and why, and what to do about it.
+pub fn run_explain_last() -> Result<()> {
+    let Some(record) = read_last_invocation()? else {
+        println!("No sgit command has run in this repository yet.");
+        return Ok(());
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    println!(
+        "Last command: sgit {}  ({})",
+        record.args.join(" "),
+        relative_time(now.saturating_sub(record.time))
+    );
+    if record.success {
+        println!("Result:       ✓ succeeded");
+    } else {
+        println!("Result:       ✗ failed");
+    }
+
+    println!();
+    if record.git.is_empty() {
+        println!("It did not need to run any git commands.");
+    } else {
+        println!("Git commands it ran:");
+        for invocation in &record.git {
+            let mark = if invocation.success { "✓" } else { "✗" };
+            println!("  {} {}", mark, invocation.command);
+            for line in invocation.output.lines() {
+                println!("      │ {}", line);
+            }
+        }
+    }
+
+    // Undo hints for anything the command changed, from the journal.
+    let changes: Vec<_> = read_recent(20)?
+        .into_iter()
+        .filter(|entry| entry.time >= record.time)
+        .collect();
+    if !changes.is_empty() {
+        println!();
+        println!("What it changed:");
+        for entry in &changes {
+            println!("  • {}", entry.description);
+            if let Some(undo) = &entry.undo {
+                println!("    undo with: {}", undo);
+            }
+        }
+    }
+
+    if record.success {
+        return Ok(());
+    }
+
+    // Each cause's first line is its summary; git's own output was shown above.
+    let mut hints = Vec::new();
+    println!();
+    println!("What went wrong:");
+    for cause in &record.error {
+        let mut lines = cause.lines();
+        if let Some(summary) = lines.next() {
+            let summary = summary.trim().trim_end_matches(':');
+            if record
+                .git
+                .iter()
+                .any(|g| !g.success && summary.starts_with(&g.command))
+            {
+                println!("  {} (its output is above)", summary);
+            } else {
+                println!("  {}", summary);
+            }
+        }
+        for line in lines {
+            if let Some(hint) = line.trim().strip_prefix("hint:") {
+                hints.push(hint.trim().to_string());
+            }
+        }
+    }
+
+    if let Some(operation) = get_operation_in_progress()? {
+        hints.push(format!(
+            "the {} is still in progress - {}",
+            operation.name(),
+            operation.next_steps()
+        ));
+    }
+    println!();
+    println!("What to do:");
+    if hints.is_empty() {
+        println!(
+            "  Fix the problem above and run 'sgit {}' again.",
+            record.args.join(" ")
+        );
+    } else {
+        for hint in &hints {
+            println!("  • {}", hint);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::format::format_size;
+use crate::git::{git_output, run_git_silent};
+use crate::status::{get_all_uncommitted_files, get_current_branch, get_repo_root, resolve_commit};
+use crate::workspace::repo_name;
+
+/// Archive formats `git archive` writes without extra tools, as `(name, extension)`.
+const FORMATS: &[(&str, &str)] = &[("zip", "zip"), ("tar.gz", "tar.gz")];
+
+/// Writes the files of `target` (a branch, tag, or commit) to a zip or tar.gz file,
+/// without the `.git` folder.
+pub fn run_export(
+    target: Option<String>,
+    format: Option<String>,
+    output: Option<String>,
+    path: Option<String>,
+) -> Result<()> {
+    let format = choose_format(format.as_deref(), output.as_deref())?;
+    let target = match target {
+        Some(target) => target,
+        None if stdin().is_terminal() => match pick_target()? {
+            Some(target) => target,
+            None => return Ok(()),
+        },
+        None => "HEAD".to_string(),
+    };
+    let commit = resolve_commit(&target)?;
+    // Name a default `HEAD` after what it stands for.
+    let target = match target.as_str() {
+        "HEAD" => get_current_branch()
+            .ok()
+            .filter(|branch| !branch.is_empty())
+            .unwrap_or_else(|| commit[..7].to_string()),
+        _ => target,
+    };
+
+    let root = get_repo_root()?;
+    let path = path
+        .map(|path| {
+            path.trim()
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string()
+        })
+        .filter(|path| !path.is_empty());
+    if let Some(path) = &path {
+        let kind =
+            git_output(&["cat-file", "-t", &format!("{}:{}", commit, path)]).unwrap_or_default();
+        if kind.trim() != "tree" {
+            bail!(
+                "'{}' is not a directory in {} - paths are from the top of the repository",
+                path,
+                target
+            );
+        }
+    }
+
+    let name = repo_name(Path::new(&root));
+    let mut stem = format!("{}-{}", name, slug(&target));
+    if let Some(path) = &path {
+        stem.push('-');
+        stem.push_str(&slug(path));
+    }
+    let file = output.unwrap_or_else(|| format!("{}.{}", stem, format));
+    if Path::new(&file).exists() {
+        bail!(
+            "{} already exists - pass --output to choose another file",
+            file
+        );
+    }
+
+    let tree = match &path {
+        Some(path) => format!("{}:{}", commit, path),
+        None => commit.clone(),
+    };
+    let what = match &path {
+        Some(path) => format!("{}/ of {}", path, target),
+        None => target.clone(),
+    };
+    println!("→ Writing the files of {} to {}...", what, file);
+    // Everything sits in one top-level folder, so unpacking does not spill files.
+    let prefix = format!("--prefix={}/", stem);
+    let format_arg = format!("--format={}", format);
+    run_git_silent(&["archive", &format_arg, &prefix, "-o", &file, &tree])
+        .with_context(|| format!("could not write {}", file))?;
+
+    let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+    println!("✓ Wrote {} ({})", file, format_size(size));
+    println!("  It holds the committed files only - no history and no .git folder.");
+    println!("  Files marked 'export-ignore' in .gitattributes are left out.");
+    let is_checked_out = resolve_commit("HEAD").is_ok_and(|head| head == commit);
+    if is_checked_out && !get_all_uncommitted_files()?.is_empty() {
+        println!("  Your uncommitted changes are not in it; commit them first to include them.");
+    }
+    Ok(())
+}
+
+/// The format from `--format`, or from the extension of `--output`, or zip.
+fn choose_format(format: Option<&str>, output: Option<&str>) -> Result<&'static str> {
+    let names = || {
+        FORMATS
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if let Some(format) = format {
+        let format = match format.trim_start_matches('.') {
+            "tgz" => "tar.gz",
+            format => format,
+        };
+        return match FORMATS.iter().find(|(name, _)| *name == format) {
+            Some((name, _)) => Ok(name),
+            None => bail!("unknown format '{}' - use one of {}", format, names()),
+        };
+    }
+    if let Some(output) = output {
+        if let Some((name, _)) = FORMATS
+            .iter()
+            .find(|(_, ext)| output.ends_with(&format!(".{}", ext)))
+        {
+            return Ok(name);
+        }
+        if output.ends_with(".tgz") {
+            return Ok("tar.gz");
+        }
+        bail!(
+            "cannot tell the format from '{}' - pass --format ({})",
+            output,
+            names()
+        );
+    }
+    Ok("zip")
+}
+
+fn pick_target() -> Result<Option<String>> {
+    match RefPicker::new(
+        "What do you want to export? (type to search)",
+        &[RefKind::Branch, RefKind::Tag, RefKind::Commit],
+    )
+    .interact()?
+    {
+        Some(Picked::Ref(picked)) => Ok(Some(match picked.kind {
+            // A short hash makes a readable file name.
+            RefKind::Commit => picked.name[..7.min(picked.name.len())].to_string(),
+            _ => picked.name,
+        })),
+        _ => {
+            println!("Nothing to export - the repository has no commits yet.");
+            Ok(None)
+        }
+    }
+}
+
+/// `release/1.0` becomes `release-1.0`, for file and folder names.
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::io::{stdout, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::{git_cmd, git_supports, run_git_quiet};
+use crate::progress::{step, Phase};
+use crate::remote::{resolve_remote, Direction};
+use crate::status::{count_commits, get_config_value, get_remotes, is_ancestor};
+
+/// Remote branches and tags, by full ref name, and the commits they point at.
+type RefTips = BTreeMap<String, String>;
+
+/// Remotes fetched at once by `--all` unless git's `fetch.parallel` says otherwise; each
+/// one mostly waits on the network.
+const DEFAULT_FETCH_JOBS: usize = 4;
+
+pub fn run_fetch(remote: Option<String>, all: bool, prune: bool) -> Result<()> {
+    // Each remote with how to name it in the progress output.
+    let remotes: Vec<(String, String)> = if all {
+        let remotes = get_remotes()?;
+        if remotes.is_empty() {
+            bail!("this repository has no remotes\n  hint: add one with 'sgit connect'");
+        }
+        remotes
+            .into_iter()
+            .map(|name| (name.clone(), name))
+            .collect()
+    } else {
+        match remote {
+            Some(remote) => vec![(remote.clone(), remote)],
+            None => {
+                let resolved = resolve_remote(Direction::Fetch)?;
+                vec![(resolved.name.clone(), resolved.describe())]
+            }
+        }
+    };
+
+    let before = ref_tips()?;
+    let failed = match remotes.as_slice() {
+        [(remote, label)] => {
+            println!("→ Fetching {}...", label);
+            let phase = Phase::start("fetch", "fetch");
+            if let Err(e) = fetch_remote(remote, prune) {
+                phase.failed(&e.to_string());
+                return Err(e);
+            }
+            phase.done();
+            Vec::new()
+        }
+        _ => fetch_concurrently(&remotes, prune),
+    };
+    let after = ref_tips()?;
+
+    print_changes(&before, &after);
+    if !failed.is_empty() {
+        bail!("could not fetch {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+fn fetch_remote(remote: &str, prune: bool) -> Result<()> {
+    let mut args = vec!["fetch", remote];
+    if prune {
+        args.push("--prune");
+    }
+    // Fetches running side by side would all write FETCH_HEAD, which nothing here reads.
+    if git_supports(2, 29) {
+        args.push("--no-write-fetch-head");
+    }
+    run_git_quiet(&args)
+}
+
+/// How many remotes are fetched at once: git's `fetch.parallel`, like `git fetch --all`,
+/// when it is set to a number other than 0.
+fn fetch_jobs() -> usize {
+    get_config_value("fetch.parallel")
+        .and_then(|value| value.parse().ok())
+        .filter(|jobs| *jobs > 0)
+        .unwrap_or(DEFAULT_FETCH_JOBS)
+}
+
+/// Fetches `remotes` a few at a time, reporting each as it finishes. Returns the ones that
+/// failed.
+fn fetch_concurrently(remotes: &[(String, String)], prune: bool) -> Vec<String> {
+    let jobs = fetch_jobs().min(remotes.len());
+    println!(
+        "→ Fetching {} remotes, {} at a time...",
+        remotes.len(),
+        jobs
+    );
+    let phase = Phase::start("fetch", "fetch");
+    let next = AtomicUsize::new(0);
+    let failed = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some((remote, _)) = remotes.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    step("fetch", "fetch", remote, "started");
+                    let result = fetch_remote(remote, prune);
+                    // One remote's lines are printed together, not mixed with another's.
+                    let mut out = stdout().lock();
+                    match result {
+                        Ok(()) => {
+                            step("fetch", "fetch", remote, "succeeded");
+                            let _ = writeln!(out, "  ✓ {}", remote);
+                        }
+                        Err(e) => {
+                            step("fetch", "fetch", remote, "failed");
+                            let _ = writeln!(out, "  ✗ {}", remote);
+                            for line in e.to_string().lines().filter(|line| !line.trim().is_empty())
+                            {
+                                let _ = writeln!(out, "      {}", line.trim());
+                            }
+                            if let Ok(mut failed) = failed.lock() {
+                                failed.push(remote.clone());
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let mut failed = failed.into_inner().unwrap_or_default();
+    // In the order the remotes were given, not the order they finished.
+    failed.sort_by_key(|name| remotes.iter().position(|(remote, _)| remote == name));
+    if failed.is_empty() {
+        phase.done();
+    } else {
+        phase.failed(&format!("could not fetch {}", failed.join(", ")));
+    }
+    failed
+}
+
+fn ref_tips() -> Result<RefTips> {
+    let output = git_cmd()
+        .args([
+            "for-each-ref",
+            "--format=%(refname) %(objectname)",
+            "refs/remotes",
+            "refs/tags",
+        ])
+        .output()
+        .context("running git for-each-ref")?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        // `origin/HEAD` only follows the remote's default branch.
+        .filter(|(name, _)| !name.ends_with("/HEAD"))
+        .map(|(name, hash)| (name.to_string(), hash.to_string()))
+        .collect())
+}
+
+fn print_changes(before: &RefTips, after: &RefTips) {
+    let mut new_branches = Vec::new();
+    let mut updated = Vec::new();
+    let mut new_tags = Vec::new();
+    for (name, hash) in after {
+        match (name.strip_prefix("refs/remotes/"), before.get(name)) {
+            (Some(branch), None) => new_branches.push(branch),
+            (Some(branch), Some(old)) if old != hash => {
+                updated.push(format!("{} {}", branch, describe_update(old, hash)))
+            }
+            (None, None) => new_tags.push(name.trim_start_matches("refs/tags/")),
+            _ => {}
+        }
+    }
+    let deleted: Vec<&str> = before
+        .keys()
+        .filter(|name| !after.contains_key(*name))
+        .map(|name| {
+            name.trim_start_matches("refs/remotes/")
+                .trim_start_matches("refs/tags/")
+        })
+        .collect();
+
+    if new_branches.is_empty() && updated.is_empty() && new_tags.is_empty() && deleted.is_empty() {
+        println!("✓ Fetched - everything was already up to date");
+        return;
+    }
+    println!("✓ Fetched");
+    if !new_branches.is_empty() {
+        println!("\nNew branches:");
+        for branch in new_branches {
+            println!("  + {}", branch);
+        }
+    }
+    if !updated.is_empty() {
+        println!("\nUpdated branches:");
+        for branch in updated {
+            println!("  ~ {}", branch);
+        }
+    }
+    if !new_tags.is_empty() {
+        println!("\nNew tags:");
+        for tag in new_tags {
+            println!("  + {}", tag);
+        }
+    }
+    if !deleted.is_empty() {
+        println!("\nDeleted on the remote, removed here:");
+        for name in deleted {
+            println!("  - {}", name);
+        }
+    }
+}
+
+fn describe_update(old: &str, new: &str) -> String {
+    if is_ancestor(old, new) {
+        match count_commits(&format!("{}..{}", old, new)) {
+            Ok(1) => "(1 new commit)".to_string(),
+            Ok(count) => format!("({} new commits)", count),
+            Err(_) => "(new commits)".to_string(),
+        }
+    } else {
+        "(rewritten - someone force-pushed it)".to_string()
+    }
+}
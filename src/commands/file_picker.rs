@@ -0,0 +1,113 @@
+use std::io::{stdout, IsTerminal};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use dialoguer::{FuzzySelect, MultiSelect};
+
+use crate::git::{git_cmd, git_output_in_dir, run_git_in_dir_silent};
+use crate::render::DiffRenderer;
+use crate::status::{get_porcelain_lines, get_repo_root};
+
+const PREVIEW_ITEM: &str = "🔍 Preview a file's diff first…";
+
+/// Which change to show when previewing a file.
+#[derive(Clone, Copy)]
+pub(crate) enum Preview {
+    /// Working tree against the index (what staging would add).
+    Unstaged,
+    /// Index against HEAD (what unstaging would take out).
+    Staged,
+    /// Working tree against HEAD (everything that is uncommitted).
+    Uncommitted,
+}
+
+/// A multi-select over repository-relative paths with an extra entry that previews one
+/// file's diff and then returns to the list, keeping what was ticked so far.
+pub(crate) fn select_files(prompt: &str, files: &[String], preview: Preview) -> Result<Vec<usize>> {
+    let mut items: Vec<&str> = files.iter().map(String::as_str).collect();
+    items.push(PREVIEW_ITEM);
+    let preview_idx = files.len();
+    let mut ticked = vec![false; items.len()];
+
+    loop {
+        let selected = MultiSelect::new()
+            .with_prompt(format!("{} (space to pick, enter to confirm)", prompt))
+            .items(&items)
+            .defaults(&ticked)
+            .interact()?;
+        if !selected.contains(&preview_idx) {
+            return Ok(selected);
+        }
+
+        ticked = (0..items.len())
+            .map(|idx| idx != preview_idx && selected.contains(&idx))
+            .collect();
+        let file = FuzzySelect::new()
+            .with_prompt("Preview which file? (type to search)")
+            .items(files)
+            .default(0)
+            .interact()?;
+        show_preview(&files[file], preview)?;
+    }
+}
+
+/// Walks through the hunks of `files` with `git add -p`, staging the ones picked. New
+/// files are marked intent-to-add first, so their lines can be picked too.
+pub(crate) fn stage_hunks(files: &[String]) -> Result<()> {
+    let repo_root = get_repo_root()?;
+    let mut args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+    args.extend(files.iter().map(String::as_str));
+    let untracked = git_output_in_dir(&args, Path::new(&repo_root))?;
+    let untracked: Vec<&str> = untracked.split('\0').filter(|p| !p.is_empty()).collect();
+    if !untracked.is_empty() {
+        let mut args = vec!["add", "--intent-to-add", "--"];
+        args.extend(untracked);
+        run_git_in_dir_silent(&args, &repo_root)?;
+    }
+
+    let mut args = vec!["add", "-p", "--"];
+    args.extend(files.iter().map(String::as_str));
+    git_cmd()
+        .args(&args)
+        .current_dir(&repo_root)
+        .status()
+        .context("failed to execute git add -p - is git installed?")?;
+    Ok(())
+}
+
+fn show_preview(file: &str, preview: Preview) -> Result<()> {
+    let repo_root = get_repo_root()?;
+    let untracked = get_porcelain_lines()?
+        .iter()
+        .any(|(code, path)| code == "??" && path == file);
+
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+    if untracked && !matches!(preview, Preview::Staged) {
+        // Untracked files have nothing to diff against, so show them as all new.
+        args.extend(["--no-index", "--", "/dev/null", file]);
+    } else {
+        match preview {
+            Preview::Unstaged => {}
+            Preview::Staged => args.push("--staged"),
+            Preview::Uncommitted => args.push("HEAD"),
+        }
+        args.extend(["--", file]);
+    }
+    // `--no-index` exits with 1 when the files differ, so the status is not checked.
+    let output = git_cmd()
+        .args(&args)
+        .current_dir(&repo_root)
+        .output()
+        .context("failed to execute git diff - is git installed?")?;
+    let diff = String::from_utf8_lossy(&output.stdout);
+
+    if diff.trim().is_empty() {
+        println!("No changes to show for {}.", file);
+    } else if stdout().is_terminal() {
+        DiffRenderer::new().print(&diff)?;
+    } else {
+        print!("{}", diff);
+    }
+    println!();
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use dialoguer::{Confirm, FuzzySelect, Input, Select};
+
+use crate::commands::operation::ensure_no_operation;
+use crate::commands::revert::run_revert;
+use crate::commands::show::run_show;
+use crate::git::run_git_quiet;
+use crate::status::{get_commits, get_porcelain_lines};
+
+const MATCH_LIMIT: &str = "200";
+
+pub fn run_find(term: Option<String>) -> Result<()> {
+    let term = match term {
+        Some(term) => term,
+        None => Input::new()
+            .with_prompt("Search commits for (message, author, or code)")
+            .interact_text()?,
+    };
+    let term = term.trim().to_string();
+    if term.is_empty() {
+        bail!("the search term cannot be empty");
+    }
+
+    // Which kinds of match each commit had, e.g. "message, code".
+    let mut reasons: HashMap<String, Vec<&str>> = HashMap::new();
+    let searches: [(&str, Vec<String>); 3] = [
+        (
+            "message",
+            vec![format!("--grep={}", term), "--regexp-ignore-case".into()],
+        ),
+        (
+            "author",
+            vec![format!("--author={}", term), "--regexp-ignore-case".into()],
+        ),
+        ("code", vec![format!("-S{}", term)]),
+    ];
+    for (reason, args) in &searches {
+        let mut log_args: Vec<&str> = vec!["--fixed-strings", "-n", MATCH_LIMIT];
+        log_args.extend(args.iter().map(String::as_str));
+        for commit in get_commits(&log_args)? {
+            reasons.entry(commit.hash).or_default().push(reason);
+        }
+    }
+    if reasons.is_empty() {
+        println!("No commits match '{}'.", term);
+        return Ok(());
+    }
+
+    // Re-read the matches in date order, newest first.
+    let mut log_args = vec!["--no-walk=sorted"];
+    log_args.extend(reasons.keys().map(String::as_str));
+    let commits = get_commits(&log_args)?;
+    let labels: Vec<String> = commits
+        .iter()
+        .map(|c| format!("{}  [{}]", c.label(), reasons[&c.hash].join(", ")))
+        .collect();
+
+    println!("{} commit(s) match '{}'.", commits.len(), term);
+    let selection = FuzzySelect::new()
+        .with_prompt("Pick a commit (type to narrow down)")
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    let commit = &commits[selection];
+
+    let action = Select::new()
+        .with_prompt(format!(
+            "What do you want to do with {}?",
+            commit.short_hash
+        ))
+        .items(&[
+            "Show its changes",
+            "Check it out (look around in detached HEAD)",
+            "Cherry-pick it onto the current branch",
+            "Revert it (new commit that undoes it)",
+            "Nothing",
+        ])
+        .default(0)
+        .interact()?;
+    match action {
+        0 => run_show(Some(commit.hash.clone()))?,
+        1 => {
+            if !get_porcelain_lines()?.is_empty() {
+                bail!("you have uncommitted changes - commit or stash them before checking out another commit");
+            }
+            run_git_quiet(&["checkout", "--quiet", "--detach", &commit.hash])?;
+            println!("✓ Checked out {} (detached HEAD)", commit.short_hash);
+            println!("  Look around, then return with 'sgit branch' or 'git switch -'.");
+        }
+        2 => {
+            ensure_no_operation("sgit find")?;
+            let confirm = Confirm::new()
+                .with_prompt(format!(
+                    "Copy '{}' onto the current branch as a new commit?",
+                    commit.subject
+                ))
+                .default(true)
+                .interact()?;
+            if !confirm {
+                println!("Aborted.");
+                return Ok(());
+            }
+            println!("→ Cherry-picking {}...", commit.short_hash);
+            run_git_quiet(&["cherry-pick", &commit.hash])?;
+            println!("✓ Cherry-picked onto the current branch");
+        }
+        3 => {
+            ensure_no_operation("sgit find")?;
+            run_revert(Some(commit.hash.clone()), false, None)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+use dialoguer::Select;
+
+use crate::cli::FixupAction;
+use crate::git::run_git_quiet_with_env;
+use crate::status::{get_commit_parents, get_commits, get_unpushed_commits, resolve_commit};
+
+const PICKER_COMMIT_LIMIT: usize = 20;
+
+pub fn run_fixup(action: FixupAction) -> Result<()> {
+    match action {
+        FixupAction::Apply => apply_fixups(),
+    }
+}
+
+/// Resolves the commit a `--fixup` should target, asking interactively when `target` is empty.
+/// Only unpushed commits qualify: `sgit fixup apply` folds fixups into those alone.
+pub(crate) fn resolve_fixup_target(target: &str) -> Result<String> {
+    let unpushed = get_unpushed_commits()?;
+    if !target.is_empty() {
+        let hash = resolve_commit(target)?;
+        if !unpushed.contains(&hash) {
+            bail!(
+                "{} is already pushed - fixups only fold into unpushed commits\n  hint: commit the change normally instead",
+                target
+            );
+        }
+        return Ok(hash);
+    }
+
+    let commits: Vec<_> = get_commits(&["-n", &PICKER_COMMIT_LIMIT.to_string()])?
+        .into_iter()
+        .filter(|c| unpushed.contains(&c.hash))
+        .collect();
+    if commits.is_empty() {
+        bail!("there are no unpushed commits to fix up - commit the change normally instead");
+    }
+    let labels: Vec<String> = commits.iter().map(|c| c.label()).collect();
+
+    let selection = Select::new()
+        .with_prompt("Which commit should these changes be folded into?")
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    Ok(commits[selection].hash.clone())
+}
+
+fn apply_fixups() -> Result<()> {
+    let unpushed = get_unpushed_commits()?;
+    if unpushed.is_empty() {
+        println!("No unpushed commits - nothing to apply.");
+        return Ok(());
+    }
+
+    let oldest = unpushed.last().cloned().unwrap_or_default();
+    let base = get_commit_parents(&oldest)?.into_iter().next();
+    let range = match base {
+        Some(ref base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+
+    let fixups = get_commits(&["--grep=^fixup! ", "--grep=^squash! ", range.as_str()])?;
+    if fixups.is_empty() {
+        println!("No fixup commits in your unpushed history.");
+        println!("  Create one with 'sgit commit --fixup'.");
+        return Ok(());
+    }
+
+    println!(
+        "Folding {} fixup commit(s) into their targets:",
+        fixups.len()
+    );
+    for commit in &fixups {
+        println!("  {}", commit.label());
+    }
+
+    let mut args = vec!["rebase", "-i", "--autosquash", "--autostash"];
+    match base {
+        Some(ref base) => args.push(base.as_str()),
+        None => args.push("--root"),
+    }
+    run_git_quiet_with_env(
+        &args,
+        &[("GIT_SEQUENCE_EDITOR", "true"), ("GIT_EDITOR", "true")],
+    )?;
+    println!("✓ Fixups applied");
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use anyhow::{bail, Context, Result};
+
+use crate::git::git_cmd;
+use crate::status::get_commits;
+
+pub struct GrepOptions {
+    pub pattern: String,
+    pub paths: Vec<String>,
+    pub ignore_case: bool,
+    /// Treat the pattern as an extended regular expression instead of plain text.
+    pub regex: bool,
+    pub history: bool,
+}
+
+pub fn run_grep(opts: GrepOptions) -> Result<()> {
+    if opts.pattern.is_empty() {
+        bail!("the search text cannot be empty");
+    }
+    if opts.history {
+        grep_history(&opts)
+    } else {
+        grep_files(&opts)
+    }
+}
+
+/// Searches the tracked files, grouping matches by file.
+fn grep_files(opts: &GrepOptions) -> Result<()> {
+    let mut args = vec!["grep", "-n", "-I", "-z", "--no-color"];
+    args.push(if opts.regex { "-E" } else { "-F" });
+    if opts.ignore_case {
+        args.push("-i");
+    }
+    args.push("-e");
+    args.push(&opts.pattern);
+    args.push("--");
+    args.extend(opts.paths.iter().map(String::as_str));
+
+    let output = git_cmd().args(&args).output().context("running git grep")?;
+    // git grep exits with 1 when nothing matched.
+    if output.status.code() == Some(1) {
+        println!("No matches for '{}' in tracked files.", opts.pattern);
+        println!("  Add --history to search past versions of the code.");
+        return Ok(());
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git grep failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut current_file = "";
+    let mut files = 0;
+    let mut matches = 0;
+    for line in stdout.lines() {
+        // `-z` separates path, line number, and text with NULs.
+        let mut fields = line.splitn(3, '\0');
+        let (Some(file), Some(number), Some(text)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if file != current_file {
+            if files > 0 {
+                println!();
+            }
+            println!("{}", file);
+            current_file = file;
+            files += 1;
+        }
+        println!("  {:>5} │ {}", number, text.trim_end());
+        matches += 1;
+    }
+    println!();
+    println!("{} match(es) in {} file(s)", matches, files);
+    Ok(())
+}
+
+/// Lists the commits that added or removed the text, oldest first.
+fn grep_history(opts: &GrepOptions) -> Result<()> {
+    let pickaxe = if opts.regex {
+        format!("-G{}", opts.pattern)
+    } else {
+        format!("-S{}", opts.pattern)
+    };
+    let mut log_args = vec![pickaxe.as_str(), "--reverse"];
+    if opts.ignore_case {
+        log_args.push("--regexp-ignore-case");
+    }
+    log_args.push("--");
+    log_args.extend(opts.paths.iter().map(String::as_str));
+
+    let commits = get_commits(&log_args)?;
+    if commits.is_empty() {
+        println!("No commit ever added or removed '{}'.", opts.pattern);
+        return Ok(());
+    }
+
+    println!(
+        "{} commit(s) added or removed '{}' (oldest first):",
+        commits.len(),
+        opts.pattern
+    );
+    for commit in &commits {
+        let (added, removed) = count_changed_lines(&commit.hash, opts)?;
+        let change = match (added, removed) {
+            (0, 0) => String::new(),
+            (a, 0) => format!("  [+{} line(s)]", a),
+            (0, r) => format!("  [-{} line(s)]", r),
+            (a, r) => format!("  [+{} -{} line(s)]", a, r),
+        };
+        println!("  {}{}", commit.label(), change);
+    }
+    println!();
+    println!("  Run 'sgit show <hash>' to see a commit's full change.");
+    Ok(())
+}
+
+/// Counts added and removed lines in a commit's diff that contain the text. Regex
+/// searches are not counted, since matching them would need a regex engine.
+fn count_changed_lines(hash: &str, opts: &GrepOptions) -> Result<(usize, usize)> {
+    if opts.regex {
+        return Ok((0, 0));
+    }
+    let mut args = vec!["show", "--format=", "--unified=0", "--no-color", hash, "--"];
+    args.extend(opts.paths.iter().map(String::as_str));
+    let output = git_cmd().args(&args).output().context("running git show")?;
+    let diff = String::from_utf8_lossy(&output.stdout);
+
+    let needle = if opts.ignore_case {
+        opts.pattern.to_lowercase()
+    } else {
+        opts.pattern.clone()
+    };
+    let contains = |line: &str| {
+        if opts.ignore_case {
+            line.to_lowercase().contains(&needle)
+        } else {
+            line.contains(&needle)
+        }
+    };
+
+    let mut added = 0;
+    let mut removed = 0;
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('+')
+            && contains(rest)
+        {
+            added += 1;
+        } else if let Some(rest) = line.strip_prefix('-')
+            && contains(rest)
+        {
+            removed += 1;
+        }
+    }
+    Ok((added, removed))
+}
@@ -0,0 +1,318 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::cli::HistoryAction;
+use crate::commands::operation::ensure_no_operation;
+use crate::git::{git_program, run_git_quiet_with_env};
+use crate::status::{
+    get_commit_message, get_commits, get_git_dir, get_unpushed_commits, CommitSummary,
+};
+
+const DEFAULT_EDIT_COUNT: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PlanAction {
+    Keep,
+    Squash,
+    Reword,
+    Drop,
+}
+
+impl PlanAction {
+    fn label(self) -> &'static str {
+        match self {
+            PlanAction::Keep => "keep  ",
+            PlanAction::Squash => "squash",
+            PlanAction::Reword => "reword",
+            PlanAction::Drop => "drop  ",
+        }
+    }
+}
+
+struct PlanEntry {
+    original_position: usize,
+    commit: CommitSummary,
+    action: PlanAction,
+    new_message: Option<String>,
+}
+
+pub fn run_history(action: HistoryAction) -> Result<()> {
+    ensure_no_operation("sgit history")?;
+    match action {
+        HistoryAction::Edit { count, force } => {
+            edit_history(count.unwrap_or(DEFAULT_EDIT_COUNT), force)
+        }
+    }
+}
+
+/// Refuses (or, with `force`, warns) when any of `commits` is already on a remote.
+/// Returns whether a force-push will be needed afterwards.
+pub(crate) fn ensure_unpushed(
+    commits: &[&CommitSummary],
+    force: bool,
+    action: &str,
+) -> Result<bool> {
+    let unpushed = get_unpushed_commits()?;
+    let pushed: Vec<_> = commits
+        .iter()
+        .filter(|c| !unpushed.contains(&c.hash))
+        .collect();
+    if pushed.is_empty() {
+        return Ok(false);
+    }
+
+    eprintln!(
+        "⚠ {} of these commits are already on a remote:",
+        pushed.len()
+    );
+    for commit in &pushed {
+        eprintln!("    {}", commit.label());
+    }
+    eprintln!(
+        "  {} rewrites history; you would need to force-push afterwards.",
+        action
+    );
+    if !force {
+        bail!("refusing to rewrite pushed commits - pass --force if you really want to");
+    }
+    Ok(true)
+}
+
+fn edit_history(count: usize, force: bool) -> Result<()> {
+    if count == 0 {
+        bail!("need at least 1 commit to edit");
+    }
+
+    let mut commits = get_commits(&["--first-parent", "-n", &(count + 1).to_string()])?;
+    if commits.is_empty() {
+        bail!("this branch has no commits yet");
+    }
+    let base = if commits.len() > count {
+        commits.pop().map(|c| c.hash)
+    } else {
+        None
+    };
+
+    let range = match base {
+        Some(ref base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+    if !get_commits(&["--merges", range.as_str()])?.is_empty() {
+        bail!(
+            "the last {} commits include a merge - history edit only supports linear history",
+            count
+        );
+    }
+
+    let needs_force_push = ensure_unpushed(
+        &commits.iter().collect::<Vec<_>>(),
+        force,
+        "Editing history",
+    )?;
+
+    // Rebase plans list the oldest commit first.
+    commits.reverse();
+    let mut plan: Vec<PlanEntry> = commits
+        .into_iter()
+        .enumerate()
+        .map(|(original_position, commit)| PlanEntry {
+            original_position,
+            commit,
+            action: PlanAction::Keep,
+            new_message: None,
+        })
+        .collect();
+
+    println!("Recent commits, oldest at the top. Squash folds a commit into the one above it.");
+    if !edit_plan(&mut plan)? {
+        println!("Aborted. Nothing was changed.");
+        return Ok(());
+    }
+
+    if plan.iter().all(|e| e.action == PlanAction::Keep) && is_original_order(&plan) {
+        println!("No changes selected.");
+        return Ok(());
+    }
+
+    apply_plan(&plan, base.as_deref())?;
+    println!("✓ History rewritten");
+    if needs_force_push {
+        println!("  Run 'git push --force-with-lease' to update the remote.");
+    }
+    Ok(())
+}
+
+/// Lets the user adjust the plan. Returns `false` if they cancelled.
+fn edit_plan(plan: &mut [PlanEntry]) -> Result<bool> {
+    let mut cursor = 0;
+    loop {
+        let mut items: Vec<String> = plan
+            .iter()
+            .map(|e| {
+                let subject = e
+                    .new_message
+                    .as_deref()
+                    .and_then(|m| m.lines().next())
+                    .unwrap_or(&e.commit.subject);
+                format!("{} {} {}", e.action.label(), e.commit.short_hash, subject)
+            })
+            .collect();
+        items.push("✓ Apply these changes".to_string());
+        items.push("✗ Cancel".to_string());
+
+        let selection = Select::new()
+            .with_prompt("Pick a commit to change")
+            .items(&items)
+            .default(cursor)
+            .interact()?;
+
+        if selection == plan.len() {
+            // Dropped commits are gone, so the first one left must not be a squash.
+            let first_kept = plan.iter().find(|e| e.action != PlanAction::Drop);
+            if first_kept.map(|e| e.action) == Some(PlanAction::Squash) {
+                eprintln!("⚠ The first commit left has nothing above it to squash into.");
+                continue;
+            }
+            return Ok(true);
+        }
+        if selection == plan.len() + 1 {
+            return Ok(false);
+        }
+
+        cursor = selection;
+        let choice = Select::new()
+            .with_prompt(format!(
+                "What should happen to {}?",
+                plan[selection].commit.short_hash
+            ))
+            .items(&[
+                "Keep",
+                "Squash into the commit above",
+                "Reword the message",
+                "Drop (delete this commit)",
+                "Move up",
+                "Move down",
+            ])
+            .default(0)
+            .interact()?;
+
+        match choice {
+            0 => plan[selection].action = PlanAction::Keep,
+            1 => plan[selection].action = PlanAction::Squash,
+            2 => {
+                let current = match plan[selection].new_message.clone() {
+                    Some(message) => message,
+                    None => get_commit_message(&plan[selection].commit.hash)?,
+                };
+                if let Some(message) = prompt_reworded_message(&current)? {
+                    plan[selection].action = PlanAction::Reword;
+                    plan[selection].new_message = Some(message);
+                }
+            }
+            3 => {
+                let sure = Confirm::new()
+                    .with_prompt("Drop this commit and its changes?")
+                    .default(false)
+                    .interact()?;
+                if sure {
+                    plan[selection].action = PlanAction::Drop;
+                }
+            }
+            4 if selection > 0 => {
+                plan.swap(selection, selection - 1);
+                cursor = selection - 1;
+            }
+            5 if selection + 1 < plan.len() => {
+                plan.swap(selection, selection + 1);
+                cursor = selection + 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Asks for a new subject line, keeping the body of `current`.
+/// Returns `None` if the user left the subject empty.
+pub(crate) fn prompt_reworded_message(current: &str) -> Result<Option<String>> {
+    let subject: String = Input::new()
+        .with_prompt("New commit message")
+        .with_initial_text(current.lines().next().unwrap_or(""))
+        .interact_text()?;
+    if subject.trim().is_empty() {
+        eprintln!("⚠ Commit message cannot be empty; keeping the old message.");
+        return Ok(None);
+    }
+
+    let body: Vec<&str> = current.lines().skip(1).collect();
+    let mut message = subject.trim().to_string();
+    if !body.is_empty() {
+        message.push('\n');
+        message.push_str(&body.join("\n"));
+    }
+    Ok(Some(message))
+}
+
+fn is_original_order(plan: &[PlanEntry]) -> bool {
+    plan.iter()
+        .enumerate()
+        .all(|(idx, e)| e.original_position == idx)
+}
+
+fn apply_plan(plan: &[PlanEntry], base: Option<&str>) -> Result<()> {
+    let git_dir = get_git_dir()?;
+    let work_dir = Path::new(&git_dir).join("sgit-history");
+    fs::create_dir_all(&work_dir)
+        .with_context(|| format!("failed to create {}", work_dir.display()))?;
+
+    let mut todo = String::new();
+    for (idx, entry) in plan.iter().enumerate() {
+        let hash = &entry.commit.hash;
+        match entry.action {
+            PlanAction::Keep => todo.push_str(&format!("pick {}\n", hash)),
+            PlanAction::Squash => todo.push_str(&format!("squash {}\n", hash)),
+            PlanAction::Drop => todo.push_str(&format!("drop {}\n", hash)),
+            PlanAction::Reword => {
+                let message_path = work_dir.join(format!("message-{}", idx));
+                fs::write(&message_path, entry.new_message.as_deref().unwrap_or(""))
+                    .with_context(|| format!("failed to write {}", message_path.display()))?;
+                todo.push_str(&format!("pick {}\n", hash));
+                todo.push_str(&format!(
+                    "exec {} commit --amend --allow-empty --no-verify -F {}\n",
+                    shell_quote(&git_program().to_string_lossy()),
+                    shell_quote(&message_path.to_string_lossy())
+                ));
+            }
+        }
+    }
+
+    let plan_path = work_dir.join("plan");
+    fs::write(&plan_path, todo)
+        .with_context(|| format!("failed to write {}", plan_path.display()))?;
+
+    let sequence_editor = format!("cp {}", shell_quote(&plan_path.to_string_lossy()));
+    let mut args = vec!["rebase", "-i"];
+    match base {
+        Some(base) => args.push(base),
+        None => args.push("--root"),
+    }
+
+    println!("→ Rewriting history...");
+    run_git_quiet_with_env(
+        &args,
+        &[
+            ("GIT_SEQUENCE_EDITOR", sequence_editor.as_str()),
+            // Squashed messages are combined automatically instead of opening an editor.
+            ("GIT_EDITOR", "true"),
+        ],
+    )?;
+
+    let _ = fs::remove_dir_all(&work_dir);
+    Ok(())
+}
+
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
@@ -0,0 +1,426 @@
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::cli::HooksAction;
+use crate::git::run_git_silent;
+use crate::hooks::{
+    find_template, has_precommit_config, hooks_dir, installed_hooks, make_executable,
+    precommit_installed, render_section, replace_section, HookTemplate, InstalledHook,
+    BACKUP_SUFFIX, DISABLED_SUFFIX, HOOK_TEMPLATES, PRECOMMIT_CONFIG, SCRIPT_HEADER,
+    TEMPLATE_MARKER,
+};
+use crate::status::{get_config_value, get_repo_root};
+
+pub fn run_hooks(action: Option<HooksAction>) -> Result<()> {
+    match action.unwrap_or(HooksAction::List) {
+        HooksAction::List => list_hooks(),
+        HooksAction::Enable { name } => set_enabled(name, true),
+        HooksAction::Disable { name } => set_enabled(name, false),
+        HooksAction::Install {
+            template,
+            shared,
+            force,
+            set,
+        } => install(template, shared, force, set),
+        HooksAction::InstallPrecommit => install_precommit(),
+    }
+}
+
+fn list_hooks() -> Result<()> {
+    let dir = hooks_dir()?;
+    let hooks = installed_hooks(&dir)?;
+    let source = if get_config_value("core.hooksPath").is_some() {
+        " (core.hooksPath)"
+    } else {
+        ""
+    };
+    if hooks.is_empty() {
+        println!("No hooks in {}{}.", display_dir(&dir), source);
+        println!("  'sgit hooks install' adds one from sgit's templates.");
+    } else {
+        println!("Hooks in {}{}:", display_dir(&dir), source);
+    }
+    for hook in &hooks {
+        let runs = if hook.precommit {
+            format!("runs the checks in {} (pre-commit)", PRECOMMIT_CONFIG)
+        } else if hook.templates.is_empty() {
+            "your own script".to_string()
+        } else {
+            format!("runs {}", hook.templates.join(", "))
+        };
+        if !hook.enabled {
+            println!(
+                "  ✗ {:<18} disabled - 'sgit hooks enable {}' turns it back on",
+                hook.name, hook.name
+            );
+        } else if !hook.executable {
+            println!(
+                "  ⚠ {:<18} {}, but not executable, so git skips it - 'sgit doctor' fixes that",
+                hook.name, runs
+            );
+        } else {
+            println!("  ✓ {:<18} {}", hook.name, runs);
+        }
+    }
+    if has_precommit_config() && !precommit_installed() {
+        println!(
+            "⚠ {} lists checks, but pre-commit is not installed as a hook, so they never run - 'sgit hooks install-precommit'",
+            PRECOMMIT_CONFIG
+        );
+    }
+    Ok(())
+}
+
+/// Enables or disables the hook `name` (picked when omitted) by renaming it.
+fn set_enabled(name: Option<String>, enable: bool) -> Result<()> {
+    let dir = hooks_dir()?;
+    let hooks = installed_hooks(&dir)?;
+    let candidates: Vec<&InstalledHook> =
+        hooks.iter().filter(|hook| hook.enabled != enable).collect();
+    let hook = match name {
+        Some(name) => {
+            let Some(hook) = hooks.iter().find(|hook| hook.name == name) else {
+                bail!(
+                    "there is no {} hook in {} - 'sgit hooks list' shows the installed ones",
+                    name,
+                    display_dir(&dir)
+                );
+            };
+            if hook.enabled == enable {
+                println!(
+                    "The {} hook is already {}.",
+                    name,
+                    if enable { "enabled" } else { "disabled" }
+                );
+                return Ok(());
+            }
+            hook
+        }
+        None => {
+            if candidates.is_empty() {
+                println!("No hooks to {}.", if enable { "enable" } else { "disable" });
+                return Ok(());
+            }
+            if !stdin().is_terminal() {
+                bail!(
+                    "name the hook: 'sgit hooks {} <name>'",
+                    if enable { "enable" } else { "disable" }
+                );
+            }
+            let names: Vec<&str> = candidates.iter().map(|hook| hook.name.as_str()).collect();
+            let selection = Select::new()
+                .with_prompt(if enable {
+                    "Which hook should run again?"
+                } else {
+                    "Which hook should stop running?"
+                })
+                .items(&names)
+                .default(0)
+                .interact()?;
+            candidates[selection]
+        }
+    };
+
+    let target = if enable {
+        dir.join(&hook.name)
+    } else {
+        dir.join(format!("{}{}", hook.name, DISABLED_SUFFIX))
+    };
+    if target.exists() {
+        bail!(
+            "{} already exists - remove one of the two copies of the {} hook first",
+            target.display(),
+            hook.name
+        );
+    }
+    fs::rename(&hook.path, &target)
+        .with_context(|| format!("failed to rename {}", hook.path.display()))?;
+    if enable {
+        make_executable(&target)?;
+        println!("✓ The {} hook runs again", hook.name);
+    } else {
+        println!(
+            "✓ Disabled the {} hook - 'sgit hooks enable {}' turns it back on",
+            hook.name, hook.name
+        );
+    }
+    Ok(())
+}
+
+/// Installs a template (picked when omitted) into the hooks directory, or into the
+/// shared directory `shared` with `core.hooksPath` pointed at it. `set` holds
+/// `name=value` parameters; the rest are asked for, or take their defaults.
+fn install(
+    template: Option<String>,
+    shared: Option<String>,
+    force: bool,
+    set: Vec<String>,
+) -> Result<()> {
+    let template = match template {
+        Some(name) => match find_template(&name) {
+            Some(template) => template,
+            None => {
+                let known: Vec<&str> = HOOK_TEMPLATES.iter().map(|t| t.name).collect();
+                bail!(
+                    "no hook template named '{}' - choose from: {}",
+                    name,
+                    known.join(", ")
+                );
+            }
+        },
+        None => pick_template()?,
+    };
+    let values = template_values(template, &set)?;
+
+    let dir = match &shared {
+        Some(shared) => Path::new(&get_repo_root()?).join(shared),
+        None => hooks_dir()?,
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    write_hook(&dir, template, &values, force)?;
+
+    if let Some(shared) = shared {
+        use_shared_dir(&shared)?;
+    }
+    Ok(())
+}
+
+fn pick_template() -> Result<&'static HookTemplate> {
+    if !stdin().is_terminal() {
+        let known: Vec<&str> = HOOK_TEMPLATES.iter().map(|t| t.name).collect();
+        bail!(
+            "name the template: 'sgit hooks install <template>' ({})",
+            known.join(", ")
+        );
+    }
+    let items: Vec<String> = HOOK_TEMPLATES
+        .iter()
+        .map(|t| format!("{:<20} {:<11} {}", t.name, t.hook, t.about))
+        .collect();
+    let selection = Select::new()
+        .with_prompt("Which hook should be installed?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(&HOOK_TEMPLATES[selection])
+}
+
+/// The value of each of `template`'s parameters: from `--set`, asked for on a terminal,
+/// or the default.
+fn template_values(template: &HookTemplate, set: &[String]) -> Result<Vec<(&'static str, String)>> {
+    let mut given: Vec<(&str, &str)> = Vec::new();
+    for pair in set {
+        let Some((name, value)) = pair.split_once('=') else {
+            bail!("--set takes NAME=VALUE, got '{}'", pair);
+        };
+        if !template.params.iter().any(|param| param.name == name) {
+            let known: Vec<&str> = template.params.iter().map(|param| param.name).collect();
+            if known.is_empty() {
+                bail!("the {} template has no parameters", template.name);
+            }
+            bail!(
+                "the {} template has no parameter '{}' - it takes: {}",
+                template.name,
+                name,
+                known.join(", ")
+            );
+        }
+        given.push((name, value));
+    }
+
+    let interactive = stdin().is_terminal();
+    let mut values = Vec::new();
+    for param in template.params {
+        let value = match given.iter().rev().find(|(name, _)| *name == param.name) {
+            Some((_, value)) => value.to_string(),
+            None if interactive => Input::<String>::new()
+                .with_prompt(param.prompt)
+                .default(param.default.to_string())
+                .interact_text()?,
+            None => param.default.to_string(),
+        };
+        values.push((param.name, value));
+    }
+    Ok(values)
+}
+
+/// Adds `template` to its hook in `dir`. A hook sgit manages gets another section, or has
+/// the template's section rewritten when it already runs it; any other script is only
+/// replaced with `force`, or when the user agrees, and kept as a backup.
+fn write_hook(
+    dir: &Path,
+    template: &HookTemplate,
+    values: &[(&str, String)],
+    force: bool,
+) -> Result<()> {
+    let path = dir.join(template.hook);
+    let existing = match fs::read_to_string(&path) {
+        Ok(script) => Some(script),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", path.display())),
+    };
+    if dir
+        .join(format!("{}{}", template.hook, DISABLED_SUFFIX))
+        .exists()
+    {
+        bail!(
+            "the {} hook is disabled - 'sgit hooks enable {}' first, then install into it",
+            template.hook,
+            template.hook
+        );
+    }
+
+    let section = render_section(template, values);
+    let mut updated = false;
+    let script = match existing {
+        None => format!("{}{}", SCRIPT_HEADER, section),
+        Some(script) if script.contains(TEMPLATE_MARKER) => {
+            match replace_section(&script, template, &section) {
+                Some(replaced) if replaced == script => {
+                    println!("The {} hook already runs {}.", template.hook, template.name);
+                    return Ok(());
+                }
+                Some(replaced) => {
+                    updated = true;
+                    replaced
+                }
+                None => format!("{}{}", script, section),
+            }
+        }
+        Some(_) => {
+            let replace = force
+                || (stdin().is_terminal()
+                    && Confirm::new()
+                        .with_prompt(format!(
+                            "{} already has a {} hook sgit did not write. Replace it (keeping a backup)?",
+                            display_dir(dir),
+                            template.hook
+                        ))
+                        .default(false)
+                        .interact()?);
+            if !replace {
+                bail!(
+                    "{} already exists - pass --force to replace it (it is kept as {}{})",
+                    path.display(),
+                    template.hook,
+                    BACKUP_SUFFIX
+                );
+            }
+            let backup = dir.join(format!("{}{}", template.hook, BACKUP_SUFFIX));
+            fs::rename(&path, &backup)
+                .with_context(|| format!("failed to back up {}", path.display()))?;
+            println!("  Kept the old hook as {}", backup.display());
+            format!("{}{}", SCRIPT_HEADER, section)
+        }
+    };
+    fs::write(&path, script).with_context(|| format!("failed to write {}", path.display()))?;
+    make_executable(&path)?;
+    if updated {
+        println!("✓ Updated {} in the {} hook", template.name, template.hook);
+    } else {
+        println!(
+            "✓ The {} hook now runs {}: {}",
+            template.hook, template.name, template.about
+        );
+    }
+    Ok(())
+}
+
+/// Runs `pre-commit install`, so git runs the checks in .pre-commit-config.yaml.
+fn install_precommit() -> Result<()> {
+    let root = get_repo_root()?;
+    if !has_precommit_config() {
+        bail!(
+            "there is no {} in {} - the pre-commit framework reads its checks from it (https://pre-commit.com)",
+            PRECOMMIT_CONFIG,
+            root
+        );
+    }
+    if let Some(path) = get_config_value("core.hooksPath") {
+        bail!(
+            "core.hooksPath is set to {}, and pre-commit only installs into .git/hooks - unset it with 'git config --unset core.hooksPath', or call 'pre-commit run' from a hook there",
+            path
+        );
+    }
+    if precommit_installed() {
+        println!(
+            "pre-commit already runs the checks in {}.",
+            PRECOMMIT_CONFIG
+        );
+        return Ok(());
+    }
+    // pre-commit moves any existing hook aside as pre-commit.legacy and keeps running it.
+    let status = match StdCommand::new("pre-commit")
+        .arg("install")
+        .current_dir(&root)
+        .status()
+    {
+        Ok(status) => status,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => bail!(
+            "pre-commit is not installed - get it with 'pipx install pre-commit' (or 'pip install pre-commit'), then run this again"
+        ),
+        Err(err) => return Err(err).context("failed to run pre-commit install"),
+    };
+    if !status.success() {
+        bail!("pre-commit install failed - its output is above");
+    }
+    println!(
+        "✓ git now runs the checks in {} before each commit",
+        PRECOMMIT_CONFIG
+    );
+    println!("  The first commit sets up the tools they use, which can take a while; 'pre-commit run --all-files' does it now.");
+    Ok(())
+}
+
+/// Points `core.hooksPath` at the shared directory, warning about hooks that stop running.
+fn use_shared_dir(shared: &str) -> Result<()> {
+    if get_config_value("core.hooksPath").as_deref() == Some(shared) {
+        return Ok(());
+    }
+    let previous = hooks_dir()?;
+    let left_behind: Vec<String> = installed_hooks(&previous)?
+        .into_iter()
+        .filter(|hook| hook.enabled)
+        .map(|hook| hook.name)
+        .collect();
+    run_git_silent(&["config", "--local", "core.hooksPath", shared])?;
+    println!("✓ git now runs the hooks in {}", shared);
+    if !left_behind.is_empty() {
+        println!(
+            "⚠ These hooks in {} no longer run: {} - move the ones you still want into {}",
+            display_dir(&previous),
+            left_behind.join(", "),
+            shared
+        );
+    }
+    println!(
+        "  Commit {} so the team gets it; each clone turns it on with 'git config core.hooksPath {}'.",
+        shared, shared
+    );
+    if !cfg!(unix) {
+        // Windows has no executable bit for git to pick up when the file is added.
+        println!(
+            "  Add it with 'git add --chmod=+x {}' so the hooks stay executable on Linux and macOS.",
+            shared
+        );
+    }
+    Ok(())
+}
+
+/// `dir` relative to the repository root when it is inside it, like `.git/hooks`.
+fn display_dir(dir: &Path) -> String {
+    get_repo_root()
+        .ok()
+        .and_then(|root| {
+            dir.strip_prefix(PathBuf::from(root))
+                .ok()
+                .map(|relative| relative.display().to_string())
+        })
+        .filter(|relative| !relative.is_empty())
+        .unwrap_or_else(|| dir.display().to_string())
+}
@@ -0,0 +1,315 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{stdin, IsTerminal, Write};
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::MultiSelect;
+
+use crate::commands::import::append_to_gitignore;
+use crate::git::git_cmd;
+use crate::status::get_repo_root;
+use crate::templates::{find_ignore_template, IGNORE_TEMPLATES};
+
+/// Adds `patterns` and the rules of `templates` to the repository's top-level .gitignore,
+/// skipping lines it already has; with neither, picks from the untracked files. With
+/// `why`, explains instead which rule (if any) ignores that path.
+pub fn run_ignore(
+    patterns: Vec<String>,
+    templates: Vec<String>,
+    why: Option<String>,
+) -> Result<()> {
+    if let Some(path) = why {
+        return explain_ignored(&path);
+    }
+    let root = get_repo_root()?;
+    let root = Path::new(&root);
+    let existing = existing_rules(root)?;
+
+    let mut patterns: Vec<String> = patterns
+        .iter()
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect();
+    if patterns.is_empty() && templates.is_empty() {
+        patterns = pick_untracked(root)?;
+        if patterns.is_empty() {
+            println!("Nothing picked - .gitignore is unchanged.");
+            return Ok(());
+        }
+    }
+
+    let mut added: Vec<String> = Vec::new();
+    let mut contents = String::new();
+    let mut present = existing.clone();
+    for pattern in &patterns {
+        if present.insert(pattern.clone()) {
+            contents.push_str(pattern);
+            contents.push('\n');
+            added.push(pattern.clone());
+        } else {
+            println!("  {} is already in .gitignore", pattern);
+        }
+    }
+    for name in &templates {
+        let Some(template) = find_ignore_template(name) else {
+            let known: Vec<&str> = IGNORE_TEMPLATES.iter().map(|t| t.name).collect();
+            bail!(
+                "no .gitignore template named '{}' - choose from: {}",
+                name,
+                known.join(", ")
+            );
+        };
+        let new: Vec<&str> = template
+            .patterns
+            .lines()
+            .filter(|line| present.insert(line.to_string()))
+            .collect();
+        if new.is_empty() {
+            println!(
+                "  The {} rules are all in .gitignore already",
+                template.name
+            );
+            continue;
+        }
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&format!("# {}\n", template.name));
+        for line in new {
+            contents.push_str(line);
+            contents.push('\n');
+            added.push(line.to_string());
+        }
+    }
+    if added.is_empty() {
+        return Ok(());
+    }
+
+    // Set a template's section apart from the rules before it.
+    if !existing.is_empty() && contents.starts_with('#') {
+        contents.insert(0, '\n');
+    }
+    append_to_gitignore(root, &contents)?;
+    println!("✓ Added {} rule(s) to .gitignore", added.len());
+    warn_tracked_matches(root, &added)?;
+    Ok(())
+}
+
+/// The rules already in the top-level .gitignore, without comments and blank lines.
+fn existing_rules(root: &Path) -> Result<HashSet<String>> {
+    let path = root.join(".gitignore");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", path.display())),
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Lets the user tick untracked files and directories, returned as rules that match
+/// exactly those paths.
+fn pick_untracked(root: &Path) -> Result<Vec<String>> {
+    if !stdin().is_terminal() {
+        bail!("give the patterns to ignore, e.g. 'sgit ignore \"*.log\" build/', or a template with --template");
+    }
+    let output = git_cmd()
+        .args([
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "--directory",
+            "-z",
+        ])
+        .current_dir(root)
+        .output()
+        .context("running git ls-files")?;
+    let untracked: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    if untracked.is_empty() {
+        println!("There are no untracked files to ignore.");
+        return Ok(Vec::new());
+    }
+    let selected = MultiSelect::new()
+        .with_prompt("Which untracked files should git ignore? (space to pick, enter to confirm)")
+        .items(&untracked)
+        .interact()?;
+    // A leading `/` keeps the rule to this path instead of any file with the same name.
+    Ok(selected
+        .into_iter()
+        .map(|idx| format!("/{}", untracked[idx]))
+        .collect())
+}
+
+/// Ignoring only affects untracked files, so points out tracked ones the new rules match.
+fn warn_tracked_matches(root: &Path, rules: &[String]) -> Result<()> {
+    let mut args = vec!["ls-files", "--cached", "--ignored", "-z"];
+    for rule in rules {
+        args.push("--exclude");
+        args.push(rule);
+    }
+    let output = git_cmd()
+        .args(&args)
+        .current_dir(root)
+        .output()
+        .context("running git ls-files")?;
+    let tracked: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    if tracked.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "⚠ {} tracked file(s) match but stay in the repository:",
+        tracked.len()
+    );
+    for path in tracked.iter().take(10) {
+        println!("  {}", path);
+    }
+    if tracked.len() > 10 {
+        println!("  … and {} more", tracked.len() - 10);
+    }
+    println!(
+        "  Stop tracking them (keeping the files) with 'git rm --cached <file>', then commit."
+    );
+    Ok(())
+}
+
+/// The rule deciding whether a path is ignored, as `git check-ignore -v` reports it.
+struct Match {
+    source: String,
+    line: String,
+    pattern: String,
+}
+
+/// Says which ignore file and rule keep `path` out of `sgit status`, or why none does.
+fn explain_ignored(path: &str) -> Result<()> {
+    let tracked = git_cmd()
+        .args(["ls-files", "--error-unmatch", "--", path])
+        .output()
+        .context("running git ls-files")?
+        .status
+        .success();
+    if tracked {
+        println!(
+            "{} is tracked, so ignore rules do not apply to it - 'sgit status' shows it whenever it changes.",
+            path
+        );
+        if let Some(rule) = matching_rule(path, true)?
+            && !rule.pattern.starts_with('!')
+        {
+            println!(
+                "  The rule '{}' in {}, line {}, would ignore it otherwise.",
+                rule.pattern, rule.source, rule.line
+            );
+            println!(
+                "  To stop tracking it but keep the file: 'git rm --cached {}', then commit.",
+                path
+            );
+        }
+        return Ok(());
+    }
+
+    match matching_rule(path, false)? {
+        Some(rule) if rule.pattern.starts_with('!') => {
+            println!(
+                "{} is not ignored: the rule '{}' in {}, line {}, brings it back.",
+                path, rule.pattern, rule.source, rule.line
+            );
+        }
+        Some(rule) => {
+            println!(
+                "{} is ignored by the rule '{}' in {}, line {}.",
+                path, rule.pattern, rule.source, rule.line
+            );
+            println!("  {}", describe_source(&rule.source));
+            if rule.pattern.ends_with('/') {
+                println!("  The rule ignores a whole directory; a '!' rule cannot bring back files inside it.");
+            }
+            println!(
+                "  To see it in 'sgit status', change or remove that rule; to commit it anyway: 'git add -f {}'.",
+                path
+            );
+        }
+        None if !Path::new(path).exists() => {
+            println!(
+                "{} does not exist, so there is nothing for 'sgit status' to show.",
+                path
+            )
+        }
+        None => println!(
+            "{} is not ignored by any rule - 'sgit status' lists it as untracked.",
+            path
+        ),
+    }
+    Ok(())
+}
+
+/// The last rule matching `path`; `no_index` also checks tracked files.
+fn matching_rule(path: &str, no_index: bool) -> Result<Option<Match>> {
+    let mut args = vec![
+        "check-ignore",
+        "--verbose",
+        "--non-matching",
+        "--stdin",
+        "-z",
+    ];
+    if no_index {
+        args.push("--no-index");
+    }
+    // `-z`, for paths with any characters, needs the path on stdin.
+    let mut child = git_cmd()
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running git check-ignore")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(format!("{}\0", path).as_bytes())?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("running git check-ignore")?;
+    // Exit code 1 just means no rule matched; anything else is an error.
+    if !output.status.success() && output.status.code() != Some(1) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git check-ignore failed: {}", stderr.trim());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split('\0');
+    let (Some(source), Some(line), Some(pattern)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return Ok(None);
+    };
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Match {
+        source: source.to_string(),
+        line: line.to_string(),
+        pattern: pattern.to_string(),
+    }))
+}
+
+/// Who the rules in an ignore file apply to.
+fn describe_source(source: &str) -> &'static str {
+    if source.ends_with("info/exclude") {
+        "That file holds rules for this clone only."
+    } else if source == ".gitignore" || source.ends_with("/.gitignore") {
+        "That file is part of the repository, so the rule applies to everyone who clones it."
+    } else {
+        "That is your global ignore file (core.excludesFile), used by every repository on this machine."
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+
+use crate::commands::connect_remote;
+use crate::format::format_size;
+use crate::git::{git_cmd, git_complaint, run_git_quiet, run_git_silent};
+use crate::lfs;
+use crate::templates::{detect_ignore_templates, render_ignore_templates, IGNORE_TEMPLATES};
+use crate::whitespace::{is_binary, BINARY_SNIFF_BYTES};
+
+const LARGE_BINARY_BYTES: u64 = 1024 * 1024;
+
+const CHOICE_LFS: &str = "Track them with Git LFS";
+const CHOICE_IGNORE: &str = "Leave them out (add them to .gitignore)";
+const CHOICE_COMMIT: &str = "Commit them normally";
+
+pub fn run_import(remote: Option<String>, split: bool) -> Result<()> {
+    let dir = std::env::current_dir().context("failed to read the current directory")?;
+    if dir.join(".git").exists() {
+        bail!("this directory is already a git repository - use 'sgit status' to see its state");
+    }
+
+    println!("This will turn {} into a git repository:", dir.display());
+    println!("  1. initialize git and write a .gitignore");
+    println!("  2. check for large or binary files");
+    println!("  3. create the initial commit(s)");
+    println!("  4. optionally connect a remote and push");
+    let proceed = Confirm::new()
+        .with_prompt("Continue?")
+        .default(true)
+        .interact()?;
+    if !proceed {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    run_git_silent(&["init"])?;
+    println!("✓ Initialized Git repository");
+
+    write_gitignore(&dir)?;
+    handle_heavy_files(&dir)?;
+    create_initial_commits(split)?;
+    connect_origin(remote)?;
+
+    println!("Done.");
+    Ok(())
+}
+
+fn write_gitignore(dir: &Path) -> Result<()> {
+    let detected = detect_ignore_templates(dir);
+    let names: Vec<&str> = IGNORE_TEMPLATES.iter().map(|t| t.name).collect();
+    let defaults: Vec<bool> = IGNORE_TEMPLATES
+        .iter()
+        .map(|t| detected.iter().any(|d| d.name == t.name))
+        .collect();
+
+    let selected = MultiSelect::new()
+        .with_prompt("Which ignore rules should be added? (detected ones are preselected)")
+        .items(&names)
+        .defaults(&defaults)
+        .interact()?;
+    if selected.is_empty() {
+        return Ok(());
+    }
+
+    let templates: Vec<_> = selected.iter().map(|&idx| &IGNORE_TEMPLATES[idx]).collect();
+    append_to_gitignore(dir, &render_ignore_templates(&templates))?;
+    println!("✓ Wrote .gitignore ({} rule set(s))", templates.len());
+    Ok(())
+}
+
+fn handle_heavy_files(dir: &Path) -> Result<()> {
+    let heavy = find_heavy_files(dir)?;
+    if heavy.is_empty() {
+        return Ok(());
+    }
+
+    println!("Found {} large or binary file(s):", heavy.len());
+    for (path, size) in &heavy {
+        println!("  {} ({})", path, format_size(*size));
+    }
+    println!("  Large files make every clone slower and many hosts reject files over 100 MB.");
+
+    let mut items = Vec::new();
+    if lfs::is_installed() {
+        items.push(CHOICE_LFS);
+    }
+    items.push(CHOICE_IGNORE);
+    items.push(CHOICE_COMMIT);
+
+    let selection = Select::new()
+        .with_prompt("What should happen to these files?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    match items[selection] {
+        CHOICE_LFS => {
+            let patterns: BTreeSet<String> =
+                heavy.iter().map(|(p, _)| lfs::track_pattern(p)).collect();
+            lfs::track_patterns(&patterns.iter().cloned().collect::<Vec<_>>())?;
+            println!("✓ Tracking {} pattern(s) with Git LFS", patterns.len());
+        }
+        CHOICE_IGNORE => {
+            let mut contents = String::from("# Large files left out by sgit import\n");
+            for (path, _) in &heavy {
+                contents.push_str(&format!("/{}\n", path));
+            }
+            append_to_gitignore(dir, &contents)?;
+            println!("✓ Added {} file(s) to .gitignore", heavy.len());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn create_initial_commits(split: bool) -> Result<()> {
+    let files = list_untracked(Path::new("."))?;
+    if files.is_empty() {
+        println!("No files to commit - the repository is empty.");
+        return Ok(());
+    }
+
+    let split = split
+        || Confirm::new()
+            .with_prompt("Create one commit per top-level directory instead of a single commit?")
+            .default(false)
+            .interact()?;
+
+    if !split {
+        run_git_silent(&["add", "-A"])?;
+        run_git_quiet(&["commit", "-m", "Initial import"])?;
+        println!("✓ Created the initial commit ({} files)", files.len());
+        return Ok(());
+    }
+
+    let mut root_files = Vec::new();
+    let mut top_dirs = BTreeSet::new();
+    for file in &files {
+        match file.split_once('/') {
+            Some((top, _)) => {
+                top_dirs.insert(top.to_string());
+            }
+            None => root_files.push(file.clone()),
+        }
+    }
+
+    let mut commits = 0;
+    if !root_files.is_empty() {
+        let mut args = vec!["add", "--"];
+        args.extend(root_files.iter().map(String::as_str));
+        run_git_silent(&args)?;
+        run_git_quiet(&["commit", "-m", "Initial commit"])?;
+        commits += 1;
+    }
+    for top in &top_dirs {
+        run_git_silent(&["add", "--", top.as_str()])?;
+        let message = format!("Import {}/", top);
+        run_git_quiet(&["commit", "-m", message.as_str()])?;
+        commits += 1;
+    }
+    println!("✓ Created {} initial commit(s)", commits);
+    Ok(())
+}
+
+fn connect_origin(remote: Option<String>) -> Result<()> {
+    let url = match remote {
+        Some(url) => url,
+        None => Input::new()
+            .with_prompt("Remote URL to push to (leave empty to skip)")
+            .allow_empty(true)
+            .interact_text()?,
+    };
+    let url = url.trim();
+    if url.is_empty() {
+        println!("  Connect a remote later with 'sgit connect <url>'.");
+        return Ok(());
+    }
+    // The import itself is done; a remote that cannot be reached only needs a retry.
+    if let Err(err) = connect_remote("origin", url) {
+        eprintln!("⚠ Could not connect the remote: {}", git_complaint(&err));
+        eprintln!(
+            "  Check the URL and your access, then run 'sgit connect {}'.",
+            url
+        );
+    }
+    Ok(())
+}
+
+/// Files at least `lfs::LARGE_FILE_BYTES`, or binary files at least `LARGE_BINARY_BYTES`.
+fn find_heavy_files(dir: &Path) -> Result<Vec<(String, u64)>> {
+    let mut heavy = Vec::new();
+    for path in list_untracked(dir)? {
+        let full = dir.join(&path);
+        let Ok(meta) = fs::metadata(&full) else {
+            continue;
+        };
+        let size = meta.len();
+        if size >= lfs::LARGE_FILE_BYTES || (size >= LARGE_BINARY_BYTES && is_binary_file(&full)) {
+            heavy.push((path, size));
+        }
+    }
+    Ok(heavy)
+}
+
+fn list_untracked(dir: &Path) -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["ls-files", "--others", "--exclude-standard", "-z"])
+        .current_dir(dir)
+        .output()
+        .context("running git ls-files")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn is_binary_file(path: &Path) -> bool {
+    let mut buf = [0u8; BINARY_SNIFF_BYTES];
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let read = file.read(&mut buf).unwrap_or(0);
+    is_binary(&buf[..read])
+}
+
+pub(crate) fn append_to_gitignore(dir: &Path, contents: &str) -> Result<()> {
+    let path = dir.join(".gitignore");
+    let needs_newline = fs::read_to_string(&path)
+        .map(|existing| !existing.is_empty() && !existing.ends_with('\n'))
+        .unwrap_or(false);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    if needs_newline {
+        writeln!(file)?;
+    }
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
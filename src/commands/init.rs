@@ -0,0 +1,201 @@
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, MultiSelect};
+
+use crate::commands::import::append_to_gitignore;
+use crate::commands::setup::ensure_identity;
+use crate::git::{git_cmd, run_git_quiet, run_git_silent};
+use crate::status::get_config_value;
+use crate::templates::{
+    detect_ignore_templates, find_ignore_template, render_ignore_templates, IgnoreTemplate,
+    IGNORE_TEMPLATES,
+};
+
+pub struct InitOptions {
+    /// Directory to create the repository in instead of the current one.
+    pub directory: Option<String>,
+    pub ignore: Vec<String>,
+    pub branch: Option<String>,
+    pub commit: bool,
+    pub remote: Option<String>,
+    /// Only do what the flags ask for, as when not running in a terminal.
+    pub yes: bool,
+    pub bare: bool,
+}
+
+/// Sets up a new project: git itself, then whatever of a .gitignore, the first branch's
+/// name, an initial commit, and a remote the flags ask for - or the user picks when
+/// asked in a terminal.
+pub fn run_init(opts: InitOptions) -> Result<()> {
+    let interactive = !opts.yes && stdin().is_terminal();
+    let mut dir = std::env::current_dir().context("failed to read the current directory")?;
+    if let Some(directory) = &opts.directory {
+        dir.push(directory);
+    }
+    if dir.join(".git").exists() || dir.join("HEAD").is_file() {
+        bail!(
+            "{} is already a git repository - use 'sgit status' to see its state",
+            dir.display()
+        );
+    }
+    if opts.bare {
+        return init_bare(&dir, opts.branch, interactive);
+    }
+
+    // Everything is asked and checked up front, so a typo leaves no half-made repository.
+    let templates = pick_ignore_templates(&dir, &opts.ignore, interactive)?;
+    let branch = pick_branch(opts.branch, interactive)?;
+    let commit = opts.commit
+        || (interactive
+            && Confirm::new()
+                .with_prompt("Create an initial commit?")
+                .default(true)
+                .interact()?);
+    let remote = match opts.remote {
+        Some(url) => Some(url),
+        None if interactive => Some(
+            Input::<String>::new()
+                .with_prompt("Remote URL (leave empty to skip)")
+                .allow_empty(true)
+                .interact_text()?,
+        ),
+        None => None,
+    }
+    .map(|url| url.trim().to_string())
+    .filter(|url| !url.is_empty());
+    if commit {
+        ensure_identity()?;
+    }
+
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    std::env::set_current_dir(&dir)
+        .with_context(|| format!("cannot change to '{}'", dir.display()))?;
+    run_git_silent(&["init"])?;
+    println!("✓ Initialized Git repository in {}", dir.display());
+    if let Some(branch) = &branch {
+        // `git init -b` needs git 2.28; pointing the unborn HEAD works everywhere.
+        run_git_silent(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+        println!("✓ The first branch is {}", branch);
+    }
+    if !templates.is_empty() {
+        append_to_gitignore(&dir, &render_ignore_templates(&templates))?;
+        let names: Vec<&str> = templates.iter().map(|t| t.name).collect();
+        println!("✓ Wrote .gitignore ({})", names.join(", "));
+    }
+    if commit {
+        if !templates.is_empty() {
+            run_git_silent(&["add", "--", ".gitignore"])?;
+        }
+        run_git_quiet(&["commit", "--allow-empty", "-m", "Initial commit"])?;
+        println!("✓ Created the initial commit");
+    }
+    if let Some(url) = &remote {
+        run_git_silent(&["remote", "add", "origin", url])?;
+        println!("✓ Added remote 'origin' ({})", url);
+    }
+
+    if commit && remote.is_some() {
+        println!("Next: 'sgit push' publishes the branch to origin.");
+    } else if commit {
+        println!("Next: 'sgit connect <url>' publishes it to a new remote repository.");
+    } else if !commit && !has_only_gitignore(&dir) {
+        println!("Next: 'sgit stage' the files to keep, then 'sgit commit'.");
+    }
+    Ok(())
+}
+
+/// A bare repository has only git's own files: the place a team pushes to and pulls from,
+/// e.g. on a shared drive or server.
+fn init_bare(dir: &Path, branch: Option<String>, interactive: bool) -> Result<()> {
+    let branch = pick_branch(branch, interactive)?;
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    std::env::set_current_dir(dir)
+        .with_context(|| format!("cannot change to '{}'", dir.display()))?;
+    run_git_silent(&["init", "--bare"])?;
+    println!("✓ Initialized bare repository in {}", dir.display());
+    if let Some(branch) = &branch {
+        run_git_silent(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)])?;
+        println!("✓ Its default branch is {}", branch);
+    }
+    println!("  It holds no working files - repositories push to and pull from it.");
+    println!(
+        "  From a project: 'sgit connect {}'; to get a copy: 'git clone {}'.",
+        dir.display(),
+        dir.display()
+    );
+    Ok(())
+}
+
+fn pick_ignore_templates(
+    dir: &Path,
+    requested: &[String],
+    interactive: bool,
+) -> Result<Vec<&'static IgnoreTemplate>> {
+    if !requested.is_empty() {
+        return requested
+            .iter()
+            .map(|name| {
+                find_ignore_template(name).with_context(|| {
+                    let known: Vec<&str> = IGNORE_TEMPLATES.iter().map(|t| t.name).collect();
+                    format!(
+                        "no .gitignore template named '{}' - choose from: {}",
+                        name,
+                        known.join(", ")
+                    )
+                })
+            })
+            .collect();
+    }
+    if !interactive {
+        return Ok(Vec::new());
+    }
+    let detected = detect_ignore_templates(dir);
+    let names: Vec<&str> = IGNORE_TEMPLATES.iter().map(|t| t.name).collect();
+    let defaults: Vec<bool> = IGNORE_TEMPLATES
+        .iter()
+        .map(|t| detected.iter().any(|d| d.name == t.name))
+        .collect();
+    let selected = MultiSelect::new()
+        .with_prompt("Which .gitignore templates should be added? (space toggles)")
+        .items(&names)
+        .defaults(&defaults)
+        .interact()?;
+    Ok(selected.iter().map(|&idx| &IGNORE_TEMPLATES[idx]).collect())
+}
+
+/// The first branch's name; `None` keeps git's own default.
+fn pick_branch(requested: Option<String>, interactive: bool) -> Result<Option<String>> {
+    let branch = match requested {
+        Some(branch) => branch,
+        None if interactive => {
+            let default =
+                get_config_value("init.defaultBranch").unwrap_or_else(|| "master".to_string());
+            Input::new()
+                .with_prompt("Name of the first branch")
+                .default(default)
+                .interact_text()?
+        }
+        None => return Ok(None),
+    };
+    let valid = git_cmd()
+        .args(["check-ref-format", "--branch", &branch])
+        .output()
+        .context("failed to execute git - is git installed?")?
+        .status
+        .success();
+    if !valid {
+        bail!("'{}' is not a valid branch name", branch);
+    }
+    Ok(Some(branch))
+}
+
+fn has_only_gitignore(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .all(|entry| matches!(entry.file_name().to_str(), Some(".git" | ".gitignore")))
+    })
+}
@@ -0,0 +1,433 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Input, Select};
+use serde::{Deserialize, Serialize};
+
+use crate::git::{git_cmd, git_output_in_dir};
+use crate::status::is_ancestor_in_dir;
+
+struct Lesson {
+    name: &'static str,
+    title: &'static str,
+    setup: fn(&Path) -> Result<()>,
+    steps: &'static [Step],
+}
+
+enum Step {
+    Explain(&'static str),
+    Task {
+        goal: &'static str,
+        hint: &'static str,
+        done: fn(&Path) -> bool,
+    },
+    Quiz {
+        question: &'static str,
+        options: &'static [&'static str],
+        answer: usize,
+        why: &'static str,
+    },
+}
+
+const LESSONS: &[Lesson] = &[
+    Lesson {
+        name: "branches",
+        title: "Branches: working on something without disturbing main",
+        setup: setup_branches,
+        steps: &[
+            Step::Explain(
+                "A branch is a name that points at a commit. New commits move the branch you are on forward, and nothing else.\nThe sandbox has one branch, main, with a single commit.",
+            ),
+            Step::Task {
+                goal: "Create a branch called `feature` and switch to it.",
+                hint: "sgit branch -c feature",
+                done: |dir| current_branch(dir).as_deref() == Some("feature"),
+            },
+            Step::Task {
+                goal: "Commit something on `feature`: create a file, then commit everything.",
+                hint: "echo hello > notes.txt, then: sgit commit --all -m \"Add notes\"",
+                done: |dir| count(dir, "main..feature") >= 1,
+            },
+            Step::Task {
+                goal: "Switch back to `main`.",
+                hint: "git checkout main (or run 'sgit branch' and pick it)",
+                done: |dir| current_branch(dir).as_deref() == Some("main"),
+            },
+            Step::Quiz {
+                question: "You are back on main. Where is the commit you just made?",
+                options: &[
+                    "It was deleted when you switched",
+                    "Still on feature - main never moved",
+                    "Copied onto main as well",
+                ],
+                answer: 1,
+                why: "Switching branches only changes which commit your files come from; feature still points at your commit.",
+            },
+        ],
+    },
+    Lesson {
+        name: "merging",
+        title: "Merging vs rebasing: bringing work together",
+        setup: setup_merging,
+        steps: &[
+            Step::Explain(
+                "You are on `feature`, which has one commit. Meanwhile `main` got a new commit too, so they have diverged.\nA merge ties the two lines together with a merge commit. A rebase replays your commits on top of the other branch, giving them new hashes but a straight history.",
+            ),
+            Step::Quiz {
+                question: "Which one rewrites your commits (new hashes)?",
+                options: &["merge", "rebase", "both", "neither"],
+                answer: 1,
+                why: "Rebase re-creates your commits on a new base; a merge leaves existing commits untouched.",
+            },
+            Step::Task {
+                goal: "Rebase `feature` onto `main` so it includes main's new commit.",
+                hint: "git rebase main",
+                done: |dir| is_ancestor_in_dir(dir, "main", "feature") && count(dir, "--merges main..feature") == 0,
+            },
+            Step::Task {
+                goal: "Now switch to `main` and merge `feature` into it.",
+                hint: "git checkout main, then: git merge feature",
+                done: |dir| {
+                    current_branch(dir).as_deref() == Some("main")
+                        && is_ancestor_in_dir(dir, "feature", "main")
+                },
+            },
+            Step::Quiz {
+                question: "Teammates already pulled your branch. How should you bring in main's changes?",
+                options: &[
+                    "Rebase - the history looks nicer",
+                    "Merge - it does not rewrite commits others have",
+                ],
+                answer: 1,
+                why: "Rebasing shared commits makes everyone else's copy disagree with yours; merging is safe for shared branches.",
+            },
+        ],
+    },
+    Lesson {
+        name: "undo",
+        title: "Undoing things: changes, commits, and messages",
+        setup: setup_undo,
+        steps: &[
+            Step::Explain(
+                "Almost everything in git can be undone - the trick is picking the right tool.\nThe sandbox has an uncommitted edit to README.md, and the last commit added secret.txt by mistake.",
+            ),
+            Step::Task {
+                goal: "Throw away the uncommitted edit to README.md.",
+                hint: "sgit reset --unstaged (or: git restore README.md)",
+                done: |dir| {
+                    git_output_in_dir(&["status", "--porcelain"], dir)
+                        .ok()
+                        .is_some_and(|s| !s.contains("README.md"))
+                },
+            },
+            Step::Task {
+                goal: "Undo the commit that added secret.txt with a new commit, which is safe even after pushing.",
+                hint: "sgit revert HEAD",
+                done: |dir| {
+                    !dir.join("secret.txt").exists()
+                        && git_output_in_dir(&["log", "-1", "--format=%s"], dir)
+                            .ok()
+                            .is_some_and(|s| s.starts_with("Revert"))
+                },
+            },
+            Step::Quiz {
+                question: "You committed with a typo in the message and have not pushed. What fixes just the message?",
+                options: &["sgit revert", "sgit reword", "sgit reset --all"],
+                answer: 1,
+                why: "reword rewrites the message of an unpushed commit; revert adds a new undo commit, and reset throws away changes.",
+            },
+            Step::Quiz {
+                question: "Which way of undoing a commit is safe once it has been pushed?",
+                options: &["Revert it", "Reset the branch and force-push"],
+                answer: 0,
+                why: "A revert only adds history, so nobody else's copy breaks.",
+            },
+        ],
+    },
+];
+
+#[derive(Default, Serialize, Deserialize)]
+struct Progress {
+    completed: Vec<String>,
+}
+
+pub fn run_learn(topic: Option<String>) -> Result<()> {
+    let mut progress = load_progress();
+    let lesson = match topic {
+        Some(topic) => match LESSONS.iter().find(|l| l.name == topic) {
+            Some(lesson) => lesson,
+            None => {
+                let names: Vec<&str> = LESSONS.iter().map(|l| l.name).collect();
+                bail!(
+                    "no lesson called '{}' - choose one of: {}",
+                    topic,
+                    names.join(", ")
+                );
+            }
+        },
+        None => {
+            let items: Vec<String> = LESSONS
+                .iter()
+                .map(|l| {
+                    let mark = if progress.completed.iter().any(|c| c == l.name) {
+                        "✓"
+                    } else {
+                        " "
+                    };
+                    format!("{} {:<9} {}", mark, l.name, l.title)
+                })
+                .collect();
+            let selection = Select::new()
+                .with_prompt("Pick a lesson")
+                .items(&items)
+                .default(
+                    LESSONS
+                        .iter()
+                        .position(|l| !progress.completed.iter().any(|c| c == l.name))
+                        .unwrap_or(0),
+                )
+                .interact()?;
+            &LESSONS[selection]
+        }
+    };
+
+    let sandbox =
+        std::env::temp_dir().join(format!("sgit-learn-{}-{}", lesson.name, std::process::id()));
+    if sandbox.exists() {
+        fs::remove_dir_all(&sandbox)
+            .with_context(|| format!("failed to clear {}", sandbox.display()))?;
+    }
+    fs::create_dir_all(&sandbox)
+        .with_context(|| format!("failed to create {}", sandbox.display()))?;
+    (lesson.setup)(&sandbox)?;
+
+    println!("{}", lesson.title);
+    println!("Practice repository: {}", sandbox.display());
+    println!(
+        "Type commands as you would in a terminal (git, sgit, or simple shell). 'hint' shows a solution, 'skip' moves on, 'quit' stops."
+    );
+
+    let finished = run_steps(lesson, &sandbox)?;
+    if finished {
+        println!();
+        println!("✓ Lesson '{}' complete!", lesson.name);
+        if !progress.completed.iter().any(|c| c == lesson.name) {
+            progress.completed.push(lesson.name.to_string());
+            save_progress(&progress)?;
+        }
+        if let Some(next) = LESSONS
+            .iter()
+            .find(|l| !progress.completed.iter().any(|c| c == l.name))
+        {
+            println!("  Next up: 'sgit learn {}'", next.name);
+        }
+    }
+
+    let keep = Confirm::new()
+        .with_prompt("Keep the practice repository to explore on your own?")
+        .default(false)
+        .interact()?;
+    if keep {
+        println!("  It is at {}", sandbox.display());
+    } else {
+        let _ = fs::remove_dir_all(&sandbox);
+    }
+    Ok(())
+}
+
+/// Returns `false` if the user quit part-way through.
+fn run_steps(lesson: &Lesson, sandbox: &Path) -> Result<bool> {
+    let total = lesson
+        .steps
+        .iter()
+        .filter(|s| !matches!(s, Step::Explain(_)))
+        .count();
+    let mut number = 0;
+    for step in lesson.steps {
+        println!();
+        if !matches!(step, Step::Explain(_)) {
+            number += 1;
+        }
+        match step {
+            Step::Explain(text) => {
+                for line in text.lines() {
+                    println!("  {}", line);
+                }
+            }
+            Step::Task { goal, hint, done } => {
+                println!("[{}/{}] {}", number, total, goal);
+                loop {
+                    let command: String = Input::new()
+                        .with_prompt("sandbox $")
+                        .allow_empty(true)
+                        .interact_text()?;
+                    match command.trim() {
+                        "" => continue,
+                        "quit" | "exit" => return Ok(false),
+                        "hint" => {
+                            println!("  Try: {}", hint);
+                            continue;
+                        }
+                        "skip" => {
+                            println!("  Skipped - one way to do it: {}", hint);
+                            break;
+                        }
+                        command => run_in_sandbox(sandbox, command)?,
+                    }
+                    if done(sandbox) {
+                        println!("✓ Nice!");
+                        break;
+                    }
+                }
+            }
+            Step::Quiz {
+                question,
+                options,
+                answer,
+                why,
+            } => {
+                println!("[{}/{}] Quiz", number, total);
+                let picked = Select::new()
+                    .with_prompt(*question)
+                    .items(options)
+                    .default(0)
+                    .interact()?;
+                if picked == *answer {
+                    println!("✓ Correct. {}", why);
+                } else {
+                    println!(
+                        "✗ Not quite - the answer is \"{}\". {}",
+                        options[*answer], why
+                    );
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Runs a command line in the sandbox through the shell, with this sgit first on PATH.
+fn run_in_sandbox(sandbox: &Path, command: &str) -> Result<()> {
+    let exe = std::env::current_exe().context("failed to locate the sgit binary")?;
+    let mut path = exe
+        .parent()
+        .map(|dir| dir.as_os_str().to_owned())
+        .unwrap_or_default();
+    if let Some(existing) = std::env::var_os("PATH") {
+        path.push(":");
+        path.push(existing);
+    }
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let status = StdCommand::new(shell)
+        .args([flag, command])
+        .current_dir(sandbox)
+        .env("PATH", path)
+        .status()
+        .with_context(|| format!("failed to run '{}'", command))?;
+    if !status.success() {
+        println!("  (that command failed - read the message above, or type 'hint')");
+    }
+    Ok(())
+}
+
+fn setup_branches(dir: &Path) -> Result<()> {
+    init_sandbox(dir)?;
+    write_and_commit(dir, "README.md", "# Practice project\n", "Initial commit")
+}
+
+fn setup_merging(dir: &Path) -> Result<()> {
+    init_sandbox(dir)?;
+    write_and_commit(dir, "README.md", "# Practice project\n", "Initial commit")?;
+    sandbox_git(dir, &["checkout", "-q", "-b", "feature"])?;
+    write_and_commit(dir, "feature.txt", "my feature\n", "Add feature")?;
+    sandbox_git(dir, &["checkout", "-q", "main"])?;
+    write_and_commit(dir, "main.txt", "meanwhile on main\n", "Update main")?;
+    sandbox_git(dir, &["checkout", "-q", "feature"])
+}
+
+fn setup_undo(dir: &Path) -> Result<()> {
+    init_sandbox(dir)?;
+    write_and_commit(dir, "README.md", "# Practice project\n", "Initial commit")?;
+    write_and_commit(dir, "app.txt", "version 1\n", "Add app")?;
+    write_and_commit(dir, "secret.txt", "password=hunter2\n", "Add secret.txt")?;
+    fs::write(dir.join("README.md"), "# Practice project\noops, an edit\n")
+        .context("failed to write README.md")
+}
+
+fn init_sandbox(dir: &Path) -> Result<()> {
+    sandbox_git(dir, &["init", "-q", "-b", "main"])?;
+    sandbox_git(dir, &["config", "user.name", "sgit learner"])?;
+    sandbox_git(dir, &["config", "user.email", "learner@example.com"])
+}
+
+fn write_and_commit(dir: &Path, file: &str, contents: &str, message: &str) -> Result<()> {
+    fs::write(dir.join(file), contents).with_context(|| format!("failed to write {}", file))?;
+    sandbox_git(dir, &["add", file])?;
+    sandbox_git(dir, &["commit", "-q", "-m", message])
+}
+
+fn sandbox_git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = git_cmd()
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("failed to execute git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "setting up the lesson failed at git {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn current_branch(dir: &Path) -> Option<String> {
+    git_output_in_dir(&["branch", "--show-current"], dir)
+        .ok()
+        .map(|branch| branch.trim().to_string())
+}
+
+fn count(dir: &Path, range: &str) -> usize {
+    let mut args = vec!["rev-list", "--count"];
+    args.extend(range.split_whitespace());
+    git_output_in_dir(&args, dir)
+        .ok()
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Completed lessons live in the user's state directory, shared by all repositories.
+fn progress_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Some(base.join("sgit").join("learn.json"))
+}
+
+fn load_progress() -> Progress {
+    progress_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_progress(progress: &Progress) -> Result<()> {
+    let Some(path) = progress_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let contents = serde_json::to_string_pretty(progress).context("failed to encode progress")?;
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use dialoguer::{FuzzySelect, Input, MultiSelect};
+
+use crate::commands::signing::describe_signature;
+use crate::git::{git_cmd, git_output, run_git};
+use crate::status::get_config_value;
+
+pub struct LogOptions {
+    pub short: bool,
+    pub author: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub path: Option<String>,
+    pub grep: Option<String>,
+    /// One line per commit with its signature status.
+    pub verify: bool,
+}
+
+impl LogOptions {
+    fn has_filters(&self) -> bool {
+        self.author.is_some()
+            || self.since.is_some()
+            || self.until.is_some()
+            || self.path.is_some()
+            || self.grep.is_some()
+    }
+}
+
+pub fn run_log(mut opts: LogOptions) -> Result<()> {
+    if !opts.short
+        && !opts.verify
+        && !opts.has_filters()
+        && stdin().is_terminal()
+        && stdout().is_terminal()
+    {
+        build_filters(&mut opts)?;
+    }
+
+    let mut args: Vec<String> = vec!["log".into()];
+    if opts.verify {
+        // %G? is the signature status, %GS the signer.
+        args.push("--format=%G?%x1f%H%x1f%h%x1f%s%x1f%an%x1f%ar%x1f%GS".into());
+    } else if opts.short {
+        args.push("--oneline".into());
+    }
+    args.push("--decorate".into());
+    if opts.has_filters() {
+        // Filters can match commits anywhere in history, so don't cut the list short.
+        if let Some(author) = &opts.author {
+            args.push(format!("--author={}", author));
+        }
+        if let Some(since) = &opts.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(until) = &opts.until {
+            args.push(format!("--until={}", until));
+        }
+        if let Some(grep) = &opts.grep {
+            args.push(format!("--grep={}", grep));
+            args.push("--regexp-ignore-case".into());
+        }
+    } else {
+        args.push("-n".into());
+        args.push(if opts.short { "20" } else { "40" }.into());
+    }
+    if let Some(path) = &opts.path {
+        args.push("--".into());
+        args.push(path.clone());
+    }
+
+    let args_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    if opts.verify {
+        return print_verified_log(&git_output(&args_refs)?);
+    }
+    run_git(&args_refs)
+}
+
+/// Prints `git log` output in the `--verify` format with a mark per commit, then a
+/// legend counting each signature status.
+fn print_verified_log(output: &str) -> Result<()> {
+    let rows: Vec<Vec<&str>> = output
+        .lines()
+        .map(|line| line.split('\x1f').collect())
+        .collect();
+    let unchecked: Vec<&str> = rows
+        .iter()
+        .filter(|fields| fields.first() == Some(&"N"))
+        .filter_map(|fields| fields.get(1).copied())
+        .collect();
+    let signed = signed_commits(&unchecked)?;
+
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for fields in &rows {
+        let [code, full_hash, hash, subject, author, date, signer] = fields[..] else {
+            continue;
+        };
+        // git reports SSH signatures it has no allowed-signers list for as unsigned.
+        let code = if code == "N" && signed.contains(full_hash) {
+            "E"
+        } else {
+            code
+        };
+        let signer = if signer.is_empty() {
+            String::new()
+        } else {
+            format!(" - signed by {}", signer)
+        };
+        println!(
+            "{} {}  {}  ({}, {}){}",
+            signature_mark(code),
+            hash,
+            subject,
+            author,
+            date,
+            signer
+        );
+        match counts.iter_mut().find(|(seen, _)| *seen == code) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((code, 1)),
+        }
+    }
+    if counts.is_empty() {
+        println!("No commits to show.");
+        return Ok(());
+    }
+
+    println!();
+    for (code, count) in &counts {
+        println!(
+            "  {} {} ({})",
+            signature_mark(code),
+            describe_signature(code),
+            count
+        );
+    }
+    let unverifiable = counts.iter().any(|(code, _)| *code == "E");
+    if unverifiable && get_config_value("gpg.ssh.allowedSignersFile").is_none() {
+        println!("  SSH signatures can only be checked against an allowed-signers list - add keys with 'sgit signers add'.");
+    }
+    Ok(())
+}
+
+/// Which of `hashes` carry a signature, read from their raw commit headers.
+fn signed_commits(hashes: &[&str]) -> Result<HashSet<String>> {
+    let mut signed = HashSet::new();
+    if hashes.is_empty() {
+        return Ok(signed);
+    }
+    let mut child = git_cmd()
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to execute git cat-file - is git installed?")?;
+    if let Some(mut input) = child.stdin.take() {
+        input.write_all(format!("{}\n", hashes.join("\n")).as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    // Each object is "<hash> commit <size>\n", <size> bytes of content, then "\n".
+    let mut rest = output.stdout.as_slice();
+    while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&rest[..end]).into_owned();
+        let mut parts = header.split(' ');
+        let (Some(hash), Some(_), Some(size)) = (parts.next(), parts.next(), parts.next()) else {
+            break;
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            break;
+        };
+        let body = &rest[end + 1..(end + 1 + size).min(rest.len())];
+        let headers = body
+            .split(|&b| b == b'\n')
+            .take_while(|line| !line.is_empty());
+        if headers
+            .into_iter()
+            .any(|line| line.starts_with(b"gpgsig ") || line.starts_with(b"gpgsig-sha256 "))
+        {
+            signed.insert(hash.to_string());
+        }
+        rest = &rest[(end + 2 + size).min(rest.len())..];
+    }
+    Ok(signed)
+}
+
+fn signature_mark(code: &str) -> &'static str {
+    match code {
+        "G" => "✓",
+        "U" => "✓?",
+        "X" | "Y" | "R" | "E" => "?",
+        "B" => "✗",
+        _ => "·",
+    }
+}
+
+/// Asks which filters to apply; picking none shows the recent history as before.
+fn build_filters(opts: &mut LogOptions) -> Result<()> {
+    let filters = [
+        "Author",
+        "Since (date)",
+        "Until (date)",
+        "Path",
+        "Message text",
+    ];
+    let picked = MultiSelect::new()
+        .with_prompt(
+            "Filter the log by (space to pick, enter to continue; none shows recent commits)",
+        )
+        .items(&filters)
+        .interact()?;
+
+    for idx in picked {
+        match idx {
+            0 => opts.author = Some(pick_author()?),
+            1 => {
+                opts.since = Some(
+                    Input::new()
+                        .with_prompt("Since (e.g. 2024-05-01, '2 weeks ago', yesterday)")
+                        .interact_text()?,
+                )
+            }
+            2 => {
+                opts.until = Some(
+                    Input::new()
+                        .with_prompt("Until (e.g. 2024-06-01, 'last monday')")
+                        .interact_text()?,
+                )
+            }
+            3 => {
+                opts.path = Some(
+                    Input::new()
+                        .with_prompt("File or directory")
+                        .interact_text()?,
+                )
+            }
+            _ => {
+                opts.grep = Some(
+                    Input::new()
+                        .with_prompt("Text in the commit message")
+                        .interact_text()?,
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lets the user search the repository's authors, most active first.
+fn pick_author() -> Result<String> {
+    let output = git_cmd()
+        .args(["shortlog", "-sne", "HEAD"])
+        .output()
+        .context("running git shortlog")?;
+    let authors: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t').map(|(_, who)| who.to_string()))
+        .collect();
+    if authors.is_empty() {
+        return Ok(Input::new().with_prompt("Author").interact_text()?);
+    }
+
+    let selection = FuzzySelect::new()
+        .with_prompt("Author (type to search)")
+        .items(&authors)
+        .default(0)
+        .interact()?;
+    // Match on the email so authors with several spellings of their name are kept together.
+    let author = &authors[selection];
+    Ok(match author.rsplit_once('<') {
+        Some((_, email)) => format!("<{}", email),
+        None => author.clone(),
+    })
+}
@@ -0,0 +1,230 @@
+use std::fs;
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+
+use crate::cli::MaintenanceAction;
+use crate::format::format_size;
+use crate::git::{git_output, git_supports, run_git_quiet, run_git_silent};
+use crate::progress::Phase;
+use crate::status::{get_git_dir, get_repo_root};
+
+/// Unreachable objects younger than this are kept, as `git gc` does, so nothing a
+/// running command or a recent reset still needs goes away.
+const PRUNE_AFTER: &str = "2.weeks.ago";
+
+/// Loose objects past which a repository is worth tidying; git's own `gc --auto` waits
+/// for about this many.
+const LOOSE_OBJECTS_HINT: u64 = 6700;
+
+struct Step {
+    phase: &'static str,
+    says: &'static str,
+    args: &'static [&'static str],
+}
+
+/// Object counts and sizes, from `git count-objects -v`.
+pub(super) struct Objects {
+    pub loose: u64,
+    pub packs: u64,
+    /// Bytes on disk, loose objects and packs together.
+    pub size: u64,
+    pub loose_size: u64,
+    pub pack_size: u64,
+}
+
+pub fn run_maintenance(action: Option<MaintenanceAction>) -> Result<()> {
+    match action.unwrap_or(MaintenanceAction::Run { full: false }) {
+        MaintenanceAction::Run { full } => run(full),
+        MaintenanceAction::Status => status(),
+        MaintenanceAction::Schedule => schedule(),
+        MaintenanceAction::Unschedule => unschedule(),
+    }
+}
+
+pub(super) fn count_objects() -> Result<Objects> {
+    let output = git_output(&["count-objects", "-v"])?;
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    // Sizes are in KiB.
+    let (loose_size, pack_size) = (field("size") * 1024, field("size-pack") * 1024);
+    Ok(Objects {
+        loose: field("count"),
+        packs: field("packs"),
+        size: loose_size + pack_size,
+        loose_size,
+        pack_size,
+    })
+}
+
+fn run(full: bool) -> Result<()> {
+    let before = count_objects()?;
+    println!(
+        "→ Tidying up the repository ({}, {} loose objects, {} {})...",
+        format_size(before.size),
+        before.loose,
+        before.packs,
+        if before.packs == 1 { "pack" } else { "packs" }
+    );
+    let repack: &'static [&'static str] = if full {
+        &["repack", "-a", "-d", "-l", "-f", "--quiet"]
+    } else {
+        &["repack", "-d", "-l", "--quiet"]
+    };
+    let steps = [
+        Step {
+            phase: "pack-refs",
+            says: "Filing branch and tag pointers into one file",
+            args: &["pack-refs", "--all", "--prune"],
+        },
+        Step {
+            phase: "repack",
+            says: if full {
+                "Recompressing everything into one pack (slow, but the smallest result)"
+            } else {
+                "Packing loose objects into compressed packs"
+            },
+            args: repack,
+        },
+        Step {
+            phase: "prune",
+            says: "Deleting objects nothing has used for two weeks",
+            args: &["prune", "--expire", PRUNE_AFTER],
+        },
+        Step {
+            phase: "commit-graph",
+            says: "Indexing history, so log, blame, and merges find commits faster",
+            args: &["commit-graph", "write", "--reachable", "--changed-paths"],
+        },
+    ];
+
+    let started = Instant::now();
+    for (idx, step) in steps.iter().enumerate() {
+        print!("  [{}/{}] {}...", idx + 1, steps.len(), step.says);
+        let _ = stdout().flush();
+        let phase = Phase::start("maintenance", step.phase);
+        let step_started = Instant::now();
+        // `--changed-paths` came with git 2.27; older ones still get the graph.
+        let args: Vec<&str> = step
+            .args
+            .iter()
+            .copied()
+            .filter(|arg| *arg != "--changed-paths" || git_supports(2, 27))
+            .collect();
+        if let Err(err) = run_git_quiet(&args) {
+            println!(" ✗");
+            phase.failed(&err.to_string());
+            return Err(err);
+        }
+        phase.done();
+        println!(" {:.1}s", step_started.elapsed().as_secs_f64());
+    }
+
+    let after = count_objects()?;
+    let saved = before.size.saturating_sub(after.size);
+    println!(
+        "✓ Done in {:.1}s: {} → {}{}",
+        started.elapsed().as_secs_f64(),
+        format_size(before.size),
+        format_size(after.size),
+        if saved > 0 {
+            format!(" ({} smaller)", format_size(saved))
+        } else {
+            String::new()
+        }
+    );
+    if !is_scheduled()? {
+        println!("  'sgit maintenance schedule' has git keep it this way in the background.");
+    }
+    Ok(())
+}
+
+fn status() -> Result<()> {
+    let objects = count_objects()?;
+    let graph = Path::new(&get_git_dir()?)
+        .join("objects")
+        .join("info")
+        .join("commit-graph");
+    let graph_split = graph.with_file_name("commit-graphs");
+    println!("Size on disk: {}", format_size(objects.size));
+    println!(
+        "Objects: {} loose, {} {}",
+        objects.loose,
+        objects.packs,
+        if objects.packs == 1 { "pack" } else { "packs" }
+    );
+    println!(
+        "History index (commit-graph): {}",
+        if graph.exists() || graph_split.is_dir() {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+    println!(
+        "Background maintenance: {}",
+        if is_scheduled()? {
+            "on ('sgit maintenance unschedule' turns it off)"
+        } else {
+            "off ('sgit maintenance schedule' turns it on)"
+        }
+    );
+    if objects.loose >= LOOSE_OBJECTS_HINT || objects.packs > 50 {
+        println!();
+        println!("  The repository could use tidying - 'sgit maintenance' does it now.");
+    }
+    Ok(())
+}
+
+/// Whether `git maintenance` runs for this repository, i.e. it is in the global
+/// `maintenance.repo` list.
+fn is_scheduled() -> Result<bool> {
+    let root = get_repo_root()?;
+    let root = canonical(&root);
+    let repos =
+        git_output(&["config", "--global", "--get-all", "maintenance.repo"]).unwrap_or_default();
+    Ok(repos.lines().any(|repo| canonical(repo.trim()) == root))
+}
+
+fn canonical(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+fn schedule() -> Result<()> {
+    if !git_supports(2, 31) {
+        bail!("background maintenance needs git 2.31 or newer - 'git --version' shows yours");
+    }
+    if is_scheduled()? {
+        println!("✓ Background maintenance is already on for this repository");
+        return Ok(());
+    }
+    println!("→ Asking the system scheduler to look after this repository...");
+    run_git_quiet(&["maintenance", "start"])?;
+    println!("✓ Background maintenance is on");
+    println!("  Every hour git fetches in the background (your branches are not touched) and");
+    println!("  updates the history index; every night it packs new objects; every week it");
+    println!("  files branch pointers away. It uses cron, launchd, systemd, or Task Scheduler.");
+    println!("  'sgit maintenance unschedule' turns it off again.");
+    Ok(())
+}
+
+fn unschedule() -> Result<()> {
+    if !is_scheduled()? {
+        println!("Background maintenance is not on for this repository.");
+        return Ok(());
+    }
+    // Leaves the scheduler in place for other repositories that use it.
+    run_git_silent(&["maintenance", "unregister"])?;
+    println!("✓ Background maintenance is off for this repository");
+    println!("  'sgit maintenance' still tidies it up whenever you run it.");
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use dialoguer::Confirm;
+
+use crate::commands::commit::{run_commit, CommitOptions};
+use crate::commands::operation::ensure_no_operation;
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::{git_output, run_git_quiet};
+use crate::status::{
+    get_commits, get_conflicted_files, get_current_branch, get_git_dir, get_merge_source,
+    get_operation_in_progress, get_staged_files, is_ancestor, resolve_commit, CommitSummary,
+    RepoOperation,
+};
+
+pub struct MergeOptions {
+    /// Picked when `None`.
+    pub branch: Option<String>,
+    pub squash: bool,
+    pub message: Option<String>,
+    pub yes: bool,
+    /// Undo the paused merge instead.
+    pub abort: bool,
+}
+
+/// Merges a branch into the current one. With `squash`, stages the branch's changes as
+/// one change instead and hands over to the commit flow with a message listing the
+/// squashed commits.
+pub fn run_merge(opts: MergeOptions) -> Result<()> {
+    let MergeOptions {
+        branch,
+        squash,
+        message,
+        yes,
+        abort,
+    } = opts;
+    if abort {
+        return abort_merge(yes);
+    }
+    ensure_no_operation("sgit merge")?;
+    let branch = match branch {
+        Some(branch) => branch,
+        None => pick_branch()?,
+    };
+    let hash = resolve_commit(&branch)?;
+    let current = get_current_branch()
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "HEAD".to_string());
+    if is_ancestor(&hash, "HEAD") {
+        println!("✓ {} already has everything in {}", current, branch);
+        return Ok(());
+    }
+
+    if squash {
+        return squash_merge(&branch, &hash, &current, message, yes);
+    }
+    println!("→ Merging {} into {}...", branch, current);
+    if let Err(err) = run_git_quiet(&["merge", "--no-edit", &branch]) {
+        if report_conflicts()? {
+            bail!("the merge stopped on conflicts - resolve them with 'sgit conflicts', then 'sgit continue' (or 'sgit merge --abort' to go back)");
+        }
+        return Err(err);
+    }
+    println!("✓ Merged {} into {}", branch, current);
+    Ok(())
+}
+
+fn pick_branch() -> Result<String> {
+    if !stdin().is_terminal() {
+        bail!("name the branch to merge: 'sgit merge <branch>'");
+    }
+    match RefPicker::new(
+        "Which branch should be merged in? (type to search)",
+        &[RefKind::Branch, RefKind::RemoteBranch],
+    )
+    .interact()?
+    {
+        Some(Picked::Ref(picked)) => Ok(picked.name),
+        _ => bail!("there are no branches to merge"),
+    }
+}
+
+fn squash_merge(
+    branch: &str,
+    hash: &str,
+    current: &str,
+    message: Option<String>,
+    yes: bool,
+) -> Result<()> {
+    let commits = get_commits(&["--reverse", &format!("HEAD..{}", hash)])?;
+    println!(
+        "→ Squashing {} commit(s) from {} into {}...",
+        commits.len(),
+        branch,
+        current
+    );
+    if let Err(err) = run_git_quiet(&["merge", "--squash", branch]) {
+        if report_conflicts()? {
+            bail!("the squashed changes conflict - resolve them with 'sgit conflicts', then commit with 'sgit commit' (or drop them with 'git reset --merge')");
+        }
+        return Err(err);
+    }
+    if get_staged_files()?.is_empty() {
+        println!(
+            "✓ {} already has the changes in {} - there is nothing to commit",
+            current, branch
+        );
+        return Ok(());
+    }
+
+    let edit = message.is_none() && stdin().is_terminal();
+    let message = message.unwrap_or_else(|| squash_message(branch, current, &commits));
+    let head_before = resolve_commit("HEAD")?;
+    run_commit(CommitOptions {
+        message: Some(message),
+        all: false,
+        staged: true,
+        unstaged: false,
+        push: false,
+        amend: false,
+        no_verify: false,
+        when_green: false,
+        fixup: None,
+        yes,
+        no_lint: false,
+        edit,
+        sign: false,
+        no_edit: false,
+    })?;
+    if resolve_commit("HEAD")? == head_before {
+        println!(
+            "  The squashed changes from {} are still staged - 'sgit commit' commits them, 'git reset --merge' drops them.",
+            branch
+        );
+    } else if resolve_commit(&format!("refs/heads/{}", branch)).is_ok() {
+        // git only sees a branch as merged when its own commits are in history.
+        println!(
+            "  git does not count {} as merged, so 'git branch -d' refuses it - 'git branch -D {}' deletes it once you are done with it.",
+            branch, branch
+        );
+    }
+    Ok(())
+}
+
+/// `Squash <branch> into <current>`, then the squashed commits, oldest first.
+fn squash_message(branch: &str, current: &str, commits: &[CommitSummary]) -> String {
+    let mut message = format!("Squash {} into {}\n\nSquashed commits:\n", branch, current);
+    for commit in commits {
+        message.push_str(&format!("- {} {}\n", commit.short_hash, commit.subject));
+    }
+    message
+}
+
+/// Lists the conflicted files, if any; `true` when there are some.
+fn report_conflicts() -> Result<bool> {
+    let conflicted = get_conflicted_files()?;
+    if conflicted.is_empty() {
+        return Ok(false);
+    }
+    eprintln!("✗ Conflicts in {} file(s):", conflicted.len());
+    for file in &conflicted {
+        eprintln!("  {}", file);
+    }
+    Ok(true)
+}
+
+/// Goes back to before the paused merge (or conflicted `--squash`), after showing what
+/// that throws away.
+fn abort_merge(yes: bool) -> Result<()> {
+    let merging = get_operation_in_progress()? == Some(RepoOperation::Merge);
+    // A squash merge leaves no MERGE_HEAD, only the message git prepared.
+    let squashing = !merging && Path::new(&get_git_dir()?).join("SQUASH_MSG").exists();
+    if !merging && !squashing {
+        bail!("no merge is in progress");
+    }
+    let what = match get_merge_source() {
+        Some(source) if merging => format!("the merge of {}", source),
+        _ if merging => "the merge".to_string(),
+        _ => "the squash merge".to_string(),
+    };
+
+    let conflicted = get_conflicted_files()?;
+    let resolved: Vec<String> =
+        git_output(&["diff", "--cached", "--name-only", "--diff-filter=ACDMRT"])?
+            .lines()
+            .filter(|path| !path.is_empty() && !conflicted.iter().any(|c| c == path))
+            .map(str::to_string)
+            .collect();
+    println!("Aborting {} throws away:", what);
+    if !resolved.is_empty() {
+        println!("  {} file(s) it changed or you resolved:", resolved.len());
+        print_some(&resolved);
+    }
+    if !conflicted.is_empty() {
+        println!("  {} file(s) still in conflict:", conflicted.len());
+        print_some(&conflicted);
+    }
+    if resolved.is_empty() && conflicted.is_empty() {
+        println!("  nothing but the merge itself");
+    }
+    println!("  Changes you had not committed before the merge are kept.");
+
+    if !yes {
+        if !stdin().is_terminal() {
+            bail!("pass --yes to abort {} without being asked", what);
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Abort {}?", what))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    if merging {
+        run_git_quiet(&["merge", "--abort"])?;
+    } else {
+        run_git_quiet(&["reset", "--merge"])?;
+    }
+    let head = get_commits(&["-n", "1"])?;
+    match head.first() {
+        Some(commit) => println!(
+            "✓ Aborted {} - back at {} {}",
+            what, commit.short_hash, commit.subject
+        ),
+        None => println!("✓ Aborted {}", what),
+    }
+    Ok(())
+}
+
+fn print_some(paths: &[String]) {
+    for path in paths.iter().take(10) {
+        println!("    {}", path);
+    }
+    if paths.len() > 10 {
+        println!("    … and {} more", paths.len() - 10);
+    }
+}
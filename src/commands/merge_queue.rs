@@ -0,0 +1,123 @@
+use anyhow::{bail, Result};
+
+use crate::checks::run_checks;
+use crate::commands::operation::ensure_no_operation;
+use crate::config::Config;
+use crate::git::{run_git_quiet, run_git_quiet_with_env};
+use crate::progress::Phase;
+use crate::remote::{resolve_remote, Direction};
+use crate::status::{
+    count_commits, get_current_branch, get_default_branch, get_porcelain_lines, is_ancestor,
+};
+
+/// One retry covers the common case of a teammate pushing while checks ran.
+const MAX_ATTEMPTS: usize = 2;
+
+/// Lands HEAD on the remote's default branch the way a merge queue would: rebase onto
+/// the latest tip, run the checks, and push only if both succeed.
+pub fn run_merge_queue(remote: Option<String>) -> Result<()> {
+    ensure_no_operation("sgit merge-queue")?;
+    if get_porcelain_lines()?.iter().any(|(code, _)| code != "??") {
+        bail!("you have uncommitted changes - commit or stash them so the checks run on exactly what gets pushed");
+    }
+    let config = Config::load()?;
+    if config.checks.commands.is_empty() {
+        bail!(
+            "no checks configured - add a [checks] commands list to .sgit.toml to use merge-queue"
+        );
+    }
+
+    let remote = match remote {
+        Some(remote) => remote,
+        None => resolve_remote(Direction::Fetch)?.name,
+    };
+    let Some(default) = get_default_branch(&remote) else {
+        bail!(
+            "could not find the default branch of '{}' - run 'git remote set-head {} --auto'",
+            remote,
+            remote
+        );
+    };
+    let target = format!("{}/{}", remote, default);
+    let branch = get_current_branch()?;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        if attempt > 1 {
+            println!();
+            println!(
+                "→ Someone pushed to {} in the meantime - trying again...",
+                target
+            );
+        }
+
+        println!("→ Fetching {}...", target);
+        let phase = Phase::start("merge-queue", "fetch");
+        if let Err(e) = run_git_quiet(&["fetch", &remote, &default]) {
+            phase.failed(&e.to_string());
+            return Err(e);
+        }
+        phase.done();
+
+        let pending = count_commits(&format!("{}..HEAD", target))?;
+        if pending == 0 {
+            println!("✓ Nothing to land - {} already contains HEAD", target);
+            return Ok(());
+        }
+
+        if !is_ancestor(&target, "HEAD") {
+            println!("→ Rebasing onto {}...", target);
+            let phase = Phase::start("merge-queue", "rebase");
+            if let Err(e) = run_git_quiet_with_env(&["rebase", &target], &[("GIT_EDITOR", "true")])
+            {
+                phase.failed(&e.to_string());
+                eprintln!("✗ Your commits conflict with {}", target);
+                eprintln!("  Resolve the conflicts, 'sgit stage .', 'sgit continue', then run 'sgit merge-queue' again.");
+                return Err(e);
+            }
+            phase.done();
+            println!("✓ Rebased onto {}", target);
+        }
+
+        println!("→ Running checks on {} commit(s)...", pending);
+        let phase = Phase::start("merge-queue", "checks");
+        if let Some(failure) = run_checks(&config.checks.commands)? {
+            phase.failed("checks failed");
+            failure.print_summary();
+            bail!("checks failed - nothing was pushed");
+        }
+        phase.done();
+        println!("✓ All checks passed");
+
+        println!("→ Pushing to {}...", target);
+        let phase = Phase::start("merge-queue", "push");
+        match run_git_quiet(&["push", &remote, &format!("HEAD:refs/heads/{}", default)]) {
+            Ok(()) => {
+                phase.done();
+                println!("✓ Landed {} commit(s) on {}", pending, target);
+                if !branch.is_empty() && branch != default {
+                    println!(
+                        "  '{}' now matches {}; delete it with 'git branch -d {}' when done.",
+                        branch, target, branch
+                    );
+                }
+                return Ok(());
+            }
+            Err(e) if is_rejected(&e) && attempt < MAX_ATTEMPTS => {
+                phase.failed(&e.to_string());
+            }
+            Err(e) => {
+                phase.failed(&e.to_string());
+                return Err(e);
+            }
+        }
+    }
+    bail!(
+        "{} kept moving while the checks ran - run 'sgit merge-queue' again",
+        target
+    )
+}
+
+fn is_rejected(err: &anyhow::Error) -> bool {
+    let text = format!("{:#}", err);
+    text.contains("[rejected]") || text.contains("fetch first") || text.contains("non-fast-forward")
+}
@@ -1,13 +1,126 @@
+mod alias;
+mod attic;
+mod auto_rebase;
+mod autosync;
+mod backup;
+mod blame;
 mod branch;
+mod bundle;
+mod ci;
 mod commit;
+mod compare;
+mod config;
+mod conflicts;
+mod connect;
+mod contains;
+mod diff;
+mod doctor;
+mod explain_last;
+mod export;
+mod fetch;
+mod file_picker;
+mod find;
+mod fixup;
+mod grep;
+mod history;
+mod hooks;
+mod ignore;
+mod import;
+mod init;
+mod learn;
+mod log;
+mod maintenance;
+mod merge;
+mod merge_queue;
+mod operation;
+mod picker;
+mod profile;
+mod prompt;
+mod repos;
 mod reset;
+mod revert;
+mod reword;
+mod setup;
+mod show;
+mod signers;
+mod signing;
+mod size;
+mod sparse;
+mod split;
+mod squash;
 mod stage;
+mod status;
+mod submodule;
+mod subtree;
 mod sync;
+mod timeline;
+mod tool;
+mod undo_push;
 mod unstage;
+mod verify_tree;
+mod watch;
+mod wip;
+mod workspace;
+mod worktree;
 
+pub use alias::run_alias;
+pub use attic::run_attic;
+pub use auto_rebase::run_auto_rebase;
+pub use autosync::run_autosync;
+pub use backup::run_backup;
+pub use blame::run_blame;
 pub use branch::{create_branch, run_branch_interactive};
-pub use commit::run_commit;
+pub use bundle::run_bundle;
+pub use ci::run_ci;
+pub use commit::{run_commit, CommitOptions};
+pub use compare::run_compare;
+pub use config::run_config;
+pub use conflicts::run_conflicts;
+pub use connect::{connect_remote, run_connect};
+pub use contains::{run_contains, run_untagged};
+pub use diff::{run_diff, DiffOptions};
+pub use doctor::run_doctor;
+pub use explain_last::run_explain_last;
+pub use export::run_export;
+pub use fetch::run_fetch;
+pub use find::run_find;
+pub use fixup::run_fixup;
+pub use grep::{run_grep, GrepOptions};
+pub use history::run_history;
+pub use hooks::run_hooks;
+pub use ignore::run_ignore;
+pub use import::run_import;
+pub use init::{run_init, InitOptions};
+pub use learn::run_learn;
+pub use log::{run_log, LogOptions};
+pub use maintenance::run_maintenance;
+pub use merge::{run_merge, MergeOptions};
+pub use merge_queue::run_merge_queue;
+pub use operation::{run_abort, run_continue};
+pub use profile::run_profile;
+pub use prompt::run_prompt;
+pub use repos::run_repos;
 pub use reset::run_reset;
+pub use revert::run_revert;
+pub use reword::run_reword;
+pub use setup::run_setup;
+pub use show::run_show;
+pub use signers::run_signers;
+pub use signing::run_signing;
+pub use size::run_size;
+pub use sparse::run_sparse;
+pub use split::run_split;
+pub use squash::run_squash;
 pub use stage::stage_targets;
+pub use status::run_status;
+pub use submodule::run_submodule;
+pub use subtree::run_subtree;
 pub use sync::{run_pull, run_push, run_sync};
+pub use timeline::run_timeline;
+pub use undo_push::run_undo_push;
 pub use unstage::restore_stage;
+pub use verify_tree::run_verify_tree;
+pub use watch::run_watch;
+pub use wip::{run_unwip, run_wip};
+pub use workspace::run_workspace;
+pub use worktree::run_worktree;
@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use dialoguer::Confirm;
+
+use crate::git::{run_git_quiet, run_git_quiet_with_env};
+use crate::status::{get_conflicted_files, get_operation_in_progress, RepoOperation};
+
+pub fn run_continue() -> Result<()> {
+    let operation = current_operation()?;
+    if operation == RepoOperation::Bisect {
+        bail!("bisect has no continue step - mark the commit with 'git bisect good' or 'git bisect bad'");
+    }
+
+    let conflicted = get_conflicted_files()?;
+    if !conflicted.is_empty() {
+        println!(
+            "{} file(s) still have unresolved conflicts:",
+            conflicted.len()
+        );
+        for file in &conflicted {
+            println!("  {}", file);
+        }
+        bail!(
+            "fix the conflict markers, run 'sgit stage <file>' for each file, then 'sgit continue'"
+        );
+    }
+
+    println!("→ Continuing the {}...", operation.name());
+    match operation {
+        // `git merge --continue` insists on an editor; committing with the prepared message is equivalent.
+        RepoOperation::Merge => run_git_quiet(&["commit", "--no-edit"])?,
+        _ => run_git_quiet_with_env(
+            &[operation.git_command(), "--continue"],
+            &[("GIT_EDITOR", "true")],
+        )?,
+    }
+
+    match get_operation_in_progress()? {
+        Some(still) => {
+            println!("→ The {} stopped again.", still.name());
+            println!("  Check 'sgit status', then run 'sgit continue' or 'sgit abort'.");
+        }
+        None => println!("✓ Finished the {}", operation.name()),
+    }
+    Ok(())
+}
+
+pub fn run_abort() -> Result<()> {
+    let operation = current_operation()?;
+
+    let prompt = match operation {
+        RepoOperation::Bisect => "Stop bisecting and go back to the branch you started on?".to_string(),
+        _ => format!(
+            "Abort the {} and go back to where you started? Conflict resolutions so far will be lost.",
+            operation.name()
+        ),
+    };
+    let confirmed = Confirm::new()
+        .with_prompt(prompt)
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    match operation {
+        RepoOperation::Bisect => run_git_quiet(&["bisect", "reset"])?,
+        _ => run_git_quiet(&[operation.git_command(), "--abort"])?,
+    }
+    println!("✓ Aborted the {}", operation.name());
+    Ok(())
+}
+
+fn current_operation() -> Result<RepoOperation> {
+    match get_operation_in_progress()? {
+        Some(operation) => Ok(operation),
+        None => bail!("no merge, rebase, cherry-pick, revert, or bisect is in progress"),
+    }
+}
+
+/// Refuses to start `action` while another git operation is paused.
+pub(crate) fn ensure_no_operation(action: &str) -> Result<()> {
+    if let Some(operation) = get_operation_in_progress()? {
+        bail!(
+            "a {} is in progress - finish it before running {}\n  {}",
+            operation.name(),
+            action,
+            operation.next_steps()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use dialoguer::FuzzySelect;
+
+use crate::git::git_cmd;
+use crate::status::{get_current_branch, get_recent_commits};
+
+const DEFAULT_COMMIT_LIMIT: usize = 30;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RefKind {
+    Branch,
+    RemoteBranch,
+    Tag,
+    Commit,
+}
+
+impl RefKind {
+    fn label(self) -> &'static str {
+        match self {
+            RefKind::Branch => "branch",
+            RefKind::RemoteBranch => "remote",
+            RefKind::Tag => "tag",
+            RefKind::Commit => "commit",
+        }
+    }
+}
+
+pub(crate) struct PickedRef {
+    pub kind: RefKind,
+    /// Branch or tag name (`origin/main` for remote branches), or the full hash for commits.
+    pub name: String,
+    pub subject: String,
+}
+
+pub(crate) enum Picked {
+    Ref(PickedRef),
+    /// Index into the items added with [`RefPicker::extra_item`].
+    Extra(usize),
+}
+
+/// A fuzzy-searchable list of branches, remote branches, tags, and recent commits,
+/// grouped by kind, where every entry previews the commit it points at.
+pub(crate) struct RefPicker<'a> {
+    prompt: &'a str,
+    kinds: &'a [RefKind],
+    extras: Vec<&'a str>,
+}
+
+impl<'a> RefPicker<'a> {
+    pub fn new(prompt: &'a str, kinds: &'a [RefKind]) -> Self {
+        Self {
+            prompt,
+            kinds,
+            extras: Vec::new(),
+        }
+    }
+
+    /// Adds a non-ref action (e.g. "Create new branch...") at the end of the list.
+    pub fn extra_item(mut self, label: &'a str) -> Self {
+        self.extras.push(label);
+        self
+    }
+
+    /// Returns `None` when there is nothing to pick from.
+    pub fn interact(self) -> Result<Option<Picked>> {
+        let mut entries: Vec<(PickedRef, String)> = Vec::new();
+        for &kind in self.kinds {
+            match kind {
+                RefKind::Commit => {
+                    for commit in get_recent_commits(DEFAULT_COMMIT_LIMIT)? {
+                        let preview = commit.label();
+                        entries.push((
+                            PickedRef {
+                                kind,
+                                name: commit.hash,
+                                subject: commit.subject,
+                            },
+                            preview,
+                        ));
+                    }
+                }
+                _ => entries.extend(list_refs(kind)?),
+            }
+        }
+        if entries.is_empty() && self.extras.is_empty() {
+            return Ok(None);
+        }
+
+        let current = get_current_branch().unwrap_or_default();
+        let width = entries
+            .iter()
+            .filter(|(r, _)| r.kind != RefKind::Commit)
+            .map(|(r, _)| r.name.len())
+            .max()
+            .unwrap_or(0);
+        let mut labels: Vec<String> = entries
+            .iter()
+            .map(|(r, preview)| {
+                let marker = if r.kind == RefKind::Branch && r.name == current {
+                    " (current)"
+                } else {
+                    ""
+                };
+                if r.kind == RefKind::Commit {
+                    format!("{:<6}  {}", r.kind.label(), preview)
+                } else {
+                    format!(
+                        "{:<6}  {:<width$}  {}{}",
+                        r.kind.label(),
+                        r.name,
+                        preview,
+                        marker,
+                        width = width
+                    )
+                }
+            })
+            .collect();
+        labels.extend(self.extras.iter().map(|e| e.to_string()));
+
+        let selection = FuzzySelect::new()
+            .with_prompt(self.prompt)
+            .items(&labels)
+            .default(0)
+            .interact()?;
+
+        if selection >= entries.len() {
+            return Ok(Some(Picked::Extra(selection - entries.len())));
+        }
+        Ok(Some(Picked::Ref(entries.swap_remove(selection).0)))
+    }
+}
+
+/// Lists refs of `kind`, most recently updated first, with a preview of their tip commit.
+fn list_refs(kind: RefKind) -> Result<Vec<(PickedRef, String)>> {
+    let namespace = match kind {
+        RefKind::Branch => "refs/heads",
+        RefKind::RemoteBranch => "refs/remotes",
+        RefKind::Tag => "refs/tags",
+        RefKind::Commit => return Ok(Vec::new()),
+    };
+    let output = git_cmd()
+        .args([
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--format=%(refname:short)%1f%(symref)%1f%(objectname:short)%1f%(subject)%1f%(committerdate:relative)",
+            namespace,
+        ])
+        .output()
+        .context("running git for-each-ref")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\x1f');
+            let name = parts.next()?.to_string();
+            // Skip `origin/HEAD`-style aliases.
+            if !parts.next()?.is_empty() {
+                return None;
+            }
+            let short = parts.next()?;
+            let subject = parts.next()?.to_string();
+            let date = parts.next()?;
+            let preview = format!("{} {} ({})", short, subject, date);
+            Some((
+                PickedRef {
+                    kind,
+                    name,
+                    subject,
+                },
+                preview,
+            ))
+        })
+        .collect())
+}
@@ -0,0 +1,238 @@
+use std::env;
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::cli::ProfileAction;
+use crate::config::{user_config_path, UserConfig};
+use crate::git::run_git_silent;
+use crate::status::{get_config_value, get_remotes};
+
+/// Local git config key recording which profile the repository uses.
+pub(crate) const PROFILE_CONFIG_KEY: &str = "sgit.profile";
+
+pub fn run_profile(action: Option<ProfileAction>) -> Result<()> {
+    match action.unwrap_or(ProfileAction::List) {
+        ProfileAction::List => list_profiles(),
+        ProfileAction::Use { name } => use_profile(name),
+    }
+}
+
+fn list_profiles() -> Result<()> {
+    let config = UserConfig::load()?;
+    if config.profiles.is_empty() {
+        print_no_profiles_help();
+        return Ok(());
+    }
+    let active = get_config_value(PROFILE_CONFIG_KEY);
+    for (key, profile) in &config.profiles {
+        let marker = if active.as_deref() == Some(key.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+        println!(
+            "{} {:<10} {} <{}>",
+            marker, key, profile.name, profile.email
+        );
+        if let Some(signing_key) = &profile.signing_key {
+            println!("  {:<10} signs with {}", "", signing_key);
+        }
+        if let Some(host) = &profile.remote_host {
+            println!("  {:<10} remotes via {}", "", host);
+        }
+    }
+    if active.is_none() {
+        println!();
+        println!("This repository uses no profile yet - pick one with 'sgit profile use <name>'.");
+    }
+    Ok(())
+}
+
+fn use_profile(name: Option<String>) -> Result<()> {
+    let mut config = UserConfig::load()?;
+    if config.profiles.is_empty() {
+        print_no_profiles_help();
+        return Ok(());
+    }
+    let key = match name {
+        Some(name) => {
+            if !config.profiles.contains_key(&name) {
+                let known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                bail!(
+                    "no profile named '{}' - the configured ones are: {}",
+                    name,
+                    known.join(", ")
+                );
+            }
+            name
+        }
+        None => {
+            let keys: Vec<String> = config.profiles.keys().cloned().collect();
+            let labels: Vec<String> = config
+                .profiles
+                .iter()
+                .map(|(key, p)| format!("{}  ({} <{}>)", key, p.name, p.email))
+                .collect();
+            let active = get_config_value(PROFILE_CONFIG_KEY);
+            let default = keys
+                .iter()
+                .position(|key| Some(key) == active.as_ref())
+                .unwrap_or(0);
+            let selection = Select::new()
+                .with_prompt("Which profile should this repository use?")
+                .items(&labels)
+                .default(default)
+                .interact()?;
+            keys[selection].clone()
+        }
+    };
+    let Some(profile) = config.profiles.remove(&key) else {
+        bail!("no profile named '{}'", key);
+    };
+
+    run_git_silent(&["config", "--local", "user.name", &profile.name])?;
+    run_git_silent(&["config", "--local", "user.email", &profile.email])?;
+    match &profile.signing_key {
+        Some(signing_key) => {
+            // git does not expand `~` in this setting.
+            let signing_key = match (signing_key.strip_prefix("~/"), env::var_os("HOME")) {
+                (Some(rest), Some(home)) => Path::new(&home).join(rest).display().to_string(),
+                _ => signing_key.clone(),
+            };
+            let signing_key = signing_key.as_str();
+            run_git_silent(&["config", "--local", "user.signingkey", signing_key])?;
+            run_git_silent(&[
+                "config",
+                "--local",
+                "gpg.format",
+                signing_format(signing_key),
+            ])?;
+        }
+        None => {
+            // Drop a key left by a previous profile, so the global one applies again.
+            let _ = run_git_silent(&["config", "--local", "--unset", "user.signingkey"]);
+            let _ = run_git_silent(&["config", "--local", "--unset", "gpg.format"]);
+        }
+    }
+    run_git_silent(&["config", "--local", PROFILE_CONFIG_KEY, &key])?;
+    println!(
+        "✓ This repository now commits as {} <{}> (profile '{}')",
+        profile.name, profile.email, key
+    );
+    if let Some(host) = &profile.remote_host {
+        point_remotes_at(host)?;
+    }
+    Ok(())
+}
+
+/// Offers to switch each remote's URL to the profile's SSH host.
+fn point_remotes_at(host: &str) -> Result<()> {
+    for remote in get_remotes()? {
+        let Some(url) = get_config_value(&format!("remote.{}.url", remote)) else {
+            continue;
+        };
+        let Some(new_url) = with_host(&url, host) else {
+            continue;
+        };
+        if !stdin().is_terminal() {
+            println!(
+                "  {} still points at {} - the profile uses {}: 'git remote set-url {} {}'",
+                remote, url, host, remote, new_url
+            );
+            continue;
+        }
+        let switch = Confirm::new()
+            .with_prompt(format!(
+                "Point {} at {} instead of {}?",
+                remote, new_url, url
+            ))
+            .default(true)
+            .interact()?;
+        if switch {
+            run_git_silent(&["remote", "set-url", &remote, &new_url])?;
+            println!("✓ {} now uses {}", remote, new_url);
+        }
+    }
+    Ok(())
+}
+
+/// `url` rewritten as an SSH URL on `host`, or `None` when it already uses that host (or
+/// is not a URL sgit understands, like a local path).
+fn with_host(url: &str, host: &str) -> Option<String> {
+    let (user, old_host, path) = if let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        let (authority, path) = rest.split_once('/')?;
+        // Any credentials in an https URL are for that host only.
+        let old_host = authority.rsplit('@').next()?;
+        ("git".to_string(), old_host.to_string(), path.to_string())
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        let (authority, path) = rest.split_once('/')?;
+        let (user, old_host) = match authority.split_once('@') {
+            Some((user, old_host)) => (user.to_string(), old_host),
+            None => ("git".to_string(), authority),
+        };
+        (user, old_host.to_string(), path.to_string())
+    } else {
+        // scp-like `user@host:path`
+        let (authority, path) = url.split_once(':')?;
+        if authority.contains('/') {
+            return None;
+        }
+        let (user, old_host) = authority.split_once('@')?;
+        (user.to_string(), old_host.to_string(), path.to_string())
+    };
+    // A port belonged to the old host, so it is dropped.
+    let old_host = old_host.split(':').next().unwrap_or(&old_host).to_string();
+    if old_host == host {
+        return None;
+    }
+    Some(format!("{}@{}:{}", user, host, path))
+}
+
+/// The `gpg.format` a signing key needs.
+fn signing_format(signing_key: &str) -> &'static str {
+    if signing_key.ends_with(".pub")
+        || signing_key.starts_with("ssh-")
+        || signing_key.starts_with("key::")
+    {
+        "ssh"
+    } else {
+        "openpgp"
+    }
+}
+
+/// The profile this repository uses and whether its identity still matches it, for
+/// `sgit status`: e.g. "work", or "work (user.email is now me@home.org)".
+pub(crate) fn describe_active_profile() -> Option<String> {
+    let key = get_config_value(PROFILE_CONFIG_KEY)?;
+    let profiles = UserConfig::load().map(|c| c.profiles).unwrap_or_default();
+    let Some(profile) = profiles.get(&key) else {
+        return Some(format!("{} (no longer in your sgit config)", key));
+    };
+    Some(match get_config_value("user.email") {
+        Some(email) if email != profile.email => {
+            format!("{} (user.email is now {})", key, email)
+        }
+        _ => format!("{} <{}>", key, profile.email),
+    })
+}
+
+fn print_no_profiles_help() {
+    let path = user_config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "~/.config/sgit/config.toml".to_string());
+    println!("No profiles defined yet. Add them to {}:", path);
+    println!();
+    println!("  [profiles.work]");
+    println!("  name = \"Jo Doe\"");
+    println!("  email = \"jo@company.com\"");
+    println!("  signing_key = \"~/.ssh/id_work.pub\"");
+    println!("  remote_host = \"github-work\"");
+    println!();
+    println!("Then run 'sgit profile use work' in a repository.");
+}
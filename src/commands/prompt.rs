@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use crate::git::git_cmd;
+use crate::status::{get_operation_in, RepoOperation};
+
+/// Prints a compact segment like `main +* ↑1 ↓2 REBASING` for shell prompts.
+/// Prints nothing (and never fails) outside a repository, so it is safe to embed in PS1.
+pub fn run_prompt() {
+    if let Some(segment) = build_segment() {
+        println!("{}", segment);
+    }
+}
+
+fn build_segment() -> Option<String> {
+    let git_dir = git_stdout(&["rev-parse", "--absolute-git-dir"])?;
+    // One status call covers branch, ahead/behind, and dirtiness.
+    let status = git_stdout(&["status", "--porcelain=v2", "--branch"])?;
+
+    let mut head = None;
+    let mut oid = None;
+    let (mut ahead, mut behind) = (0, 0);
+    let (mut staged, mut unstaged, mut untracked) = (false, false, false);
+    for line in status.lines() {
+        if let Some(name) = line.strip_prefix("# branch.head ") {
+            head = Some(name.to_string());
+        } else if let Some(hash) = line.strip_prefix("# branch.oid ") {
+            oid = Some(hash.to_string());
+        } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            let mut parts = counts.split_whitespace();
+            ahead = parts
+                .next()
+                .and_then(|a| a.trim_start_matches('+').parse().ok())
+                .unwrap_or(0);
+            behind = parts
+                .next()
+                .and_then(|b| b.trim_start_matches('-').parse().ok())
+                .unwrap_or(0);
+        } else if line.starts_with("? ") {
+            untracked = true;
+        } else if line.starts_with("u ") {
+            staged = true;
+            unstaged = true;
+        } else if let Some(rest) = line.strip_prefix("1 ").or_else(|| line.strip_prefix("2 ")) {
+            let mut xy = rest.chars();
+            staged |= xy.next().is_some_and(|x| x != '.');
+            unstaged |= xy.next().is_some_and(|y| y != '.');
+        }
+    }
+
+    let mut segment = match head.as_deref() {
+        Some("(detached)") => match oid.as_deref() {
+            Some(hash) => format!("({})", &hash[..hash.len().min(7)]),
+            None => "(detached)".to_string(),
+        },
+        Some(name) => name.to_string(),
+        None => return None,
+    };
+
+    let mut markers = String::new();
+    if staged {
+        markers.push('+');
+    }
+    if unstaged {
+        markers.push('*');
+    }
+    if untracked {
+        markers.push('?');
+    }
+    if !markers.is_empty() {
+        segment.push(' ');
+        segment.push_str(&markers);
+    }
+    if ahead > 0 {
+        segment.push_str(&format!(" ↑{}", ahead));
+    }
+    if behind > 0 {
+        segment.push_str(&format!(" ↓{}", behind));
+    }
+    if let Some(operation) = get_operation_in(Path::new(&git_dir)) {
+        segment.push(' ');
+        segment.push_str(prompt_label(operation));
+    }
+    Some(segment)
+}
+
+fn prompt_label(operation: RepoOperation) -> &'static str {
+    match operation {
+        RepoOperation::Merge => "MERGING",
+        RepoOperation::Rebase => "REBASING",
+        RepoOperation::CherryPick => "CHERRY-PICKING",
+        RepoOperation::Revert => "REVERTING",
+        RepoOperation::Am => "AM",
+        RepoOperation::Bisect => "BISECTING",
+    }
+}
+
+/// Runs git with stderr discarded; `None` on any failure.
+fn git_stdout(args: &[&str]) -> Option<String> {
+    let output = git_cmd()
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
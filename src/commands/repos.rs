@@ -0,0 +1,137 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::cli::{ReposAction, WorkspaceAction};
+use crate::commands::workspace::run_across;
+use crate::config::UserConfig;
+use crate::git::git_cmd;
+use crate::workspace::{load_registry, repo_name, save_registry, scan_repos};
+
+pub fn run_repos(action: Option<ReposAction>) -> Result<()> {
+    match action.unwrap_or(ReposAction::List) {
+        ReposAction::List => list_repos(),
+        ReposAction::Add { path } => add_repo(path),
+        ReposAction::Remove { repo } => remove_repo(&repo),
+        ReposAction::Scan { dir, depth } => scan(dir, depth),
+    }
+}
+
+fn list_repos() -> Result<()> {
+    let repos = load_registry()?;
+    if repos.is_empty() {
+        println!("No repositories are registered yet.");
+        println!(
+            "Add this one with 'sgit repos add', or find them all with 'sgit repos scan ~/src'."
+        );
+        return Ok(());
+    }
+    if !UserConfig::load()?.workspace.repos.is_empty() {
+        println!("(sgit workspace uses [workspace] repos from your user config, not this list)");
+    }
+    run_across(&repos, "registered repositories", WorkspaceAction::Status)
+}
+
+fn add_repo(path: Option<String>) -> Result<()> {
+    let dir = match path {
+        Some(path) => PathBuf::from(path),
+        None => env::current_dir().context("failed to read the current directory")?,
+    };
+    let root = repo_root_of(&dir)?;
+    let mut repos = load_registry()?;
+    if repos.contains(&root) {
+        println!("{} is already registered", root.display());
+        return Ok(());
+    }
+    repos.push(root.clone());
+    save_registry(&repos)?;
+    println!("✓ Registered {} ({})", repo_name(&root), root.display());
+    Ok(())
+}
+
+/// The top of the repository `dir` is in, absolute.
+fn repo_root_of(dir: &Path) -> Result<PathBuf> {
+    let output = git_cmd()
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("cannot look at {}", dir.display()))?;
+    if !output.status.success() {
+        bail!("{} is not inside a git repository", dir.display());
+    }
+    let root = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(root.canonicalize().unwrap_or(root))
+}
+
+/// Unregisters the repository named `repo`, or found at that path.
+fn remove_repo(repo: &str) -> Result<()> {
+    let mut repos = load_registry()?;
+    let given = Path::new(repo);
+    let given = given.canonicalize().unwrap_or_else(|_| given.to_path_buf());
+    let matches: Vec<PathBuf> = repos
+        .iter()
+        .filter(|path| **path == given || repo_name(path) == repo)
+        .cloned()
+        .collect();
+    let target = match matches.as_slice() {
+        [] => bail!(
+            "no registered repository is called or found at '{}'\n  hint: 'sgit repos list' shows them",
+            repo
+        ),
+        [only] => only.clone(),
+        several => {
+            let paths: Vec<String> = several
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            bail!(
+                "several registered repositories are called '{}' - give the path of one: {}",
+                repo,
+                paths.join(", ")
+            );
+        }
+    };
+    repos.retain(|path| *path != target);
+    save_registry(&repos)?;
+    println!("✓ Unregistered {}", target.display());
+    Ok(())
+}
+
+fn scan(dir: Option<String>, depth: usize) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => env::current_dir().context("failed to read the current directory")?,
+    };
+    if !dir.is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    let dir = dir.canonicalize().unwrap_or(dir);
+    println!("→ Looking for repositories in {}...", dir.display());
+    let found = scan_repos(&dir, depth);
+    let mut repos = load_registry()?;
+    let new: Vec<PathBuf> = found
+        .iter()
+        .filter(|path| !repos.contains(path))
+        .cloned()
+        .collect();
+    for path in &new {
+        println!("  + {}", path.display());
+    }
+    if new.is_empty() {
+        println!(
+            "✓ Found {} repositories, all registered already",
+            found.len()
+        );
+        return Ok(());
+    }
+    repos.extend(new.iter().cloned());
+    save_registry(&repos)?;
+    println!(
+        "✓ Found {} repositories and registered {} new ones ({} in all)",
+        found.len(),
+        new.len(),
+        repos.len()
+    );
+    Ok(())
+}
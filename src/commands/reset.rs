@@ -1,6 +1,7 @@
 use anyhow::Result;
-use dialoguer::{MultiSelect, Select};
+use dialoguer::Select;
 
+use crate::commands::file_picker::{select_files, Preview};
 use crate::git::run_git_silent;
 use crate::status::{
     get_all_uncommitted_files, get_porcelain_lines, get_repo_root, get_staged_files,
@@ -107,10 +108,7 @@ fn reset_custom() -> Result<()> {
         return Ok(());
     }
 
-    let selected = MultiSelect::new()
-        .with_prompt("Select files to reset")
-        .items(&files)
-        .interact()?;
+    let selected = select_files("Select files to reset", &files, Preview::Uncommitted)?;
 
     if selected.is_empty() {
         println!("No files selected.");
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::run_git_quiet;
+use crate::status::{get_commit_parents, get_commits, resolve_commit};
+
+pub fn run_revert(target: Option<String>, no_commit: bool, mainline: Option<usize>) -> Result<()> {
+    let hash = match target {
+        Some(target) => resolve_commit(&target)?,
+        None => {
+            let Some(Picked::Ref(commit)) = RefPicker::new(
+                "Select a commit to revert (type to search)",
+                &[RefKind::Commit],
+            )
+            .interact()?
+            else {
+                println!("No commits to revert.");
+                return Ok(());
+            };
+            let confirm = Confirm::new()
+                .with_prompt(format!(
+                    "Create a new commit that undoes '{}'?",
+                    commit.subject
+                ))
+                .default(true)
+                .interact()?;
+            if !confirm {
+                println!("Aborted.");
+                return Ok(());
+            }
+            commit.name
+        }
+    };
+
+    let parents = get_commit_parents(&hash)?;
+    let mainline = if parents.len() > 1 {
+        match mainline {
+            Some(n) if n >= 1 && n <= parents.len() => Some(n),
+            Some(n) => bail!(
+                "--mainline {} is out of range - this merge commit has {} parents",
+                n,
+                parents.len()
+            ),
+            None => Some(pick_mainline(&parents)?),
+        }
+    } else {
+        if mainline.is_some() {
+            bail!("--mainline only applies to merge commits");
+        }
+        None
+    };
+
+    let mainline_str = mainline.map(|n| n.to_string());
+    let mut args = vec!["revert"];
+    if no_commit {
+        args.push("--no-commit");
+    } else {
+        args.push("--no-edit");
+    }
+    if let Some(ref n) = mainline_str {
+        args.push("-m");
+        args.push(n.as_str());
+    }
+    args.push(hash.as_str());
+
+    println!("→ Reverting {}...", &hash[..hash.len().min(7)]);
+    run_git_quiet(&args)?;
+    if no_commit {
+        println!("✓ Revert applied to your working tree and staged");
+        println!("  Review with 'sgit diff --staged', then run 'sgit commit' when ready.");
+    } else {
+        println!("✓ Revert commit created");
+        println!("  Run 'sgit push' to publish the revert.");
+    }
+    Ok(())
+}
+
+/// Asks which parent of a merge commit is the mainline the revert should keep.
+fn pick_mainline(parents: &[String]) -> Result<usize> {
+    let mut labels = Vec::with_capacity(parents.len());
+    for (idx, parent) in parents.iter().enumerate() {
+        let summary = get_commits(&["-n", "1", parent.as_str()])?
+            .into_iter()
+            .next()
+            .map(|c| format!("{} {}", c.short_hash, c.subject))
+            .unwrap_or_else(|| parent.clone());
+        labels.push(format!("Parent {}: {}", idx + 1, summary));
+    }
+
+    println!("This is a merge commit. Reverting it undoes the changes brought in relative to one parent.");
+    println!("  Usually that is parent 1, the branch that was merged into.");
+    let selection = Select::new()
+        .with_prompt("Which side should be kept?")
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    Ok(selection + 1)
+}
@@ -0,0 +1,213 @@
+use anyhow::{bail, Result};
+use dialoguer::MultiSelect;
+
+use crate::commands::history::{ensure_unpushed, prompt_reworded_message};
+use crate::commands::operation::ensure_no_operation;
+use crate::config::Config;
+use crate::conventional::check_scope;
+use crate::git::{run_git_quiet, run_git_quiet_with_env};
+use crate::message::edit_in_editor;
+use crate::status::{
+    get_commit_message, get_commit_parents, get_commits, get_unpushed_commits, is_ancestor,
+    resolve_commit, CommitSummary,
+};
+
+const PICKER_COMMIT_LIMIT: usize = 20;
+
+pub fn run_reword(
+    target: Option<String>,
+    message: Option<String>,
+    edit: bool,
+    force: bool,
+) -> Result<()> {
+    ensure_no_operation("sgit reword")?;
+    let commit = match target {
+        Some(target) => {
+            let hash = resolve_commit(&target)?;
+            match get_commits(&["-n", "1", hash.as_str()])?.into_iter().next() {
+                Some(commit) => commit,
+                None => bail!("could not read commit '{}'", target),
+            }
+        }
+        None => {
+            let mut picked = pick_unpushed_commits()?;
+            match picked.len() {
+                0 => return Ok(()),
+                1 => picked.remove(0),
+                _ => {
+                    if message.is_some() {
+                        bail!("--message only works when rewording a single commit");
+                    }
+                    return reword_several(picked, force);
+                }
+            }
+        }
+    };
+
+    let head = resolve_commit("HEAD")?;
+    if commit.hash != head && !is_ancestor(&commit.hash, &head) {
+        bail!("{} is not part of the current branch", commit.short_hash);
+    }
+    let needs_force_push = ensure_unpushed(&[&commit], force, "Rewording")?;
+
+    let current = get_commit_message(&commit.hash)?;
+    let new_message = match message {
+        Some(message) => message,
+        None => {
+            let edited = if edit {
+                edit_message(&commit, &current)?
+            } else {
+                prompt_reworded_message(&current)?
+            };
+            match edited {
+                Some(message) => message,
+                None => return Ok(()),
+            }
+        }
+    };
+    if new_message.trim().is_empty() {
+        bail!("commit message cannot be empty");
+    }
+    check_scope(&new_message, &Config::load()?.commit.scopes)?;
+    if new_message.trim() == current.trim() {
+        println!("Message unchanged.");
+        return Ok(());
+    }
+
+    if commit.hash == head {
+        run_git_quiet(&["commit", "--amend", "--only", "-m", new_message.as_str()])?;
+    } else {
+        reword_older_commit(&commit, &new_message)?;
+    }
+
+    println!("✓ Reworded {}", commit.short_hash);
+    if needs_force_push {
+        println!("  Run 'git push --force-with-lease' to update the remote.");
+    }
+    Ok(())
+}
+
+/// Opens each commit's message in the editor, then applies every change with a single
+/// autosquash rebase from the oldest picked commit.
+fn reword_several(commits: Vec<CommitSummary>, force: bool) -> Result<()> {
+    let refs: Vec<&CommitSummary> = commits.iter().collect();
+    let needs_force_push = ensure_unpushed(&refs, force, "Rewording")?;
+    let scopes = Config::load()?.commit.scopes;
+
+    let mut rewordings = Vec::new();
+    for commit in &commits {
+        println!("→ Editing the message of {}...", commit.label());
+        let current = get_commit_message(&commit.hash)?;
+        let Some(new_message) = edit_message(commit, &current)? else {
+            println!("  Left unchanged.");
+            continue;
+        };
+        if new_message.trim() == current.trim() {
+            println!("  Left unchanged.");
+            continue;
+        }
+        check_scope(&new_message, &scopes)?;
+        rewordings.push((commit, new_message));
+    }
+    if rewordings.is_empty() {
+        println!("No messages changed.");
+        return Ok(());
+    }
+
+    // The picker lists newest first, so the oldest picked commit comes last.
+    let Some(oldest) = commits.last() else {
+        bail!("no commits were picked to reword");
+    };
+    for (commit, new_message) in &rewordings {
+        // The hash, unlike the subject, cannot match some other commit.
+        let amend_message = format!("amend! {}\n\n{}", commit.hash, new_message);
+        run_git_quiet(&[
+            "commit",
+            "--only",
+            "--allow-empty",
+            "--no-verify",
+            "-m",
+            amend_message.as_str(),
+        ])?;
+    }
+    rebase_autosquash(oldest)?;
+
+    println!("✓ Reworded {} commit(s)", rewordings.len());
+    if needs_force_push {
+        println!("  Run 'git push --force-with-lease' to update the remote.");
+    }
+    Ok(())
+}
+
+/// Lets the user edit `current` in git's configured editor. `#` lines are dropped, and an
+/// empty result keeps the old message.
+fn edit_message(commit: &CommitSummary, current: &str) -> Result<Option<String>> {
+    let template = format!(
+        "{}\n\n# New message for {}. Lines starting with '#' are ignored;\n# an empty message keeps the old one.\n",
+        current.trim_end(),
+        commit.label()
+    );
+    edit_in_editor("REWORD_EDITMSG", &template)
+}
+
+/// Records an empty `amend!` commit and lets an autosquash rebase fold it into `commit`.
+fn reword_older_commit(commit: &CommitSummary, new_message: &str) -> Result<()> {
+    // The hash, unlike the subject, cannot match some other commit.
+    let amend_message = format!("amend! {}\n\n{}", commit.hash, new_message);
+    run_git_quiet(&[
+        "commit",
+        "--only",
+        "--allow-empty",
+        "--no-verify",
+        "-m",
+        amend_message.as_str(),
+    ])?;
+
+    rebase_autosquash(commit)
+}
+
+/// Folds the pending `amend!` commits into their targets, starting at `oldest`.
+fn rebase_autosquash(oldest: &CommitSummary) -> Result<()> {
+    let parent = get_commit_parents(&oldest.hash)?.into_iter().next();
+    let mut args = vec!["rebase", "-i", "--autosquash", "--autostash"];
+    match parent {
+        Some(ref parent) => args.push(parent.as_str()),
+        None => args.push("--root"),
+    }
+
+    println!("→ Rewriting history from {}...", oldest.short_hash);
+    run_git_quiet_with_env(
+        &args,
+        &[("GIT_SEQUENCE_EDITOR", "true"), ("GIT_EDITOR", "true")],
+    )
+}
+
+fn pick_unpushed_commits() -> Result<Vec<CommitSummary>> {
+    let unpushed = get_unpushed_commits()?;
+    let commits: Vec<CommitSummary> = get_commits(&["-n", &PICKER_COMMIT_LIMIT.to_string()])?
+        .into_iter()
+        .filter(|c| unpushed.contains(&c.hash))
+        .collect();
+    if commits.is_empty() {
+        println!("No unpushed commits to reword.");
+        println!("  Pass a ref explicitly (with --force) to reword a pushed commit.");
+        return Ok(Vec::new());
+    }
+
+    let labels: Vec<String> = commits.iter().map(|c| c.label()).collect();
+    let selection = MultiSelect::new()
+        .with_prompt(
+            "Which commit messages do you want to change? (space to pick, enter to confirm)",
+        )
+        .items(&labels)
+        .interact()?;
+    if selection.is_empty() {
+        println!("Nothing picked.");
+    }
+    Ok(commits
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| selection.contains(idx))
+        .map(|(_, commit)| commit)
+        .collect())
+}
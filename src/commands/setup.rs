@@ -0,0 +1,85 @@
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Input, Select};
+
+use crate::git::{check_in_repo, git_cmd, run_git_silent};
+use crate::status::get_config_value;
+
+pub fn run_setup() -> Result<()> {
+    let name: String = Input::new()
+        .with_prompt("Your name (as it should appear on commits)")
+        .with_initial_text(get_config_value("user.name").unwrap_or_default())
+        .validate_with(|input: &String| {
+            if input.trim().is_empty() {
+                Err("the name cannot be empty")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    let email: String = Input::new()
+        .with_prompt("Your email (use the one your forge account knows)")
+        .with_initial_text(get_config_value("user.email").unwrap_or_default())
+        .validate_with(|input: &String| {
+            let input = input.trim();
+            if input.contains('@') && !input.contains(char::is_whitespace) {
+                Ok(())
+            } else {
+                Err("that does not look like an email address")
+            }
+        })
+        .interact_text()?;
+
+    let scope_flag = if check_in_repo().is_ok() {
+        let scope = Select::new()
+            .with_prompt("Save it for")
+            .items(&[
+                "All your repositories (global config)",
+                "This repository only",
+            ])
+            .default(0)
+            .interact()?;
+        if scope == 0 {
+            "--global"
+        } else {
+            "--local"
+        }
+    } else {
+        "--global"
+    };
+
+    run_git_silent(&["config", scope_flag, "user.name", name.trim()])?;
+    run_git_silent(&["config", scope_flag, "user.email", email.trim()])?;
+    let where_to = if scope_flag == "--global" {
+        "your global git config"
+    } else {
+        "this repository's config"
+    };
+    println!(
+        "✓ Commits will be made as {} <{}> (saved in {})",
+        name.trim(),
+        email.trim(),
+        where_to
+    );
+    Ok(())
+}
+
+/// Makes sure git knows the committer's name and email before committing, running the
+/// setup wizard when it does not (or explaining how when there is no terminal).
+pub(crate) fn ensure_identity() -> Result<()> {
+    let known = git_cmd()
+        .args(["var", "GIT_COMMITTER_IDENT"])
+        .output()
+        .context("failed to execute git - is git installed?")?
+        .status
+        .success();
+    if known {
+        return Ok(());
+    }
+    if !stdin().is_terminal() {
+        bail!("git does not know your name and email yet, so it cannot record who made the commit\n  hint: run 'sgit setup', or 'git config --global user.name \"Your Name\"' and 'git config --global user.email you@example.com'");
+    }
+    println!("git does not know your name and email yet - they are recorded in every commit.");
+    run_setup()
+}
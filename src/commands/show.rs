@@ -0,0 +1,58 @@
+use std::io::{stdout, IsTerminal};
+
+use anyhow::Result;
+
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::run_git;
+use crate::status::{get_commit_parents, resolve_commit};
+
+/// Header, indented message, and file summary, followed by the patch.
+const SHOW_FORMAT: &str = "format:%C(yellow)commit %H%C(auto)%d%C(reset)%n\
+Author: %an <%ae>%n\
+Date:   %ad (%ar)%n\
+%n%w(0,4,4)%B";
+
+pub fn run_show(target: Option<String>) -> Result<()> {
+    let hash = match target {
+        Some(target) => resolve_commit(&target)?,
+        None => {
+            let Some(Picked::Ref(commit)) = RefPicker::new(
+                "Select a commit to show (type to search)",
+                &[RefKind::Commit],
+            )
+            .interact()?
+            else {
+                println!("No commits to show.");
+                return Ok(());
+            };
+            commit.name
+        }
+    };
+
+    let parents = get_commit_parents(&hash)?;
+    let mut args = vec![
+        "show",
+        "--date=format:%Y-%m-%d %H:%M",
+        "--stat",
+        "--patch",
+        // Without this a merge shows no diff at all; compare against its first parent.
+        "--diff-merges=first-parent",
+    ];
+    let format_arg = format!("--format={}", SHOW_FORMAT);
+    args.push(format_arg.as_str());
+    if stdout().is_terminal() {
+        args.push("--color=always");
+    }
+    args.push(hash.as_str());
+    run_git(&args)?;
+
+    if parents.len() > 1 {
+        println!();
+        println!(
+            "This is a merge of {} parents; the diff above is against the first one ({}).",
+            parents.len(),
+            &parents[0][..7.min(parents[0].len())]
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Input, MultiSelect, Select};
+
+use crate::cli::SignersAction;
+use crate::forge::Forge;
+use crate::git::run_git_silent;
+use crate::paths::expand_home;
+use crate::status::{get_config_value, get_git_dir};
+
+const SIGNERS_CONFIG_KEY: &str = "gpg.ssh.allowedSignersFile";
+
+pub fn run_signers(action: Option<SignersAction>) -> Result<()> {
+    let action = match action {
+        Some(action) => action,
+        None => {
+            let selection = Select::new()
+                .with_prompt("What would you like to do with allowed signers?")
+                .items(&["List signers", "Add a signer", "Remove signers"])
+                .default(0)
+                .interact()?;
+            match selection {
+                0 => SignersAction::List,
+                1 => SignersAction::Add {
+                    email: None,
+                    key: None,
+                    user: None,
+                },
+                _ => SignersAction::Remove { email: None },
+            }
+        }
+    };
+
+    match action {
+        SignersAction::List => list_signers(),
+        SignersAction::Add { email, key, user } => add_signer(email, key, user),
+        SignersAction::Remove { email } => remove_signers(email),
+    }
+}
+
+fn list_signers() -> Result<()> {
+    let path = signers_file()?;
+    let entries = read_entries(&path)?;
+    if entries.is_empty() {
+        println!("No allowed signers in {}.", path.display());
+        println!("  Add one with 'sgit signers add <email> --user <forge-username>'.");
+        return Ok(());
+    }
+
+    println!("Allowed signers ({}):", path.display());
+    for line in &entries {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let principal = fields.first().copied().unwrap_or("");
+        let key_type = fields
+            .iter()
+            .position(|f| !f.contains('=') && *f != principal);
+        match key_type {
+            Some(idx) => {
+                let blob = fields.get(idx + 1).copied().unwrap_or("");
+                let tail = &blob[blob.len().saturating_sub(16)..];
+                println!("  {}  {} …{}", principal, fields[idx], tail);
+            }
+            None => println!("  {}", principal),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn add_signer(
+    email: Option<String>,
+    key: Option<String>,
+    user: Option<String>,
+) -> Result<()> {
+    let email = match email {
+        Some(email) => email,
+        None => Input::new().with_prompt("Signer email").interact_text()?,
+    };
+    let email = email.trim().to_string();
+    if email.is_empty() || email.contains(char::is_whitespace) {
+        bail!("signer email cannot be empty or contain whitespace");
+    }
+
+    let keys = match (key, user) {
+        (Some(key), _) => vec![read_key(&key)?],
+        (None, Some(user)) => fetch_forge_keys(&user)?,
+        (None, None) => {
+            let source = Select::new()
+                .with_prompt("Where should the key come from?")
+                .items(&["Fetch from forge username", "Paste a public key"])
+                .default(0)
+                .interact()?;
+            if source == 0 {
+                let user: String = Input::new().with_prompt("Forge username").interact_text()?;
+                fetch_forge_keys(user.trim())?
+            } else {
+                let key: String = Input::new().with_prompt("Public key").interact_text()?;
+                vec![read_key(&key)?]
+            }
+        }
+    };
+
+    let path = signers_file()?;
+    let mut entries = read_entries(&path)?;
+    let mut added = 0;
+    for key in keys {
+        let entry = format!("{} namespaces=\"git\" {}", email, key);
+        if entries
+            .iter()
+            .any(|e| e.ends_with(&key) && principal_of(e) == email)
+        {
+            continue;
+        }
+        entries.push(entry);
+        added += 1;
+    }
+
+    write_entries(&path, &entries)?;
+    ensure_configured(&path)?;
+    if added == 0 {
+        println!("Signer '{}' already has all of those keys.", email);
+    } else {
+        println!("✓ Added {} key(s) for '{}'", added, email);
+    }
+    Ok(())
+}
+
+fn remove_signers(email: Option<String>) -> Result<()> {
+    let path = signers_file()?;
+    let entries = read_entries(&path)?;
+    if entries.is_empty() {
+        println!("No allowed signers to remove.");
+        return Ok(());
+    }
+
+    let before = entries.len();
+    let remaining: Vec<String> = match email {
+        Some(email) => entries
+            .into_iter()
+            .filter(|e| principal_of(e) != email)
+            .collect(),
+        None => {
+            let selected = MultiSelect::new()
+                .with_prompt("Select signers to remove")
+                .items(&entries)
+                .interact()?;
+            if selected.is_empty() {
+                println!("No signers selected.");
+                return Ok(());
+            }
+            entries
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, _)| !selected.contains(idx))
+                .map(|(_, e)| e)
+                .collect()
+        }
+    };
+
+    write_entries(&path, &remaining)?;
+    println!("✓ Removed {} signer key(s)", before - remaining.len());
+    Ok(())
+}
+
+fn fetch_forge_keys(user: &str) -> Result<Vec<String>> {
+    let forge = Forge::detect()?;
+    println!(
+        "→ Fetching signing keys for '{}' from {}...",
+        user, forge.host
+    );
+    let keys = forge.fetch_signing_keys(user)?;
+    if keys.is_empty() {
+        bail!(
+            "'{}' has no SSH signing keys on {} - ask them to add one in their account settings",
+            user,
+            forge.host
+        );
+    }
+    Ok(keys)
+}
+
+fn read_key(key_or_path: &str) -> Result<String> {
+    let key_or_path = key_or_path.trim();
+    let key = if Path::new(key_or_path).is_file() {
+        fs::read_to_string(key_or_path)
+            .with_context(|| format!("failed to read key file {}", key_or_path))?
+    } else {
+        key_or_path.to_string()
+    };
+
+    // Keep only "<type> <base64>", dropping any trailing comment.
+    let parts: Vec<&str> = key.split_whitespace().take(2).collect();
+    if parts.len() != 2 {
+        bail!("'{}' does not look like an SSH public key", key_or_path);
+    }
+    Ok(parts.join(" "))
+}
+
+fn principal_of(entry: &str) -> &str {
+    entry.split_whitespace().next().unwrap_or("")
+}
+
+fn signers_file() -> Result<PathBuf> {
+    if let Some(path) = get_config_value(SIGNERS_CONFIG_KEY) {
+        return Ok(expand_home(&path));
+    }
+    Ok(Path::new(&get_git_dir()?).join("allowed_signers"))
+}
+
+fn ensure_configured(path: &Path) -> Result<()> {
+    if get_config_value(SIGNERS_CONFIG_KEY).is_none() {
+        let path = path.to_string_lossy();
+        run_git_silent(&["config", SIGNERS_CONFIG_KEY, &path])?;
+        println!("→ Set {} to {}", SIGNERS_CONFIG_KEY, path);
+    }
+    Ok(())
+}
+
+fn read_entries(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+fn write_entries(path: &Path, entries: &[String]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut contents = entries.join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,263 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::cli::SigningAction;
+use crate::commands::signers::add_signer;
+use crate::config::Config;
+use crate::git::{git_cmd, run_git_silent};
+use crate::status::{get_config_value, EMPTY_TREE};
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyKind {
+    Ssh,
+    Gpg,
+}
+
+impl KeyKind {
+    /// The `gpg.format` value git expects for this kind of key.
+    fn format(self) -> &'static str {
+        match self {
+            KeyKind::Ssh => "ssh",
+            KeyKind::Gpg => "openpgp",
+        }
+    }
+}
+
+struct SigningKey {
+    kind: KeyKind,
+    /// What goes in `user.signingkey`: a `.pub` path for SSH, a key ID for GPG.
+    key: String,
+    label: String,
+}
+
+pub fn run_signing(action: Option<SigningAction>) -> Result<()> {
+    match action.unwrap_or(SigningAction::Status) {
+        SigningAction::Status => signing_status(),
+        SigningAction::Setup => signing_setup(),
+    }
+}
+
+fn signing_status() -> Result<()> {
+    let format = get_config_value("gpg.format").unwrap_or_else(|| "openpgp".to_string());
+    let key = get_config_value("user.signingkey");
+    let by_default = get_config_value("commit.gpgsign").is_some_and(|v| v == "true");
+    let required = Config::load()?.commit.sign;
+
+    println!("Signing format:  {}", format);
+    println!(
+        "Signing key:     {}",
+        key.as_deref()
+            .unwrap_or("(not set; gpg picks one from your email)")
+    );
+    println!(
+        "Sign by default: {}",
+        match (by_default, required) {
+            (true, _) => "yes (commit.gpgsign)",
+            (false, true) => "yes ([commit] sign in your sgit config)",
+            (false, false) => "no - pass --sign to 'sgit commit'",
+        }
+    );
+
+    let output = git_cmd()
+        .args(["log", "-1", "--format=%G?"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !head.is_empty() {
+        println!("Last commit:     {}", describe_signature(&head));
+    }
+    if key.is_none() && format == "ssh" {
+        println!();
+        println!("SSH signing needs a key - run 'sgit signing setup'.");
+    }
+    Ok(())
+}
+
+/// Explains a `%G?` code.
+pub(crate) fn describe_signature(code: &str) -> &'static str {
+    match code {
+        "G" => "signed, good signature",
+        "U" => "signed, good signature from an unknown key",
+        "X" | "Y" => "signed, but the signature or key has expired",
+        "R" => "signed with a revoked key",
+        "E" => "signed, but the signature cannot be checked (missing key or allowed signers)",
+        "B" => "bad signature",
+        _ => "not signed",
+    }
+}
+
+fn signing_setup() -> Result<()> {
+    let keys = detect_keys();
+    if keys.is_empty() {
+        bail!(
+            "no SSH or GPG keys found\n  hint: create an SSH key with 'ssh-keygen -t ed25519 -C \"you@example.com\"' (or a GPG key with 'gpg --full-generate-key'), then run 'sgit signing setup' again"
+        );
+    }
+    let labels: Vec<&str> = keys.iter().map(|key| key.label.as_str()).collect();
+    let current = get_config_value("user.signingkey");
+    let default = keys
+        .iter()
+        .position(|key| Some(&key.key) == current.as_ref())
+        .unwrap_or(0);
+    let selection = Select::new()
+        .with_prompt("Which key should sign your commits?")
+        .items(&labels)
+        .default(default)
+        .interact()?;
+    let key = &keys[selection];
+
+    let scope = Select::new()
+        .with_prompt("Use it for")
+        .items(&[
+            "All your repositories (global config)",
+            "This repository only",
+        ])
+        .default(0)
+        .interact()?;
+    let scope_flag = if scope == 0 { "--global" } else { "--local" };
+
+    run_git_silent(&["config", scope_flag, "gpg.format", key.kind.format()])?;
+    run_git_silent(&["config", scope_flag, "user.signingkey", &key.key])?;
+    println!("✓ Set gpg.format = {}", key.kind.format());
+    println!("✓ Set user.signingkey = {}", key.key);
+
+    let by_default = Confirm::new()
+        .with_prompt("Sign every commit by default?")
+        .default(true)
+        .interact()?;
+    if by_default {
+        run_git_silent(&["config", scope_flag, "commit.gpgsign", "true"])?;
+        println!("✓ Set commit.gpgsign = true");
+    }
+
+    println!("→ Making a test signature...");
+    verify_test_signature(key)?;
+    println!(
+        "  Remember to add the key to your forge account as a signing key, so it shows your commits as verified."
+    );
+    Ok(())
+}
+
+/// Signs a throwaway commit object with the new settings, then asks git to verify it.
+fn verify_test_signature(key: &SigningKey) -> Result<()> {
+    let output = git_cmd()
+        .args(["commit-tree", "-S", "-m", "sgit signing test", EMPTY_TREE])
+        .output()
+        .context("failed to execute git commit-tree - is git installed?")?;
+    if !output.status.success() {
+        bail!(
+            "git could not sign with this key:\n  {}\n  hint: for GPG, check that 'gpg' can use the key and that GPG_TTY is set (export GPG_TTY=$(tty))",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    println!("✓ Signing works");
+
+    if verifies(&commit) {
+        println!("✓ The test signature verifies");
+        return Ok(());
+    }
+    if key.kind == KeyKind::Gpg {
+        println!("  git could not verify the test signature; check the key's trust with 'gpg --edit-key {}'.", key.key);
+        return Ok(());
+    }
+
+    // SSH signatures only verify against an allowed-signers list.
+    let Some(email) = get_config_value("user.email") else {
+        println!("  To verify SSH signatures locally, add your key with 'sgit signers add <email> --key {}'.", key.key);
+        return Ok(());
+    };
+    let trust = Confirm::new()
+        .with_prompt(format!(
+            "Add your key to the allowed signers as {}, so git can verify your signatures?",
+            email
+        ))
+        .default(true)
+        .interact()?;
+    if trust {
+        add_signer(Some(email), Some(key.key.clone()), None)?;
+        if verifies(&commit) {
+            println!("✓ The test signature verifies");
+        }
+    }
+    Ok(())
+}
+
+fn verifies(commit: &str) -> bool {
+    git_cmd()
+        .args(["verify-commit", commit])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// SSH public keys in `~/.ssh`, then GPG secret keys that can sign.
+fn detect_keys() -> Vec<SigningKey> {
+    let mut keys = Vec::new();
+    if let Some(home) = env::var_os("HOME") {
+        let ssh_dir = Path::new(&home).join(".ssh");
+        let mut paths: Vec<_> = fs::read_dir(&ssh_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "pub"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        for path in paths {
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut fields = contents.split_whitespace();
+            let kind = fields.next().unwrap_or("");
+            fields.next();
+            let comment: Vec<&str> = fields.collect();
+            let shown = format!(
+                "~/.ssh/{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            );
+            keys.push(SigningKey {
+                kind: KeyKind::Ssh,
+                key: path.to_string_lossy().into_owned(),
+                label: format!("SSH  {}  ({} {})", shown, kind, comment.join(" ")),
+            });
+        }
+    }
+
+    let Ok(output) = StdCommand::new("gpg")
+        .args(["--list-secret-keys", "--with-colons"])
+        .output()
+    else {
+        return keys;
+    };
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut pending: Option<String> = None;
+    for line in listing.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields.first().copied() {
+            // Field 12 holds the key's capabilities; `s`/`S` means it can sign.
+            Some("sec") if fields.get(11).is_some_and(|caps| caps.contains(['s', 'S'])) => {
+                pending = fields.get(4).map(|id| id.to_string());
+            }
+            Some("sec") => pending = None,
+            Some("uid") => {
+                if let Some(id) = pending.take() {
+                    let uid = fields.get(9).copied().unwrap_or("");
+                    keys.push(SigningKey {
+                        kind: KeyKind::Gpg,
+                        label: format!("GPG  {}  ({})", id, uid),
+                        key: id,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    keys
+}
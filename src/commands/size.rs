@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::commands::maintenance::count_objects;
+use crate::format::format_size;
+use crate::git::git_output;
+use crate::status::get_git_dir;
+
+/// A blob in the object database, in the order `--batch-check` prints it.
+struct Blob {
+    id: String,
+    size: u64,
+    /// Bytes in the pack, after compression and deltas.
+    disk: u64,
+}
+
+/// Where a blob came into history.
+struct Origin {
+    path: String,
+    commit: String,
+}
+
+pub fn run_size(top: usize) -> Result<()> {
+    if top == 0 {
+        bail!("--top must be at least 1");
+    }
+    let objects = count_objects()?;
+    println!("Repository size");
+    println!(
+        "  History (.git/objects):  {} in {} {}, plus {} in {} loose {}",
+        format_size(objects.pack_size),
+        objects.packs,
+        if objects.packs == 1 { "pack" } else { "packs" },
+        format_size(objects.loose_size),
+        objects.loose,
+        if objects.loose == 1 {
+            "object"
+        } else {
+            "objects"
+        }
+    );
+    let lfs = dir_size(&Path::new(&get_git_dir()?).join("lfs"));
+    if lfs > 0 {
+        println!("  Git LFS files (.git/lfs): {}", format_size(lfs));
+    }
+    let (files, checkout) = head_files()?;
+    println!(
+        "  Current commit:          {} {}, {}",
+        files,
+        if files == 1 { "file" } else { "files" },
+        format_size(checkout)
+    );
+
+    let blobs = largest_blobs(top)?;
+    if blobs.is_empty() {
+        return Ok(());
+    }
+    let in_head = head_blob_ids()?;
+    println!();
+    println!("Largest files in history (size, and packed size)");
+    let mut history_only = false;
+    let mut still_here = false;
+    for blob in &blobs {
+        let origin = origin_of(&blob.id);
+        let sizes = format!(
+            "{:>9}  {:>9}",
+            format_size(blob.size),
+            format_size(blob.disk)
+        );
+        match origin {
+            Some(origin) => {
+                let state = if in_head.contains(&blob.id) {
+                    still_here = true;
+                    "still in the current commit"
+                } else {
+                    history_only = true;
+                    "only in history"
+                };
+                println!("  {}  {}", sizes, origin.path);
+                println!("  {:>20}  added in {} - {}", "", origin.commit, state);
+            }
+            None => println!(
+                "  {}  (in no commit - unreachable, 'sgit maintenance' drops it after two weeks)",
+                sizes
+            ),
+        }
+    }
+    if still_here || history_only {
+        println!();
+    }
+    if still_here {
+        println!(
+            "  Big files still in use belong in Git LFS: 'git lfs migrate import --include=<path>'"
+        );
+        println!("  moves them there (it rewrites history, so agree on it with everyone first).");
+    }
+    if history_only {
+        println!("  Files only in history still take space in every clone; removing them means");
+        println!("  rewriting history, e.g. 'git filter-repo --invert-paths --path <path>'.");
+    }
+    Ok(())
+}
+
+/// The number and total size of the files in HEAD.
+fn head_files() -> Result<(usize, u64)> {
+    let Ok(listing) = git_output(&["ls-tree", "-r", "-l", "HEAD"]) else {
+        return Ok((0, 0));
+    };
+    let mut count = 0;
+    let mut total = 0;
+    for line in listing.lines() {
+        // `<mode> blob <id> <size>\t<path>`; submodules have `-` for a size.
+        let Some((meta, _)) = line.split_once('\t') else {
+            continue;
+        };
+        if let Some(size) = meta
+            .split_whitespace()
+            .nth(3)
+            .and_then(|s| s.parse::<u64>().ok())
+        {
+            count += 1;
+            total += size;
+        }
+    }
+    Ok((count, total))
+}
+
+fn head_blob_ids() -> Result<HashSet<String>> {
+    let listing = git_output(&["ls-tree", "-r", "HEAD"]).unwrap_or_default();
+    Ok(listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(2).map(str::to_string))
+        .collect())
+}
+
+/// The `top` biggest blobs in the object database, reachable or not.
+fn largest_blobs(top: usize) -> Result<Vec<Blob>> {
+    let output = git_output(&[
+        "cat-file",
+        "--batch-all-objects",
+        "--batch-check=%(objecttype) %(objectname) %(objectsize) %(objectsize:disk)",
+    ])?;
+    let mut blobs: Vec<Blob> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != "blob" {
+                return None;
+            }
+            Some(Blob {
+                id: fields.next()?.to_string(),
+                size: fields.next()?.parse().ok()?,
+                disk: fields.next()?.parse().ok()?,
+            })
+        })
+        .collect();
+    blobs.sort_by_key(|blob| std::cmp::Reverse(blob.size));
+    blobs.truncate(top);
+    Ok(blobs)
+}
+
+/// The oldest commit on any branch or tag that added the blob, and its path there.
+fn origin_of(id: &str) -> Option<Origin> {
+    let find = format!("--find-object={}", id);
+    let log = git_output(&[
+        "log",
+        "--all",
+        &find,
+        "--raw",
+        "--no-abbrev",
+        "--format=%x1e%H %s (%an, %ar)",
+    ])
+    .ok()?;
+    // Newest first, so the last record is where it came in.
+    let record = log.split('\x1e').rfind(|r| !r.trim().is_empty())?;
+    let mut lines = record.lines();
+    // `--no-abbrev` is for the ids below, but it reaches `%h` too; shorten by hand.
+    let heading = lines.next()?.trim();
+    let commit = match heading.split_once(' ') {
+        Some((hash, rest)) => format!("{} {}", &hash[..7], rest),
+        None => heading.to_string(),
+    };
+    // `:<old mode> <new mode> <old id> <new id> <status>\t<path>`
+    let path = lines.find_map(|line| {
+        let (meta, path) = line.split_once('\t')?;
+        meta.split_whitespace()
+            .nth(3)
+            .filter(|new| *new == id)
+            .map(|_| path.rsplit('\t').next().unwrap_or(path).to_string())
+    })?;
+    Some(Origin { path, commit })
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{bail, Result};
+use dialoguer::MultiSelect;
+
+use crate::cli::SparseAction;
+use crate::git::{git_output, git_supports, run_git_quiet};
+use crate::status::get_config_value;
+
+pub fn run_sparse(action: Option<SparseAction>) -> Result<()> {
+    if !git_supports(2, 27) {
+        bail!("sparse checkout needs git 2.27 or newer - 'git --version' shows yours");
+    }
+    match action.unwrap_or(SparseAction::Status) {
+        SparseAction::Status => status(),
+        SparseAction::Pick => pick(),
+        SparseAction::Add { paths } => add(&paths),
+        SparseAction::Remove { paths } => remove(&paths),
+        SparseAction::Disable => disable(),
+    }
+}
+
+fn is_enabled() -> bool {
+    get_config_value("core.sparseCheckout").is_some_and(|value| value == "true")
+}
+
+fn is_cone() -> bool {
+    get_config_value("core.sparseCheckoutCone").is_some_and(|value| value == "true")
+}
+
+/// The directories checked out, in cone mode; top-level files always are.
+fn included() -> Result<Vec<String>> {
+    if !is_enabled() {
+        return Ok(Vec::new());
+    }
+    Ok(git_output(&["sparse-checkout", "list"])?
+        .lines()
+        .map(|line| line.trim().trim_matches('/').to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Each top-level directory of HEAD with the number of files in it.
+fn top_level_dirs() -> Result<BTreeMap<String, usize>> {
+    let mut dirs = BTreeMap::new();
+    for path in git_output(&["ls-tree", "-r", "--name-only", "HEAD"])?.lines() {
+        if let Some((dir, _)) = path.split_once('/') {
+            *dirs.entry(dir.to_string()).or_insert(0) += 1;
+        }
+    }
+    Ok(dirs)
+}
+
+fn files(count: usize) -> String {
+    match count {
+        1 => "1 file".to_string(),
+        count => format!("{} files", count),
+    }
+}
+
+fn status() -> Result<()> {
+    if !is_enabled() {
+        println!("Sparse checkout is off - every file of the repository is checked out.");
+        println!("  'sgit sparse pick' checks out only the directories you choose.");
+        return Ok(());
+    }
+    if !is_cone() {
+        println!("Sparse checkout is on, with patterns ('git sparse-checkout list' shows them).");
+        println!("  'sgit sparse pick' switches to picking directories instead.");
+        return Ok(());
+    }
+    let dirs = top_level_dirs()?;
+    let included = included()?;
+    let total: usize = dirs.values().sum();
+    let here: usize = included
+        .iter()
+        .map(|path| count_under(path, &dirs))
+        .sum::<usize>();
+    println!("Sparse checkout is on, with the files at the top of the repository and:");
+    for path in &included {
+        println!("  {}/", path);
+    }
+    if included.is_empty() {
+        println!("  (no directories - only top-level files)");
+    }
+    println!();
+    println!(
+        "About {} of the {} in directories are checked out.",
+        files(here),
+        files(total)
+    );
+    println!("  'sgit sparse add <dir>' and 'sgit sparse remove <dir>' change the set,");
+    println!("  'sgit sparse disable' checks everything out again.");
+    Ok(())
+}
+
+/// Files under `path`, counting a nested path as its whole top-level directory.
+fn count_under(path: &str, dirs: &BTreeMap<String, usize>) -> usize {
+    let top = path.split('/').next().unwrap_or(path);
+    dirs.get(top).copied().unwrap_or(0)
+}
+
+/// Lets the user tick the top-level directories to check out.
+fn pick() -> Result<()> {
+    if !stdin().is_terminal() {
+        bail!("picking needs a terminal - name the directories with 'sgit sparse add <dir>...'");
+    }
+    let dirs = top_level_dirs()?;
+    if dirs.is_empty() {
+        bail!("this repository has no directories to choose from");
+    }
+    let current = if is_enabled() && is_cone() {
+        included()?
+    } else {
+        Vec::new()
+    };
+    let names: Vec<&String> = dirs.keys().collect();
+    let labels: Vec<String> = dirs
+        .iter()
+        .map(|(dir, count)| format!("{}/  ({})", dir, files(*count)))
+        .collect();
+    let defaults: Vec<bool> = names
+        .iter()
+        .map(|name| current.iter().any(|path| path == *name))
+        .collect();
+    let picked = MultiSelect::new()
+        .with_prompt("Directories to check out (space to tick, enter to apply)")
+        .items(&labels)
+        .defaults(&defaults)
+        .interact()?;
+    let chosen: Vec<String> = picked.into_iter().map(|idx| names[idx].clone()).collect();
+    // Nested paths added by hand stay, when their top-level directory was not dropped.
+    let mut keep: Vec<String> = current
+        .into_iter()
+        .filter(|path| path.contains('/'))
+        .filter(|path| {
+            let top = path.split('/').next().unwrap_or_default();
+            chosen.iter().any(|dir| dir == top)
+        })
+        .collect();
+    keep.extend(chosen);
+    apply(&keep)
+}
+
+/// Checks out exactly `paths` (and the top-level files), turning cone mode on if needed.
+fn apply(paths: &[String]) -> Result<()> {
+    let dirs = top_level_dirs()?;
+    let mut paths: Vec<String> = paths.to_vec();
+    paths.sort();
+    paths.dedup();
+    // A path inside one that is already there adds nothing.
+    let nested: Vec<String> = paths
+        .iter()
+        .filter(|path| {
+            paths
+                .iter()
+                .any(|other| path.starts_with(&format!("{}/", other)))
+        })
+        .cloned()
+        .collect();
+    paths.retain(|path| !nested.contains(path));
+
+    println!("→ Updating which files are checked out...");
+    let mut args = vec!["sparse-checkout", "set"];
+    if !git_supports(2, 35) {
+        if !is_enabled() || !is_cone() {
+            run_git_quiet(&["sparse-checkout", "init", "--cone"])?;
+        }
+    } else {
+        args.push("--cone");
+    }
+    args.push("--");
+    args.extend(paths.iter().map(String::as_str));
+    run_git_quiet(&args)?;
+
+    let checked_out: usize = paths.iter().map(|path| count_under(path, &dirs)).sum();
+    let total: usize = dirs.values().sum();
+    match paths.as_slice() {
+        [] => println!("✓ Checked out only the files at the top of the repository"),
+        _ => println!(
+            "✓ Checked out {} (about {} of the {} in directories)",
+            paths
+                .iter()
+                .map(|path| format!("{}/", path))
+                .collect::<Vec<_>>()
+                .join(", "),
+            files(checked_out),
+            files(total)
+        ),
+    }
+    println!("  The rest is still in the repository, just not on disk; history, branches,");
+    println!("  and commits work as before. 'sgit sparse disable' brings it all back.");
+    Ok(())
+}
+
+fn validate(paths: &[String]) -> Result<Vec<String>> {
+    let mut cleaned = Vec::new();
+    for path in paths {
+        let path = path.trim().trim_start_matches("./").trim_matches('/');
+        if path.is_empty() {
+            continue;
+        }
+        let kind = git_output(&["cat-file", "-t", &format!("HEAD:{}", path)]).unwrap_or_default();
+        match kind.trim() {
+            "tree" => cleaned.push(path.to_string()),
+            "" => bail!("'{}' is not a directory in this repository's current commit", path),
+            _ => bail!(
+                "'{}' is a file - sparse checkout picks directories (top-level files are always checked out)",
+                path
+            ),
+        }
+    }
+    Ok(cleaned)
+}
+
+fn add(paths: &[String]) -> Result<()> {
+    let paths = validate(paths)?;
+    let mut all = if is_enabled() && is_cone() {
+        included()?
+    } else {
+        Vec::new()
+    };
+    let new: Vec<&String> = paths.iter().filter(|path| !all.contains(path)).collect();
+    if is_enabled() && new.is_empty() {
+        println!("Already checked out: {}", paths.join(", "));
+        return Ok(());
+    }
+    all.extend(paths.iter().cloned());
+    apply(&all)
+}
+
+fn remove(paths: &[String]) -> Result<()> {
+    if !is_enabled() {
+        bail!("sparse checkout is off, so there is nothing to remove - 'sgit sparse pick' turns it on");
+    }
+    if !is_cone() {
+        bail!(
+            "sparse checkout uses patterns here - 'sgit sparse pick' switches to directories first"
+        );
+    }
+    let current = included()?;
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            path.trim()
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string()
+        })
+        .collect();
+    let missing: Vec<&String> = paths
+        .iter()
+        .filter(|path| !current.contains(path))
+        .collect();
+    if !missing.is_empty() {
+        let missing: Vec<&str> = missing.iter().map(|path| path.as_str()).collect();
+        bail!(
+            "not in the sparse checkout: {}\n  hint: 'sgit sparse' lists what is",
+            missing.join(", ")
+        );
+    }
+    let remaining: Vec<String> = current
+        .into_iter()
+        .filter(|path| !paths.contains(path))
+        .collect();
+    apply(&remaining)
+}
+
+fn disable() -> Result<()> {
+    if !is_enabled() {
+        println!("Sparse checkout is already off - every file is checked out.");
+        return Ok(());
+    }
+    println!("→ Checking out every file again (this can take a while in a big repository)...");
+    run_git_quiet(&["sparse-checkout", "disable"])?;
+    println!("✓ Sparse checkout is off");
+    Ok(())
+}
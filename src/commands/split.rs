@@ -0,0 +1,153 @@
+use anyhow::{bail, Context, Result};
+use dialoguer::{Input, Select};
+
+use crate::commands::file_picker::{select_files, stage_hunks, Preview};
+use crate::commands::history::{ensure_unpushed, prompt_reworded_message};
+use crate::commands::operation::ensure_no_operation;
+use crate::git::{git_cmd, run_git_in_dir_silent, run_git_quiet, run_git_silent};
+use crate::status::{
+    get_commit_message, get_commit_parents, get_commits, get_porcelain_lines, get_repo_root,
+    get_staged_files,
+};
+
+pub fn run_split(force: bool) -> Result<()> {
+    ensure_no_operation("sgit split")?;
+    let Some(commit) = get_commits(&["-n", "1"])?.into_iter().next() else {
+        bail!("this branch has no commits yet");
+    };
+    match get_commit_parents(&commit.hash)?.len() {
+        0 => bail!("the first commit of a repository cannot be split"),
+        1 => {}
+        _ => bail!("merge commits cannot be split"),
+    }
+
+    let has_tracked_changes = get_porcelain_lines()?
+        .iter()
+        .any(|(status, _)| status != "??");
+    if has_tracked_changes {
+        bail!("you have uncommitted changes - commit or stash them before splitting");
+    }
+    let needs_force_push = ensure_unpushed(&[&commit], force, "Splitting")?;
+
+    let repo_root = get_repo_root()?;
+    let files = changed_files(&commit.hash, &repo_root)?;
+    if files.is_empty() {
+        bail!("the last commit has no changes to split");
+    }
+    let original_message = get_commit_message(&commit.hash)?;
+
+    println!("Splitting {}", commit.label());
+    println!(
+        "  If anything goes wrong, 'git reset --hard {}' restores the original commit.",
+        commit.short_hash
+    );
+    run_git_silent(&["reset", "HEAD~1"])?;
+
+    let mut remaining = files;
+    let mut created = 0;
+    while !remaining.is_empty() {
+        println!();
+        println!("{} file(s) left to commit.", remaining.len());
+        let mode = Select::new()
+            .with_prompt(format!("What goes into commit #{}?", created + 1))
+            .items(&[
+                "Pick files",
+                "Pick individual hunks",
+                "Everything that is left",
+            ])
+            .default(0)
+            .interact()?;
+
+        match mode {
+            0 => {
+                let selected = select_files(
+                    "Select files for this commit",
+                    &remaining,
+                    Preview::Unstaged,
+                )?;
+                if selected.is_empty() {
+                    println!("No files selected.");
+                    continue;
+                }
+                let mut args = vec!["add", "--"];
+                args.extend(selected.iter().map(|&idx| remaining[idx].as_str()));
+                run_git_in_dir_silent(&args, &repo_root)?;
+            }
+            1 => stage_hunks(&remaining)?,
+            _ => {
+                let mut args = vec!["add", "--"];
+                args.extend(remaining.iter().map(String::as_str));
+                run_git_in_dir_silent(&args, &repo_root)?;
+            }
+        }
+
+        if get_staged_files()?.is_empty() {
+            println!("Nothing staged for this commit.");
+            continue;
+        }
+
+        // The first commit starts from the original message, body included.
+        let message = if created == 0 {
+            prompt_reworded_message(&original_message)?
+        } else {
+            let subject: String = Input::new()
+                .with_prompt("Commit message")
+                .allow_empty(true)
+                .interact_text()?;
+            Some(subject.trim().to_string()).filter(|s| !s.is_empty())
+        };
+        let Some(message) = message else {
+            eprintln!("⚠ Commit message cannot be empty; the changes stay staged.");
+            continue;
+        };
+        run_git_quiet(&["commit", "-m", message.as_str()])?;
+        created += 1;
+        println!("✓ Commit #{} created", created);
+
+        remaining = still_changed(&remaining, &repo_root)?;
+    }
+
+    println!();
+    println!("✓ Split {} into {} commit(s)", commit.short_hash, created);
+    if needs_force_push {
+        println!("  Run 'git push --force-with-lease' to update the remote.");
+    }
+    Ok(())
+}
+
+fn changed_files(hash: &str, repo_root: &str) -> Result<Vec<String>> {
+    let range = format!("{}~1", hash);
+    let output = git_cmd()
+        .args(["diff", "--name-only", "--no-renames", range.as_str(), hash])
+        .current_dir(repo_root)
+        .output()
+        .context("running git diff --name-only")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Filters `files` down to those that still differ from HEAD (including untracked ones).
+fn still_changed(files: &[String], repo_root: &str) -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["status", "--porcelain", "--no-renames", "-uall", "-z"])
+        .current_dir(repo_root)
+        .output()
+        .context("running git status --porcelain")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let dirty: Vec<&str> = stdout
+        .split('\0')
+        .filter(|entry| entry.len() > 3)
+        .map(|entry| &entry[3..])
+        .collect();
+    Ok(files
+        .iter()
+        .filter(|f| dirty.contains(&f.as_str()))
+        .cloned()
+        .collect())
+}
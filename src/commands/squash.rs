@@ -0,0 +1,129 @@
+use anyhow::{bail, Result};
+use dialoguer::{Confirm, Input, Select};
+
+use crate::commands::history::ensure_unpushed;
+use crate::commands::operation::ensure_no_operation;
+use crate::git::{run_git_quiet, run_git_silent};
+use crate::status::{
+    get_commit_message, get_commits, get_staged_files, resolve_commit, CommitSummary,
+};
+
+const PICKER_COMMIT_LIMIT: usize = 20;
+
+pub fn run_squash(count: Option<usize>, message: Option<String>, force: bool) -> Result<()> {
+    ensure_no_operation("sgit squash")?;
+    if !get_staged_files()?.is_empty() {
+        bail!("you have staged changes - commit or unstage them before squashing");
+    }
+
+    let count = match count {
+        Some(n) => n,
+        None => pick_count()?,
+    };
+    if count < 2 {
+        bail!("need at least 2 commits to squash");
+    }
+
+    let commits = first_parent_commits(count + 1)?;
+    if commits.len() <= count {
+        bail!(
+            "cannot squash {} commits - the branch only has {} and the root commit cannot be squashed",
+            count,
+            commits.len()
+        );
+    }
+    let to_squash = &commits[..count];
+
+    let needs_force_push =
+        ensure_unpushed(&to_squash.iter().collect::<Vec<_>>(), force, "Squashing")?;
+
+    println!("Commits to squash (newest first):");
+    for commit in to_squash {
+        println!("  {}", commit.label());
+    }
+
+    let message = match message {
+        Some(message) => message,
+        None => prompt_message(to_squash.iter().rev().map(|c| c.hash.as_str()))?,
+    };
+    if message.trim().is_empty() {
+        bail!("commit message cannot be empty");
+    }
+
+    let original_head = resolve_commit("HEAD")?;
+    let base = &commits[count].hash;
+    run_git_silent(&["reset", "--soft", base])?;
+    if let Err(err) = run_git_quiet(&["commit", "-m", message.as_str()]) {
+        run_git_silent(&["reset", "--soft", &original_head])?;
+        return Err(err.context("squash commit failed - your original commits were restored"));
+    }
+
+    println!("✓ Squashed {} commits into one", count);
+    if needs_force_push {
+        println!("  Run 'git push --force-with-lease' to update the remote.");
+    }
+    Ok(())
+}
+
+fn pick_count() -> Result<usize> {
+    let commits = first_parent_commits(PICKER_COMMIT_LIMIT)?;
+    if commits.len() < 3 {
+        bail!("not enough commits to squash");
+    }
+    // The last commit in the list is never selectable: it becomes the base.
+    let labels: Vec<String> = commits[..commits.len() - 1]
+        .iter()
+        .map(|c| c.label())
+        .collect();
+    let selection = Select::new()
+        .with_prompt("Squash everything from HEAD down to which commit?")
+        .items(&labels)
+        .default(1)
+        .interact()?;
+    Ok(selection + 1)
+}
+
+/// Squashing follows `HEAD~N`, so merged-in side branches are not listed separately.
+fn first_parent_commits(limit: usize) -> Result<Vec<CommitSummary>> {
+    get_commits(&["--first-parent", "-n", &limit.to_string()])
+}
+
+fn prompt_message<'a>(hashes_oldest_first: impl Iterator<Item = &'a str>) -> Result<String> {
+    let mut messages = Vec::new();
+    for hash in hashes_oldest_first {
+        messages.push(get_commit_message(hash)?);
+    }
+
+    let choice = Select::new()
+        .with_prompt("How should the commit message look?")
+        .items(&["Combine all messages", "Write a new message"])
+        .default(0)
+        .interact()?;
+
+    if choice == 0 {
+        let combined = messages.join("\n\n");
+        println!("Combined message:\n");
+        for line in combined.lines() {
+            println!("  {}", line);
+        }
+        println!();
+        let ok = Confirm::new()
+            .with_prompt("Use this message?")
+            .default(true)
+            .interact()?;
+        if ok {
+            return Ok(combined);
+        }
+    }
+
+    let first_subject = messages
+        .first()
+        .and_then(|m| m.lines().next())
+        .unwrap_or("")
+        .to_string();
+    let message: String = Input::new()
+        .with_prompt("Commit message")
+        .with_initial_text(first_subject)
+        .interact_text()?;
+    Ok(message)
+}
@@ -1,18 +1,45 @@
-use anyhow::Result;
-use dialoguer::{MultiSelect, Select};
+use std::io::{IsTerminal, Read};
 
-use crate::git::run_git_silent;
+use anyhow::{bail, Context, Result};
+use dialoguer::Select;
+
+use crate::choices::{remember_choice, usual_choice};
+use crate::commands::file_picker::{select_files, Preview};
+use crate::git::{run_git_silent, run_git_silent_with_input};
 use crate::status::{get_repo_root, PorcelainStatus};
 
+/// The target that means "read the paths from stdin".
+pub(crate) const STDIN_TARGET: &str = "-";
+
 pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()> {
+    if targets.iter().any(|t| t == STDIN_TARGET) {
+        let Some(paths) = read_stdin_paths(targets)? else {
+            println!("No paths on stdin.");
+            return Ok(());
+        };
+        run_git_silent_with_input(
+            &[
+                "--literal-pathspecs",
+                "add",
+                "--pathspec-from-file=-",
+                "--pathspec-file-nul",
+            ],
+            &paths.input,
+        )?;
+        println!("✓ Staged {} path(s)", paths.count);
+        return Ok(());
+    }
+
     let is_interactive = targets.is_empty() && !all && !tracked;
 
     if is_interactive {
+        let modes = ["All files", "Tracked files only", "Specific files"];
         let selection = Select::new()
             .with_prompt("What would you like to stage?")
-            .items(&["All files", "Tracked files only", "Specific files"])
-            .default(0)
+            .items(&modes)
+            .default(usual_choice("stage.mode", &modes, 0))
             .interact()?;
+        remember_choice("stage.mode", modes[selection]);
 
         match selection {
             0 => {
@@ -33,10 +60,8 @@ pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()>
                     return Ok(());
                 }
                 let files_owned: Vec<String> = files.iter().map(|s| s.to_string()).collect();
-                let selected = MultiSelect::new()
-                    .with_prompt("Select files to stage")
-                    .items(&files_owned)
-                    .interact()?;
+                let selected =
+                    select_files("Select files to stage", &files_owned, Preview::Unstaged)?;
 
                 if selected.is_empty() {
                     println!("No files selected.");
@@ -80,3 +105,46 @@ pub fn stage_targets(targets: &[String], all: bool, tracked: bool) -> Result<()>
         Ok(())
     }
 }
+
+pub(crate) struct StdinPaths {
+    /// NUL-separated paths, ready for `--pathspec-from-file=- --pathspec-file-nul`.
+    pub input: Vec<u8>,
+    pub count: usize,
+}
+
+/// Collects the paths piped to stdin (NUL-separated if the input contains a NUL,
+/// newline-separated otherwise) together with any other targets on the command line.
+/// Returns `None` when there are no paths at all.
+pub(crate) fn read_stdin_paths(targets: &[String]) -> Result<Option<StdinPaths>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        bail!("'-' reads paths from stdin - pipe them in, e.g. 'fd -e rs | sgit stage -'");
+    }
+    let mut raw = Vec::new();
+    stdin
+        .read_to_end(&mut raw)
+        .context("reading paths from stdin")?;
+
+    let nul_separated = raw.contains(&0);
+    let separator = if nul_separated { b'\0' } else { b'\n' };
+    let mut paths: Vec<&[u8]> = targets
+        .iter()
+        .filter(|t| t.as_str() != STDIN_TARGET)
+        .map(|t| t.as_bytes())
+        .collect();
+    paths.extend(
+        raw.split(|&b| b == separator)
+            .map(|p| match p.strip_suffix(b"\r") {
+                Some(trimmed) if !nul_separated => trimmed,
+                _ => p,
+            })
+            .filter(|p| !p.is_empty()),
+    );
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let count = paths.len();
+    let input = paths.join(&0);
+    Ok(Some(StdinPaths { input, count }))
+}
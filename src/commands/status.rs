@@ -0,0 +1,274 @@
+use std::io::{stdout, IsTerminal, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::commands::profile::describe_active_profile;
+use crate::git::{git_cmd, run_git};
+use crate::lfs::{self, LfsStatus};
+use crate::status::{
+    get_ahead_behind, get_commits, get_conflicted_files, get_current_branch, get_detached_head,
+    get_merge_source, get_operation_in_progress, get_porcelain_lines, get_repo_root,
+    get_stash_count, get_upstream, RepoOperation,
+};
+use crate::submodule::submodules_in;
+
+const CONFLICT_CODES: &[&str] = &["DD", "AU", "UD", "UA", "DU", "AA", "UU"];
+const WATCH_POLL: Duration = Duration::from_millis(700);
+
+pub fn run_status(short: bool, raw: bool, watch: bool) -> Result<()> {
+    if watch {
+        return watch_status();
+    }
+    print_repo_state()?;
+    if short {
+        run_git(&["status", "-sb"])
+    } else if raw {
+        run_git(&["status"])
+    } else {
+        print_status()
+    }
+}
+
+fn print_status() -> Result<()> {
+    print_branch_line()?;
+    if let Some(profile) = describe_active_profile() {
+        println!("Profile: {}", profile);
+    }
+    match get_commits(&["-n", "1"])?.first() {
+        Some(last) => println!("Last commit: {}", last.label()),
+        None => println!("No commits yet"),
+    }
+    let stashes = get_stash_count();
+    if stashes > 0 {
+        println!("Stashes: {} (see 'git stash list')", stashes);
+    }
+    if let Some(lfs) = lfs::status() {
+        print_lfs_status(&lfs);
+    }
+
+    // git shows a submodule that moved or has changes inside as one modified "file"; they
+    // get a section of their own that says which it is.
+    let submodules = submodules_in(Path::new(&get_repo_root()?)).unwrap_or_default();
+    let attention: Vec<String> = submodules
+        .iter()
+        .filter_map(|submodule| {
+            let problems = submodule.problems();
+            (!problems.is_empty()).then(|| format!("{}  {}", submodule.path, problems.join("; ")))
+        })
+        .collect();
+
+    let mut conflicts = Vec::new();
+    let mut staged = Vec::new();
+    let mut unstaged = Vec::new();
+    let mut untracked = Vec::new();
+    for (code, path) in get_porcelain_lines()? {
+        if code == "??" {
+            untracked.push(path);
+            continue;
+        }
+        if CONFLICT_CODES.contains(&code.as_str()) {
+            conflicts.push(path);
+            continue;
+        }
+        let mut chars = code.chars();
+        let x = chars.next().unwrap_or(' ');
+        let y = chars.next().unwrap_or(' ');
+        if x != ' ' {
+            staged.push(format!("{:<10} {}", describe_change(x), path));
+        }
+        if y != ' ' && !submodules.iter().any(|submodule| submodule.path == path) {
+            unstaged.push(format!("{:<10} {}", describe_change(y), path));
+        }
+    }
+
+    println!();
+    if conflicts.is_empty()
+        && staged.is_empty()
+        && unstaged.is_empty()
+        && untracked.is_empty()
+        && attention.is_empty()
+    {
+        println!("Working tree clean - nothing to commit.");
+        return Ok(());
+    }
+    print_section("Conflicts", &conflicts, "fix, then 'sgit stage <file>'");
+    print_section("Staged", &staged, "'sgit commit' to commit");
+    print_section("Unstaged", &unstaged, "'sgit stage <file>' to include");
+    print_section(
+        "Untracked",
+        &untracked,
+        "'sgit stage <file>' to start tracking",
+    );
+    print_section(
+        "Submodules",
+        &attention,
+        "'sgit submodule status' for details",
+    );
+    Ok(())
+}
+
+/// Redraws the status view on an alternate screen whenever the repository changes.
+fn watch_status() -> Result<()> {
+    if !stdout().is_terminal() {
+        bail!("--watch needs an interactive terminal");
+    }
+    let running = Arc::new(AtomicBool::new(true));
+    let flag = Arc::clone(&running);
+    ctrlc::set_handler(move || flag.store(false, Ordering::SeqCst))
+        .context("failed to install the Ctrl-C handler")?;
+
+    // Alternate screen + hidden cursor; both are undone below, even after an error.
+    print!("\x1b[?1049h\x1b[?25l");
+    let result = watch_loop(&running);
+    print!("\x1b[?25h\x1b[?1049l");
+    stdout().flush()?;
+    result
+}
+
+fn watch_loop(running: &AtomicBool) -> Result<()> {
+    let mut last_fingerprint = None;
+    while running.load(Ordering::SeqCst) {
+        let fingerprint = status_fingerprint()?;
+        if last_fingerprint.as_ref() != Some(&fingerprint) {
+            print!("\x1b[2J\x1b[H");
+            println!("sgit status --watch - redraws when something changes (Ctrl-C to quit)");
+            println!();
+            print_repo_state()?;
+            print_status()?;
+            stdout().flush()?;
+            last_fingerprint = Some(fingerprint);
+        }
+        thread::sleep(WATCH_POLL);
+    }
+    Ok(())
+}
+
+/// Everything the status view shows, in a form that is cheap to compare between polls.
+fn status_fingerprint() -> Result<String> {
+    let output = git_cmd()
+        .args(["status", "--porcelain", "-b"])
+        .output()
+        .context("running git status --porcelain")?;
+    let head = git_cmd()
+        .args(["rev-parse", "-q", "--verify", "HEAD"])
+        .output()
+        .context("running git rev-parse")?;
+    Ok(format!(
+        "{}{}{}{:?}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&head.stdout),
+        get_stash_count(),
+        get_operation_in_progress()?.map(|op| op.name())
+    ))
+}
+
+fn print_branch_line() -> Result<()> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        let at = get_detached_head().unwrap_or_else(|| "an unborn commit".to_string());
+        println!("Not on a branch (HEAD detached at {})", at);
+        return Ok(());
+    }
+
+    let Some(upstream) = get_upstream() else {
+        println!("On branch {} (not tracking a remote branch)", branch);
+        return Ok(());
+    };
+    let mut line = format!("On branch {} → {}", branch, upstream);
+    match get_ahead_behind(&upstream) {
+        Some((0, 0)) => line.push_str("  ✓ up to date"),
+        Some((ahead, behind)) => {
+            if ahead > 0 {
+                line.push_str(&format!("  ↑{}", ahead));
+            }
+            if behind > 0 {
+                line.push_str(&format!("  ↓{}", behind));
+            }
+        }
+        None => line.push_str("  (upstream is gone)"),
+    }
+    println!("{}", line);
+    Ok(())
+}
+
+fn print_lfs_status(lfs: &LfsStatus) {
+    if !lfs.installed {
+        println!("LFS: this repository uses Git LFS, which is not installed - its large files are checked out as pointers (see https://git-lfs.com)");
+        return;
+    }
+    if lfs.missing > 0 {
+        println!(
+            "LFS: {} file(s), {} not downloaded ('git lfs pull' fetches them)",
+            lfs.files, lfs.missing
+        );
+    } else {
+        println!("LFS: {} file(s)", lfs.files);
+    }
+    if !lfs.locked.is_empty() {
+        println!("Locked by you: {}", lfs.locked.join(", "));
+    }
+}
+
+fn print_section(title: &str, entries: &[String], hint: &str) {
+    if entries.is_empty() {
+        return;
+    }
+    println!("{} ({}) - {}:", title, entries.len(), hint);
+    for entry in entries {
+        println!("  {}", entry);
+    }
+    println!();
+}
+
+fn describe_change(code: char) -> &'static str {
+    match code {
+        'M' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'T' => "type",
+        _ => "changed",
+    }
+}
+
+/// Prints a banner for paused operations and detached HEAD, since these are easy to miss.
+fn print_repo_state() -> Result<()> {
+    if let Some(operation) = get_operation_in_progress()? {
+        let conflicted = get_conflicted_files()?.len();
+        let name = match get_merge_source() {
+            Some(source) if operation == RepoOperation::Merge => format!("Merge of {}", source),
+            _ => capitalize(operation.name()),
+        };
+        if conflicted > 0 {
+            println!(
+                "⚠ {} in progress - {} file(s) with conflicts",
+                name, conflicted
+            );
+        } else {
+            println!("⚠ {} in progress", name);
+        }
+        println!("  Next: {}", operation.next_steps());
+        println!();
+    } else if let Some(hash) = get_detached_head() {
+        println!("⚠ Detached HEAD at {} - you are not on any branch", hash);
+        println!(
+            "  Next: 'sgit branch' to switch back, or 'sgit branch -c <name>' to keep new commits"
+        );
+        println!();
+    }
+    Ok(())
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
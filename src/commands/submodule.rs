@@ -0,0 +1,273 @@
+use std::env;
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use dialoguer::Confirm;
+
+use crate::cli::SubmoduleAction;
+use crate::git::{git_output, run_git_quiet, run_git_silent};
+use crate::status::get_repo_root;
+use crate::submodule::{submodules_in, Submodule, SubmoduleSync};
+
+pub fn run_submodule(action: Option<SubmoduleAction>) -> Result<()> {
+    let root = PathBuf::from(get_repo_root()?);
+    let cwd = env::current_dir().context("failed to read the current directory")?;
+    // Paths below are from the top of the repository, as in .gitmodules.
+    env::set_current_dir(&root).with_context(|| format!("cannot change to {}", root.display()))?;
+    match action.unwrap_or(SubmoduleAction::Status) {
+        SubmoduleAction::Status => status(&root),
+        SubmoduleAction::Add { url, path, branch } => {
+            let path = path.map(|path| from_root(&root, &cwd, &path)).transpose()?;
+            add(&url, path, branch)
+        }
+        SubmoduleAction::Update { force } => update(&root, force),
+        SubmoduleAction::Remove { path, force, yes } => {
+            remove(&root, &from_root(&root, &cwd, &path)?, force, yes)
+        }
+    }
+}
+
+/// `path`, given from `cwd`, as a path from the top of the repository.
+fn from_root(root: &Path, cwd: &Path, path: &str) -> Result<String> {
+    let full = cwd.join(path.trim_end_matches('/'));
+    let Ok(relative) = full.strip_prefix(root) else {
+        bail!("{} is outside this repository", full.display());
+    };
+    // `a/./b` and `a/../b` the way git would read them.
+    let mut parts: Vec<String> = Vec::new();
+    for part in relative.components() {
+        match part.as_os_str().to_string_lossy().as_ref() {
+            "." => {}
+            ".." => {
+                if parts.pop().is_none() {
+                    bail!("{} is outside this repository", path);
+                }
+            }
+            part => parts.push(part.to_string()),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Every submodule under `dir`, nested ones included, with its path from the top.
+fn all_submodules(dir: &Path, prefix: &str) -> Result<Vec<(String, Submodule)>> {
+    let mut all = Vec::new();
+    for submodule in submodules_in(dir)? {
+        let full = format!("{}{}", prefix, submodule.path);
+        let nested = if submodule.checked_out.is_some() {
+            all_submodules(&dir.join(&submodule.path), &format!("{}/", full))?
+        } else {
+            Vec::new()
+        };
+        all.push((full, submodule));
+        all.extend(nested);
+    }
+    Ok(all)
+}
+
+fn short(hash: Option<&String>) -> String {
+    match hash {
+        Some(hash) => hash.chars().take(7).collect(),
+        None => "-------".to_string(),
+    }
+}
+
+fn status(root: &Path) -> Result<()> {
+    let all = all_submodules(root, "")?;
+    if all.is_empty() {
+        println!("This repository has no submodules.");
+        println!("  'sgit submodule add <url>' adds one.");
+        return Ok(());
+    }
+    println!("A submodule is another repository checked out inside this one; this one records");
+    println!("the commit each submodule should be at.");
+    println!();
+    let width = all.iter().map(|(path, _)| path.len()).max().unwrap_or(0);
+    let mut attention = 0;
+    for (path, submodule) in &all {
+        let problems = submodule.problems();
+        let (mark, state) = if problems.is_empty() {
+            ("✓", "in sync".to_string())
+        } else {
+            attention += 1;
+            ("⚠", problems.join("; "))
+        };
+        // The commit it is at, or the one it should be at until it is checked out.
+        let commit = short(
+            submodule
+                .checked_out
+                .as_ref()
+                .or(submodule.recorded.as_ref()),
+        );
+        println!(
+            "{} {:<width$}  {}  {}",
+            mark,
+            path,
+            commit,
+            state,
+            width = width
+        );
+    }
+    println!();
+    match attention {
+        0 if all.len() == 1 => println!("✓ The submodule is in sync"),
+        0 => println!("✓ All {} submodules are in sync", all.len()),
+        count => println!("{} of {} submodules need attention", count, all.len()),
+    }
+    Ok(())
+}
+
+fn add(url: &str, path: Option<String>, branch: Option<String>) -> Result<()> {
+    let mut args = vec!["submodule", "add"];
+    if let Some(ref branch) = branch {
+        args.extend(["--branch", branch.as_str()]);
+    }
+    args.extend(["--", url]);
+    if let Some(ref path) = path {
+        args.push(path);
+    }
+    println!("→ Cloning {} into this repository as a submodule...", url);
+    run_git_quiet(&args)?;
+
+    // git adds the new one at the end of .gitmodules.
+    let added = submodules_in(Path::new("."))?.pop();
+    let (shown, commit) = match &added {
+        Some(submodule) => (
+            submodule.path.clone(),
+            short(submodule.checked_out.as_ref()),
+        ),
+        None => (
+            path.unwrap_or_else(|| "the submodule".to_string()),
+            "its latest commit".to_string(),
+        ),
+    };
+    println!("✓ Added {} at {}", shown, commit);
+    println!("  It is a repository of its own: commit in it as usual, and this repository");
+    println!("  records which of its commits to use. .gitmodules and the submodule are staged;");
+    println!("  'sgit commit' shares them, and others get it with 'sgit submodule update'.");
+    Ok(())
+}
+
+fn update(root: &Path, force: bool) -> Result<()> {
+    let before = all_submodules(root, "")?;
+    if before.is_empty() {
+        println!("This repository has no submodules - nothing to update.");
+        return Ok(());
+    }
+    // Moving these back to the recorded commit would leave their new commits behind.
+    let ahead: Vec<&str> = before
+        .iter()
+        .filter(|(_, submodule)| {
+            matches!(
+                submodule.sync,
+                SubmoduleSync::Ahead | SubmoduleSync::Elsewhere
+            )
+        })
+        .map(|(path, _)| path.as_str())
+        .collect();
+    if !ahead.is_empty() && !force {
+        bail!(
+            "{} {} at a commit this repository does not record, and updating would move {} back\n  hint: 'sgit stage <path>' records the commit first, or pass --force to update anyway",
+            ahead.join(", "),
+            if ahead.len() == 1 { "is" } else { "are" },
+            if ahead.len() == 1 { "it" } else { "them" }
+        );
+    }
+
+    println!("→ Checking out the commit this repository records for each submodule,");
+    println!("  cloning any that are not there yet (nested ones too)...");
+    // Picks up URLs changed in .gitmodules since the submodules were cloned.
+    run_git_silent(&["submodule", "sync", "--recursive", "--quiet"])?;
+    run_git_quiet(&["submodule", "update", "--init", "--recursive"])?;
+
+    let after = all_submodules(root, "")?;
+    let mut changed = 0;
+    for (path, submodule) in &after {
+        let was = before
+            .iter()
+            .find(|(before_path, _)| before_path == path)
+            .and_then(|(_, before)| before.checked_out.clone());
+        match (&was, &submodule.checked_out) {
+            (None, Some(now)) => println!("  + {}  cloned, at {}", path, short(Some(now))),
+            (Some(was), Some(now)) if was != now => println!(
+                "  ~ {}  moved from {} to {}",
+                path,
+                short(Some(was)),
+                short(Some(now))
+            ),
+            _ => continue,
+        }
+        changed += 1;
+    }
+    match changed {
+        0 => println!("✓ All {} submodules were already up to date", after.len()),
+        count => println!("✓ Updated {} of {} submodules", count, after.len()),
+    }
+    let dirty: Vec<&str> = after
+        .iter()
+        .filter(|(_, submodule)| submodule.changes > 0)
+        .map(|(path, _)| path.as_str())
+        .collect();
+    if !dirty.is_empty() {
+        println!(
+            "  Uncommitted changes inside were left alone: {}",
+            dirty.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn remove(root: &Path, path: &str, force: bool, yes: bool) -> Result<()> {
+    let Some(submodule) = submodules_in(root)?
+        .into_iter()
+        .find(|submodule| submodule.path == path)
+    else {
+        bail!(
+            "{} is not a submodule of this repository\n  hint: 'sgit submodule status' lists them",
+            path
+        );
+    };
+    if !force && (submodule.changes > 0 || submodule.untracked > 0) {
+        bail!(
+            "{} has uncommitted or untracked files inside - commit them there first, or pass --force to throw them away",
+            path
+        );
+    }
+    if !yes {
+        if !stdin().is_terminal() {
+            bail!("pass --yes to remove the submodule {} without asking", path);
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Remove the submodule {}? Its files and its clone in .git/modules are deleted.",
+                path
+            ))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Nothing removed.");
+            return Ok(());
+        }
+    }
+
+    // What `git rm` alone leaves: the settings in .git/config and the clone in .git/modules.
+    println!("→ Emptying {} and dropping its settings...", path);
+    run_git_silent(&["submodule", "deinit", "--force", "--", path])?;
+    println!("→ Removing it from .gitmodules and from what this repository records...");
+    run_git_silent(&["rm", "--force", "--quiet", "--", path])?;
+    // Submodule clones are shared by all worktrees.
+    let common_dir = git_output(&["rev-parse", "--git-common-dir"])?;
+    let clone = Path::new(common_dir.trim())
+        .join("modules")
+        .join(&submodule.name);
+    if clone.exists() {
+        println!("→ Deleting its clone in .git/modules...");
+        fs::remove_dir_all(&clone)
+            .with_context(|| format!("failed to delete {}", clone.display()))?;
+    }
+    println!("✓ Removed the submodule {}", path);
+    println!("  The removal is staged; 'sgit commit' records it.");
+    Ok(())
+}
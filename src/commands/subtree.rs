@@ -0,0 +1,306 @@
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use dialoguer::{Input, Select};
+use toml_edit::{Item, Table};
+
+use crate::cli::SubtreeAction;
+use crate::commands::config::{read_document, write_document, Layer};
+use crate::config::{Config, SubtreeConfig, REPO_CONFIG_FILE};
+use crate::git::{git_cmd, git_output, run_git_quiet, run_git_silent};
+use crate::status::{get_all_uncommitted_files, get_repo_root};
+
+pub fn run_subtree(action: Option<SubtreeAction>) -> Result<()> {
+    match action.unwrap_or(SubtreeAction::List) {
+        SubtreeAction::List => list(),
+        SubtreeAction::Add {
+            prefix,
+            remote,
+            branch,
+            full_history,
+        } => add(prefix, remote, branch, !full_history),
+        SubtreeAction::Pull { prefix } => pull(prefix),
+        SubtreeAction::Push { prefix, branch } => push(prefix, branch),
+    }
+}
+
+fn ensure_subtree_installed() -> Result<()> {
+    let output = git_cmd().args(["subtree", "-h"]).output()?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    if !text.contains("git subtree") {
+        bail!("git subtree is not installed - it ships with git, but some packages leave it out (on Debian and Ubuntu it is in the 'git' package's contrib scripts)");
+    }
+    Ok(())
+}
+
+/// git subtree refuses to run with uncommitted changes; say so before it does.
+fn ensure_clean() -> Result<()> {
+    let files = get_all_uncommitted_files()?;
+    if !files.is_empty() {
+        bail!(
+            "commit or stash your {} uncommitted {} first - a subtree pull commits on top of a clean tree",
+            files.len(),
+            if files.len() == 1 { "change" } else { "changes" }
+        );
+    }
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let subtrees = Config::load()?.subtrees;
+    if subtrees.is_empty() {
+        println!("No subtrees are set up in this repository.");
+        println!("  'sgit subtree add' copies another repository into a directory here.");
+        return Ok(());
+    }
+    let width = subtrees.keys().map(String::len).max().unwrap_or(0);
+    for (prefix, subtree) in &subtrees {
+        let pulled = last_pulled(prefix).unwrap_or_else(|| "never pulled".to_string());
+        println!(
+            "  {:<width$}  {} {}{}  ({})",
+            prefix,
+            subtree.remote,
+            subtree.branch,
+            if subtree.squash { "" } else { ", full history" },
+            pulled,
+            width = width
+        );
+    }
+    println!();
+    println!("  'sgit subtree pull' brings in their new commits, 'sgit subtree push' sends yours.");
+    Ok(())
+}
+
+/// When `prefix` last took in commits from its repository, from the trailer git subtree
+/// leaves in the commit it makes.
+fn last_pulled(prefix: &str) -> Option<String> {
+    let grep = format!("^git-subtree-dir: {}/*$", prefix);
+    let date = git_output(&["log", "-1", "--format=%cr", "--grep", &grep])
+        .ok()?
+        .trim()
+        .to_string();
+    (!date.is_empty()).then(|| format!("last pulled {}", date))
+}
+
+fn add(
+    prefix: Option<String>,
+    remote: Option<String>,
+    branch: Option<String>,
+    squash: bool,
+) -> Result<()> {
+    ensure_subtree_installed()?;
+    ensure_clean()?;
+    let interactive = stdin().is_terminal();
+    let remote = match remote {
+        Some(remote) => remote,
+        None if interactive => Input::new()
+            .with_prompt("Repository to copy in (URL or remote name)")
+            .interact_text()?,
+        None => bail!("name the repository: 'sgit subtree add <prefix> <url> [branch]'"),
+    };
+    let prefix = match prefix {
+        Some(prefix) => prefix,
+        None if interactive => Input::new()
+            .with_prompt("Directory to put it in")
+            .default(format!("vendor/{}", repo_name_of(&remote)))
+            .interact_text()?,
+        None => bail!("name the directory: 'sgit subtree add <prefix> <url> [branch]'"),
+    };
+    let prefix = prefix.trim().trim_matches('/').to_string();
+    if prefix.is_empty() {
+        bail!("the directory for the subtree cannot be empty");
+    }
+    if Path::new(&get_repo_root()?).join(&prefix).exists() {
+        bail!(
+            "{} already exists - pick a new directory for the subtree\n  hint: 'sgit subtree pull {}' updates a subtree that is already there",
+            prefix,
+            prefix
+        );
+    }
+    let branch = match branch {
+        Some(branch) => branch,
+        None => {
+            let default = default_branch_of(&remote);
+            match (interactive, default) {
+                (true, default) => Input::new()
+                    .with_prompt("Branch to follow")
+                    .default(default.unwrap_or_else(|| "main".to_string()))
+                    .interact_text()?,
+                (false, Some(default)) => default,
+                (false, None) => bail!(
+                    "cannot tell the default branch of {} - name it: 'sgit subtree add {} {} <branch>'",
+                    remote,
+                    prefix,
+                    remote
+                ),
+            }
+        }
+    };
+
+    println!("→ Copying {} ({}) into {}/...", remote, branch, prefix);
+    let prefix_arg = format!("--prefix={}", prefix);
+    let mut args = vec!["subtree", "add", prefix_arg.as_str()];
+    if squash {
+        args.push("--squash");
+    }
+    args.extend([remote.as_str(), branch.as_str()]);
+    run_git_quiet(&args)?;
+
+    save_subtree(
+        &prefix,
+        &SubtreeConfig {
+            remote: remote.clone(),
+            branch: branch.clone(),
+            squash,
+        },
+    )?;
+    // The tree was clean before, so this commit holds only the new setting.
+    run_git_silent(&["add", "--", REPO_CONFIG_FILE])?;
+    let message = format!("Record where the {} subtree comes from", prefix);
+    run_git_silent(&["commit", "--quiet", "-m", &message, "--", REPO_CONFIG_FILE])?;
+
+    println!("✓ Added {} as a subtree and committed it", prefix);
+    println!("  Its files are ordinary files in this repository, so nobody needs to do anything");
+    println!(
+        "  extra to get them. Where it comes from is committed in {}, so",
+        REPO_CONFIG_FILE
+    );
+    println!("  'sgit subtree pull' and 'sgit subtree push' work without arguments, for everyone.");
+    Ok(())
+}
+
+/// `https://host/team/lib.git` becomes `lib`.
+fn repo_name_of(remote: &str) -> String {
+    let name = remote
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(remote);
+    name.trim_end_matches(".git").to_string()
+}
+
+/// The branch `remote`'s HEAD points at.
+fn default_branch_of(remote: &str) -> Option<String> {
+    let output = git_cmd()
+        .args(["ls-remote", "--symref", remote, "HEAD"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let target = line.strip_prefix("ref: refs/heads/")?;
+            Some(target.split_whitespace().next()?.to_string())
+        })
+}
+
+fn save_subtree(prefix: &str, subtree: &SubtreeConfig) -> Result<()> {
+    let mut document = read_document(Layer::Repo)?;
+    if !document.contains_key("subtrees") {
+        let mut subtrees = Table::new();
+        // Only `[subtrees."vendor/lib"]` headers, without an empty `[subtrees]`.
+        subtrees.set_implicit(true);
+        document["subtrees"] = Item::Table(subtrees);
+    }
+    let mut entry = Table::new();
+    entry["remote"] = toml_edit::value(subtree.remote.as_str());
+    entry["branch"] = toml_edit::value(subtree.branch.as_str());
+    entry["squash"] = toml_edit::value(subtree.squash);
+    document["subtrees"][prefix] = Item::Table(entry);
+    write_document(Layer::Repo, &document)
+}
+
+/// The configured subtree at `prefix`, or the only one, or the one the user picks.
+fn choose_subtree(prefix: Option<String>) -> Result<(String, SubtreeConfig)> {
+    let mut subtrees = Config::load()?.subtrees;
+    if subtrees.is_empty() {
+        bail!(
+            "no subtrees are set up in {}\n  hint: 'sgit subtree add' sets one up",
+            REPO_CONFIG_FILE
+        );
+    }
+    if let Some(prefix) = prefix {
+        let prefix = prefix.trim().trim_matches('/').to_string();
+        return match subtrees.remove(&prefix) {
+            Some(subtree) => Ok((prefix, subtree)),
+            None => bail!(
+                "{} is not a subtree set up in {}\n  hint: 'sgit subtree list' shows them",
+                prefix,
+                REPO_CONFIG_FILE
+            ),
+        };
+    }
+    let prefixes: Vec<String> = subtrees.keys().cloned().collect();
+    let prefix = match prefixes.as_slice() {
+        [only] => only.clone(),
+        _ if !stdin().is_terminal() => {
+            bail!("there are several subtrees - name the directory of one")
+        }
+        _ => {
+            let selection = Select::new()
+                .with_prompt("Which subtree?")
+                .items(&prefixes)
+                .default(0)
+                .interact()?;
+            prefixes[selection].clone()
+        }
+    };
+    match subtrees.remove(&prefix) {
+        Some(subtree) => Ok((prefix, subtree)),
+        None => bail!("{} is not a subtree set up in {}", prefix, REPO_CONFIG_FILE),
+    }
+}
+
+fn pull(prefix: Option<String>) -> Result<()> {
+    ensure_subtree_installed()?;
+    let (prefix, subtree) = choose_subtree(prefix)?;
+    ensure_clean()?;
+    println!(
+        "→ Bringing the new commits of {} ({}) into {}/...",
+        subtree.remote, subtree.branch, prefix
+    );
+    let before = git_output(&["rev-parse", "HEAD"])?;
+    let prefix_arg = format!("--prefix={}", prefix);
+    let mut args = vec!["subtree", "pull", prefix_arg.as_str()];
+    if subtree.squash {
+        args.push("--squash");
+    }
+    args.extend([subtree.remote.as_str(), subtree.branch.as_str()]);
+    run_git_quiet(&args)?;
+    if git_output(&["rev-parse", "HEAD"])? == before {
+        println!("✓ {} was already up to date", prefix);
+    } else {
+        println!("✓ Updated {} and committed the result", prefix);
+        println!("  'sgit push' shares it as usual.");
+    }
+    Ok(())
+}
+
+/// Sends the commits made here that touch `prefix` back to its own repository.
+fn push(prefix: Option<String>, branch: Option<String>) -> Result<()> {
+    ensure_subtree_installed()?;
+    let (prefix, subtree) = choose_subtree(prefix)?;
+    let branch = branch.unwrap_or(subtree.branch);
+    println!(
+        "→ Sending the changes to {}/ back to {} ({})...",
+        prefix, subtree.remote, branch
+    );
+    println!("  This goes through the history of the directory, which can take a while.");
+    let prefix_arg = format!("--prefix={}", prefix);
+    run_git_quiet(&[
+        "subtree",
+        "push",
+        prefix_arg.as_str(),
+        subtree.remote.as_str(),
+        branch.as_str(),
+    ])?;
+    println!(
+        "✓ Pushed the changes to {} to {} ({})",
+        prefix, subtree.remote, branch
+    );
+    Ok(())
+}
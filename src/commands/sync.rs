@@ -1,67 +1,334 @@
+use std::io::{stdin, IsTerminal};
+use std::path::PathBuf;
+
 use anyhow::Result;
+use dialoguer::{Confirm, Select};
+
+use crate::commands::auto_rebase::auto_rebase_onto_default;
+use crate::commands::operation::ensure_no_operation;
+use crate::config::{Config, SyncMode};
+use crate::git::{run_git_quiet, run_git_silent};
+use crate::progress::Phase;
+use crate::remote::{configured_remote, resolve_remote, Direction, ResolvedRemote};
+use crate::snapshot::save_snapshot;
+use crate::status::{
+    count_commits, get_config_value, get_conflicted_files, get_current_branch, get_git_dir,
+    get_operation_in_progress, get_remotes, get_upstream, is_ancestor, resolve_commit,
+    RepoOperation,
+};
+
+/// Upstream name and tip recorded before fetching, used to spot force-pushes.
+struct UpstreamTip {
+    name: String,
+    hash: String,
+}
 
-use crate::git::run_git_quiet;
-use crate::status::get_current_branch;
+enum RewriteOutcome {
+    NotRewritten,
+    Resolved,
+    Skipped,
+}
 
-pub fn run_push(remote: Option<String>, branch: Option<String>) -> Result<()> {
+pub fn run_push(
+    remote: Option<String>,
+    branch: Option<String>,
+    all_remotes: bool,
+    force: bool,
+) -> Result<()> {
     if remote.is_none() && branch.is_some() {
         anyhow::bail!("cannot specify --branch without --remote");
     }
-
-    print!("→ Pushing");
-    if let Some(ref r) = remote {
-        print!(" to {}", r);
+    if let Some(target) = protected_push_target(branch.as_deref())? {
+        anyhow::bail!(
+            "'{}' is a protected branch, so sgit does not push to it directly\n  hint: push a branch of your own and open a review, or use 'sgit merge-queue' if your team lands changes that way",
+            target
+        );
     }
-    if let Some(ref b) = branch {
-        print!("/{}", b);
+    if remote.is_none() && (all_remotes || Config::load()?.sync.push_all_remotes) {
+        let remotes = get_remotes()?;
+        // With a single remote (or none, which the usual push explains) there is nothing
+        // to mirror.
+        if remotes.len() > 1 {
+            return push_to_all(&remotes);
+        }
     }
-    println!("...");
 
-    let mut args_owned = vec!["push".to_string()];
-    if let Some(remote) = remote {
-        args_owned.push(remote);
-        if let Some(branch) = branch {
-            args_owned.push(branch);
+    let mut args_owned = match remote {
+        Some(remote) => {
+            print!("→ Pushing to {}", remote);
+            if let Some(ref b) = branch {
+                print!("/{}", b);
+            }
+            println!("...");
+            let mut args = vec!["push".to_string(), remote];
+            args.extend(branch);
+            args
+        }
+        None => {
+            let resolved = resolve_remote(Direction::Push)?;
+            println!("→ Pushing to {}...", resolved.describe());
+            push_args(&resolved)
         }
+    };
+    if force {
+        args_owned.insert(1, "--force-with-lease".to_string());
     }
 
     let args_refs: Vec<&str> = args_owned.iter().map(String::as_str).collect();
-    run_git_quiet(&args_refs)?;
+    let phase = Phase::start("push", "push");
+    if let Err(e) = run_git_quiet(&args_refs) {
+        phase.failed(&e.to_string());
+        return Err(e);
+    }
+    phase.done();
     println!("✓ Pushed successfully");
     Ok(())
 }
 
-pub fn run_pull(remote: Option<String>, branch: Option<String>) -> Result<()> {
-    print!("→ Pulling");
-    if let Some(ref r) = remote {
-        print!(" from {}", r);
+/// Pushes the current branch to each of `remotes`, carrying on past failures and
+/// reporting each remote's outcome.
+fn push_to_all(remotes: &[String]) -> Result<()> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        anyhow::bail!("not on a branch (detached HEAD) - check out the branch to push first");
+    }
+    println!("→ Pushing {} to {} remotes...", branch, remotes.len());
+    let refspec = format!("HEAD:refs/heads/{}", branch);
+    let mut failed = Vec::new();
+    for remote in remotes {
+        let phase = Phase::start("push", "push");
+        match run_git_quiet(&["push", remote, &refspec]) {
+            Ok(()) => {
+                phase.done();
+                println!("  ✓ {}", remote);
+            }
+            Err(e) => {
+                phase.failed(&e.to_string());
+                println!("  ✗ {}", remote);
+                for line in e.to_string().lines().filter(|line| !line.trim().is_empty()) {
+                    println!("      {}", line.trim());
+                }
+                failed.push(remote.as_str());
+            }
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "pushed to {} of {} remotes; {} failed",
+            remotes.len() - failed.len(),
+            remotes.len(),
+            failed.join(", ")
+        );
+    }
+    println!("✓ Pushed to every remote");
+    Ok(())
+}
+
+/// The branch a push would update, if the sgit config protects it: the `--branch`
+/// (its destination, for a `src:dst` refspec), else the current branch.
+fn protected_push_target(branch: Option<&str>) -> Result<Option<String>> {
+    let target = match branch {
+        Some(branch) => branch.rsplit(':').next().unwrap_or(branch).to_string(),
+        None => get_current_branch()?,
+    };
+    let target = target
+        .strip_prefix("refs/heads/")
+        .unwrap_or(&target)
+        .to_string();
+    let protected = !target.is_empty() && Config::load()?.branches.is_protected(&target);
+    Ok(protected.then_some(target))
+}
+
+/// `--rebase` or `--no-rebase` when the sgit config sets `[sync] mode`.
+fn sync_mode_flag() -> Result<Option<&'static str>> {
+    Ok(Config::load()?.sync.mode.map(|mode| match mode {
+        SyncMode::Rebase => "--rebase",
+        SyncMode::Merge => "--no-rebase",
+    }))
+}
+
+/// Plain `git push` when git knows the remote; otherwise push to the chosen remote and
+/// make it the branch's upstream so next time it does.
+fn push_args(resolved: &ResolvedRemote) -> Vec<String> {
+    if resolved.configured_by.is_some() {
+        return vec!["push".to_string()];
     }
-    if let Some(ref b) = branch {
-        print!("/{}", b);
+    println!("  (setting it as the upstream of the current branch)");
+    ["push", "-u", &resolved.name, "HEAD"]
+        .map(str::to_string)
+        .to_vec()
+}
+
+/// Plain `git pull` when git knows the remote; otherwise name the remote and the
+/// current branch, since git cannot guess the branch either.
+fn pull_args(resolved: &ResolvedRemote) -> Result<Vec<String>> {
+    if resolved.configured_by.is_some() {
+        return Ok(vec!["pull".to_string()]);
+    }
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        anyhow::bail!("not on a branch (detached HEAD) - pass --remote and --branch to pull");
+    }
+    Ok(vec!["pull".to_string(), resolved.name.clone(), branch])
+}
+
+/// Whether a pull rebases: `--rebase` or `--merge` first, then `[sync] mode`, then git's
+/// `branch.<name>.rebase` and `pull.rebase`. The flag to pass along is `None` when it is
+/// left to git.
+fn pull_mode(rebase: bool, merge: bool) -> Result<(bool, Option<&'static str>)> {
+    let chosen = if rebase {
+        Some(SyncMode::Rebase)
+    } else if merge {
+        Some(SyncMode::Merge)
+    } else {
+        Config::load()?.sync.mode
+    };
+    Ok(match chosen {
+        Some(SyncMode::Rebase) => (true, Some("--rebase")),
+        Some(SyncMode::Merge) => (false, Some("--no-rebase")),
+        None => {
+            let branch = get_current_branch().unwrap_or_default();
+            let configured = get_config_value(&format!("branch.{}.rebase", branch))
+                .filter(|_| !branch.is_empty())
+                .or_else(|| get_config_value("pull.rebase"));
+            let rebases = configured.is_some_and(|value| {
+                !matches!(value.to_lowercase().as_str(), "false" | "no" | "off" | "0")
+            });
+            (rebases, None)
+        }
+    })
+}
+
+/// Explains how to carry on when a pull stopped on conflicts; `false` when it did not.
+fn report_stopped_pull() -> Result<bool> {
+    let conflicted = get_conflicted_files()?;
+    let operation = get_operation_in_progress()?;
+    if conflicted.is_empty() && operation.is_none() {
+        return Ok(false);
+    }
+    if operation == Some(RepoOperation::Rebase) {
+        eprintln!(
+            "✗ The rebase stopped: {} file(s) conflict while replaying your commits on top of the remote ones",
+            conflicted.len()
+        );
+        for file in &conflicted {
+            eprintln!("  {}", file);
+        }
+        eprintln!("  1. Run 'sgit conflicts' to resolve them");
+        eprintln!("  2. Run 'sgit continue' to replay the rest of your commits");
+        eprintln!("  Or run 'sgit abort' to go back to before the pull.");
+        let git_dir = PathBuf::from(get_git_dir()?);
+        if ["rebase-merge", "rebase-apply"]
+            .iter()
+            .any(|dir| git_dir.join(dir).join("autostash").exists())
+        {
+            eprintln!(
+                "  Your uncommitted changes were set aside and come back once the rebase is done."
+            );
+        }
+    } else {
+        eprintln!("✗ Pull failed due to merge conflicts");
+        for file in &conflicted {
+            eprintln!("  {}", file);
+        }
+        eprintln!("  1. Run 'sgit conflicts' to resolve them");
+        eprintln!("  2. Run 'sgit continue' to complete the merge");
+        eprintln!("  Or run 'sgit merge --abort' to go back to before the pull.");
+    }
+    Ok(true)
+}
+
+pub fn run_pull(
+    remote: Option<String>,
+    branch: Option<String>,
+    rebase: bool,
+    merge: bool,
+) -> Result<()> {
+    ensure_no_operation("sgit pull")?;
+    let (rebases, mode_flag) = pull_mode(rebase, merge)?;
+    if remote.is_none()
+        && let Some(before) = record_upstream_tip()
+    {
+        println!("→ Fetching {}...", before.name);
+        let phase = Phase::start("pull", "fetch");
+        if let Err(e) = run_git_quiet(&["fetch"]) {
+            phase.failed(&e.to_string());
+            return Err(e);
+        }
+        phase.done();
+        match handle_upstream_rewrite(Some(before))? {
+            RewriteOutcome::NotRewritten => {}
+            RewriteOutcome::Resolved => return Ok(()),
+            RewriteOutcome::Skipped => {
+                println!("Nothing pulled. Run 'sgit pull' again when you have decided.");
+                return Ok(());
+            }
+        }
     }
-    println!("...");
 
-    let mut args_owned = vec!["pull".to_string()];
-    if let Some(remote) = remote {
-        args_owned.push(remote);
-        if let Some(branch) = branch {
-            args_owned.push(branch);
+    let mut args_owned = match remote {
+        Some(remote) => {
+            print!("→ Pulling from {}", remote);
+            if let Some(ref b) = branch {
+                print!("/{}", b);
+            }
+            println!("...");
+            let mut args = vec!["pull".to_string(), remote];
+            args.extend(branch);
+            args
+        }
+        None => {
+            let resolved = resolve_remote(Direction::Fetch)?;
+            println!("→ Pulling from {}...", resolved.describe());
+            pull_args(&resolved)?
         }
+    };
+    if rebases {
+        println!("  (rebasing your commits on top of the remote ones)");
+        // A rebase refuses to start with uncommitted changes; set them aside instead.
+        args_owned.insert(1, "--autostash".to_string());
+    }
+    if let Some(flag) = mode_flag {
+        args_owned.insert(1, flag.to_string());
     }
 
     let args_refs: Vec<&str> = args_owned.iter().map(String::as_str).collect();
-    run_git_quiet(&args_refs)?;
+    let phase = Phase::start("pull", "pull");
+    if let Err(e) = run_git_quiet(&args_refs) {
+        phase.failed(&e.to_string());
+        if report_stopped_pull()? {
+            anyhow::bail!("the pull stopped on conflicts - see the steps above");
+        }
+        return Err(e);
+    }
+    phase.done();
     println!("✓ Pulled successfully");
     Ok(())
 }
 
 pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
-    let remote_name = remote.unwrap_or("origin");
+    ensure_no_operation("sgit sync")?;
+    // The remote to fetch from, and the one to push to (which `remote.pushDefault`
+    // or `branch.<name>.pushRemote` may point elsewhere).
+    let (remote_name, fetch_remote, push_remote) = match remote {
+        Some(remote) => (remote.to_string(), None, None),
+        None => {
+            let fetch = resolve_remote(Direction::Fetch)?;
+            let push = configured_remote(Direction::Push).unwrap_or_else(|| fetch.clone());
+            (fetch.name.clone(), Some(fetch), Some(push))
+        }
+    };
+    let upstream_before = record_upstream_tip();
 
-    println!("→ Fetching from {}...", remote_name);
-    let fetch_result = run_git_quiet(&["fetch", remote_name]);
+    match fetch_remote {
+        Some(ref resolved) => println!("→ Fetching from {}...", resolved.describe()),
+        None => println!("→ Fetching from {}...", remote_name),
+    }
+    let phase = Phase::start("sync", "fetch");
+    let fetch_result = run_git_quiet(&["fetch", &remote_name]);
     if let Err(e) = fetch_result {
         let err_str = e.to_string();
+        phase.failed(&err_str);
         if err_str.contains("could not resolve host") || err_str.contains("network") {
             eprintln!("✗ Network error: cannot reach '{}'", remote_name);
             return Err(e);
@@ -69,34 +336,43 @@ pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
         eprintln!("⚠ Fetch failed: {}", e);
         eprintln!("  Continuing with local state...");
     } else {
+        phase.done();
         println!("✓ Fetch complete");
+        match handle_upstream_rewrite(upstream_before)? {
+            RewriteOutcome::NotRewritten => {}
+            RewriteOutcome::Resolved => {
+                println!("  Run 'sgit push' when you are ready to publish your commits.");
+                return Ok(());
+            }
+            RewriteOutcome::Skipped => {
+                println!(
+                    "Sync stopped before pulling so nothing is merged into the rewritten history."
+                );
+                return Ok(());
+            }
+        }
     }
 
     println!("→ Pulling changes...");
-    let mut pull_args = vec!["pull"];
-    let mut pull_owned: Vec<String> = Vec::new();
-    if let Some(r) = remote {
-        pull_owned.push(r.to_string());
-        if let Some(b) = branch {
-            pull_owned.push(b.to_string());
+    let mut pull_owned = match fetch_remote {
+        Some(ref resolved) => pull_args(resolved)?,
+        None => {
+            let mut args = vec!["pull".to_string(), remote_name.to_string()];
+            args.extend(branch.map(str::to_string));
+            args
         }
-    }
-    let pull_refs: Vec<&str> = if pull_owned.is_empty() {
-        pull_args
-    } else {
-        pull_args.extend(pull_owned.iter().map(String::as_str));
-        pull_args
     };
+    if let Some(flag) = sync_mode_flag()? {
+        pull_owned.insert(1, flag.to_string());
+    }
+    let pull_refs: Vec<&str> = pull_owned.iter().map(String::as_str).collect();
 
+    let phase = Phase::start("sync", "pull");
     let pull_result = run_git_quiet(&pull_refs);
     if let Err(e) = pull_result {
         let err_str = e.to_string();
-        if err_str.contains("CONFLICT") || err_str.contains("merge conflict") {
-            eprintln!("✗ Pull failed due to merge conflicts");
-            eprintln!("  Resolve conflicts manually:");
-            eprintln!("    1. Edit conflicting files (marked with <<<<<<<)");
-            eprintln!("    2. Run 'sgit stage .' to stage resolved files");
-            eprintln!("    3. Run 'sgit commit' to complete the merge");
+        phase.failed(&err_str);
+        if report_stopped_pull()? {
             return Err(e);
         }
         if err_str.contains("no tracking information") {
@@ -111,28 +387,46 @@ pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
         eprintln!("⚠ Pull failed: {}", e);
         eprintln!("  Attempting to push local changes anyway...");
     } else {
+        phase.done();
         println!("✓ Pull complete");
     }
 
-    println!("→ Pushing changes...");
-    let mut push_args = vec!["push"];
-    let mut push_owned: Vec<String> = Vec::new();
-    if let Some(r) = remote {
-        push_owned.push(r.to_string());
-        if let Some(b) = branch {
-            push_owned.push(b.to_string());
-        }
+    let rebased = auto_rebase_onto_default(&remote_name)?;
+
+    if let Some(target) = protected_push_target(branch)? {
+        println!(
+            "✓ Sync complete: fetched and pulled. '{}' is protected, so nothing was pushed.",
+            target
+        );
+        return Ok(());
     }
-    let push_refs: Vec<&str> = if push_owned.is_empty() {
-        push_args
-    } else {
-        push_args.extend(push_owned.iter().map(String::as_str));
-        push_args
+
+    let mut push_owned = match push_remote {
+        Some(ref resolved) => {
+            println!("→ Pushing changes to {}...", resolved.describe());
+            push_args(resolved)
+        }
+        None => {
+            println!("→ Pushing changes...");
+            let mut args = vec!["push".to_string(), remote_name.to_string()];
+            args.extend(branch.map(str::to_string));
+            args
+        }
     };
+    if rebased && rewrote_pushed_commits() {
+        if !confirm_force_push()? {
+            println!("Nothing pushed. The rebased branch is only on this machine for now.");
+            return Ok(());
+        }
+        push_owned.insert(1, "--force-with-lease".to_string());
+    }
+    let push_refs: Vec<&str> = push_owned.iter().map(String::as_str).collect();
 
+    let phase = Phase::start("sync", "push");
     let push_result = run_git_quiet(&push_refs);
     if let Err(e) = push_result {
         let err_str = e.to_string();
+        phase.failed(&err_str);
         if err_str.contains("rejected") {
             eprintln!("✗ Push rejected: remote has new commits");
             eprintln!("  Run 'sgit pull' first to integrate remote changes.");
@@ -148,7 +442,120 @@ pub fn run_sync(remote: Option<&str>, branch: Option<&str>) -> Result<()> {
         }
         return Err(e);
     }
+    phase.done();
 
     println!("✓ Sync complete: fetched, pulled, and pushed successfully.");
     Ok(())
 }
+
+fn record_upstream_tip() -> Option<UpstreamTip> {
+    let name = get_upstream()?;
+    let hash = resolve_commit(&name).ok()?;
+    Some(UpstreamTip { name, hash })
+}
+
+/// Whether the upstream has commits the current branch no longer contains, as after a
+/// rebase of a branch that was already pushed.
+fn rewrote_pushed_commits() -> bool {
+    get_upstream().is_some_and(|upstream| !is_ancestor(&upstream, "HEAD"))
+}
+
+/// Asks before replacing the remote branch with rebased history. Without a terminal
+/// nobody can answer, so it refuses.
+fn confirm_force_push() -> Result<bool> {
+    let branch = get_current_branch()?;
+    eprintln!(
+        "⚠ '{}' was rebased, so pushing it replaces the version on the remote.",
+        branch
+    );
+    if !stdin().is_terminal() {
+        anyhow::bail!(
+            "not force-pushing '{}' without asking\n  hint: run 'sgit push --force' when you are sure",
+            branch
+        );
+    }
+    Ok(Confirm::new()
+        .with_prompt("Force-push it (with --force-with-lease)?")
+        .default(false)
+        .interact()?)
+}
+
+/// Detects whether the upstream was force-pushed since `before` was recorded and,
+/// if so, walks the user through recovering their local commits.
+fn handle_upstream_rewrite(before: Option<UpstreamTip>) -> Result<RewriteOutcome> {
+    let Some(before) = before else {
+        return Ok(RewriteOutcome::NotRewritten);
+    };
+    let Ok(after) = resolve_commit(&before.name) else {
+        return Ok(RewriteOutcome::NotRewritten);
+    };
+    if after == before.hash || is_ancestor(&before.hash, &after) {
+        return Ok(RewriteOutcome::NotRewritten);
+    }
+
+    let local = count_commits(&format!("{}..HEAD", before.hash))?;
+    let short = |h: &str| h[..h.len().min(7)].to_string();
+
+    eprintln!(
+        "⚠ '{}' was rewritten on the remote (someone force-pushed).",
+        before.name
+    );
+    eprintln!(
+        "  The version you had ({}) is no longer part of it; it now points at {}.",
+        short(&before.hash),
+        short(&after)
+    );
+    if local > 0 {
+        eprintln!(
+            "  You have {} commit(s) of your own on top of the old version.",
+            local
+        );
+    } else {
+        eprintln!("  You have no commits of your own on top of it.");
+    }
+    eprintln!("  Pulling normally would merge the old and new history together.");
+    if !stdin().is_terminal() {
+        eprintln!("  hint: run it again in a terminal to choose how to recover your commits.");
+        return Ok(RewriteOutcome::Skipped);
+    }
+
+    let rebase_label = format!("Move my {} commit(s) onto the new '{}'", local, before.name);
+    let reset_label = format!(
+        "Reset my branch to the new '{}' (a snapshot of my current state is saved first)",
+        before.name
+    );
+    let items = [
+        rebase_label.as_str(),
+        reset_label.as_str(),
+        "Do nothing for now",
+    ];
+    let selection = Select::new()
+        .with_prompt("How would you like to continue?")
+        .items(&items)
+        .default(if local > 0 { 0 } else { 1 })
+        .interact()?;
+
+    match selection {
+        0 => {
+            println!("→ Rebasing your commits onto {}...", before.name);
+            run_git_quiet(&["rebase", "--autostash", "--onto", &after, &before.hash])?;
+            println!(
+                "✓ Your commits now sit on top of the rewritten '{}'",
+                before.name
+            );
+            Ok(RewriteOutcome::Resolved)
+        }
+        1 => {
+            let snapshot = save_snapshot(&format!("before reset to rewritten {}", before.name))?;
+            run_git_silent(&["reset", "--hard", &after])?;
+            println!("✓ Branch reset to the new '{}'", before.name);
+            println!("  Your previous state is saved as {}", snapshot);
+            println!(
+                "  Restore it with 'git reset --hard {}' if needed.",
+                snapshot
+            );
+            Ok(RewriteOutcome::Resolved)
+        }
+        _ => Ok(RewriteOutcome::Skipped),
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{bail, Context, Result};
+
+use crate::git::git_cmd;
+use crate::status::{count_commits, get_current_branch};
+
+struct ReflogEntry {
+    hash: String,
+    timestamp: u64,
+    time_label: String,
+    action: String,
+}
+
+pub fn run_timeline(branch: Option<String>, since: Option<String>) -> Result<()> {
+    let branch = match branch {
+        Some(branch) => branch,
+        None => {
+            let current = get_current_branch()?;
+            if current.is_empty() {
+                bail!("HEAD is detached - pass --branch to pick a branch");
+            }
+            current
+        }
+    };
+    let (since_arg, period) = match since {
+        Some(since) => (since.clone(), format!("since {}", since)),
+        None => ("midnight".to_string(), "today".to_string()),
+    };
+    let cutoff = parse_since(&since_arg)?;
+    // Only show the time of day when everything happened today.
+    let date_format = if period == "today" {
+        "%H:%M"
+    } else {
+        "%Y-%m-%d %H:%M"
+    };
+
+    // Newest first, as git prints it.
+    let entries = read_reflog(&branch, date_format)?;
+    let in_window = entries.iter().take_while(|e| e.timestamp >= cutoff).count();
+    if in_window == 0 {
+        println!("No recorded movement of '{}' {}.", branch, period);
+        return Ok(());
+    }
+
+    println!("Timeline of '{}' {}:", branch, period);
+    let mut added_total = 0;
+    let mut dropped_total = 0;
+    for idx in (0..in_window).rev() {
+        let entry = &entries[idx];
+        let previous = entries.get(idx + 1).map(|e| e.hash.as_str());
+        let (added, dropped) = match previous {
+            Some(prev) if prev != entry.hash => (
+                count_commits(&format!("{}..{}", prev, entry.hash)).unwrap_or(0),
+                count_commits(&format!("{}..{}", entry.hash, prev)).unwrap_or(0),
+            ),
+            Some(_) => (0, 0),
+            None => (count_commits(&entry.hash).unwrap_or(0), 0),
+        };
+        added_total += added;
+        dropped_total += dropped;
+
+        let mut line = format!(
+            "  {}  {}  {}",
+            entry.time_label,
+            &entry.hash[..7.min(entry.hash.len())],
+            describe(&entry.action)
+        );
+        match (added, dropped) {
+            (0, 0) => {}
+            (a, 0) => line.push_str(&format!(" (+{})", a)),
+            (0, d) => line.push_str(&format!(" (-{})", d)),
+            (a, d) => line.push_str(&format!(" (+{} / -{})", a, d)),
+        }
+        println!("{}", line);
+    }
+
+    println!();
+    println!(
+        "{} move(s): {} commit(s) added, {} dropped from the branch.",
+        in_window, added_total, dropped_total
+    );
+    if dropped_total > 0 {
+        println!("  Dropped commits are still reachable by the hashes above, e.g. 'git branch rescue <hash>'.");
+    }
+    Ok(())
+}
+
+/// Turns git's reflog subject into a sentence a human would say.
+fn describe(action: &str) -> String {
+    let (kind, detail) = action.split_once(": ").unwrap_or((action, ""));
+    match kind {
+        "commit" | "commit (initial)" => format!("committed \"{}\"", detail),
+        "commit (amend)" => format!("amended the last commit → \"{}\"", detail),
+        "commit (merge)" => format!("finished a merge: \"{}\"", detail),
+        "reset" => format!("reset ({})", detail.trim_start_matches("moving ")),
+        "branch" => format!("branch created ({})", detail.to_lowercase()),
+        "cherry-pick" => format!("cherry-picked \"{}\"", detail),
+        "revert" => format!("reverted: \"{}\"", detail),
+        "update by push" => "updated by a push".to_string(),
+        k if k.starts_with("rebase") => format!("rebased ({})", detail),
+        k if k.starts_with("pull") => format!("pulled ({})", detail.to_lowercase()),
+        k if k.starts_with("merge ") => format!(
+            "merged {} ({})",
+            k.trim_start_matches("merge "),
+            detail.to_lowercase()
+        ),
+        _ => action.to_string(),
+    }
+}
+
+/// Lets git interpret `since` (e.g. "2 hours ago", "yesterday", a date) as a unix timestamp.
+fn parse_since(since: &str) -> Result<u64> {
+    let arg = format!("--since={}", since);
+    let output = git_cmd()
+        .args(["rev-parse", arg.as_str()])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .strip_prefix("--max-age=")
+        .and_then(|ts| ts.parse().ok())
+        .with_context(|| format!("could not understand the time '{}'", since))
+}
+
+fn read_reflog(branch: &str, date_format: &str) -> Result<Vec<ReflogEntry>> {
+    let refname = format!("refs/heads/{}", branch);
+    let date_arg = format!("--date=format:%s {}", date_format);
+    let output = git_cmd()
+        .args([
+            "reflog",
+            "show",
+            date_arg.as_str(),
+            "--format=%H%x1f%gd%x1f%gs",
+            refname.as_str(),
+        ])
+        .output()
+        .context("running git reflog")?;
+
+    if !output.status.success() {
+        bail!("no reflog found for branch '{}'", branch);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\x1f');
+            let hash = parts.next()?.to_string();
+            // The selector looks like `main@{<unix> <formatted>}`.
+            let selector = parts.next()?;
+            let date = selector.split_once("@{")?.1.strip_suffix('}')?;
+            let (timestamp, time_label) = date.split_once(' ')?;
+            Some(ReflogEntry {
+                hash,
+                timestamp: timestamp.parse().ok()?,
+                time_label: time_label.to_string(),
+                action: parts.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
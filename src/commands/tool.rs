@@ -0,0 +1,212 @@
+use std::env;
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::git::{git_cmd, run_git_silent};
+use crate::status::get_config_value;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ToolKind {
+    Diff,
+    Merge,
+}
+
+impl ToolKind {
+    fn git_command(self) -> &'static str {
+        match self {
+            ToolKind::Diff => "difftool",
+            ToolKind::Merge => "mergetool",
+        }
+    }
+
+    fn config_key(self) -> &'static str {
+        match self {
+            ToolKind::Diff => "diff.tool",
+            ToolKind::Merge => "merge.tool",
+        }
+    }
+}
+
+/// A tool sgit knows how to find on PATH.
+struct KnownTool {
+    /// The name git knows it by.
+    name: &'static str,
+    binary: &'static str,
+    label: &'static str,
+    /// Commands for tools git has no built-in support for.
+    diff_cmd: Option<&'static str>,
+    merge_cmd: Option<&'static str>,
+}
+
+const KNOWN_TOOLS: &[KnownTool] = &[
+    KnownTool {
+        name: "vscode",
+        binary: "code",
+        label: "VS Code",
+        diff_cmd: Some("code --wait --diff \"$LOCAL\" \"$REMOTE\""),
+        merge_cmd: Some("code --wait --merge \"$REMOTE\" \"$LOCAL\" \"$BASE\" \"$MERGED\""),
+    },
+    KnownTool {
+        name: "meld",
+        binary: "meld",
+        label: "Meld",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "kdiff3",
+        binary: "kdiff3",
+        label: "KDiff3",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "smerge",
+        binary: "smerge",
+        label: "Sublime Merge",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "p4merge",
+        binary: "p4merge",
+        label: "P4Merge",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "opendiff",
+        binary: "opendiff",
+        label: "FileMerge",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "nvimdiff",
+        binary: "nvim",
+        label: "Neovim",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+    KnownTool {
+        name: "vimdiff",
+        binary: "vim",
+        label: "Vim",
+        diff_cmd: None,
+        merge_cmd: None,
+    },
+];
+
+/// Runs `git difftool`/`git mergetool` with `args`, using the configured tool or one
+/// the user picks from those installed.
+pub(crate) fn launch_tool(kind: ToolKind, args: &[&str]) -> Result<()> {
+    let mut git_args: Vec<String> = Vec::new();
+    if !has_configured_tool(kind) {
+        let tool = pick_tool(kind)?;
+        if let Some(cmd) = tool_cmd(tool, kind) {
+            git_args.push("-c".to_string());
+            git_args.push(format!("{}.{}.cmd={}", kind.git_command(), tool.name, cmd));
+        }
+        git_args.push(kind.git_command().to_string());
+        git_args.push(format!("--tool={}", tool.name));
+        offer_to_remember(kind, tool)?;
+    } else {
+        git_args.push(kind.git_command().to_string());
+    }
+    git_args.push("--no-prompt".to_string());
+    git_args.extend(args.iter().map(|arg| arg.to_string()));
+
+    // The tool needs the terminal (or opens a window), so nothing is captured here.
+    let status = git_cmd()
+        .args(&git_args)
+        .status()
+        .with_context(|| format!("failed to execute git {}", kind.git_command()))?;
+    if !status.success() {
+        bail!("git {} exited with an error", kind.git_command());
+    }
+    Ok(())
+}
+
+/// git falls back from `diff.tool` to `merge.tool`, so either counts for diffs.
+fn has_configured_tool(kind: ToolKind) -> bool {
+    get_config_value(kind.config_key()).is_some()
+        || (kind == ToolKind::Diff && get_config_value("merge.tool").is_some())
+}
+
+fn pick_tool(kind: ToolKind) -> Result<&'static KnownTool> {
+    let installed: Vec<&KnownTool> = KNOWN_TOOLS
+        .iter()
+        .filter(|tool| on_path(tool.binary))
+        .collect();
+    match installed.as_slice() {
+        [] => bail!(
+            "no {} is configured and none of the usual ones (VS Code, Meld, KDiff3, Vim, ...) is installed\n  hint: set one with 'git config --global {} <tool>'",
+            kind.git_command(),
+            kind.config_key()
+        ),
+        _ if !stdin().is_terminal() => bail!(
+            "no {} is configured\n  hint: set one with 'git config --global {} {}'",
+            kind.config_key(),
+            kind.config_key(),
+            installed[0].name
+        ),
+        [only] => Ok(only),
+        _ => {
+            let labels: Vec<&str> = installed.iter().map(|tool| tool.label).collect();
+            let selection = Select::new()
+                .with_prompt(format!("No {} is configured - which one?", kind.config_key()))
+                .items(&labels)
+                .default(0)
+                .interact()?;
+            Ok(installed[selection])
+        }
+    }
+}
+
+fn offer_to_remember(kind: ToolKind, tool: &KnownTool) -> Result<()> {
+    if !stdin().is_terminal() {
+        return Ok(());
+    }
+    let remember = Confirm::new()
+        .with_prompt(format!(
+            "Use {} as your {} from now on (saved in your global git config)?",
+            tool.label,
+            kind.git_command()
+        ))
+        .default(true)
+        .interact()?;
+    if !remember {
+        return Ok(());
+    }
+    run_git_silent(&["config", "--global", kind.config_key(), tool.name])?;
+    if let Some(cmd) = tool_cmd(tool, kind) {
+        let key = format!("{}.{}.cmd", kind.git_command(), tool.name);
+        run_git_silent(&["config", "--global", &key, cmd])?;
+    }
+    if kind == ToolKind::Merge {
+        // Otherwise git leaves a `.orig` copy next to every resolved file.
+        run_git_silent(&["config", "--global", "mergetool.keepBackup", "false"])?;
+    }
+    println!("✓ Saved {} = {}", kind.config_key(), tool.name);
+    Ok(())
+}
+
+fn tool_cmd(tool: &KnownTool, kind: ToolKind) -> Option<&'static str> {
+    match kind {
+        ToolKind::Diff => tool.diff_cmd,
+        ToolKind::Merge => tool.merge_cmd,
+    }
+}
+
+fn on_path(binary: &str) -> bool {
+    let Some(paths) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&paths).any(|dir| {
+        let candidate = dir.join(binary);
+        candidate.is_file() || Path::new(&format!("{}.exe", candidate.display())).is_file()
+    })
+}
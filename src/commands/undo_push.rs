@@ -0,0 +1,253 @@
+use anyhow::{bail, Context, Result};
+use dialoguer::{Confirm, Select};
+
+use crate::commands::operation::ensure_no_operation;
+use crate::git::{git_cmd, run_git_quiet, run_git_silent};
+use crate::journal::{self, JournalEntry};
+use crate::status::{
+    get_commits, get_config_value, get_current_branch, get_porcelain_lines, is_ancestor,
+    resolve_commit, CommitSummary,
+};
+
+const PICKER_COMMIT_LIMIT: usize = 20;
+
+pub fn run_undo_push() -> Result<()> {
+    ensure_no_operation("sgit undo-push")?;
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        bail!("HEAD is detached - switch to the branch you pushed first");
+    }
+    let (remote, remote_branch) = upstream_of(&branch)?;
+    let tracking = format!("{}/{}", remote, remote_branch);
+
+    println!("→ Checking {}...", tracking);
+    run_git_quiet(&["fetch", remote.as_str(), remote_branch.as_str()])?;
+    let pushed_tip = resolve_commit(&tracking)?;
+
+    // The pushed commits, newest first; the user picks the last good one among them.
+    let history = get_commits(&["-n", &PICKER_COMMIT_LIMIT.to_string(), pushed_tip.as_str()])?;
+    if history.len() < 2 {
+        bail!("{} has no earlier commit to go back to", tracking);
+    }
+    let labels: Vec<String> = history.iter().skip(1).map(|c| c.label()).collect();
+    let good_idx = Select::new()
+        .with_prompt(format!(
+            "Which commit on {} was the last good one? Everything after it is undone.",
+            tracking
+        ))
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    let good = &history[good_idx + 1];
+    let bad = &history[..=good_idx];
+
+    println!("These pushed commits will be undone:");
+    for commit in bad {
+        println!("  {}", commit.label());
+    }
+    print_sharing_hint(&tracking, &pushed_tip)?;
+
+    let choice = Select::new()
+        .with_prompt("How should they be undone?")
+        .items(&[
+            "Revert: add commit(s) that undo them and push (safe if others may have pulled)",
+            "Roll back: move the remote branch back with --force-with-lease (rewrites history)",
+            "Cancel",
+        ])
+        .default(0)
+        .interact()?;
+
+    match choice {
+        0 => revert_and_push(&remote, &remote_branch, &pushed_tip, good, bad),
+        1 => roll_back(&remote, &remote_branch, &pushed_tip, good),
+        _ => {
+            println!("Nothing changed.");
+            Ok(())
+        }
+    }
+}
+
+fn upstream_of(branch: &str) -> Result<(String, String)> {
+    let remote = get_config_value(&format!("branch.{}.remote", branch));
+    let merge = get_config_value(&format!("branch.{}.merge", branch));
+    match (remote, merge) {
+        (Some(remote), Some(merge)) if remote != "." => {
+            let name = merge
+                .strip_prefix("refs/heads/")
+                .unwrap_or(&merge)
+                .to_string();
+            Ok((remote, name))
+        }
+        _ => bail!(
+            "'{}' has no upstream on a remote - there is no push to undo",
+            branch
+        ),
+    }
+}
+
+/// Tells the user whether teammates are likely to have the bad commits already.
+fn print_sharing_hint(tracking: &str, pushed_tip: &str) -> Result<()> {
+    let output = git_cmd()
+        .args(["branch", "-r", "--contains", pushed_tip])
+        .output()
+        .context("running git branch --contains")?;
+    let others: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty() && l != tracking && !l.contains(" -> "))
+        .collect();
+    if others.is_empty() {
+        println!("  No other remote branch contains these commits yet.");
+        println!("  If nobody has pulled since your push, rolling back is fine; otherwise prefer reverting.");
+    } else {
+        println!(
+            "  ⚠ Other remote branches already build on them ({}); prefer reverting.",
+            others.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn revert_and_push(
+    remote: &str,
+    remote_branch: &str,
+    pushed_tip: &str,
+    good: &CommitSummary,
+    bad: &[CommitSummary],
+) -> Result<()> {
+    let tracking = format!("{}/{}", remote, remote_branch);
+    let head = resolve_commit("HEAD")?;
+    if !is_ancestor(pushed_tip, &head) {
+        bail!(
+            "your branch does not contain {} - run 'sgit pull' first, then try again",
+            tracking
+        );
+    }
+    if get_porcelain_lines()?.iter().any(|(code, _)| code != "??") {
+        bail!("you have uncommitted changes - commit or stash them before reverting");
+    }
+
+    let range = format!("{}..{}", good.hash, pushed_tip);
+    println!("→ Reverting {} commit(s)...", bad.len());
+    run_git_quiet(&["revert", "--no-edit", range.as_str()])?;
+    let reverted_head = resolve_commit("HEAD")?;
+
+    println!("→ Pushing the revert(s)...");
+    let refspec = format!("HEAD:refs/heads/{}", remote_branch);
+    run_git_quiet(&["push", remote, refspec.as_str()])?;
+    verify_remote(remote, remote_branch, &reverted_head)?;
+
+    journal::record(
+        &JournalEntry::new(
+            "undo-push",
+            format!(
+                "reverted {} pushed commit(s) on {} back to the state of {}",
+                bad.len(),
+                tracking,
+                good.short_hash
+            ),
+        )
+        .with_undo(format!("git revert --no-edit {}..{}", head, reverted_head)),
+    )?;
+    println!(
+        "✓ The bad commits are undone on {}; history was not rewritten.",
+        tracking
+    );
+    Ok(())
+}
+
+fn roll_back(
+    remote: &str,
+    remote_branch: &str,
+    pushed_tip: &str,
+    good: &CommitSummary,
+) -> Result<()> {
+    let tracking = format!("{}/{}", remote, remote_branch);
+    let confirmed = Confirm::new()
+        .with_prompt(format!(
+            "Force {} back to {}? Anyone who already pulled will have to repair their branch.",
+            tracking, good.short_hash
+        ))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    let refspec = format!("{}:refs/heads/{}", good.hash, remote_branch);
+    let lease = format!(
+        "--force-with-lease=refs/heads/{}:{}",
+        remote_branch, pushed_tip
+    );
+    println!("→ Moving {} back to {}...", tracking, good.short_hash);
+    run_git_quiet(&["push", lease.as_str(), remote, refspec.as_str()])?;
+    verify_remote(remote, remote_branch, &good.hash)?;
+
+    journal::record(
+        &JournalEntry::new(
+            "undo-push",
+            format!(
+                "rolled {} back from {} to {}",
+                tracking,
+                &pushed_tip[..7],
+                good.short_hash
+            ),
+        )
+        .with_undo(format!(
+            "git push --force-with-lease={}:{} {} {}:refs/heads/{}",
+            remote_branch, good.hash, remote, pushed_tip, remote_branch
+        )),
+    )?;
+    println!("✓ {} now points at {}", tracking, good.label());
+
+    let head = resolve_commit("HEAD")?;
+    if head != good.hash && is_ancestor(&good.hash, &head) {
+        let keep = Select::new()
+            .with_prompt("Your local branch still has the undone commits. What now?")
+            .items(&[
+                "Keep them locally so I can fix them and push again",
+                "Move my local branch back too (uncommitted changes are kept)",
+            ])
+            .default(0)
+            .interact()?;
+        if keep == 1 {
+            run_git_silent(&["reset", "--keep", good.hash.as_str()])?;
+            journal::record(
+                &JournalEntry::new(
+                    "undo-push",
+                    format!(
+                        "moved the local branch back from {} to {}",
+                        &head[..7],
+                        good.short_hash
+                    ),
+                )
+                .with_undo(format!("git reset --keep {}", head)),
+            )?;
+            println!(
+                "✓ Local branch moved back; the old tip {} is in the sgit journal",
+                &head[..7]
+            );
+        } else {
+            println!("  Don't run a plain 'sgit push' until they are fixed - it would publish them again.");
+        }
+    }
+    Ok(())
+}
+
+/// Re-fetches the remote branch and checks it landed where we expect.
+fn verify_remote(remote: &str, remote_branch: &str, expected: &str) -> Result<()> {
+    let tracking = format!("{}/{}", remote, remote_branch);
+    run_git_silent(&["fetch", remote, remote_branch])?;
+    let actual = resolve_commit(&tracking)?;
+    if actual != expected {
+        bail!(
+            "{} is at {} after the push, not {} as expected - check 'sgit timeline' and the remote",
+            tracking,
+            &actual[..7],
+            &expected[..7]
+        );
+    }
+    println!("✓ Verified {} on the remote", tracking);
+    Ok(())
+}
@@ -1,10 +1,31 @@
 use anyhow::Result;
-use dialoguer::{MultiSelect, Select};
+use dialoguer::Select;
 
-use crate::git::run_git_silent;
+use crate::commands::file_picker::{select_files, Preview};
+use crate::commands::stage::{read_stdin_paths, STDIN_TARGET};
+use crate::git::{run_git_silent, run_git_silent_with_input};
 use crate::status::{get_repo_root, get_staged_files};
 
 pub fn restore_stage(targets: &[String], all: bool) -> Result<()> {
+    if targets.iter().any(|t| t == STDIN_TARGET) {
+        let Some(paths) = read_stdin_paths(targets)? else {
+            println!("No paths on stdin.");
+            return Ok(());
+        };
+        run_git_silent_with_input(
+            &[
+                "--literal-pathspecs",
+                "restore",
+                "--staged",
+                "--pathspec-from-file=-",
+                "--pathspec-file-nul",
+            ],
+            &paths.input,
+        )?;
+        println!("✓ Unstaged {} path(s)", paths.count);
+        return Ok(());
+    }
+
     let is_interactive = targets.is_empty() && !all;
 
     if is_interactive {
@@ -26,10 +47,7 @@ pub fn restore_stage(targets: &[String], all: bool) -> Result<()> {
                     println!("No staged files to unstage.");
                     return Ok(());
                 }
-                let selected = MultiSelect::new()
-                    .with_prompt("Select files to unstage")
-                    .items(&files)
-                    .interact()?;
+                let selected = select_files("Select files to unstage", &files, Preview::Staged)?;
 
                 if selected.is_empty() {
                     println!("No files selected.");
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cli::ExitCode;
+use crate::git::git_output;
+use crate::status::resolve_commit;
+
+/// The working tree differs from the ref.
+const EXIT_MISMATCH: i32 = 1;
+/// The check itself could not run, e.g. the ref does not exist.
+const EXIT_ERROR: i32 = 2;
+
+#[derive(Serialize, Default)]
+struct Report {
+    #[serde(rename = "ref")]
+    target: String,
+    commit: String,
+    matches: bool,
+    /// Tracked files whose contents differ from the ref.
+    modified: Vec<String>,
+    /// Files in the ref that are gone from the working tree.
+    missing: Vec<String>,
+    /// Files in the working tree that the ref does not have.
+    extra: Vec<String>,
+    /// Files that became a symlink, submodule, or regular file.
+    type_changed: Vec<String>,
+}
+
+impl Report {
+    fn categories(&self) -> [(&'static str, &Vec<String>); 4] {
+        [
+            ("Modified", &self.modified),
+            ("Missing", &self.missing),
+            ("Extra", &self.extra),
+            ("Type changed", &self.type_changed),
+        ]
+    }
+}
+
+/// Checks that the working tree is exactly `target`. Exits with 0 when it is,
+/// 1 when it differs, and 2 when the check could not run.
+pub fn run_verify_tree(target: Option<String>, json: bool) -> Result<()> {
+    let target = target.unwrap_or_else(|| "HEAD".to_string());
+    let report = match build_report(&target) {
+        Ok(report) => report,
+        Err(err) => {
+            for cause in err.chain() {
+                eprintln!("error: {}", cause);
+            }
+            return Err(ExitCode(EXIT_ERROR).into());
+        }
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).context("failed to encode report")?
+        );
+    } else if report.matches {
+        println!(
+            "✓ Working tree matches {} ({}) exactly",
+            report.target,
+            &report.commit[..7.min(report.commit.len())]
+        );
+    } else {
+        println!(
+            "✗ Working tree does not match {} ({}):",
+            report.target,
+            &report.commit[..7.min(report.commit.len())]
+        );
+        for (label, files) in report.categories() {
+            if files.is_empty() {
+                continue;
+            }
+            println!();
+            println!("{} ({}):", label, files.len());
+            for file in files {
+                println!("  {}", file);
+            }
+        }
+    }
+
+    if report.matches {
+        Ok(())
+    } else {
+        Err(ExitCode(EXIT_MISMATCH).into())
+    }
+}
+
+fn build_report(target: &str) -> Result<Report> {
+    let commit = resolve_commit(target)?;
+    let mut report = Report {
+        target: target.to_string(),
+        commit: commit.clone(),
+        ..Report::default()
+    };
+
+    // Ref against the working tree, for every path git tracks on either side.
+    let diff = git_output(&[
+        "diff",
+        "--no-renames",
+        "--no-ext-diff",
+        "--ignore-submodules=none",
+        "--name-status",
+        "-z",
+        &commit,
+        "--",
+    ])?;
+    let mut fields = diff.split('\0').filter(|field| !field.is_empty());
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let path = path.to_string();
+        match status.chars().next() {
+            Some('M') => report.modified.push(path),
+            Some('D') => report.missing.push(path),
+            Some('A') => report.extra.push(path),
+            Some('T') => report.type_changed.push(path),
+            _ => report.modified.push(path),
+        }
+    }
+
+    // `:/` and `--full-name` cover the whole repository even from a subdirectory.
+    let untracked = git_output(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--full-name",
+        "-z",
+        "--",
+        ":/",
+    ])?;
+    report.extra.extend(
+        untracked
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string),
+    );
+    report.extra.sort();
+    report.extra.dedup();
+
+    report.matches = report
+        .categories()
+        .iter()
+        .all(|(_, files)| files.is_empty());
+    Ok(report)
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::{git_cmd, git_output, run_git_silent};
+use crate::snapshot::working_tree_tree;
+use crate::status::{get_current_branch, get_git_dir, is_ancestor, resolve_commit};
+
+/// The commits `sgit watch` makes go on `sgit/watch/<branch>`, next to the branch itself.
+const WATCH_BRANCH_PREFIX: &str = "sgit/watch/";
+
+/// How often the working tree is looked at.
+const WATCH_POLL: Duration = Duration::from_secs(1);
+
+/// Without `--interval`, changes are saved once they have stayed the same this long, so a
+/// burst of saves from the editor becomes one commit.
+const SETTLE: Duration = Duration::from_secs(2);
+
+pub fn run_watch(interval: Option<u64>) -> Result<()> {
+    let interval = match interval {
+        Some(0) => bail!("--interval must be at least 1 second"),
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => None,
+    };
+    let index = Path::new(&get_git_dir()?).join("sgit-watch-index");
+    let running = Arc::new(AtomicBool::new(true));
+    let flag = Arc::clone(&running);
+    ctrlc::set_handler(move || flag.store(false, Ordering::SeqCst))
+        .context("failed to install the Ctrl-C handler")?;
+
+    let first_branch = watch_branch()?;
+    match interval {
+        Some(interval) => println!(
+            "Watching the working tree - every {}s, changes are committed to {} (Ctrl-C to stop)",
+            interval.as_secs(),
+            first_branch
+        ),
+        None => println!(
+            "Watching the working tree - changes are committed to {} as you save (Ctrl-C to stop)",
+            first_branch
+        ),
+    }
+    println!("Your branch, staged changes, and files are left as they are.");
+    println!();
+
+    let result = watch_loop(&running, &index, interval);
+    let _ = fs::remove_file(&index);
+    let saved = result?;
+
+    println!();
+    match saved.as_slice() {
+        [] => println!("Stopped - nothing changed, so nothing was saved."),
+        _ => {
+            println!("Stopped after saving {} times.", saved.len());
+            // The user may have switched branches while watching.
+            let mut branches = saved.clone();
+            branches.sort();
+            branches.dedup();
+            for branch in branches {
+                println!("  'git log --stat {}' lists what was saved", branch);
+            }
+            println!("  'git restore --source <commit> -- <path>' brings a file back");
+        }
+    }
+    Ok(())
+}
+
+/// Polls until Ctrl-C, returning the watch branch of each commit made.
+fn watch_loop(
+    running: &AtomicBool,
+    index: &Path,
+    interval: Option<Duration>,
+) -> Result<Vec<String>> {
+    let mut saved = Vec::new();
+    // A changed tree, and since when it has looked like this.
+    let mut pending: Option<(String, Instant)> = None;
+    let mut last_save = Instant::now();
+    while running.load(Ordering::SeqCst) {
+        let branch = watch_branch()?;
+        let tree = working_tree_tree(index)?;
+        let base = tree_of(&format!("refs/heads/{}", branch)).or_else(|| tree_of("HEAD"));
+        if base.as_deref() == Some(tree.as_str()) {
+            pending = None;
+        } else {
+            let due = match (interval, &pending) {
+                (Some(interval), _) => last_save.elapsed() >= interval,
+                (None, Some((pending_tree, since))) => {
+                    *pending_tree == tree && since.elapsed() >= SETTLE
+                }
+                (None, None) => false,
+            };
+            if due {
+                save(&branch, &tree, base.as_deref())?;
+                saved.push(branch);
+                pending = None;
+                last_save = Instant::now();
+            } else if pending
+                .as_ref()
+                .is_none_or(|(pending_tree, _)| *pending_tree != tree)
+            {
+                pending = Some((tree, Instant::now()));
+            }
+        }
+        // Short sleeps, so Ctrl-C stops promptly.
+        let started = Instant::now();
+        while started.elapsed() < WATCH_POLL && running.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+    Ok(saved)
+}
+
+fn watch_branch() -> Result<String> {
+    let branch = get_current_branch()?;
+    let branch = if branch.is_empty() {
+        "detached".to_string()
+    } else {
+        branch
+    };
+    Ok(format!("{}{}", WATCH_BRANCH_PREFIX, branch))
+}
+
+fn tree_of(rev: &str) -> Option<String> {
+    let output = git_cmd()
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{tree}}", rev),
+        ])
+        .output()
+        .ok()?;
+    let tree = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !tree.is_empty()).then_some(tree)
+}
+
+/// Commits `tree` on the watch branch. Once HEAD moves on (a real commit, a pull), it
+/// becomes a second parent, so the branch keeps every earlier save and also follows HEAD.
+fn save(branch: &str, tree: &str, base: Option<&str>) -> Result<()> {
+    let watch_ref = format!("refs/heads/{}", branch);
+    let tip = resolve_commit(&watch_ref).ok();
+    let head = resolve_commit("HEAD").ok();
+    let parents: Vec<&str> = match (&tip, &head) {
+        (Some(tip), Some(head)) if is_ancestor(head, tip) => vec![tip],
+        (Some(tip), Some(head)) => vec![head, tip],
+        (Some(only), None) | (None, Some(only)) => vec![only],
+        (None, None) => Vec::new(),
+    };
+
+    let changed: Vec<String> = match base {
+        Some(base) => git_output(&["diff-tree", "-r", "--name-only", base, tree])?
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        None => Vec::new(),
+    };
+    let mut message = "WIP: saved by sgit watch\n\n".to_string();
+    for path in &changed {
+        message.push_str(&format!("- {}\n", path));
+    }
+
+    let mut args = vec!["commit-tree", tree, "-m", message.as_str()];
+    for parent in &parents {
+        args.push("-p");
+        args.push(parent);
+    }
+    let commit = git_output(&args)?.trim().to_string();
+    let mut update = vec![
+        "update-ref",
+        "-m",
+        "sgit watch",
+        watch_ref.as_str(),
+        &commit,
+    ];
+    if let Some(ref tip) = tip {
+        update.push(tip);
+    }
+    run_git_silent(&update)?;
+
+    let time = git_output(&[
+        "log",
+        "-1",
+        "--format=%cd",
+        "--date=format:%H:%M:%S",
+        &commit,
+    ])?;
+    let shown: Vec<&str> = changed.iter().take(3).map(String::as_str).collect();
+    let more = if changed.len() > 3 {
+        format!(" and {} more", changed.len() - 3)
+    } else {
+        String::new()
+    };
+    println!(
+        "  {}  saved {} {}: {}{}",
+        time.trim(),
+        changed.len(),
+        if changed.len() == 1 { "file" } else { "files" },
+        shown.join(", "),
+        more
+    );
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use anyhow::{bail, Result};
+
+use crate::cli::WipAction;
+use crate::git::{run_git_quiet, run_git_silent};
+use crate::remote::{resolve_remote, Direction};
+use crate::snapshot::create_worktree_commit;
+use crate::status::{
+    get_all_uncommitted_files, get_commit_message, get_commit_parents, get_config_value,
+    get_current_branch, get_operation_in_progress, get_upstream, is_ancestor,
+};
+
+pub fn run_wip(action: Option<WipAction>, message: Option<String>) -> Result<()> {
+    match action {
+        None => wip_commit(message),
+        Some(WipAction::Push { remote, message }) => wip_push(remote, message),
+        Some(WipAction::Clean { remote }) => wip_clean(remote),
+    }
+}
+
+/// Commits everything, untracked files included, as a WIP commit `sgit unwip` can take
+/// apart again.
+fn wip_commit(message: Option<String>) -> Result<()> {
+    if let Some(operation) = get_operation_in_progress()? {
+        bail!(
+            "a {} is in progress - finish it before parking your work",
+            operation.name()
+        );
+    }
+    let files = get_all_uncommitted_files()?;
+    if files.is_empty() {
+        bail!("nothing to park - the working tree is clean");
+    }
+    let branch = get_current_branch()?;
+    let message = message
+        .map(|message| format!("WIP: {}", message))
+        .unwrap_or_else(|| generated_message(&branch, &files));
+
+    run_git_silent(&["add", "--all"])?;
+    // Hooks check finished work; a quick save should not have to pass them.
+    run_git_silent(&["commit", "--quiet", "--no-verify", "-m", &message])?;
+    println!(
+        "✓ Parked {} {} in a WIP commit: {}",
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" },
+        message
+    );
+    println!("  'sgit unwip' puts the changes back when you return; 'sgit wip push' shares");
+    println!("  your work with others without committing.");
+    Ok(())
+}
+
+/// `WIP on main: src/app.rs, README.md and 3 more`.
+fn generated_message(branch: &str, files: &[String]) -> String {
+    let shown: Vec<&str> = files.iter().take(3).map(String::as_str).collect();
+    let more = if files.len() > 3 {
+        format!(" and {} more", files.len() - 3)
+    } else {
+        String::new()
+    };
+    match branch {
+        "" => format!("WIP: {}{}", shown.join(", "), more),
+        branch => format!("WIP on {}: {}{}", branch, shown.join(", "), more),
+    }
+}
+
+/// Undoes the last commit if it is a WIP commit, keeping its changes staged.
+pub fn run_unwip() -> Result<()> {
+    let message = get_commit_message("HEAD")?;
+    let subject = message.lines().next().unwrap_or_default();
+    // Only the subjects `sgit wip` writes, so a "Wipe stale cache" commit is left alone.
+    if !subject.starts_with("WIP: ") && !subject.starts_with("WIP on ") {
+        bail!(
+            "the last commit is not a WIP commit: {}\n  hint: 'git reset --soft HEAD~1' takes back an ordinary commit",
+            subject
+        );
+    }
+    let parents = get_commit_parents("HEAD")?;
+    let [parent] = parents.as_slice() else {
+        bail!("the WIP commit has no single parent to go back to - undo it with git directly");
+    };
+    if let Some(upstream) = get_upstream()
+        && is_ancestor("HEAD", &upstream)
+    {
+        bail!(
+            "the WIP commit is already on {} - unwipping it would rewrite pushed history\n  hint: 'sgit undo-push' takes it back on the remote first",
+            upstream
+        );
+    }
+    run_git_silent(&["reset", "--soft", parent])?;
+    println!("✓ Unparked: {}", subject);
+    println!(
+        "  Its changes are back in the working tree, staged ('sgit unstage' to unstage them)."
+    );
+    Ok(())
+}
+
+fn wip_push(remote: Option<String>, message: Option<String>) -> Result<()> {
+    let remote = match remote {
+        Some(remote) => remote,
+        None => resolve_remote(Direction::Push)?.name,
+    };
+    let branch = current_branch_or_bail()?;
+    let wip_ref = wip_ref_name(&branch);
+
+    let message = message.unwrap_or_else(|| format!("WIP on {}", branch));
+    println!("→ Snapshotting your working tree...");
+    let commit = create_worktree_commit(&message)?;
+
+    println!("→ Pushing to {}/{}...", remote, wip_ref);
+    let refspec = format!("+{}:refs/heads/{}", commit, wip_ref);
+    run_git_quiet(&["push", remote.as_str(), refspec.as_str()])?;
+
+    println!("✓ Shared your work as {}/{}", remote, wip_ref);
+    println!(
+        "  Your branch '{}' and working tree were not changed.",
+        branch
+    );
+    println!();
+    println!("  A teammate can look at it with:");
+    println!("    git fetch {} {}", remote, wip_ref);
+    println!("    git switch --detach FETCH_HEAD");
+    println!();
+    println!("  Remove it later with 'sgit wip clean'.");
+    Ok(())
+}
+
+fn wip_clean(remote: Option<String>) -> Result<()> {
+    let remote = match remote {
+        Some(remote) => remote,
+        None => resolve_remote(Direction::Push)?.name,
+    };
+    let branch = current_branch_or_bail()?;
+    let wip_ref = wip_ref_name(&branch);
+
+    println!("→ Deleting {}/{}...", remote, wip_ref);
+    run_git_quiet(&["push", remote.as_str(), "--delete", wip_ref.as_str()])?;
+    println!("✓ Removed the wip ref for '{}'", branch);
+    Ok(())
+}
+
+fn current_branch_or_bail() -> Result<String> {
+    let branch = get_current_branch()?;
+    if branch.is_empty() {
+        bail!("not on a branch (detached HEAD) - switch to a branch first");
+    }
+    Ok(branch)
+}
+
+fn wip_ref_name(branch: &str) -> String {
+    format!("wip/{}/{}", wip_user(), branch)
+}
+
+/// Picks a short, ref-safe name for the current user.
+fn wip_user() -> String {
+    let raw = get_config_value("user.email")
+        .and_then(|e| e.split('@').next().map(str::to_string))
+        .or_else(|| get_config_value("user.name"))
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "me".to_string());
+
+    let slug: String = raw
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches(['-', '.']).to_string();
+    if slug.is_empty() {
+        "me".to_string()
+    } else {
+        slug
+    }
+}
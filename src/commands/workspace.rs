@@ -0,0 +1,273 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::{bail, Result};
+
+use crate::cli::WorkspaceAction;
+use crate::config::Config;
+use crate::git::{git_complaint, run_git_in_dir_silent};
+use crate::workspace::{repo_name, repo_state, workspace_repos, RepoState};
+
+/// Repositories worked on at once; each one mostly waits on the network.
+const WORKSPACE_JOBS: usize = 4;
+
+pub(super) enum Outcome {
+    Done(String),
+    /// Left alone, for a reason that is not a problem.
+    Skipped(String),
+    /// Needs the user's attention.
+    Failed(String),
+}
+
+struct Row {
+    name: String,
+    state: Option<RepoState>,
+    outcome: Outcome,
+}
+
+pub fn run_workspace(action: Option<WorkspaceAction>) -> Result<()> {
+    let action = action.unwrap_or(WorkspaceAction::Status);
+    let (repos, source) = workspace_repos()?;
+    if repos.is_empty() {
+        bail!(
+            "found no {}\n  hint: list them in your user config, under [workspace] repos = [\"~/src/app\", ...]",
+            source
+        );
+    }
+    run_across(&repos, &source, action)
+}
+
+/// Runs `action` in each of `repos` (described by `source`) and prints a line for each.
+pub(super) fn run_across(repos: &[PathBuf], source: &str, action: WorkspaceAction) -> Result<()> {
+    let verb = match action {
+        WorkspaceAction::Status => "Checking",
+        WorkspaceAction::Pull => "Pulling",
+        WorkspaceAction::Push => "Pushing",
+        WorkspaceAction::Sync => "Syncing",
+    };
+    println!("→ {} {} {}...", verb, repos.len(), source);
+
+    let rows = for_each_repo(repos, |dir| process(dir, &action));
+    print_table(&rows, !matches!(action, WorkspaceAction::Status));
+
+    let failed = rows
+        .iter()
+        .filter(|row| matches!(row.outcome, Outcome::Failed(_)))
+        .count();
+    if failed > 0 {
+        bail!(
+            "{} of {} repositories need attention - see the table above",
+            failed,
+            rows.len()
+        );
+    }
+    Ok(())
+}
+
+/// Runs `work` on each repository, a few at a time, keeping the rows in the order of
+/// `repos`.
+fn for_each_repo<F>(repos: &[PathBuf], work: F) -> Vec<Row>
+where
+    F: Fn(&Path) -> Row + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let rows: Mutex<Vec<(usize, Row)>> = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..WORKSPACE_JOBS.min(repos.len()) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(dir) = repos.get(idx) else {
+                    break;
+                };
+                let row = work(dir);
+                if let Ok(mut rows) = rows.lock() {
+                    rows.push((idx, row));
+                }
+            });
+        }
+    });
+    let mut rows = rows.into_inner().unwrap_or_default();
+    rows.sort_by_key(|(idx, _)| *idx);
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+fn process(dir: &Path, action: &WorkspaceAction) -> Row {
+    let name = repo_name(dir);
+    if !dir.join(".git").exists() {
+        return Row {
+            name,
+            state: None,
+            outcome: Outcome::Failed(format!("{} is not a git repository", dir.display())),
+        };
+    }
+    let outcome = match action {
+        WorkspaceAction::Status => Ok(Outcome::Done(String::new())),
+        WorkspaceAction::Pull => fetch(dir).and_then(|()| pull(dir)),
+        WorkspaceAction::Push => push(dir),
+        WorkspaceAction::Sync => sync_repo(dir),
+    };
+    let state = repo_state(dir);
+    let outcome = match (outcome, &state) {
+        (Err(e), _) => Outcome::Failed(git_complaint(&e)),
+        (Ok(_), Err(e)) => Outcome::Failed(git_complaint(e)),
+        (Ok(outcome), Ok(_)) => outcome,
+    };
+    Row {
+        name,
+        state: state.ok(),
+        outcome,
+    }
+}
+
+fn fetch(dir: &Path) -> Result<()> {
+    run_git_in_dir_silent(&["fetch", "--quiet"], dir)
+}
+
+/// Fast-forwards the current branch to its upstream; anything else is left for the user.
+fn pull(dir: &Path) -> Result<Outcome> {
+    let state = repo_state(dir)?;
+    if state.branch.is_none() {
+        return Ok(Outcome::Skipped("detached HEAD".to_string()));
+    }
+    if state.upstream.is_none() {
+        return Ok(Outcome::Skipped("no upstream".to_string()));
+    }
+    if state.behind == 0 {
+        return Ok(Outcome::Done("up to date".to_string()));
+    }
+    if state.ahead > 0 {
+        return Ok(Outcome::Failed(
+            "diverged - run 'sgit pull' in it".to_string(),
+        ));
+    }
+    // Uncommitted changes stay put unless the new commits touch the same files.
+    if run_git_in_dir_silent(&["merge", "--ff-only", "--quiet", "@{u}"], dir).is_err() {
+        return Ok(Outcome::Failed(
+            "uncommitted changes are in the way of the pull".to_string(),
+        ));
+    }
+    Ok(Outcome::Done(format!("pulled {}", commits(state.behind))))
+}
+
+fn push(dir: &Path) -> Result<Outcome> {
+    let state = repo_state(dir)?;
+    let Some(branch) = state.branch else {
+        return Ok(Outcome::Skipped("detached HEAD".to_string()));
+    };
+    if state.upstream.is_none() {
+        return Ok(Outcome::Skipped("no upstream".to_string()));
+    }
+    if state.ahead == 0 {
+        return Ok(Outcome::Done("nothing to push".to_string()));
+    }
+    if state.behind > 0 {
+        return Ok(Outcome::Failed(
+            "behind its upstream - pull first".to_string(),
+        ));
+    }
+    if Config::load_in(Some(dir))?.branches.is_protected(&branch) {
+        return Ok(Outcome::Skipped(format!(
+            "{} is protected - not pushed",
+            branch
+        )));
+    }
+    run_git_in_dir_silent(&["push", "--quiet"], dir)?;
+    Ok(Outcome::Done(format!("pushed {}", commits(state.ahead))))
+}
+
+/// Fetches the repository in `dir`, fast-forwards it, and pushes what it has over its
+/// upstream, as `sgit workspace sync` does for each repository.
+pub(super) fn sync_repo(dir: &Path) -> Result<Outcome> {
+    fetch(dir)?;
+    sync(dir)
+}
+
+fn sync(dir: &Path) -> Result<Outcome> {
+    let pulled = pull(dir)?;
+    let Outcome::Done(pulled) = pulled else {
+        return Ok(pulled);
+    };
+    Ok(match push(dir)? {
+        Outcome::Done(pushed) if pushed == "nothing to push" => Outcome::Done(pulled),
+        Outcome::Done(pushed) if pulled == "up to date" => Outcome::Done(pushed),
+        Outcome::Done(pushed) => Outcome::Done(format!("{}, {}", pulled, pushed)),
+        Outcome::Skipped(reason) => Outcome::Skipped(format!("{}; {}", pulled, reason)),
+        Outcome::Failed(reason) => Outcome::Failed(format!("{}; {}", pulled, reason)),
+    })
+}
+
+fn commits(count: usize) -> String {
+    match count {
+        1 => "1 commit".to_string(),
+        _ => format!("{} commits", count),
+    }
+}
+
+fn print_table(rows: &[Row], with_results: bool) {
+    let cells: Vec<[String; 4]> = rows
+        .iter()
+        .map(|row| {
+            let (branch, changes, upstream) = match &row.state {
+                Some(state) => describe_state(state),
+                None => ("-".to_string(), "-".to_string(), "-".to_string()),
+            };
+            [row.name.clone(), branch, changes, upstream]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..4)
+        .map(|col| {
+            cells
+                .iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    println!();
+    for (row, cells) in rows.iter().zip(&cells) {
+        let (mark, result) = match &row.outcome {
+            Outcome::Done(text) => ("✓", text.as_str()),
+            Outcome::Skipped(text) => ("·", text.as_str()),
+            Outcome::Failed(text) => ("✗", text.as_str()),
+        };
+        // A status only has something to say about repositories it could not read.
+        let mark = if with_results || matches!(row.outcome, Outcome::Failed(_)) {
+            mark
+        } else {
+            " "
+        };
+        let mut line = format!("{} ", mark);
+        for (col, cell) in cells.iter().enumerate() {
+            line.push_str(&format!("{:<width$}  ", cell, width = widths[col]));
+        }
+        // "up to date" or "no upstream" already shows in the upstream column.
+        if result != cells[3] {
+            line.push_str(result);
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
+/// The branch, the uncommitted changes, and where it stands against its upstream.
+fn describe_state(state: &RepoState) -> (String, String, String) {
+    let branch = state
+        .branch
+        .clone()
+        .unwrap_or_else(|| "(detached)".to_string());
+    let changes = match state.changes {
+        0 => "clean".to_string(),
+        1 => "1 change".to_string(),
+        count => format!("{} changes", count),
+    };
+    let upstream = match (&state.upstream, state.ahead, state.behind) {
+        (None, _, _) => "no upstream".to_string(),
+        (Some(_), 0, 0) => "up to date".to_string(),
+        (Some(_), ahead, 0) => format!("↑{}", ahead),
+        (Some(_), 0, behind) => format!("↓{}", behind),
+        (Some(_), ahead, behind) => format!("↑{} ↓{}", ahead, behind),
+    };
+    (branch, changes, upstream)
+}
@@ -0,0 +1,374 @@
+use std::env;
+use std::io::{stdin, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use dialoguer::{Input, Select};
+
+use crate::cli::WorktreeAction;
+use crate::commands::picker::{Picked, RefKind, RefPicker};
+use crate::git::{git_output, run_git_quiet, run_git_silent};
+use crate::status::{get_branches, get_repo_root};
+use crate::workspace::{repo_name, repo_state};
+
+struct Worktree {
+    path: PathBuf,
+    /// `None` on a detached HEAD.
+    branch: Option<String>,
+    head: String,
+    /// The main worktree, the one the repository was cloned into.
+    main: bool,
+    locked: bool,
+    /// Its directory is gone; `git worktree prune` forgets it.
+    missing: bool,
+}
+
+pub fn run_worktree(action: Option<WorktreeAction>) -> Result<()> {
+    match action.unwrap_or(WorktreeAction::List) {
+        WorktreeAction::List => list(),
+        WorktreeAction::Add { branch, path } => add(branch, path),
+        WorktreeAction::Remove { worktree, force } => remove(worktree, force),
+    }
+}
+
+fn worktrees() -> Result<Vec<Worktree>> {
+    let output = git_output(&["worktree", "list", "--porcelain"])?;
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.push(Worktree {
+                path: PathBuf::from(path),
+                branch: None,
+                head: String::new(),
+                main: worktrees.is_empty(),
+                locked: false,
+                missing: false,
+            });
+            continue;
+        }
+        let Some(worktree) = worktrees.last_mut() else {
+            continue;
+        };
+        if let Some(head) = line.strip_prefix("HEAD ") {
+            worktree.head = head.chars().take(7).collect();
+        } else if let Some(branch) = line.strip_prefix("branch ") {
+            worktree.branch = Some(branch.trim_start_matches("refs/heads/").to_string());
+        } else if line == "locked" || line.starts_with("locked ") {
+            worktree.locked = true;
+        } else if line == "prunable" || line.starts_with("prunable ") {
+            worktree.missing = true;
+        }
+    }
+    Ok(worktrees)
+}
+
+fn list() -> Result<()> {
+    let worktrees = worktrees()?;
+    let here = current_root();
+    let width = worktrees
+        .iter()
+        .map(|w| describe_branch(w).chars().count())
+        .max()
+        .unwrap_or(0);
+    for worktree in &worktrees {
+        let mark = if here.as_deref() == Some(worktree.path.as_path()) {
+            "*"
+        } else {
+            " "
+        };
+        let mut notes = Vec::new();
+        if worktree.main {
+            notes.push("main".to_string());
+        }
+        if worktree.missing {
+            notes.push("directory is gone - 'git worktree prune' forgets it".to_string());
+        } else if let Ok(state) = repo_state(&worktree.path) {
+            notes.push(match state.changes {
+                0 => "clean".to_string(),
+                1 => "1 change".to_string(),
+                count => format!("{} changes", count),
+            });
+        }
+        if worktree.locked {
+            notes.push("locked".to_string());
+        }
+        println!(
+            "{} {:<width$}  {}  ({})",
+            mark,
+            describe_branch(worktree),
+            worktree.path.display(),
+            notes.join(", "),
+            width = width
+        );
+    }
+    if worktrees.len() == 1 {
+        println!();
+        println!("  'sgit worktree add' checks out another branch next to this one.");
+    }
+    Ok(())
+}
+
+fn describe_branch(worktree: &Worktree) -> String {
+    match &worktree.branch {
+        Some(branch) => branch.clone(),
+        None => format!("(detached at {})", worktree.head),
+    }
+}
+
+/// The worktree the current directory is in.
+fn current_root() -> Option<PathBuf> {
+    let root = PathBuf::from(get_repo_root().ok()?);
+    Some(root.canonicalize().unwrap_or(root))
+}
+
+/// Checks out `branch` (picked interactively when not given) in a new worktree, by default
+/// `<repo>-<branch>` next to the main worktree.
+fn add(branch: Option<String>, path: Option<String>) -> Result<()> {
+    let worktrees = worktrees()?;
+    let branch = match branch {
+        Some(branch) => BranchChoice::from_name(&branch)?,
+        None => pick_branch()?,
+    };
+    let local = branch.local_name();
+    if let Some(taken) = worktrees
+        .iter()
+        .find(|w| w.branch.as_deref() == Some(local))
+    {
+        bail!(
+            "'{}' is already checked out in {}\n  hint: cd there, or pick another branch",
+            local,
+            taken.path.display()
+        );
+    }
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => default_path(&worktrees, local),
+    };
+    if path.exists() {
+        bail!(
+            "{} already exists - choose another directory with --path",
+            path.display()
+        );
+    }
+    let shown = path.display().to_string();
+    println!("→ Checking out '{}' in {}...", local, shown);
+    match &branch {
+        BranchChoice::Local(name) => run_git_quiet(&["worktree", "add", &shown, name])?,
+        BranchChoice::Remote(remote) => {
+            run_git_quiet(&["worktree", "add", "--track", "-b", local, &shown, remote])?
+        }
+        BranchChoice::New(name) => run_git_quiet(&["worktree", "add", "-b", name, &shown])?,
+    }
+    match &branch {
+        BranchChoice::Remote(remote) => {
+            println!("✓ Created '{}' tracking {} in {}", local, remote, shown)
+        }
+        BranchChoice::New(name) => {
+            println!("✓ Created '{}' from the current commit in {}", name, shown)
+        }
+        BranchChoice::Local(name) => println!("✓ Checked out '{}' in {}", name, shown),
+    }
+    println!(
+        "  cd {}  to work there; both checkouts share commits,",
+        shown
+    );
+    println!("  branches, and stashes. 'sgit worktree remove' cleans it up.");
+    Ok(())
+}
+
+enum BranchChoice {
+    Local(String),
+    /// A remote branch (`origin/feature`) that gets a local tracking branch.
+    Remote(String),
+    New(String),
+}
+
+impl BranchChoice {
+    fn local_name(&self) -> &str {
+        match self {
+            BranchChoice::Local(name) | BranchChoice::New(name) => name,
+            BranchChoice::Remote(remote) => remote
+                .split_once('/')
+                .map(|(_, name)| name)
+                .unwrap_or(remote),
+        }
+    }
+
+    /// The local branch of that name when there is one already, so it is not created twice.
+    fn for_remote(remote: &str) -> Result<Self> {
+        let local = remote
+            .split_once('/')
+            .map(|(_, name)| name)
+            .unwrap_or(remote);
+        if get_branches()?.iter().any(|b| b == local) {
+            Ok(BranchChoice::Local(local.to_string()))
+        } else {
+            Ok(BranchChoice::Remote(remote.to_string()))
+        }
+    }
+
+    /// A branch given by name: a local one, else a remote one, else a new one.
+    fn from_name(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!("'{}' is not a valid branch name", name);
+        }
+        if get_branches()?.iter().any(|b| b == name) {
+            return Ok(BranchChoice::Local(name.to_string()));
+        }
+        let remote_branches =
+            git_output(&["for-each-ref", "--format=%(refname:short)", "refs/remotes"])?;
+        let tracked: Vec<&str> = remote_branches
+            .lines()
+            .filter(|remote| {
+                *remote == name || remote.split_once('/').map(|(_, b)| b) == Some(name)
+            })
+            .collect();
+        match tracked.as_slice() {
+            [] => Ok(BranchChoice::New(name.to_string())),
+            [only] => Ok(BranchChoice::for_remote(only)?),
+            several => bail!(
+                "'{}' is on several remotes ({}) - name one, e.g. 'sgit worktree add {}'",
+                name,
+                several.join(", "),
+                several[0]
+            ),
+        }
+    }
+}
+
+fn pick_branch() -> Result<BranchChoice> {
+    if !stdin().is_terminal() {
+        bail!("name the branch to check out: 'sgit worktree add <branch>'");
+    }
+    let picked = RefPicker::new(
+        "Which branch should the new worktree have? (type to search)",
+        &[RefKind::Branch, RefKind::RemoteBranch],
+    )
+    .extra_item("Create new branch...")
+    .interact()?;
+    match picked {
+        Some(Picked::Ref(branch)) if branch.kind == RefKind::RemoteBranch => {
+            BranchChoice::for_remote(&branch.name)
+        }
+        Some(Picked::Ref(branch)) => Ok(BranchChoice::Local(branch.name)),
+        Some(Picked::Extra(_)) | None => {
+            let name: String = Input::new().with_prompt("New branch name").interact()?;
+            let name = name.trim().replace(' ', "-");
+            if name.is_empty() {
+                bail!("branch name cannot be empty");
+            }
+            Ok(BranchChoice::New(name))
+        }
+    }
+}
+
+/// `~/src/app` checks `feature/login` out in `~/src/app-feature-login`.
+fn default_path(worktrees: &[Worktree], branch: &str) -> PathBuf {
+    let main = worktrees
+        .iter()
+        .find(|w| w.main)
+        .map(|w| w.path.clone())
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_default();
+    let slug: String = branch
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = format!("{}-{}", repo_name(&main), slug.trim_matches('-'));
+    main.parent().unwrap_or(Path::new(".")).join(name)
+}
+
+fn remove(worktree: Option<String>, force: bool) -> Result<()> {
+    let worktrees = worktrees()?;
+    let linked: Vec<&Worktree> = worktrees.iter().filter(|w| !w.main).collect();
+    if linked.is_empty() {
+        bail!("there are no worktrees besides the main one");
+    }
+    let target = match worktree {
+        Some(given) => {
+            if worktrees[0].branch.as_deref() == Some(given.as_str()) {
+                bail!("'{}' is in the main worktree, which stays", given);
+            }
+            find_worktree(&linked, &given)?
+        }
+        None => {
+            if !stdin().is_terminal() {
+                bail!("name the worktree to remove, by branch or path: 'sgit worktree remove <worktree>'");
+            }
+            let labels: Vec<String> = linked
+                .iter()
+                .map(|w| format!("{}  {}", describe_branch(w), w.path.display()))
+                .collect();
+            let selection = Select::new()
+                .with_prompt("Which worktree should be removed?")
+                .items(&labels)
+                .default(0)
+                .interact()?;
+            linked[selection]
+        }
+    };
+    let shown = target.path.display().to_string();
+
+    if current_root().as_deref() == Some(target.path.as_path()) {
+        bail!("you are inside {} - cd to another worktree first", shown);
+    }
+    if target.missing {
+        run_git_silent(&["worktree", "prune"])?;
+        println!("✓ Forgot {} (its directory was already gone)", shown);
+        return Ok(());
+    }
+    if !force
+        && let Ok(state) = repo_state(&target.path)
+        && state.changes > 0
+    {
+        bail!(
+            "{} has {} uncommitted {} - commit there first, or pass --force to throw it all away",
+            shown,
+            state.changes,
+            if state.changes == 1 {
+                "change"
+            } else {
+                "changes"
+            }
+        );
+    }
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        // Twice, so a locked worktree goes too.
+        args.extend(["--force", "--force"]);
+    }
+    args.push(&shown);
+    run_git_quiet(&args)?;
+    println!("✓ Removed the worktree in {}", shown);
+    if let Some(branch) = &target.branch {
+        println!(
+            "  The branch '{}' is still there; 'git branch -d {}' deletes it.",
+            branch, branch
+        );
+    }
+    Ok(())
+}
+
+/// The worktree with `given` as its branch or path.
+fn find_worktree<'a>(linked: &[&'a Worktree], given: &str) -> Result<&'a Worktree> {
+    let path = Path::new(given);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    linked
+        .iter()
+        .find(|w| w.branch.as_deref() == Some(given) || w.path == path)
+        .copied()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "no worktree has the branch or path '{}'\n  hint: 'sgit worktree list' shows them",
+                given
+            )
+        })
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::alias::AliasValue;
+use crate::status::get_repo_root;
+
+pub const REPO_CONFIG_FILE: &str = ".sgit.toml";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub checks: ChecksConfig,
+    pub commit: CommitConfig,
+    pub prompts: PromptsConfig,
+    pub lint: LintConfig,
+    pub branches: BranchesConfig,
+    pub sync: SyncConfig,
+    /// `[subtrees."vendor/lib"]`, by directory: where each subtree comes from, written by
+    /// `sgit subtree add` so later pulls and pushes need no arguments.
+    pub subtrees: BTreeMap<String, SubtreeConfig>,
+    /// `name = "command --flags"`, expanded when `name` is used as a subcommand.
+    pub aliases: BTreeMap<String, AliasValue>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ChecksConfig {
+    pub commands: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// Canonical Conventional Commit scopes; empty means any scope is allowed.
+    pub scopes: Vec<String>,
+    /// Start the interactive commit with a gitmoji picker.
+    pub gitmoji: bool,
+    /// Sign every commit made with `sgit commit`.
+    pub sign: bool,
+    /// Refuse to commit a file bigger than this many megabytes; 0 turns the check off.
+    pub max_file_size_mb: u64,
+}
+
+impl Default for CommitConfig {
+    fn default() -> Self {
+        Self {
+            scopes: Vec::new(),
+            gitmoji: false,
+            sign: false,
+            max_file_size_mb: 50,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PromptsConfig {
+    /// Preselect the option the user usually picks in recurring prompts.
+    pub remember: bool,
+}
+
+impl Default for PromptsConfig {
+    fn default() -> Self {
+        Self { remember: true }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Off,
+    /// Print the problems and commit anyway.
+    #[default]
+    Warn,
+    /// Refuse to commit until the message is fixed (or `--no-lint` is passed).
+    Block,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub level: LintLevel,
+    /// 0 turns the length check off.
+    pub max_subject_length: usize,
+    pub no_trailing_period: bool,
+    /// Flag subjects that start with "Added" or "Adds" instead of "Add".
+    pub imperative: bool,
+    /// Require a `type(scope): description` subject.
+    pub conventional: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            level: LintLevel::Warn,
+            max_subject_length: 72,
+            no_trailing_period: true,
+            imperative: true,
+            conventional: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BranchesConfig {
+    /// Branches nobody commits or pushes to directly; a trailing `*` matches a prefix,
+    /// as in `release/*`.
+    pub protected: Vec<String>,
+}
+
+impl BranchesConfig {
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => branch.starts_with(prefix),
+                None => branch == pattern,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    Merge,
+    Rebase,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// How `sgit pull` and `sgit sync` bring in remote commits; unset leaves it to
+    /// git's `pull.rebase`.
+    pub mode: Option<SyncMode>,
+    /// Make `sgit push` push the current branch to every remote, as `--all-remotes` does.
+    pub push_all_remotes: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SubtreeConfig {
+    /// URL or remote name of the repository it comes from.
+    pub remote: String,
+    pub branch: String,
+    /// Whether its history comes in as one squashed commit per pull.
+    #[serde(default = "default_true")]
+    pub squash: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Settings that belong to the person rather than the repository, kept in
+/// `$XDG_CONFIG_HOME/sgit/config.toml` (`~/.config/sgit/config.toml` by default).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UserConfig {
+    pub profiles: BTreeMap<String, Profile>,
+    pub git: GitConfig,
+    pub workspace: WorkspaceConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// The repositories `sgit workspace` works across (`~/` allowed); when empty, it
+    /// finds the ones next to the current repository.
+    pub repos: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// The git executable to run instead of the one on PATH (`SGIT_GIT` overrides it).
+    pub path: Option<String>,
+}
+
+/// An identity to commit under, e.g. `[profiles.work]`.
+#[derive(Debug, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub email: String,
+    /// A `.pub` path or `ssh-...` key for SSH signing, or a GPG key ID.
+    pub signing_key: Option<String>,
+    /// The SSH host (or `~/.ssh/config` alias) that reaches the forge as this identity.
+    pub remote_host: Option<String>,
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("sgit").join("config.toml"))
+}
+
+impl UserConfig {
+    pub fn load() -> Result<Self> {
+        let Some(path) = user_config_path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+    }
+}
+
+impl Config {
+    /// The defaults, overridden by the `[commit]`, `[lint]`, ... sections of the user
+    /// config, overridden in turn by the repository's `.sgit.toml`, so team policy wins.
+    pub fn load() -> Result<Self> {
+        // `sgit config` also reads the settings outside a repository.
+        Self::load_in(get_repo_root().ok().as_deref().map(Path::new))
+    }
+
+    /// Like [`Config::load`], for the repository at `root` instead of the current one.
+    pub fn load_in(root: Option<&Path>) -> Result<Self> {
+        let repo_config = root.map(|root| root.join(REPO_CONFIG_FILE));
+        Self::from_layers([user_config_path(), repo_config].into_iter().flatten())
+    }
+
+    /// The settings in the files at `paths`, each overriding the ones before it; missing
+    /// files are skipped.
+    fn from_layers(paths: impl IntoIterator<Item = PathBuf>) -> Result<Self> {
+        let mut merged = toml::Table::new();
+        for path in paths {
+            if let Some(layer) = read_layer(&path)? {
+                merge_tables(&mut merged, layer);
+            }
+        }
+        toml::Value::Table(merged)
+            .try_into()
+            .context("failed to combine the sgit settings")
+    }
+}
+
+/// One config file as a table, after checking that it holds valid settings on its own
+/// (so errors name the file at fault).
+fn read_layer(path: &Path) -> Result<Option<toml::Table>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str::<Config>(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    let table =
+        toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(table))
+}
+
+/// Sections merge key by key; any other value, lists included, is replaced whole.
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => {
+                merge_tables(existing, value)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        toml::from_str(text).unwrap()
+    }
+
+    /// Writes `files` (name and contents) to a fresh directory for this test.
+    fn write_layers(test: &str, files: &[(&str, &str)]) -> Vec<PathBuf> {
+        let dir = env::temp_dir().join(format!("sgit-config-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        files
+            .iter()
+            .map(|(name, contents)| {
+                let path = dir.join(name);
+                fs::write(&path, contents).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_sections_key_by_key() {
+        let mut base = table("[lint]\nlevel = \"block\"\nmax_subject_length = 50\n");
+        merge_tables(
+            &mut base,
+            table("[lint]\nlevel = \"warn\"\n[sync]\nmode = \"rebase\"\n"),
+        );
+        assert_eq!(
+            base,
+            table("[lint]\nlevel = \"warn\"\nmax_subject_length = 50\n[sync]\nmode = \"rebase\"\n")
+        );
+    }
+
+    #[test]
+    fn replaces_lists_whole() {
+        let mut base = table("[branches]\nprotected = [\"main\", \"release/*\"]\n");
+        merge_tables(&mut base, table("[branches]\nprotected = [\"trunk\"]\n"));
+        assert_eq!(base, table("[branches]\nprotected = [\"trunk\"]\n"));
+    }
+
+    #[test]
+    fn repo_config_overrides_user_config() {
+        let layers = write_layers(
+            "layering",
+            &[
+                (
+                    "config.toml",
+                    "[lint]\nlevel = \"off\"\nmax_subject_length = 50\n\n[commit]\ngitmoji = true\n",
+                ),
+                (REPO_CONFIG_FILE, "[lint]\nlevel = \"block\"\n"),
+            ],
+        );
+        let config = Config::from_layers(layers).unwrap();
+        assert_eq!(config.lint.level, LintLevel::Block);
+        assert_eq!(config.lint.max_subject_length, 50);
+        assert!(config.commit.gitmoji);
+        // Untouched settings keep their defaults.
+        assert!(config.lint.no_trailing_period);
+        assert_eq!(config.commit.max_file_size_mb, 50);
+    }
+
+    #[test]
+    fn skips_missing_layers() {
+        let mut layers = write_layers(
+            "missing",
+            &[(REPO_CONFIG_FILE, "[sync]\nmode = \"merge\"\n")],
+        );
+        layers.insert(0, layers[0].with_file_name("absent.toml"));
+        let config = Config::from_layers(layers).unwrap();
+        assert_eq!(config.sync.mode, Some(SyncMode::Merge));
+    }
+
+    #[test]
+    fn names_the_file_with_a_bad_setting() {
+        let layers = write_layers(
+            "invalid",
+            &[
+                ("config.toml", "[lint]\nlevel = \"warn\"\n"),
+                (REPO_CONFIG_FILE, "[lint]\nlevel = \"loud\"\n"),
+            ],
+        );
+        let err = Config::from_layers(layers).unwrap_err();
+        assert!(err.to_string().contains(REPO_CONFIG_FILE), "{}", err);
+    }
+
+    #[test]
+    fn matches_protected_branches() {
+        let branches = BranchesConfig {
+            protected: vec!["main".to_string(), "release/*".to_string()],
+        };
+        assert!(branches.is_protected("main"));
+        assert!(branches.is_protected("release/1.0"));
+        assert!(branches.is_protected("release/"));
+        assert!(!branches.is_protected("main2"));
+        assert!(!branches.is_protected("release"));
+        assert!(!branches.is_protected("feature/release/1.0"));
+    }
+
+    #[test]
+    fn a_lone_star_protects_every_branch() {
+        let branches = BranchesConfig {
+            protected: vec!["*".to_string()],
+        };
+        assert!(branches.is_protected("main"));
+        assert!(branches.is_protected("feature/x"));
+        assert!(!BranchesConfig::default().is_protected("main"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+/// Conventional Commit types offered by the commit wizard, with a short explanation.
+pub const COMMIT_TYPES: &[(&str, &str)] = &[
+    ("feat", "a new feature"),
+    ("fix", "a bug fix"),
+    ("docs", "documentation only"),
+    ("style", "formatting, no code change"),
+    (
+        "refactor",
+        "code change that neither fixes a bug nor adds a feature",
+    ),
+    ("perf", "performance improvement"),
+    ("test", "adding or fixing tests"),
+    ("build", "build system or dependencies"),
+    ("ci", "CI configuration"),
+    ("chore", "other changes that don't touch src or tests"),
+    ("revert", "reverts a previous commit"),
+];
+
+/// Gitmoji (emoji, shortcode, meaning) offered when `[commit] gitmoji` is on.
+pub const GITMOJIS: &[(&str, &str, &str)] = &[
+    ("✨", ":sparkles:", "introduce a new feature"),
+    ("🐛", ":bug:", "fix a bug"),
+    ("🚑️", ":ambulance:", "critical hotfix"),
+    ("📝", ":memo:", "add or update documentation"),
+    ("🎨", ":art:", "improve structure or format of the code"),
+    ("♻️", ":recycle:", "refactor code"),
+    ("⚡️", ":zap:", "improve performance"),
+    ("🔥", ":fire:", "remove code or files"),
+    ("✅", ":white_check_mark:", "add, update, or pass tests"),
+    ("💄", ":lipstick:", "add or update the UI and style files"),
+    ("🔒️", ":lock:", "fix security issues"),
+    ("🚨", ":rotating_light:", "fix compiler or linter warnings"),
+    ("🚧", ":construction:", "work in progress"),
+    ("💚", ":green_heart:", "fix the CI build"),
+    (
+        "👷",
+        ":construction_worker:",
+        "add or update the CI build system",
+    ),
+    ("⬆️", ":arrow_up:", "upgrade dependencies"),
+    ("⬇️", ":arrow_down:", "downgrade dependencies"),
+    ("➕", ":heavy_plus_sign:", "add a dependency"),
+    ("➖", ":heavy_minus_sign:", "remove a dependency"),
+    ("🔧", ":wrench:", "add or update configuration files"),
+    (
+        "🌐",
+        ":globe_with_meridians:",
+        "internationalization and localization",
+    ),
+    ("✏️", ":pencil2:", "fix typos"),
+    ("⏪️", ":rewind:", "revert changes"),
+    ("🚚", ":truck:", "move or rename files"),
+    ("💥", ":boom:", "introduce breaking changes"),
+    ("♿️", ":wheelchair:", "improve accessibility"),
+    ("🏷️", ":label:", "add or update types"),
+    ("🗑️", ":wastebasket:", "deprecate code"),
+    ("🔖", ":bookmark:", "release or version tag"),
+    ("🎉", ":tada:", "begin a project"),
+];
+
+/// Puts `emoji` in front of the description: after `type(scope): ` in a Conventional
+/// Commit, so the header still parses, and at the very start otherwise.
+pub fn with_gitmoji(message: &str, emoji: &str) -> String {
+    if header_type(message).is_some()
+        && let Some((head, rest)) = message.split_once(": ")
+    {
+        return format!("{}: {} {}", head, emoji, rest);
+    }
+    format!("{} {}", emoji, message)
+}
+
+/// Returns the type of a `type(scope)!: description` subject line, if it is one.
+pub fn header_type(message: &str) -> Option<&str> {
+    let subject = message.lines().next()?;
+    let (head, _) = subject.split_once(": ")?;
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let kind = match head.split_once('(') {
+        Some((kind, rest)) => rest.ends_with(')').then_some(kind)?,
+        None => head,
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(kind)
+}
+
+/// Returns the scope of a `type(scope)!: description` subject line, if it has one.
+pub fn header_scope(message: &str) -> Option<&str> {
+    let subject = message.lines().next()?;
+    let (head, _) = subject.split_once(": ")?;
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let (kind, rest) = head.split_once('(')?;
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    rest.strip_suffix(')')
+}
+
+pub fn format_header(kind: &str, scope: Option<&str>, breaking: bool, description: &str) -> String {
+    let mut header = kind.to_string();
+    if let Some(scope) = scope {
+        header.push_str(&format!("({})", scope));
+    }
+    if breaking {
+        header.push('!');
+    }
+    header.push_str(": ");
+    header.push_str(description.trim());
+    header
+}
+
+/// Directories that only group code, so the one below them names the scope instead.
+const CONTAINER_DIRS: &[&str] = &["src", "lib", "crates", "packages", "apps", "libs"];
+
+/// Files that mark the root of a crate or package inside a repository.
+const PACKAGE_MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "go.mod", "pyproject.toml"];
+
+/// Suggests a scope for a commit touching `files` (relative to `repo_root`): the
+/// package they live in, or else the directory they share. `None` unless one candidate
+/// covers most of the files.
+pub fn suggest_scope(files: &[String], repo_root: &Path) -> Option<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for file in files {
+        if let Some(scope) = scope_for_path(file, repo_root) {
+            *counts.entry(scope).or_default() += 1;
+        }
+    }
+    let (scope, count) = counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+    (count * 2 > files.len()).then_some(scope)
+}
+
+fn scope_for_path(file: &str, repo_root: &Path) -> Option<String> {
+    let path = Path::new(file);
+    // The nearest package root below the repository root, e.g. `crates/parser`.
+    let package = path.ancestors().skip(1).find(|dir| {
+        !dir.as_os_str().is_empty()
+            && PACKAGE_MANIFESTS
+                .iter()
+                .any(|manifest| repo_root.join(dir).join(manifest).is_file())
+    });
+    if let Some(dir) = package {
+        return dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+    }
+
+    let dirs: Vec<String> = path
+        .parent()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let scope = match dirs.as_slice() {
+        [] => return None,
+        [container, inner, ..] if CONTAINER_DIRS.contains(&container.as_str()) => inner,
+        [container] if CONTAINER_DIRS.contains(&container.as_str()) => return None,
+        [top, ..] => top,
+    };
+    // `.github` → `github`
+    Some(scope.trim_start_matches('.').to_string())
+}
+
+/// Rejects messages whose Conventional Commit scope isn't in the configured list.
+/// Messages without a scope, or that aren't Conventional Commits at all, pass.
+pub fn check_scope(message: &str, scopes: &[String]) -> Result<()> {
+    if scopes.is_empty() {
+        return Ok(());
+    }
+    let Some(scope) = header_scope(message) else {
+        return Ok(());
+    };
+    if !scopes.iter().any(|s| s == scope) {
+        bail!(
+            "unknown commit scope '{}' - this repository allows: {}\n  The list lives under [commit] scopes in .sgit.toml.",
+            scope,
+            scopes.join(", ")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Local crash reports: when sgit panics or fails in a way it did not expect, everything a
+//! maintainer needs to reproduce the problem is written to `.git/sgit/crash/` instead of
+//! being lost. Nothing is sent anywhere.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::git::git_version;
+use crate::journal::{read_recent, recent_git_invocations, redact_credentials};
+use crate::status::get_git_dir;
+
+const JOURNAL_ENTRIES_IN_REPORT: usize = 10;
+
+/// Replaces the default panic output with a short apology and a crash report.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let message = panic_message(info);
+        // `sgit log | head` closing the pipe early is not a bug.
+        if message.contains("Broken pipe") {
+            std::process::exit(141);
+        }
+        let report = build_report(
+            &format!("panic:       {}", message),
+            &Backtrace::force_capture().to_string(),
+        );
+        eprintln!();
+        eprintln!("✗ sgit hit an internal error - this is a bug in sgit, not something you did.");
+        eprintln!("  {}", message);
+        save_and_announce(&report);
+    }));
+}
+
+/// Whether `err` points at a bug in sgit rather than something the user can fix. sgit
+/// words the problems it recognizes itself; a root cause of one of these library types
+/// means a code path met something it did not expect.
+pub fn is_internal(err: &anyhow::Error) -> bool {
+    let root = err.root_cause();
+    root.is::<serde_json::Error>()
+        || root.is::<std::str::Utf8Error>()
+        || root.is::<std::string::FromUtf8Error>()
+        || root.is::<std::num::ParseIntError>()
+        || root.is::<std::fmt::Error>()
+}
+
+/// Writes a crash report for an unexpected error that ended the run.
+pub fn report_error(err: &anyhow::Error) {
+    let causes: Vec<String> = err.chain().map(|cause| cause.to_string()).collect();
+    let backtrace = match err.backtrace().status() {
+        BacktraceStatus::Captured => err.backtrace().to_string(),
+        _ => "(not captured - run again with RUST_BACKTRACE=1 to include one)".to_string(),
+    };
+    let report = build_report(&format!("error:       {}", causes.join(": ")), &backtrace);
+    eprintln!();
+    eprintln!("✗ That error was unexpected - it is likely a bug in sgit.");
+    save_and_announce(&report);
+}
+
+fn save_and_announce(report: &str) {
+    match write_report(report) {
+        Ok(path) => {
+            eprintln!("  A crash report was saved to {}", path.display());
+            eprintln!(
+                "  Please attach it to a bug report on the sgit issue tracker. It stays on your machine unless you share it; check it for anything private first."
+            );
+        }
+        Err(err) => {
+            eprintln!(
+                "  The crash report could not be saved ({}); here it is:",
+                err
+            );
+            eprintln!();
+            eprintln!("{}", report);
+        }
+    }
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    match info.location() {
+        Some(location) => format!("{} (at {}:{})", payload, location.file(), location.line()),
+        None => payload,
+    }
+}
+
+/// The report text; `failure` is the `panic:` or `error:` line.
+fn build_report(failure: &str, backtrace: &str) -> String {
+    let mut report = String::new();
+    let args: Vec<String> = std::env::args().collect();
+    let _ = writeln!(report, "sgit crash report");
+    let _ = writeln!(report, "=================");
+    let _ = writeln!(report, "time:        {}", unix_now());
+    let _ = writeln!(
+        report,
+        "command:     {}",
+        redact_credentials(&args.join(" "))
+    );
+    let _ = writeln!(report, "sgit:        {}", env!("CARGO_PKG_VERSION"));
+    let git = git_version()
+        .map(|version| version.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let _ = writeln!(report, "git:         {}", git);
+    let _ = writeln!(
+        report,
+        "os:          {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "{}", failure);
+
+    let _ = writeln!(report);
+    let _ = writeln!(
+        report,
+        "git commands run by this invocation (oldest first):"
+    );
+    let invocations = recent_git_invocations();
+    if invocations.is_empty() {
+        let _ = writeln!(report, "  (none)");
+    }
+    for invocation in invocations {
+        let mark = if invocation.success { "ok" } else { "FAILED" };
+        let _ = writeln!(report, "  [{}] {}", mark, invocation.command);
+        for line in invocation.output.lines() {
+            let _ = writeln!(report, "        {}", line);
+        }
+    }
+
+    let _ = writeln!(report);
+    let _ = writeln!(report, "recent sgit journal entries (oldest first):");
+    match read_recent(JOURNAL_ENTRIES_IN_REPORT) {
+        Ok(entries) if entries.is_empty() => {
+            let _ = writeln!(report, "  (none)");
+        }
+        Ok(entries) => {
+            for entry in entries {
+                let _ = writeln!(
+                    report,
+                    "  {} {}: {}",
+                    entry.time, entry.command, entry.description
+                );
+            }
+        }
+        Err(err) => {
+            let _ = writeln!(report, "  (unavailable: {})", err);
+        }
+    }
+
+    let _ = writeln!(report);
+    let _ = writeln!(report, "backtrace:");
+    let _ = writeln!(report, "{}", backtrace);
+    report
+}
+
+/// Saves the report under `.git/sgit/crash/`, or the temp directory outside a repository.
+fn write_report(report: &str) -> std::io::Result<PathBuf> {
+    let dir = match get_git_dir() {
+        Ok(git_dir) => PathBuf::from(git_dir).join("sgit").join("crash"),
+        Err(_) => std::env::temp_dir().join("sgit-crash"),
+    };
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("crash-{}-{}.txt", unix_now(), std::process::id()));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
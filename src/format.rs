@@ -0,0 +1,11 @@
+//! Formatting shared by commands that report on files and repositories.
+
+/// `bytes` as whole KB below a megabyte, else MB with one decimal.
+pub fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{:.0} KB", bytes as f64 / 1024.0)
+    }
+}
@@ -1,21 +1,386 @@
-use std::process::Command as StdCommand;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::process::{Child, Command as StdCommand, ExitStatus, Output, Stdio};
+use std::sync::OnceLock;
 
 use anyhow::{bail, Context, Result};
 
+use crate::config::UserConfig;
+use crate::journal::note_git_invocation;
+use crate::paths::expand_home;
+
 pub const NOT_IN_REPO_HINT: &str =
     "not in a git repository - run 'sgit init' or cd into a repo first";
 pub const NO_STAGED_HINT: &str = "nothing to commit - use 'sgit stage' to stage changes first";
 
+/// Names the git executable to run instead of the `git` on PATH.
+pub const GIT_PROGRAM_ENV: &str = "SGIT_GIT";
+
+/// The git executable sgit runs: `$SGIT_GIT`, else `[git] path` in the user config, else
+/// `git` from PATH. A repository's `.sgit.toml` cannot set it, since anyone can commit one.
+pub fn git_program() -> &'static OsStr {
+    static PROGRAM: OnceLock<OsString> = OnceLock::new();
+    PROGRAM.get_or_init(|| {
+        if let Some(program) = env::var_os(GIT_PROGRAM_ENV).filter(|p| !p.is_empty()) {
+            return program;
+        }
+        UserConfig::load()
+            .ok()
+            .and_then(|config| config.git.path)
+            .filter(|path| !path.is_empty())
+            .map(|path| expand_home(&path).into_os_string())
+            .unwrap_or_else(|| "git".into())
+    })
+}
+
+/// A command that runs [`git_program`]; every git invocation goes through it.
+pub fn git_cmd() -> GitCommand {
+    GitCommand {
+        inner: StdCommand::new(git_program()),
+        args: Vec::new(),
+    }
+}
+
+/// A git command, built like a [`std::process::Command`]. Running it notes the command and
+/// what it printed for crash reports and `sgit explain-last`, wherever it is run from.
+pub struct GitCommand {
+    inner: StdCommand,
+    args: Vec<String>,
+}
+
+impl GitCommand {
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.as_ref().to_string_lossy().into_owned());
+        self.inner.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        for arg in args {
+            self.arg(arg);
+        }
+        self
+    }
+
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.inner.current_dir(dir);
+        self
+    }
+
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, value: V) -> &mut Self {
+        self.inner.env(key, value);
+        self
+    }
+
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.inner.envs(vars);
+        self
+    }
+
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.inner.stdin(cfg);
+        self
+    }
+
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.inner.stdout(cfg);
+        self
+    }
+
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.inner.stderr(cfg);
+        self
+    }
+
+    pub fn output(&mut self) -> io::Result<Output> {
+        let output = self.inner.output()?;
+        note_git_invocation(&self.args, &output);
+        Ok(output)
+    }
+
+    pub fn status(&mut self) -> io::Result<ExitStatus> {
+        let status = self.inner.status()?;
+        // What it printed went straight to the terminal (or nowhere).
+        let output = Output {
+            status,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+        note_git_invocation(&self.args, &output);
+        Ok(status)
+    }
+
+    pub fn spawn(&mut self) -> io::Result<GitChild> {
+        Ok(GitChild {
+            inner: self.inner.spawn()?,
+            args: self.args.clone(),
+        })
+    }
+}
+
+/// A running [`GitCommand`]; waiting for its output notes it like [`GitCommand::output`].
+pub struct GitChild {
+    inner: Child,
+    args: Vec<String>,
+}
+
+impl GitChild {
+    pub fn wait_with_output(self) -> io::Result<Output> {
+        let output = self.inner.wait_with_output()?;
+        note_git_invocation(&self.args, &output);
+        Ok(output)
+    }
+}
+
+impl Deref for GitChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.inner
+    }
+}
+
+impl DerefMut for GitChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.inner
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The oldest git sgit works with. Commands newer than this (`git switch`, `git restore`,
+/// `--pathspec-from-file`, ...) are swapped for their older equivalents when needed.
+pub const MIN_GIT_VERSION: GitVersion = GitVersion {
+    major: 2,
+    minor: 20,
+    patch: 0,
+};
+
+/// What `git --version` printed, asked once per run; `None` if git could not be run.
+fn git_version_output() -> Option<&'static str> {
+    static OUTPUT: OnceLock<Option<String>> = OnceLock::new();
+    OUTPUT
+        .get_or_init(|| {
+            let output = git_cmd().arg("--version").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .as_deref()
+}
+
+/// The installed git's version; `None` if git is missing or its version string is
+/// unusual (some vendor builds), in which case sgit assumes a modern one.
+pub fn git_version() -> Option<GitVersion> {
+    parse_git_version(git_version_output()?)
+}
+
+/// The version in `git --version` output such as `git version 2.39.3 (Apple Git-146)`.
+fn parse_git_version(output: &str) -> Option<GitVersion> {
+    let mut numbers = output
+        .trim_start_matches("git version ")
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse::<u32>().ok());
+    Some(GitVersion {
+        major: numbers.next()??,
+        minor: numbers.next()??,
+        patch: numbers.next().flatten().unwrap_or(0),
+    })
+}
+
+/// Whether the installed git is at least `major.minor`.
+pub fn git_supports(major: u32, minor: u32) -> bool {
+    supports(git_version(), major, minor)
+}
+
+/// Whether `version` is at least `major.minor`; an unknown version is assumed to be modern.
+fn supports(version: Option<GitVersion>, major: u32, minor: u32) -> bool {
+    version.is_none_or(|version| (version.major, version.minor) >= (major, minor))
+}
+
+/// Stops with an explanation when git is missing or older than [`MIN_GIT_VERSION`].
+pub fn ensure_git_version() -> Result<()> {
+    if git_version_output().is_none() {
+        if git_program() != "git" {
+            bail!(
+                "cannot run the git at {} (set by ${} or [git] path in your sgit config)",
+                git_program().to_string_lossy(),
+                GIT_PROGRAM_ENV
+            );
+        }
+        bail!("git is not installed (or not on your PATH) - sgit needs it to work\n  hint: install it from https://git-scm.com/downloads");
+    }
+    if let Some(version) = git_version()
+        && version < MIN_GIT_VERSION
+    {
+        bail!(
+            "git {} is too old - sgit needs git {}.{} or newer\n  hint: upgrade from https://git-scm.com/downloads (or your package manager)",
+            version,
+            MIN_GIT_VERSION.major,
+            MIN_GIT_VERSION.minor
+        );
+    }
+    Ok(())
+}
+
+/// A git command for `args`, rewritten for the installed git: `git switch` (2.23) becomes
+/// `git checkout`, `git restore` (2.23) becomes `git reset`/`git checkout`, `git branch
+/// --show-current` (2.22) becomes `git symbolic-ref`, and `--pathspec-from-file` (2.26) is
+/// replaced by the paths themselves. `stdin` is what the caller would pipe in for
+/// `--pathspec-from-file=-`; the returned flag says whether git still reads it.
+pub fn git_command(args: &[&str], stdin: Option<&[u8]>) -> (GitCommand, bool) {
+    let (args, reads_stdin) = args_for_version(args, stdin, git_version());
+    let mut command = git_cmd();
+    command.args(&args);
+    (command, reads_stdin)
+}
+
+/// `args` rewritten for git `version`, as [`git_command`] describes.
+fn args_for_version(
+    args: &[&str],
+    stdin: Option<&[u8]>,
+    version: Option<GitVersion>,
+) -> (Vec<String>, bool) {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let mut reads_stdin = stdin.is_some();
+    if !supports(version, 2, 26) {
+        reads_stdin = inline_pathspec_file(&mut args, stdin);
+    }
+    if let Some(position) = subcommand_index(&args) {
+        let rest = args.split_off(position);
+        args.extend(for_version(rest, version));
+    }
+    (args, reads_stdin)
+}
+
+/// Where the subcommand starts, after options like `--literal-pathspecs` or `-c key=value`.
+pub(crate) fn subcommand_index(args: &[String]) -> Option<usize> {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        match arg.as_str() {
+            "-C" | "-c" => index += 2,
+            arg if arg.starts_with('-') => index += 1,
+            _ => return Some(index),
+        }
+    }
+    None
+}
+
+/// `subcommand` (with its arguments) spelled the way git `version` understands.
+fn for_version(mut subcommand: Vec<String>, version: Option<GitVersion>) -> Vec<String> {
+    match subcommand[0].as_str() {
+        "branch" if !supports(version, 2, 22) && subcommand[1..] == ["--show-current"] => {
+            ["symbolic-ref", "--short", "-q", "HEAD"]
+                .map(str::to_string)
+                .to_vec()
+        }
+        "switch" if !supports(version, 2, 23) => {
+            subcommand[0] = "checkout".to_string();
+            for arg in &mut subcommand[1..] {
+                let older = match arg.as_str() {
+                    "-c" | "--create" => "-b",
+                    "-C" | "--force-create" => "-B",
+                    "--discard-changes" => "-f",
+                    _ => continue,
+                };
+                *arg = older.to_string();
+            }
+            subcommand
+        }
+        "restore" if !supports(version, 2, 23) => {
+            let mut staged = false;
+            let mut worktree = false;
+            let mut rest = Vec::new();
+            for arg in subcommand.drain(1..) {
+                match arg.as_str() {
+                    "--staged" | "-S" => staged = true,
+                    "--worktree" | "-W" => worktree = true,
+                    "--" => {}
+                    _ => rest.push(arg),
+                }
+            }
+            // Options such as `--pathspec-file-nul` go before the `--`.
+            let (options, paths): (Vec<String>, Vec<String>) =
+                rest.into_iter().partition(|arg| arg.starts_with("--"));
+            let mut older: Vec<String> = match (staged, worktree) {
+                (true, false) => vec!["reset".into(), "-q".into(), "HEAD".into()],
+                (true, true) => vec!["checkout".into(), "HEAD".into()],
+                _ => vec!["checkout".into()],
+            };
+            older.extend(options);
+            if !paths.is_empty() {
+                older.push("--".into());
+                older.extend(paths);
+            }
+            older
+        }
+        _ => subcommand,
+    }
+}
+
+/// Replaces `--pathspec-from-file` (and `--pathspec-file-nul`) with the listed paths,
+/// read from `stdin` for `-`. Returns whether git still needs to read `stdin`.
+fn inline_pathspec_file(args: &mut Vec<String>, stdin: Option<&[u8]>) -> bool {
+    let Some(position) = args
+        .iter()
+        .position(|arg| arg.starts_with("--pathspec-from-file="))
+    else {
+        return stdin.is_some();
+    };
+    let source = args.remove(position)["--pathspec-from-file=".len()..].to_string();
+    let nul = match args.iter().position(|arg| arg == "--pathspec-file-nul") {
+        Some(flag) => {
+            args.remove(flag);
+            true
+        }
+        None => false,
+    };
+    let contents = if source == "-" {
+        stdin.map(<[u8]>::to_vec).unwrap_or_default()
+    } else {
+        fs::read(&source).unwrap_or_default()
+    };
+    let separator = if nul { b'\0' } else { b'\n' };
+    args.push("--".to_string());
+    args.extend(
+        contents
+            .split(|byte| *byte == separator)
+            .filter(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).into_owned()),
+    );
+    false
+}
+
 pub fn run_git(args: &[&str]) -> Result<()> {
-    let output = StdCommand::new("git")
-        .args(args)
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to execute git {} - is git installed?",
-                args.join(" ")
-            )
-        })?;
+    let output = git_command(args, None).0.output().with_context(|| {
+        format!(
+            "failed to execute git {} - is git installed?",
+            args.join(" ")
+        )
+    })?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -25,19 +390,47 @@ pub fn run_git(args: &[&str]) -> Result<()> {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
+        Err(git_failure(args, &stderr))
+    }
+}
+
+/// Like [`run_git`], but returns stdout instead of printing it.
+pub fn git_output(args: &[&str]) -> Result<String> {
+    let output = git_command(args, None).0.output().with_context(|| {
+        format!(
+            "failed to execute git {} - is git installed?",
+            args.join(" ")
+        )
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git_failure(args, &stderr))
     }
 }
 
 pub fn run_git_quiet(args: &[&str]) -> Result<()> {
-    let output = StdCommand::new("git")
-        .args(args)
+    let output = git_command(args, None).0.output().with_context(|| {
+        format!(
+            "failed to execute git {} - is git installed?",
+            args.join(" ")
+        )
+    })?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git_failure(args, &stderr))
+    }
+}
+
+pub fn run_git_quiet_with_env(args: &[&str], envs: &[(&str, &str)]) -> Result<()> {
+    let output = git_command(args, None)
+        .0
+        .envs(envs.iter().copied())
         .output()
         .with_context(|| {
             format!(
@@ -50,19 +443,13 @@ pub fn run_git_quiet(args: &[&str]) -> Result<()> {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
+        Err(git_failure(args, &stderr))
     }
 }
 
 pub fn run_git_silent(args: &[&str]) -> Result<()> {
-    let output = StdCommand::new("git")
-        .args(args)
+    let output = git_command(args, None)
+        .0
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::piped())
         .output()
@@ -77,19 +464,72 @@ pub fn run_git_silent(args: &[&str]) -> Result<()> {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
+        Err(git_failure(args, &stderr))
+    }
+}
+
+/// Like [`run_git_silent`], but feeds `input` to git's stdin (for `--pathspec-from-file=-`).
+pub fn run_git_silent_with_input(args: &[&str], input: &[u8]) -> Result<()> {
+    let (mut command, reads_stdin) = git_command(args, Some(input));
+    let mut child = command
+        .stdin(if reads_stdin {
+            std::process::Stdio::piped()
+        } else {
+            std::process::Stdio::null()
+        })
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "failed to execute git {} - is git installed?",
+                args.join(" ")
+            )
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .with_context(|| format!("writing to git {}", args.join(" ")))?;
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("waiting for git {}", args.join(" ")))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git_failure(args, &stderr))
+    }
+}
+
+/// Like [`git_output`], but runs git in `dir` instead of the current directory.
+pub fn git_output_in_dir(args: &[&str], dir: &Path) -> Result<String> {
+    let output = git_command(args, None)
+        .0
+        .current_dir(dir)
+        .output()
+        .with_context(|| {
+            format!(
+                "failed to execute git {} in {} - is git installed?",
+                args.join(" "),
+                dir.display()
+            )
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(git_failure(args, &stderr))
     }
 }
 
-pub fn run_git_in_dir_silent(args: &[&str], dir: &str) -> Result<()> {
-    let output = StdCommand::new("git")
-        .args(args)
+pub fn run_git_in_dir_silent(args: &[&str], dir: impl AsRef<Path>) -> Result<()> {
+    let dir = dir.as_ref();
+    let output = git_command(args, None)
+        .0
         .current_dir(dir)
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::piped())
@@ -98,7 +538,7 @@ pub fn run_git_in_dir_silent(args: &[&str], dir: &str) -> Result<()> {
             format!(
                 "failed to execute git {} in {} - is git installed?",
                 args.join(" "),
-                dir
+                dir.display()
             )
         })?;
 
@@ -106,18 +546,39 @@ pub fn run_git_in_dir_silent(args: &[&str], dir: &str) -> Result<()> {
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        let hint = suggest_hint_for_git_error(&stderr, args);
-        bail!(
-            "git {} failed:{}{}",
-            args.join(" "),
-            format_stderr(&stderr),
-            hint
-        );
+        Err(git_failure(args, &stderr))
     }
 }
 
+/// The error the `run_git*` functions return when `git <args>` fails with `stderr`.
+pub fn git_failure(args: &[&str], stderr: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "git {} failed:{}{}",
+        args.join(" "),
+        format_stderr(stderr),
+        suggest_hint_for_git_error(stderr, args)
+    )
+}
+
+/// git's first line of complaint in an error from the `run_git*` functions (without its
+/// `fatal:`), for places with room for one line. Other errors give their first line.
+pub fn git_complaint(err: &anyhow::Error) -> String {
+    let text = err.to_string();
+    let mut lines = text.lines().map(str::trim);
+    let first = lines.next().unwrap_or_default();
+    lines
+        .filter(|line| !line.is_empty() && !line.starts_with("hint:"))
+        .map(|line| {
+            line.trim_start_matches("fatal: ")
+                .trim_start_matches("error: ")
+        })
+        .next()
+        .unwrap_or(first)
+        .to_string()
+}
+
 pub fn check_in_repo() -> Result<()> {
-    StdCommand::new("git")
+    git_cmd()
         .args(["rev-parse", "--git-dir"])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
@@ -153,6 +614,15 @@ fn suggest_hint_for_git_error(stderr: &str, args: &[&str]) -> String {
         return format!("\n  hint: {}", NO_STAGED_HINT);
     }
 
+    if stderr_lower.contains("please tell me who you are") {
+        return "\n  hint: git needs your name and email for commits - run 'sgit setup'"
+            .to_string();
+    }
+
+    if (cmd == "commit" || cmd == "commit-tree") && stderr_lower.contains("sign") {
+        return "\n  hint: signing failed - check your key with 'sgit signing', or set it up again with 'sgit signing setup'".to_string();
+    }
+
     if cmd == "push" {
         if stderr_lower.contains("no upstream branch") {
             return "\n  hint: set upstream with 'git push -u origin <branch>' or use 'sgit push' from a tracked branch".to_string();
@@ -171,7 +641,7 @@ fn suggest_hint_for_git_error(stderr: &str, args: &[&str]) -> String {
             return "\n  hint: branch has no upstream - try 'git branch --set-upstream-to=origin/<branch>'".to_string();
         }
         if stderr_lower.contains("conflict") {
-            return "\n  hint: resolve merge conflicts, then commit the resolution".to_string();
+            return "\n  hint: resolve merge conflicts, run 'sgit stage .', then 'sgit continue' (or 'sgit abort')".to_string();
         }
     }
 
@@ -184,6 +654,12 @@ fn suggest_hint_for_git_error(stderr: &str, args: &[&str]) -> String {
         }
     }
 
+    if matches!(cmd, "rebase" | "revert" | "cherry-pick" | "merge")
+        && stderr_lower.contains("conflict")
+    {
+        return "\n  hint: resolve the conflicts, run 'sgit stage .', then 'sgit continue' (or 'sgit abort')".to_string();
+    }
+
     if cmd == "branch" && stderr_lower.contains("already exists") {
         return "\n  hint: branch name already in use, choose a different name".to_string();
     }
@@ -194,3 +670,151 @@ fn suggest_hint_for_git_error(stderr: &str, args: &[&str]) -> String {
 
     String::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_GIT: Option<GitVersion> = Some(MIN_GIT_VERSION);
+
+    fn version(major: u32, minor: u32, patch: u32) -> Option<GitVersion> {
+        Some(GitVersion {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    fn rewrite(args: &[&str], version: Option<GitVersion>) -> Vec<String> {
+        args_for_version(args, None, version).0
+    }
+
+    #[test]
+    fn parses_version_strings() {
+        assert_eq!(parse_git_version("git version 2.43.0"), version(2, 43, 0));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)"),
+            version(2, 39, 3)
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.1.windows.1"),
+            version(2, 45, 1)
+        );
+        assert_eq!(parse_git_version("git version 2.20"), version(2, 20, 0));
+        assert_eq!(
+            parse_git_version("git version 2.34.1-rc0"),
+            version(2, 34, 1)
+        );
+        assert_eq!(parse_git_version("hub version 2"), None);
+        assert_eq!(parse_git_version(""), None);
+    }
+
+    #[test]
+    fn compares_versions() {
+        assert!(version(2, 23, 0) > version(2, 22, 9));
+        assert!(supports(version(2, 23, 0), 2, 23));
+        assert!(supports(version(3, 0, 0), 2, 26));
+        assert!(!supports(version(2, 22, 5), 2, 23));
+        assert!(supports(None, 2, 26));
+    }
+
+    #[test]
+    fn leaves_commands_alone_on_modern_git() {
+        for args in [
+            &["switch", "-c", "feature"][..],
+            &["restore", "--staged", "--", "a.txt"],
+            &["branch", "--show-current"],
+            &["add", "--pathspec-from-file=-", "--pathspec-file-nul"],
+        ] {
+            assert_eq!(rewrite(args, version(2, 43, 0)), args);
+            assert_eq!(rewrite(args, None), args);
+        }
+    }
+
+    #[test]
+    fn rewrites_switch_as_checkout() {
+        assert_eq!(
+            rewrite(&["switch", "-c", "feature", "origin/main"], OLD_GIT),
+            ["checkout", "-b", "feature", "origin/main"]
+        );
+        assert_eq!(
+            rewrite(&["switch", "--force-create", "topic"], OLD_GIT),
+            ["checkout", "-B", "topic"]
+        );
+        assert_eq!(
+            rewrite(&["switch", "--discard-changes", "main"], OLD_GIT),
+            ["checkout", "-f", "main"]
+        );
+        assert_eq!(
+            rewrite(&["-c", "core.quotepath=false", "switch", "main"], OLD_GIT),
+            ["-c", "core.quotepath=false", "checkout", "main"]
+        );
+    }
+
+    #[test]
+    fn rewrites_restore_by_what_it_restores() {
+        assert_eq!(
+            rewrite(&["restore", "--staged", "--", "a.txt"], OLD_GIT),
+            ["reset", "-q", "HEAD", "--", "a.txt"]
+        );
+        assert_eq!(
+            rewrite(&["restore", "-S", "-W", "a.txt", "b.txt"], OLD_GIT),
+            ["checkout", "HEAD", "--", "a.txt", "b.txt"]
+        );
+        assert_eq!(
+            rewrite(&["restore", "--", "a.txt"], OLD_GIT),
+            ["checkout", "--", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn rewrites_show_current_as_symbolic_ref() {
+        assert_eq!(
+            rewrite(&["branch", "--show-current"], version(2, 21, 0)),
+            ["symbolic-ref", "--short", "-q", "HEAD"]
+        );
+        assert_eq!(
+            rewrite(&["branch", "--show-current"], version(2, 22, 0)),
+            ["branch", "--show-current"]
+        );
+        assert_eq!(
+            rewrite(&["branch", "-D", "old"], OLD_GIT),
+            ["branch", "-D", "old"]
+        );
+    }
+
+    #[test]
+    fn inlines_pathspec_files_from_stdin() {
+        let (args, reads_stdin) = args_for_version(
+            &[
+                "restore",
+                "--staged",
+                "--pathspec-from-file=-",
+                "--pathspec-file-nul",
+            ],
+            Some(b"a.txt\0dir/b c.txt\0"),
+            version(2, 25, 0),
+        );
+        assert_eq!(args, ["restore", "--staged", "--", "a.txt", "dir/b c.txt"]);
+        assert!(!reads_stdin);
+
+        let (args, reads_stdin) = args_for_version(
+            &["add", "--pathspec-from-file=-"],
+            Some(b"one\ntwo\n"),
+            version(2, 25, 0),
+        );
+        assert_eq!(args, ["add", "--", "one", "two"]);
+        assert!(!reads_stdin);
+    }
+
+    #[test]
+    fn keeps_pathspec_files_on_git_that_reads_them() {
+        let (args, reads_stdin) = args_for_version(
+            &["add", "--pathspec-from-file=-"],
+            Some(b"one\n"),
+            version(2, 26, 0),
+        );
+        assert_eq!(args, ["add", "--pathspec-from-file=-"]);
+        assert!(reads_stdin);
+    }
+}
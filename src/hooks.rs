@@ -0,0 +1,329 @@
+//! Git hooks: where git looks for them, which are installed and enabled, and the
+//! templates `sgit hooks install` writes.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::git::git_cmd;
+use crate::paths::is_executable;
+use crate::status::get_repo_root;
+
+/// Starts each template's section of a hook script sgit manages, followed by its name.
+pub const TEMPLATE_MARKER: &str = "# Installed by sgit hooks:";
+
+/// A disabled hook is renamed with this suffix, which git ignores.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// A hook replaced by `sgit hooks install --force` is kept with this suffix.
+pub const BACKUP_SUFFIX: &str = ".backup";
+
+/// The pre-commit framework's configuration, at the repository root.
+pub const PRECOMMIT_CONFIG: &str = ".pre-commit-config.yaml";
+
+/// In the hook scripts `pre-commit install` writes.
+const PRECOMMIT_SIGNATURE: &str = "File generated by pre-commit";
+
+/// A value a template needs, written into its body wherever `@name@` appears.
+pub struct HookParam {
+    pub name: &'static str,
+    pub prompt: &'static str,
+    pub default: &'static str,
+}
+
+pub struct HookTemplate {
+    pub name: &'static str,
+    /// The git hook it installs as.
+    pub hook: &'static str,
+    pub about: &'static str,
+    pub params: &'static [HookParam],
+    /// The shell commands to run; the hook fails when they do. Parameters only appear
+    /// inside single quotes.
+    pub body: &'static str,
+}
+
+pub const HOOK_TEMPLATES: &[HookTemplate] = &[
+    HookTemplate {
+        name: "cargo-fmt",
+        hook: "pre-commit",
+        about: "refuse commits that are not formatted with cargo fmt",
+        params: &[],
+        body: "cargo fmt --all -- --check || {\n  echo \"Run 'cargo fmt' and stage the result, then commit again.\" >&2\n  exit 1\n}\n",
+    },
+    HookTemplate {
+        name: "prettier",
+        hook: "pre-commit",
+        about: "refuse commits that are not formatted with prettier",
+        params: &[],
+        body: "npx --no-install prettier --check . || {\n  echo \"Run 'npx prettier --write .' and stage the result, then commit again.\" >&2\n  exit 1\n}\n",
+    },
+    HookTemplate {
+        name: "black",
+        hook: "pre-commit",
+        about: "refuse commits that are not formatted with black",
+        params: &[],
+        body: "black --check --quiet . || {\n  echo \"Run 'black .' and stage the result, then commit again.\" >&2\n  exit 1\n}\n",
+    },
+    HookTemplate {
+        name: "cargo-clippy",
+        hook: "pre-commit",
+        about: "refuse commits with clippy warnings",
+        params: &[],
+        body: "cargo clippy --all-targets --quiet -- -D warnings\n",
+    },
+    HookTemplate {
+        name: "eslint",
+        hook: "pre-commit",
+        about: "refuse commits with eslint errors",
+        params: &[],
+        body: "npx --no-install eslint .\n",
+    },
+    HookTemplate {
+        name: "ruff",
+        hook: "pre-commit",
+        about: "refuse commits with ruff lint errors",
+        params: &[],
+        body: "ruff check --quiet .\n",
+    },
+    HookTemplate {
+        name: "cargo-test",
+        hook: "pre-commit",
+        about: "run cargo test before every commit",
+        params: &[],
+        body: "cargo test --quiet\n",
+    },
+    HookTemplate {
+        name: "npm-test",
+        hook: "pre-commit",
+        about: "run npm test before every commit",
+        params: &[],
+        body: "npm test --silent\n",
+    },
+    HookTemplate {
+        name: "pytest",
+        hook: "pre-commit",
+        about: "run pytest before every commit",
+        params: &[],
+        body: "python -m pytest -q\n",
+    },
+    HookTemplate {
+        name: "run-command",
+        hook: "pre-commit",
+        about: "run a command of your choice before every commit",
+        params: &[HookParam {
+            name: "command",
+            prompt: "Command to run before each commit",
+            default: "make check",
+        }],
+        body: "command='@command@'\nsh -c \"$command\"\n",
+    },
+    HookTemplate {
+        name: "commit-message",
+        hook: "commit-msg",
+        about: "require a short first line and a blank line after it",
+        params: &[HookParam {
+            name: "max_subject",
+            prompt: "Longest first line allowed, in characters",
+            default: "72",
+        }],
+        body: r#"max_subject='@max_subject@'
+subject=$(grep -v '^#' "$1" | head -n 1)
+if [ -z "$subject" ]; then
+  echo "The commit message is empty." >&2
+  exit 1
+fi
+if [ "${#subject}" -gt "$max_subject" ]; then
+  echo "The first line of the commit message is ${#subject} characters; keep it to $max_subject." >&2
+  exit 1
+fi
+if [ -n "$(grep -v '^#' "$1" | sed -n 2p)" ]; then
+  echo "Leave the second line of the commit message blank." >&2
+  exit 1
+fi
+"#,
+    },
+    HookTemplate {
+        name: "conventional-commits",
+        hook: "commit-msg",
+        about: "require messages like 'feat: ...' or 'fix(scope): ...'",
+        params: &[HookParam {
+            name: "types",
+            prompt: "Allowed types, separated by |",
+            default: "feat|fix|docs|style|refactor|perf|test|build|ci|chore|revert",
+        }],
+        body: r#"types='@types@'
+subject=$(grep -v '^#' "$1" | head -n 1)
+case "$subject" in
+  "Merge "*|"Revert "*|"fixup! "*|"squash! "*) ;;
+  *)
+    if ! printf '%s\n' "$subject" | grep -Eq "^($types)(\([^)]+\))?!?: .+"; then
+      echo "Start the commit message with a type, like 'feat: add login' or 'fix(api): handle timeouts'." >&2
+      echo "Allowed types: $types" >&2
+      exit 1
+    fi
+    ;;
+esac
+"#,
+    },
+    HookTemplate {
+        name: "branch-name",
+        hook: "pre-push",
+        about: "refuse to push branches whose names break the team's pattern",
+        params: &[HookParam {
+            name: "pattern",
+            prompt: "Pattern branch names must match (extended regex)",
+            default: "^(main|master|develop|(feature|fix|chore|release)/[a-z0-9._-]+)$",
+        }],
+        // Reads the refs being pushed from stdin, so it has to be the only section that does.
+        body: r#"pattern='@pattern@'
+while read -r local_ref local_sha remote_ref remote_sha; do
+  case "$local_ref" in
+    refs/heads/*) ;;
+    *) continue ;;
+  esac
+  branch=${local_ref#refs/heads/}
+  if ! printf '%s\n' "$branch" | grep -Eq "$pattern"; then
+    echo "The branch name '$branch' does not match $pattern - rename it with 'git branch -m'." >&2
+    exit 1
+  fi
+done
+"#,
+    },
+];
+
+pub fn find_template(name: &str) -> Option<&'static HookTemplate> {
+    HOOK_TEMPLATES
+        .iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name))
+}
+
+/// The start of a hook script sgit manages; template sections follow it.
+pub const SCRIPT_HEADER: &str =
+    "#!/bin/sh\n# Managed by sgit - 'sgit hooks list' shows what it runs.\nset -e\n";
+
+/// The part of a hook script that runs `template` with the parameter `values`.
+pub fn render_section(template: &HookTemplate, values: &[(&str, String)]) -> String {
+    let mut body = template.body.to_string();
+    for (name, value) in values {
+        // Parameters sit inside single quotes, which cannot be escaped within them.
+        body = body.replace(&format!("@{}@", name), &value.replace('\'', "'\\''"));
+    }
+    format!(
+        "\n{} {}\n# {}\n{}",
+        TEMPLATE_MARKER, template.name, template.about, body
+    )
+}
+
+/// `script` with the section for `template` swapped for `section`, or `None` when it has
+/// no such section.
+pub fn replace_section(script: &str, template: &HookTemplate, section: &str) -> Option<String> {
+    let start = script.find(&format!("\n{} {}\n", TEMPLATE_MARKER, template.name))?;
+    let end = script[start + 1..]
+        .find(&format!("\n{} ", TEMPLATE_MARKER))
+        .map_or(script.len(), |offset| start + 1 + offset);
+    Some(format!("{}{}{}", &script[..start], section, &script[end..]))
+}
+
+/// The directory git runs hooks from: `core.hooksPath`, or `.git/hooks`.
+pub fn hooks_dir() -> Result<PathBuf> {
+    let output = git_cmd()
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    // Relative to the current directory; made absolute so it can be shown and used anywhere.
+    let dir = env::current_dir()
+        .context("failed to read the current directory")?
+        .join(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(dir.canonicalize().unwrap_or(dir))
+}
+
+pub struct InstalledHook {
+    /// The git hook's name, without the disabled suffix.
+    pub name: String,
+    pub path: PathBuf,
+    pub enabled: bool,
+    pub executable: bool,
+    /// The sgit templates it runs.
+    pub templates: Vec<String>,
+    /// Written by `pre-commit install`, to run the checks in .pre-commit-config.yaml.
+    pub precommit: bool,
+}
+
+/// The hooks in `dir`, enabled or disabled, leaving out git's `.sample` files and the
+/// backups sgit and pre-commit keep.
+pub fn installed_hooks(dir: &Path) -> Result<Vec<InstalledHook>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut hooks: Vec<InstalledHook> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_none_or(|ext| ext != "sample" && ext != "backup" && ext != "legacy")
+        })
+        .map(|path| {
+            let file_name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let (name, enabled) = match file_name.strip_suffix(DISABLED_SUFFIX) {
+                Some(name) => (name.to_string(), false),
+                None => (file_name, true),
+            };
+            let script = fs::read_to_string(&path).unwrap_or_default();
+            let templates = script
+                .lines()
+                .filter_map(|line| line.strip_prefix(TEMPLATE_MARKER))
+                .map(|name| name.trim().to_string())
+                .collect();
+            InstalledHook {
+                name,
+                executable: is_executable(&path),
+                path,
+                enabled,
+                templates,
+                precommit: script.contains(PRECOMMIT_SIGNATURE),
+            }
+        })
+        .collect();
+    hooks.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(hooks)
+}
+
+/// Whether the repository configures the pre-commit framework.
+pub fn has_precommit_config() -> bool {
+    get_repo_root().is_ok_and(|root| Path::new(&root).join(PRECOMMIT_CONFIG).is_file())
+}
+
+/// Whether git runs the pre-commit framework before each commit.
+pub fn precommit_installed() -> bool {
+    hooks_dir()
+        .and_then(|dir| installed_hooks(&dir))
+        .is_ok_and(|hooks| {
+            hooks
+                .iter()
+                .any(|hook| hook.name == "pre-commit" && hook.enabled && hook.precommit)
+        })
+}
+
+#[cfg(unix)]
+pub fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = path
+        .metadata()
+        .with_context(|| format!("failed to read {}", path.display()))?
+        .permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+        .with_context(|| format!("failed to change the permissions of {}", path.display()))
+}
+
+#[cfg(not(unix))]
+pub fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
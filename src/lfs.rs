@@ -0,0 +1,151 @@
+//! Git LFS helpers: spotting staged files that belong in LFS, setting up tracking for
+//! them, and summarising the repository's LFS files and locks for `sgit status`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::git::{git_cmd, run_git_silent};
+use crate::status::get_repo_root;
+
+/// Files at least this big are suggested for LFS whatever their type.
+pub const LARGE_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Types that are binary, rarely diff usefully, and tend to grow with every edit.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "psd", "ai", "sketch", "fig", "blend", "fbx", "obj", "max", "zip", "7z", "rar", "tar", "gz",
+    "tgz", "bz2", "xz", "iso", "dmg", "mp4", "mov", "avi", "mkv", "webm", "wav", "mp3", "flac",
+    "aiff", "exe", "dll", "so", "dylib", "jar", "bin", "pak",
+];
+
+pub fn is_installed() -> bool {
+    git_cmd()
+        .args(["lfs", "version"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Whether the repository's .gitattributes sends any files through LFS.
+pub fn repo_uses_lfs() -> bool {
+    let Ok(root) = get_repo_root() else {
+        return false;
+    };
+    fs::read_to_string(Path::new(&root).join(".gitattributes"))
+        .is_ok_and(|attributes| attributes.contains("filter=lfs"))
+}
+
+/// The `(path, size)` entries of `staged` (paths from the repository root) that are large
+/// or of a binary type and not already stored in LFS.
+pub fn candidates(staged: &[(String, u64)]) -> Result<Vec<(String, u64)>> {
+    let root = get_repo_root()?;
+    let suspicious: Vec<&(String, u64)> = staged
+        .iter()
+        .filter(|(path, size)| *size >= LARGE_FILE_BYTES || has_binary_extension(path))
+        .collect();
+    if suspicious.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut args = vec!["check-attr", "-z", "filter", "--"];
+    args.extend(suspicious.iter().map(|(path, _)| path.as_str()));
+    let output = git_cmd()
+        .args(&args)
+        .current_dir(&root)
+        .output()
+        .context("running git check-attr")?;
+    // `path NUL attribute NUL value NUL` for each path.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    let in_lfs: Vec<&str> = fields
+        .chunks(3)
+        .filter(|chunk| chunk.len() == 3 && chunk[2] == "lfs")
+        .map(|chunk| chunk[0])
+        .collect();
+    Ok(suspicious
+        .into_iter()
+        .filter(|(path, _)| !in_lfs.contains(&path.as_str()))
+        .cloned()
+        .collect())
+}
+
+fn has_binary_extension(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').is_some_and(|(stem, ext)| {
+        !stem.is_empty() && BINARY_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+    })
+}
+
+/// The `git lfs track` pattern for a file: every file with its extension, or the file
+/// itself when it has none.
+pub fn track_pattern(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("*.{}", ext),
+        _ => path.to_string(),
+    }
+}
+
+/// Installs the LFS hooks in this repository and tracks `patterns` in .gitattributes.
+pub fn track_patterns(patterns: &[String]) -> Result<()> {
+    run_git_silent(&["lfs", "install", "--local"])?;
+    for pattern in patterns {
+        run_git_silent(&["lfs", "track", pattern])?;
+    }
+    Ok(())
+}
+
+/// What `sgit status` shows about LFS.
+pub struct LfsStatus {
+    pub installed: bool,
+    pub files: usize,
+    /// Files checked out as pointers because their content was never downloaded.
+    pub missing: usize,
+    /// Files you hold a lock on, as git-lfs last heard from the server.
+    pub locked: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Lock {
+    path: String,
+}
+
+/// The LFS state of the repository, or `None` when it does not use LFS.
+pub fn status() -> Option<LfsStatus> {
+    if !repo_uses_lfs() {
+        return None;
+    }
+    if !is_installed() {
+        return Some(LfsStatus {
+            installed: false,
+            files: 0,
+            missing: 0,
+            locked: Vec::new(),
+        });
+    }
+    // Each line is `<oid> <*|-> <path>`: `*` when the content is present, `-` for a pointer.
+    let listing = git_cmd().args(["lfs", "ls-files"]).output().ok()?;
+    let listing = String::from_utf8_lossy(&listing.stdout);
+    let marks: Vec<&str> = listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    // `--local` reads the cached locks instead of asking the server, so status stays fast.
+    let locked = git_cmd()
+        .args(["lfs", "locks", "--local", "--json"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| serde_json::from_slice::<Vec<Lock>>(&output.stdout).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lock| lock.path)
+        .collect();
+    Some(LfsStatus {
+        installed: true,
+        files: marks.len(),
+        missing: marks.iter().filter(|mark| **mark == "-").count(),
+        locked,
+    })
+}
@@ -0,0 +1,133 @@
+//! Checks a commit message against the rules in the `[lint]` section of the sgit config.
+
+use anyhow::{bail, Result};
+
+use crate::config::{LintConfig, LintLevel};
+use crate::conventional::{header_type, COMMIT_TYPES};
+
+/// Subjects git or sgit generate, which are not the user's to word.
+const GENERATED_PREFIXES: &[&str] = &["fixup! ", "squash! ", "amend! ", "Merge ", "Revert \""];
+
+/// Words ending in "ed" that are not past tense.
+const NOT_PAST_TENSE: &[&str] = &[
+    "bleed", "breed", "embed", "exceed", "feed", "need", "proceed", "seed", "shed", "shred",
+    "speed", "spread", "succeed", "thread",
+];
+
+/// Third-person verbs that often start a subject ("Adds", "Fixes").
+const THIRD_PERSON: &[&str] = &[
+    "adds",
+    "allows",
+    "bumps",
+    "changes",
+    "cleans",
+    "creates",
+    "deletes",
+    "fixes",
+    "implements",
+    "improves",
+    "makes",
+    "moves",
+    "refactors",
+    "removes",
+    "renames",
+    "replaces",
+    "supports",
+    "updates",
+    "uses",
+];
+
+/// Every rule the message breaks, as a sentence each.
+pub fn lint_message(message: &str, config: &LintConfig) -> Vec<String> {
+    let subject = message.lines().next().unwrap_or("").trim_end();
+    if GENERATED_PREFIXES
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+    {
+        return Vec::new();
+    }
+    let mut problems = Vec::new();
+
+    let length = subject.chars().count();
+    if config.max_subject_length > 0 && length > config.max_subject_length {
+        problems.push(format!(
+            "the subject line is {} characters long; keep it to {} or fewer",
+            length, config.max_subject_length
+        ));
+    }
+    if config.no_trailing_period && subject.ends_with('.') && !subject.ends_with("...") {
+        problems.push("the subject line ends with a period; leave it off".to_string());
+    }
+
+    let kind = header_type(message);
+    if config.conventional {
+        match kind {
+            None => problems.push(
+                "the subject is not in Conventional Commit form, e.g. 'feat(cli): add --yes'"
+                    .to_string(),
+            ),
+            Some(kind) if !COMMIT_TYPES.iter().any(|(known, _)| *known == kind) => {
+                let known: Vec<&str> = COMMIT_TYPES.iter().map(|(known, _)| *known).collect();
+                problems.push(format!(
+                    "'{}' is not a Conventional Commit type; use one of {}",
+                    kind,
+                    known.join(", ")
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    if config.imperative {
+        // In a Conventional Commit the description starts after the `type(scope): `.
+        let description = match kind {
+            Some(_) => subject.split_once(": ").map_or(subject, |(_, rest)| rest),
+            None => subject,
+        };
+        // Skip a leading gitmoji, whether an emoji or a `:shortcode:`.
+        if let Some(word) = description
+            .split_whitespace()
+            .find(|word| word.chars().any(char::is_alphanumeric) && !word.starts_with(':'))
+            && !is_imperative(word)
+        {
+            problems.push(format!(
+                "'{}' does not read as a command; write the subject in the imperative mood, e.g. 'Fix crash' rather than 'Fixed crash' or 'Fixes crash'",
+                word
+            ));
+        }
+    }
+    problems
+}
+
+/// Prints the problems with `message` and fails when `[lint] level` is "block".
+pub fn enforce(message: &str, config: &LintConfig) -> Result<()> {
+    if config.level == LintLevel::Off {
+        return Ok(());
+    }
+    let problems = lint_message(message, config);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("⚠ Commit message: {}", problem);
+    }
+    if config.level == LintLevel::Block {
+        bail!("the commit message breaks the [lint] rules in your sgit config - reword it, or pass --no-lint to commit anyway");
+    }
+    Ok(())
+}
+
+fn is_imperative(word: &str) -> bool {
+    let word: String = word
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<String>()
+        .to_lowercase();
+    if word.len() > 4 && word.ends_with("ed") && !NOT_PAST_TENSE.contains(&word.as_str()) {
+        return false;
+    }
+    if word.len() > 5 && word.ends_with("ing") && word != "string" {
+        return false;
+    }
+    !THIRD_PERSON.contains(&word.as_str())
+}
@@ -1,77 +1,330 @@
+mod alias;
+mod capabilities;
+mod checks;
+mod choices;
 mod cli;
 mod commands;
+mod config;
+mod conventional;
+mod crash;
+mod forge;
+mod format;
 mod git;
+mod hooks;
+mod journal;
+mod lfs;
+mod lint;
+mod message;
+mod paths;
+mod plugin;
+mod progress;
+mod remote;
+mod render;
+mod snapshot;
 mod status;
+mod submodule;
+mod templates;
+mod whitespace;
+mod workspace;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use capabilities::{check_capabilities, Gate};
 use clap::Parser;
-use cli::{Cli, SgitCommand};
+use cli::{BundleAction, Cli, ExitCode, SgitCommand};
 use commands::{
-    create_branch, restore_stage, run_branch_interactive, run_commit, run_pull, run_push,
-    run_reset, run_sync, stage_targets,
+    create_branch, restore_stage, run_abort, run_alias, run_attic, run_auto_rebase, run_autosync,
+    run_backup, run_blame, run_branch_interactive, run_bundle, run_ci, run_commit, run_compare,
+    run_config, run_conflicts, run_connect, run_contains, run_continue, run_diff, run_doctor,
+    run_explain_last, run_export, run_fetch, run_find, run_fixup, run_grep, run_history, run_hooks,
+    run_ignore, run_import, run_init, run_learn, run_log, run_maintenance, run_merge,
+    run_merge_queue, run_profile, run_prompt, run_pull, run_push, run_repos, run_reset, run_revert,
+    run_reword, run_setup, run_show, run_signers, run_signing, run_size, run_sparse, run_split,
+    run_squash, run_status, run_submodule, run_subtree, run_sync, run_timeline, run_undo_push,
+    run_untagged, run_unwip, run_verify_tree, run_watch, run_wip, run_workspace, run_worktree,
+    stage_targets, CommitOptions, DiffOptions, GrepOptions, InitOptions, LogOptions, MergeOptions,
 };
-use git::{check_in_repo, run_git, run_git_silent};
+use git::check_in_repo;
 
 fn main() {
-    if let Err(err) = run() {
+    crash::install_panic_hook();
+    let args: Vec<String> = std::env::args().collect();
+    // The shell prompt helper runs before every prompt: it skips aliases, plugins, and the
+    // git version check, and prints nothing at all when something goes wrong.
+    if is_prompt(&args) {
+        if change_directory(args).is_ok() {
+            run_prompt();
+        }
+        return;
+    }
+    let result = change_directory(args).and_then(|args| {
+        let outcome = run(args.clone());
+        record_invocation(&args, &outcome);
+        outcome
+    });
+    if let Err(err) = result {
+        if let Some(ExitCode(code)) = err.downcast_ref::<ExitCode>() {
+            std::process::exit(*code);
+        }
         for cause in err.chain() {
             eprintln!("error: {}", cause);
         }
+        if crash::is_internal(&err) {
+            crash::report_error(&err);
+        }
         std::process::exit(1);
     }
 }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Keeps how this run went for `sgit explain-last`. The shell prompt helper runs on
+/// every prompt and would always overwrite the interesting record, so it is skipped.
+fn record_invocation(args: &[String], result: &Result<()>) {
+    let args: Vec<String> = args.iter().skip(1).cloned().collect();
+    let subcommand = args.iter().find(|arg| !arg.starts_with('-'));
+    if matches!(
+        subcommand.map(String::as_str),
+        None | Some("explain-last" | "prompt")
+    ) {
+        return;
+    }
+    // Outside a repository there is nowhere to keep it.
+    let _ = journal::record_invocation(args, result);
+}
+
+/// Whether this is a plain `sgit [-C <dir>] prompt`.
+fn is_prompt(args: &[String]) -> bool {
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-C" => {
+                rest.next();
+            }
+            arg if arg.starts_with("-C") => {}
+            "prompt" => return rest.next().is_none(),
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Handles the `-C <dir>` options before the subcommand the way git does: by moving into
+/// each directory in turn, so aliases, plugins, and every git call see that repository.
+/// Returns the arguments without them.
+fn change_directory(args: Vec<String>) -> Result<Vec<String>> {
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    remaining.extend(args.next());
+    while let Some(arg) = args.next() {
+        let dir = match arg.strip_prefix("-C") {
+            Some("") => match args.next() {
+                Some(dir) => dir,
+                None => bail!("-C needs a directory, e.g. 'sgit -C ~/projects/app status'"),
+            },
+            Some(dir) => dir.to_string(),
+            None if arg.starts_with('-') => {
+                remaining.push(arg);
+                continue;
+            }
+            // The subcommand; anything after it belongs to it.
+            None => {
+                remaining.push(arg);
+                remaining.extend(args);
+                break;
+            }
+        };
+        // An empty directory leaves the current one, as in git.
+        if !dir.is_empty() {
+            std::env::set_current_dir(&dir)
+                .with_context(|| format!("cannot change to '{}'", dir))?;
+        }
+    }
+    Ok(remaining)
+}
+
+fn run(args: Vec<String>) -> Result<()> {
+    let args = alias::expand_aliases(args)?;
+    if let Some(code) = plugin::run_external(&args)? {
+        if code != 0 {
+            return Err(ExitCode(code).into());
+        }
+        return Ok(());
+    }
+    let cli = Cli::parse_from(args);
+
+    if cli.progress_json {
+        progress::enable_json_events();
+    }
 
     if cli.explain {
         print_explanations();
         return Ok(());
     }
 
+    git::ensure_git_version()?;
+
     let command = match cli.command {
         Some(command) => command,
         None => bail!("'sgit' requires a subcommand; use --help to see the available list"),
     };
 
-    if !matches!(command, SgitCommand::Init) {
+    if !matches!(
+        command,
+        SgitCommand::Init { .. }
+            | SgitCommand::Import { .. }
+            | SgitCommand::ImportBundle { verify: false, .. }
+            | SgitCommand::Bundle {
+                action: BundleAction::Fetch { .. }
+            }
+            | SgitCommand::Learn { .. }
+            | SgitCommand::Prompt
+            | SgitCommand::Setup
+            | SgitCommand::Config { .. }
+            | SgitCommand::Alias { .. }
+            | SgitCommand::Doctor
+            | SgitCommand::Repos { .. }
+            | SgitCommand::Workspace { .. }
+    ) {
         check_in_repo()?;
+        if let Gate::Handled = check_capabilities(&command)? {
+            return Ok(());
+        }
     }
 
     match command {
-        SgitCommand::Init => {
-            run_git_silent(&["init"])?;
-            println!("✓ Initialized Git repository");
+        SgitCommand::Init {
+            directory,
+            ignore,
+            branch,
+            commit,
+            remote,
+            yes,
+            bare,
+        } => run_init(InitOptions {
+            directory,
+            ignore,
+            branch,
+            commit,
+            remote,
+            yes,
+            bare,
+        })?,
+        SgitCommand::Import { remote, split } => run_import(remote, split)?,
+        SgitCommand::Export {
+            target,
+            format,
+            output,
+            path,
+        } => run_export(target, format, output, path)?,
+        SgitCommand::Bundle { action } => run_bundle(action)?,
+        SgitCommand::ExportBundle { target, output } => {
+            let (since, tip) = match target.as_deref().and_then(|t| t.split_once("..")) {
+                Some((base, tip)) => (Some(base.to_string()), tip.to_string()),
+                None => (None, target.unwrap_or_default()),
+            };
+            run_bundle(BundleAction::Create {
+                refs: [tip].into_iter().filter(|tip| !tip.is_empty()).collect(),
+                all: false,
+                since,
+                output,
+            })?
         }
+        SgitCommand::ImportBundle {
+            file,
+            branch,
+            verify,
+        } => run_bundle(match verify {
+            true => BundleAction::Verify { file },
+            false => BundleAction::Fetch {
+                file,
+                branches: Vec::new(),
+                into: branch,
+            },
+        })?,
         SgitCommand::Stage {
             targets,
             all,
             tracked,
         } => stage_targets(&targets, all, tracked)?,
         SgitCommand::Unstage { targets, all } => restore_stage(&targets, all)?,
-        SgitCommand::Status { short } => {
-            if short {
-                run_git(&["status", "-sb"])?;
-            } else {
-                run_git(&["status"])?;
-            }
-        }
-        SgitCommand::Log { short } => {
-            if short {
-                run_git(&["log", "--oneline", "--decorate", "-n", "20"])?;
-            } else {
-                run_git(&["log", "--decorate", "-n", "40"])?;
-            }
-        }
-        SgitCommand::Diff { path, staged } => {
-            if staged {
-                run_git(&["diff", "--staged"])?;
-            } else if let Some(path) = path {
-                run_git(&["diff", path.as_str()])?;
-            } else {
-                run_git(&["diff"])?;
-            }
-        }
+        SgitCommand::Ignore {
+            patterns,
+            template,
+            why,
+        } => run_ignore(patterns, template, why)?,
+        SgitCommand::Status { short, raw, watch } => run_status(short, raw, watch)?,
+        SgitCommand::Log {
+            short,
+            author,
+            since,
+            until,
+            path,
+            grep,
+            verify,
+        } => run_log(LogOptions {
+            short,
+            author,
+            since,
+            until,
+            path,
+            grep,
+            verify,
+        })?,
+        SgitCommand::Learn { topic } => run_learn(topic)?,
+        SgitCommand::Find { term } => run_find(term)?,
+        SgitCommand::Grep {
+            pattern,
+            paths,
+            ignore_case,
+            regex,
+            history,
+        } => run_grep(GrepOptions {
+            pattern,
+            paths,
+            ignore_case,
+            regex,
+            history,
+        })?,
+        SgitCommand::Blame { path, lines } => run_blame(path, lines)?,
+        SgitCommand::Show { target } => run_show(target)?,
+        SgitCommand::Prompt => run_prompt(),
+        SgitCommand::ExplainLast => run_explain_last()?,
+        SgitCommand::Timeline { branch, since, .. } => run_timeline(branch, since)?,
+        SgitCommand::History { action } => run_history(action)?,
+        SgitCommand::Reword {
+            target,
+            message,
+            edit,
+            force,
+        } => run_reword(target, message, edit, force)?,
+        SgitCommand::Split { force } => run_split(force)?,
+        SgitCommand::Squash {
+            count,
+            message,
+            force,
+        } => run_squash(count, message, force)?,
+        SgitCommand::Compare { a, b, diff } => run_compare(a, b, diff)?,
+        SgitCommand::Diff {
+            targets,
+            staged,
+            highlight_moved,
+            words,
+            stat,
+            tool,
+            plain,
+            against,
+            from,
+            to,
+        } => run_diff(DiffOptions {
+            targets,
+            staged,
+            highlight_moved,
+            words,
+            stat,
+            tool,
+            plain,
+            against,
+            from,
+            to,
+        })?,
         SgitCommand::Reset {
             all,
             staged,
@@ -79,22 +332,70 @@ fn run() -> Result<()> {
             tracked,
             untracked,
         } => run_reset(all, staged, unstaged, tracked, untracked)?,
-        SgitCommand::Branch { create } => {
-            if let Some(branch_name) = create {
+        SgitCommand::Revert {
+            target,
+            no_commit,
+            mainline,
+        } => run_revert(target, no_commit, mainline)?,
+        SgitCommand::Merge {
+            branch,
+            squash,
+            message,
+            yes,
+            abort,
+        } => run_merge(MergeOptions {
+            branch,
+            squash,
+            message,
+            yes,
+            abort,
+        })?,
+        SgitCommand::Continue => run_continue()?,
+        SgitCommand::Abort => run_abort()?,
+        SgitCommand::Branch {
+            create,
+            contains,
+            untagged,
+        } => {
+            if let Some(target) = contains {
+                run_contains(target)?;
+            } else if untagged {
+                run_untagged()?;
+            } else if let Some(branch_name) = create {
                 create_branch(&branch_name)?;
             } else {
                 run_branch_interactive()?;
             }
         }
-        SgitCommand::Push { remote, branch } => {
-            run_push(remote, branch)?;
+        SgitCommand::Connect { url, name } => run_connect(url, name)?,
+        SgitCommand::Push {
+            remote,
+            branch,
+            all_remotes,
+            force,
+        } => {
+            run_push(remote, branch, all_remotes, force)?;
         }
-        SgitCommand::Pull { remote, branch } => {
-            run_pull(remote, branch)?;
+        SgitCommand::Repos { action } => run_repos(action)?,
+        SgitCommand::Workspace { action } => run_workspace(action)?,
+        SgitCommand::Backup { action } => run_backup(action)?,
+        SgitCommand::UndoPush => run_undo_push()?,
+        SgitCommand::Fetch { remote, all, prune } => run_fetch(remote, all, prune)?,
+        SgitCommand::Pull {
+            remote,
+            branch,
+            rebase,
+            merge,
+        } => {
+            run_pull(remote, branch, rebase, merge)?;
         }
+        SgitCommand::Autosync { action } => run_autosync(action)?,
         SgitCommand::Sync { remote, branch } => {
             run_sync(remote.as_deref(), branch.as_deref())?;
         }
+        SgitCommand::MergeQueue { remote } => run_merge_queue(remote)?,
+        SgitCommand::AutoRebase { action } => run_auto_rebase(action)?,
+        SgitCommand::Ci { action } => run_ci(action)?,
         SgitCommand::Commit {
             message,
             all,
@@ -103,9 +404,52 @@ fn run() -> Result<()> {
             push,
             amend,
             no_verify,
+            when_green,
+            fixup,
+            yes,
+            no_lint,
+            edit,
+            sign,
+            no_edit,
         } => {
-            run_commit(message, all, staged, unstaged, push, amend, no_verify)?;
+            run_commit(CommitOptions {
+                message,
+                all,
+                staged,
+                unstaged,
+                push,
+                amend,
+                no_verify,
+                when_green,
+                fixup,
+                yes,
+                no_lint,
+                edit,
+                sign,
+                no_edit,
+            })?;
         }
+        SgitCommand::Fixup { action } => run_fixup(action)?,
+        SgitCommand::Wip { action, message } => run_wip(action, message)?,
+        SgitCommand::Unwip => run_unwip()?,
+        SgitCommand::Maintenance { action } => run_maintenance(action)?,
+        SgitCommand::Size { top } => run_size(top)?,
+        SgitCommand::Sparse { action } => run_sparse(action)?,
+        SgitCommand::Submodule { action } => run_submodule(action)?,
+        SgitCommand::Subtree { action } => run_subtree(action)?,
+        SgitCommand::Worktree { action } => run_worktree(action)?,
+        SgitCommand::Watch { interval } => run_watch(interval)?,
+        SgitCommand::Conflicts { action, tool } => run_conflicts(action, tool)?,
+        SgitCommand::VerifyTree { target, json } => run_verify_tree(target, json)?,
+        SgitCommand::Attic { action } => run_attic(action)?,
+        SgitCommand::Signers { action } => run_signers(action)?,
+        SgitCommand::Signing { action } => run_signing(action)?,
+        SgitCommand::Setup => run_setup()?,
+        SgitCommand::Profile { action } => run_profile(action)?,
+        SgitCommand::Config { action } => run_config(action)?,
+        SgitCommand::Hooks { action } => run_hooks(action)?,
+        SgitCommand::Alias { action } => run_alias(action)?,
+        SgitCommand::Doctor => run_doctor()?,
     }
 
     Ok(())
@@ -114,20 +458,103 @@ fn run() -> Result<()> {
 fn print_explanations() {
     println!("SGIT simplifies Git for beginners by wrapping each major workflow:");
     println!();
-    println!("  init    – initialize a Git repository (runs `git init`).");
-    println!("  stage   – add files to the staging area (interactive, or use --all/--tracked).");
-    println!("  unstage – remove staged files safely (interactive, or use --all).");
-    println!("  status  – show what is staged vs unstaged (`--short` uses `git status -sb`).");
-    println!("  log     – view history (`--short` shows compact entries).");
-    println!("  diff    – compare working changes (`--staged` shows what will be committed).");
-    println!("  branch  – list and checkout branches (interactive); use -c <name> to create a new branch.");
-    println!("  reset   – discard changes (interactive, or use --all/--staged/--unstaged/--tracked/--untracked).");
+    println!("  init    – start a new project: runs `git init`, then offers a .gitignore template (Rust, Node, Python…), the first branch's name, an initial commit, and a remote (or pass --ignore/--branch/--commit/--remote); `--bare` makes a repository with no working files for others to push to.");
+    println!(
+        "  import  – turn an existing folder into a repo: writes a .gitignore, handles large files, makes the first commit, and can push to a new remote."
+    );
+    println!("  bundle  – move branches between machines that cannot reach each other: `create` writes them (or only the commits `--since` a tag) to one file, `verify` checks a file you received, `fetch` brings its branches in (or clones it outside a repository).");
+    println!("  export  – save the files of a branch, tag, or commit as a zip or tar.gz (`--path <dir>` for one directory), to hand someone the code without its history.");
+    println!("  stage   – add files to the staging area (interactive, or use --all/--tracked; `-` reads paths from stdin).");
+    println!("  unstage – remove staged files safely (interactive, or use --all; `-` reads paths from stdin).");
+    println!("  ignore  – add patterns to .gitignore without duplicates (`sgit ignore \"*.log\" build/`), pick untracked files to ignore, or add a bundled template with `--template rust`; `--why <path>` explains which rule hides a file from status.");
+    println!("  status  – show branch, ahead/behind, and staged/unstaged/untracked files (`--short` uses `git status -sb`, `--raw` plain `git status`, `--watch` keeps it live in a side terminal); warns about paused merges/rebases, bisects, and detached HEAD.");
+    println!("  log     – view history (`--short` shows compact entries); filter with `--author`, `--since`/`--until`, `--path`, `--grep`, or pick filters interactively; `--verify` shows whether each commit is signed and the signature checks out.");
+    println!("  learn   – hands-on lessons (branches, merging vs rebasing, undoing things) in a throwaway practice repository, with quizzes; remembers which you finished.");
+    println!("  find    – search commits by message, author, or code, pick one from a searchable list, then show, check out, cherry-pick, or revert it.");
+    println!("  grep    – search tracked files for text (`-i` ignores case, `-E` for a regex); `--history` lists the commits that added or removed it.");
+    println!("  blame   – see who last changed each line of a file and when (`-L 10-20` for a range), then open the commit behind any line.");
+    println!("  show    – display one commit's author, date, message, and changes; pick from recent commits when no ref is given.");
+    println!(
+        "  prompt  – print a short `branch +*? ↑ahead ↓behind STATE` segment for your shell prompt; prints nothing outside a repo."
+    );
+    println!("  explain-last – replay the previous sgit command: the git commands it ran and their output, what failed, and what to do next.");
+    println!("  timeline – replay how a branch moved today (or `--since <time>`) from the reflog: commits, amends, resets, rebases.");
+    println!("  history – `history edit` lets you keep, squash, reword, drop, or reorder recent commits without learning `git rebase -i`.");
+    println!("  reword  – fix the message of an unpushed commit, even one further back than the last commit; pick several to edit each in your editor.");
+    println!("  squash  – combine the last N commits into one (refuses pushed commits unless `--force`).");
+    println!("  split   – undo the last commit and walk through re-committing its changes as several smaller commits (files or hunks).");
+    println!("  compare – which commits are only in one branch or the other, plus the files changed (`--diff` for the full diff); no `a...b` syntax needed.");
+    println!("  diff    – compare working changes (`--staged` shows what will be committed, `--highlight-moved` dims code that only moved, `--words` highlights changed words, `--stat` summarizes per file, `--tool` opens your diff tool, `--plain` skips the syntax-highlighted view, `--against [ref]` compares with any branch, tag, or commit; `sgit diff <ref1> <ref2> [path]` or `--from/--to` compares two refs).");
+    println!(
+        "  branch  – pick a local or remote branch to checkout (type to search); use -c <name> to create a new branch, `--contains [commit]` to see which branches and tags include a commit, `--untagged` to list commits not released in any tag."
+    );
+    println!(
+        "  reset   – discard changes (interactive, or use --all/--staged/--unstaged/--tracked/--untracked)."
+    );
+    println!(
+        "  revert  – undo a commit by creating a new commit (safe for pushed history); `--no-commit` only stages the undo."
+    );
+    println!("  merge   – merge a branch into this one, with conflicts handed to `sgit conflicts`; `--squash` stages its changes as one and opens the commit flow with a message listing the squashed commits; `--abort` goes back to before a conflicted merge, listing what it throws away.");
+    println!(
+        "  continue/abort – finish or cancel whichever merge, rebase, cherry-pick, or revert stopped on conflicts."
+    );
+    println!("  connect – link this repository to a newly created, empty remote: asks for its URL, checks it answers, adds it as origin, and pushes with upstream tracking.");
+    println!(
+        "  push    – send commits to your remote (uses Git's defaults unless you pass `--remote`/`--branch`); `--all-remotes` pushes the current branch to every remote."
+    );
+    println!("  repos   – register repositories (or scan a directory tree for them) and list each one's branch, changes, and ahead/behind state; sgit workspace then works across them.");
+    println!("  workspace – status, pull, push, or sync every repository next to this one (or listed under [workspace] repos in your user config), with one line per repository.");
+    println!("  backup  – mirror every branch and tag to a backup remote, chosen the first time; run it from cron to back up on a schedule.");
+    println!(
+        "  undo-push – pushed something bad? pick the last good commit, then revert (safe) or roll the remote back with --force-with-lease; recorded in the sgit journal."
+    );
+    println!("  fetch   – download new commits, branches, and tags from a remote (or all with --all) without touching your branches, and summarize what changed; --prune drops branches deleted on the remote.");
+    println!("  pull    – fetch + merge from your remote repository; `--rebase` replays your commits on top instead (also the default with [sync] mode = \"rebase\" or git's pull.rebase), setting uncommitted changes aside meanwhile.");
     println!(
-        "  push    – send commits to your remote (uses Git's defaults unless you pass `--remote`/`--branch`)."
+        "  commit  – make commits; `--all` stages everything, `--unstaged` stages only modified tracked files, `--push` runs `git push`, `--amend` rewrites the last commit (`--amend --no-edit` keeps its message), `-S/--sign` signs it, `--no-verify` skips hooks, `--when-green` only commits once the checks in .sgit.toml pass, `--yes` skips the summary confirmation, `--no-lint` skips the message checks, and `--edit` writes the message in your editor (starting from the commit template, if there is one)."
     );
-    println!("  pull    – fetch + merge from your remote repository.");
     println!(
-        "  commit  – make commits; `--all` stages everything, `--unstaged` stages only modified tracked files, `--push` runs `git push`, `--amend` rewrites the last commit, and `--no-verify` skips hooks."
+        "  fixup   – `commit --fixup` records changes meant for an earlier commit; `fixup apply` folds them in before you push."
     );
+    println!("  autosync – `start [--interval 10m]` fetches, fast-forwards, and pushes in the background; problems go to .git/sgit/autosync.log and a desktop notification; `stop` ends it.");
     println!("  sync    – fetch, pull, and push in one command with graceful error handling.");
+    println!(
+        "  merge-queue – land your commits on the default branch safely: rebase onto the latest remote tip, run the .sgit.toml checks, and push only if both succeed (retrying once if someone pushed meanwhile)."
+    );
+    println!(
+        "  auto-rebase – `auto-rebase on` makes `sync` rebase the current branch onto the default branch before pushing."
+    );
+    println!(
+        "  ci      – `ci status` shows the GitHub/GitLab checks of your branch's open pull request (or your last pushed commit); `--watch` waits until they finish and fails if any check failed."
+    );
+    println!(
+        "  --progress-json – print one JSON progress event per line on stderr (for editors and GUIs embedding sgit)."
+    );
+    println!("  watch   – keep committing your work in progress to sgit/watch/<branch> as you save (or every --interval seconds) until Ctrl-C, without touching your branch.");
+    println!(
+        "  wip     – `wip` parks everything in a WIP commit and `unwip` takes it apart again; `wip push` shares a snapshot of your work on wip/<you>/<branch> without touching your branch; `wip clean` removes it."
+    );
+    println!("  maintenance – tidy the repository up so it stays fast: packs loose objects, drops ones unused for two weeks, and indexes history; `maintenance schedule` has git do it in the background.");
+    println!("  size    – how much space the repository takes and the largest files in its history, with the commit that added each and whether it is still in use.");
+    println!("  sparse  – check out only some top-level directories of a big repository (`sparse pick` ticks them from a list), `sparse add`/`remove` change the set, `sparse disable` brings everything back.");
+    println!("  submodule – add, update (clone and check out, recursively), and remove submodules, explaining each step; `submodule status` and `sgit status` flag ones that are dirty or not at the recorded commit.");
+    println!("  subtree – copy another repository into a directory here (`subtree add`), remembering in .sgit.toml where it came from, so `subtree pull` and `subtree push` need no arguments.");
+    println!("  worktree – check out another branch in a directory of its own (<repo>-<branch> next to this one), so you can work on both at once; `worktree remove` cleans it up.");
+    println!("  verify-tree – check that the working tree is exactly a ref (HEAD by default): lists modified, missing, and extra files; exits 1 on any difference, `--json` for scripts.");
+    println!("  conflicts – go through each conflicted file: keep our side or theirs, edit it, or view both sides (`conflicts show <file>` prints the conflicted regions in two labelled columns); resolved files are staged and the merge can be finished right away. `--tool` resolves them in your merge tool (picked from installed ones if none is configured).");
+    println!("  attic   – `attic put <name>` parks staged, unstaged, and untracked work under a name; `list`, `diff`, `restore`, and `drop` manage the entries.");
+    println!(
+        "  signers – manage trusted SSH signing keys (list/add/remove); `add --user <name>` fetches a teammate's keys from GitHub/GitLab."
+    );
+    println!(
+        "  signing – `signing setup` finds your SSH or GPG keys, configures git to sign with one (optionally by default), and checks a test signature; plain `signing` shows the current setup."
+    );
+    println!("  setup   – set the name and email git records on your commits, globally or for one repository; `sgit commit` runs it for you when they are missing.");
+    println!("  profile – `profile use <name>` applies a work/personal identity from ~/.config/sgit/config.toml (name, email, signing key, remote host) to this repository; `sgit status` shows the active one.");
+    println!("  doctor  – check that git is new enough, your name and email are set, the remote answers, the branch has an upstream, hooks are executable, and line endings are sane; offers to apply the fixes it suggests.");
+    println!("  config  – list, get, set, or unset sgit's settings in .sgit.toml (or your user config with `--user`; the repository's file wins) and common git ones (default-branch, pull.rebase, editor), checking values first; plain `config` opens a menu.");
+    println!("  hooks   – list the git hooks and whether each runs, enable or disable one, or `hooks install` a template (e.g. cargo-fmt before each commit) into .git/hooks or, with --shared, a committed .githooks directory.");
+    println!("  alias   – `alias list` shows the shortcuts defined under [aliases] (e.g. `s = \"status --short\"`, `ship = \"commit --all --push\"`); run them as `sgit s` or `sgit ship`, with any extra arguments appended.");
+    println!("  sgit-<name> – any other subcommand runs the `sgit-<name>` executable from your PATH as a plugin, with SGIT_REPO_ROOT, SGIT_BRANCH, SGIT_CONFIG, and friends in its environment.");
+    println!("  -C <dir> – run any command as if sgit was started in <dir>, e.g. `sgit -C ~/projects/app sync`, without cd'ing there first.");
 }
@@ -0,0 +1,96 @@
+//! Commit messages written outside a one-line prompt: the repository's commit template
+//! and git's configured editor.
+
+use std::fs;
+use std::io::{stdin, IsTerminal};
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::git_cmd;
+use crate::status::{get_git_dir, get_repo_root};
+
+/// Looked for at the repository root when `commit.template` is not set.
+const TEMPLATE_FILE: &str = ".gitmessage";
+
+/// The commit template: the file `commit.template` names (relative paths are taken from
+/// the repository root), or a `.gitmessage` file at the root.
+pub fn commit_template() -> Result<Option<String>> {
+    let repo_root = get_repo_root()?;
+    let configured = git_cmd()
+        .args(["config", "--path", "--get", "commit.template"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    let configured = String::from_utf8_lossy(&configured.stdout)
+        .trim()
+        .to_string();
+
+    let path = if configured.is_empty() {
+        let path = Path::new(&repo_root).join(TEMPLATE_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        path
+    } else {
+        Path::new(&repo_root).join(&configured)
+    };
+    let contents = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "failed to read the commit template {} (set by commit.template)",
+            path.display()
+        )
+    })?;
+    Ok(Some(contents))
+}
+
+/// `text` without `#` comment lines and surrounding blank lines.
+pub fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Opens `contents` in git's configured editor as `.git/sgit/<file_name>` and returns
+/// what was saved, without comment lines; `None` if that is empty.
+pub fn edit_in_editor(file_name: &str, contents: &str) -> Result<Option<String>> {
+    if !stdin().is_terminal() {
+        bail!("editing messages needs a terminal - pass --message instead");
+    }
+    let path = Path::new(&get_git_dir()?).join("sgit").join(file_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    if !open_in_editor(&path)? {
+        bail!("the editor exited with an error; the message was not changed");
+    }
+
+    let edited =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let _ = fs::remove_file(&path);
+    let message = strip_comments(&edited);
+    Ok((!message.trim().is_empty()).then_some(message))
+}
+
+/// Opens `path` in git's configured editor and waits for it; `false` when the editor
+/// exits with an error.
+pub fn open_in_editor(path: &Path) -> Result<bool> {
+    let editor = git_cmd()
+        .args(["var", "GIT_EDITOR"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    let editor = String::from_utf8_lossy(&editor.stdout).trim().to_string();
+    if editor.is_empty() {
+        bail!("no editor configured - set one with 'git config --global core.editor <editor>'");
+    }
+
+    // Run it the way git does, so editors configured with arguments keep working.
+    let status = StdCommand::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to start the editor '{}'", editor))?;
+    Ok(status.success())
+}
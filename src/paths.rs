@@ -0,0 +1,27 @@
+//! Small filesystem helpers shared across sgit: expanding `~/` in configured paths and
+//! telling whether a file can be run.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// `~/projects/app` becomes `$HOME/projects/app`; other paths are left as they are.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows has no executable bit; git runs hooks through its own shell, and plugins are
+/// started by their extension, so any file will do.
+#[cfg(not(unix))]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,91 @@
+//! External subcommands: `sgit foo` runs an executable named `sgit-foo` from PATH when
+//! sgit has no `foo` of its own, the way git and cargo find their plugins.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use anyhow::{Context, Result};
+
+use crate::alias::{is_builtin, subcommand_position};
+use crate::config::Config;
+use crate::git::{git_program, GIT_PROGRAM_ENV};
+use crate::paths::is_executable;
+use crate::status::{get_current_branch, get_git_dir, get_repo_root};
+
+/// Looked up as `sgit-<name>` on PATH.
+const PLUGIN_PREFIX: &str = "sgit-";
+
+/// Runs the plugin `args` (with the program name first) asks for, when its subcommand is
+/// not one of sgit's own, and returns the plugin's exit code; `None` if there is none.
+pub fn run_external(args: &[String]) -> Result<Option<i32>> {
+    let Some(position) = subcommand_position(args) else {
+        return Ok(None);
+    };
+    let name = &args[position];
+    if is_builtin(name)
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Ok(None);
+    }
+    let Some(executable) = search_path(&format!("{}{}", PLUGIN_PREFIX, name)) else {
+        return Ok(None);
+    };
+    let progress_json = args[1..position].iter().any(|arg| arg == "--progress-json");
+    run_plugin(&executable, &args[position + 1..], progress_json).map(Some)
+}
+
+fn search_path(file_name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Runs the plugin with what it needs to know about sgit and the repository in
+/// `SGIT_*` variables, and returns its exit code.
+///
+/// - `SGIT_VERSION`, `SGIT_EXE` to call back into sgit, and `SGIT_GIT`, the git it runs
+/// - `SGIT_REPO_ROOT`, `SGIT_GIT_DIR`, `SGIT_BRANCH` (empty when detached), inside a
+///   repository
+/// - `SGIT_CONFIG`: the merged sgit config as JSON
+/// - `SGIT_PROGRESS_JSON=1` when `--progress-json` was given
+fn run_plugin(executable: &Path, args: &[String], progress_json: bool) -> Result<i32> {
+    let mut command = StdCommand::new(executable);
+    command
+        .args(args)
+        .env("SGIT_VERSION", env!("CARGO_PKG_VERSION"))
+        .env(GIT_PROGRAM_ENV, git_program());
+    // A plugin started from another plugin must not see its parent's repository.
+    for name in [
+        "SGIT_REPO_ROOT",
+        "SGIT_GIT_DIR",
+        "SGIT_BRANCH",
+        "SGIT_PROGRESS_JSON",
+    ] {
+        command.env_remove(name);
+    }
+    if let Ok(exe) = env::current_exe() {
+        command.env("SGIT_EXE", exe);
+    }
+    if let Ok(root) = get_repo_root() {
+        command.env("SGIT_REPO_ROOT", root);
+        if let Ok(git_dir) = get_git_dir() {
+            command.env("SGIT_GIT_DIR", git_dir);
+        }
+        command.env("SGIT_BRANCH", get_current_branch().unwrap_or_default());
+    }
+    if let Ok(config) = Config::load()
+        && let Ok(json) = serde_json::to_string(&config)
+    {
+        command.env("SGIT_CONFIG", json);
+    }
+    if progress_json {
+        command.env("SGIT_PROGRESS_JSON", "1");
+    }
+    let status = command
+        .status()
+        .with_context(|| format!("failed to run the plugin {}", executable.display()))?;
+    Ok(status.code().unwrap_or(1))
+}
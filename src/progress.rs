@@ -0,0 +1,80 @@
+//! Machine-readable progress events for tools embedding sgit.
+//!
+//! With `--progress-json`, each phase of a long operation writes one JSON
+//! object per line to stderr, e.g.
+//! `{"event":"phase","operation":"sync","phase":"fetch","status":"started"}`.
+//! Human-readable output on stdout is unchanged.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+static JSON_EVENTS: AtomicBool = AtomicBool::new(false);
+
+pub fn enable_json_events() {
+    JSON_EVENTS.store(true, Ordering::Relaxed);
+}
+
+fn emit(event: Value) {
+    if JSON_EVENTS.load(Ordering::Relaxed) {
+        eprintln!("{}", event);
+    }
+}
+
+/// One phase of an operation. Emits `started` on creation and exactly one
+/// terminal status when finished.
+pub struct Phase {
+    operation: &'static str,
+    phase: &'static str,
+    started: Instant,
+}
+
+impl Phase {
+    pub fn start(operation: &'static str, phase: &'static str) -> Self {
+        emit(json!({
+            "event": "phase",
+            "operation": operation,
+            "phase": phase,
+            "status": "started",
+        }));
+        Self {
+            operation,
+            phase,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn done(self) {
+        self.finish("succeeded", None);
+    }
+
+    pub fn failed(self, message: &str) {
+        self.finish("failed", Some(message));
+    }
+
+    fn finish(self, status: &str, message: Option<&str>) {
+        let mut event = json!({
+            "event": "phase",
+            "operation": self.operation,
+            "phase": self.phase,
+            "status": status,
+            "elapsed_ms": self.started.elapsed().as_millis() as u64,
+        });
+        if let Some(message) = message {
+            event["message"] = Value::from(message);
+        }
+        emit(event);
+    }
+}
+
+/// A named sub-step inside a phase, such as an individual check command.
+pub fn step(operation: &'static str, phase: &'static str, name: &str, status: &str) {
+    emit(json!({
+        "event": "step",
+        "operation": operation,
+        "phase": phase,
+        "name": name,
+        "status": status,
+    }));
+}
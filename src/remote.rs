@@ -0,0 +1,99 @@
+//! Works out which remote a command should talk to when the user did not name one,
+//! following the same configuration git reads, and asks when git would have to guess.
+
+use std::io::{stdin, IsTerminal};
+
+use anyhow::{bail, Result};
+use dialoguer::Select;
+
+use crate::status::{get_config_value, get_current_branch, get_remotes};
+
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Fetch,
+    Push,
+}
+
+#[derive(Clone)]
+pub struct ResolvedRemote {
+    pub name: String,
+    /// The config key it came from. `None` when sgit chose it, which means plain
+    /// `git push`/`git pull` would not know about it and it must be passed explicitly.
+    pub configured_by: Option<String>,
+}
+
+impl ResolvedRemote {
+    /// e.g. "origin (from branch.main.remote)".
+    pub fn describe(&self) -> String {
+        match self.configured_by {
+            Some(ref key) => format!("{} (from {})", self.name, key),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The remote git itself would use for the current branch, if configuration says so:
+/// `branch.<name>.pushRemote` and `remote.pushDefault` for pushes, then
+/// `branch.<name>.remote`.
+pub fn configured_remote(direction: Direction) -> Option<ResolvedRemote> {
+    let branch = get_current_branch().unwrap_or_default();
+    let mut keys = Vec::new();
+    if !branch.is_empty() && matches!(direction, Direction::Push) {
+        keys.push(format!("branch.{}.pushRemote", branch));
+    }
+    if matches!(direction, Direction::Push) {
+        keys.push("remote.pushDefault".to_string());
+    }
+    if !branch.is_empty() {
+        keys.push(format!("branch.{}.remote", branch));
+    }
+
+    keys.into_iter().find_map(|key| {
+        // "." means the branch tracks another local branch, not a remote.
+        let name = get_config_value(&key).filter(|name| name != ".")?;
+        Some(ResolvedRemote {
+            name,
+            configured_by: Some(key),
+        })
+    })
+}
+
+/// Like [`configured_remote`], falling back to the only remote, or a picker when there
+/// are several (and `origin` when there is no terminal to ask on).
+pub fn resolve_remote(direction: Direction) -> Result<ResolvedRemote> {
+    if let Some(remote) = configured_remote(direction) {
+        return Ok(remote);
+    }
+
+    let remotes = get_remotes()?;
+    let name = match remotes.as_slice() {
+        [] => bail!("no remotes configured - add one with 'sgit connect <url>'"),
+        [only] => only.clone(),
+        _ if stdin().is_terminal() => pick_remote(&remotes)?,
+        _ if remotes.iter().any(|r| r == "origin") => "origin".to_string(),
+        _ => bail!("several remotes are configured and none is set for this branch - pass --remote to choose one"),
+    };
+    Ok(ResolvedRemote {
+        name,
+        configured_by: None,
+    })
+}
+
+fn pick_remote(remotes: &[String]) -> Result<String> {
+    let labels: Vec<String> = remotes
+        .iter()
+        .map(
+            |remote| match get_config_value(&format!("remote.{}.url", remote)) {
+                Some(url) => format!("{}  ({})", remote, url),
+                None => remote.clone(),
+            },
+        )
+        .collect();
+    let default = remotes.iter().position(|r| r == "origin").unwrap_or(0);
+    let selection = Select::new()
+        .with_prompt("Several remotes are configured and none is set for this branch - which one?")
+        .items(&labels)
+        .default(default)
+        .interact()?;
+    Ok(remotes[selection].clone())
+}
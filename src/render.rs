@@ -0,0 +1,262 @@
+//! Renders unified diffs for the terminal: a header per file, old and new line numbers
+//! side by side, and syntax-highlighted code on green/red backgrounds.
+
+use std::io::{stdout, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+const THEME: &str = "base16-ocean.dark";
+const ADDED_BG: &str = "\x1b[48;2;18;58;28m";
+const REMOVED_BG: &str = "\x1b[48;2;72;22;28m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+/// Clears to the end of the line in the current background, so changed lines are
+/// highlighted across the whole terminal width.
+const FILL: &str = "\x1b[K";
+
+struct FileDiff {
+    /// From the `diff --git a/... b/...` line, for changes with no `---`/`+++` lines.
+    fallback_path: String,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    /// `new file mode`, `rename from`, `Binary files ... differ`, and the like.
+    notes: Vec<String>,
+    header_printed: bool,
+}
+
+impl FileDiff {
+    fn note(&self, prefix: &str) -> Option<String> {
+        self.notes
+            .iter()
+            .find_map(|note| note.strip_prefix(prefix).map(str::to_string))
+    }
+}
+
+pub struct DiffRenderer {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+}
+
+impl DiffRenderer {
+    pub fn new() -> Self {
+        let mut themes = ThemeSet::load_defaults();
+        let theme = themes
+            .themes
+            .remove(THEME)
+            .unwrap_or_else(|| themes.themes.into_values().next().unwrap_or_default());
+        Self {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme,
+        }
+    }
+
+    /// Prints the output of `git diff --no-color` (or `git show`) with highlighting.
+    pub fn print(&self, diff: &str) -> Result<()> {
+        let mut out = BufWriter::new(stdout().lock());
+        let mut file: Option<FileDiff> = None;
+        let mut highlighter: Option<HighlightLines> = None;
+        let (mut old_line, mut new_line) = (0usize, 0usize);
+
+        for line in diff.lines() {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                if let Some(previous) = file.as_mut() {
+                    self.print_header(&mut out, previous)?;
+                }
+                let fallback_path = paths
+                    .rsplit_once(" b/")
+                    .map(|(_, new)| new.to_string())
+                    .unwrap_or_else(|| paths.to_string());
+                file = Some(FileDiff {
+                    fallback_path,
+                    old_path: None,
+                    new_path: None,
+                    notes: Vec::new(),
+                    header_printed: false,
+                });
+                highlighter = None;
+                continue;
+            }
+            let Some(current) = file.as_mut() else {
+                // Anything before the first file, e.g. a `git show` commit header.
+                writeln!(out, "{}", line)?;
+                continue;
+            };
+
+            if highlighter.is_none() {
+                if let Some(path) = line.strip_prefix("--- ") {
+                    current.old_path = strip_side(path);
+                    continue;
+                }
+                if let Some(path) = line.strip_prefix("+++ ") {
+                    current.new_path = strip_side(path);
+                    continue;
+                }
+                if !line.starts_with("@@") {
+                    current.notes.push(line.to_string());
+                    continue;
+                }
+            }
+
+            if let Some(hunk) = line.strip_prefix("@@ ") {
+                self.print_header(&mut out, current)?;
+                let (ranges, context) = hunk.split_once(" @@").unwrap_or((hunk, ""));
+                let mut sides = ranges.split(' ');
+                old_line = parse_start(sides.next().unwrap_or(""));
+                new_line = parse_start(sides.next().unwrap_or(""));
+                writeln!(out, "{}{}  ⋯{}{}", DIM, CYAN, context, RESET)?;
+                let path = current
+                    .new_path
+                    .as_deref()
+                    .or(current.old_path.as_deref())
+                    .unwrap_or("");
+                highlighter = Some(HighlightLines::new(self.syntax_for(path), &self.theme));
+                continue;
+            }
+
+            let Some(hl) = highlighter.as_mut() else {
+                continue;
+            };
+            let (marker, text) = line.split_at(line.len().min(1));
+            let code = self.highlight(hl, text);
+            match marker {
+                "+" => {
+                    writeln!(
+                        out,
+                        "{}{:>5} {:>5} │+{}{}{}",
+                        ADDED_BG, "", new_line, code, FILL, RESET
+                    )?;
+                    new_line += 1;
+                }
+                "-" => {
+                    writeln!(
+                        out,
+                        "{}{:>5} {:>5} │-{}{}{}",
+                        REMOVED_BG, old_line, "", code, FILL, RESET
+                    )?;
+                    old_line += 1;
+                }
+                "\\" => writeln!(out, "{}            │ {}{}", DIM, line, RESET)?,
+                _ => {
+                    writeln!(
+                        out,
+                        "{}{:>5} {:>5} │{} {}{}",
+                        DIM, old_line, new_line, RESET, code, RESET
+                    )?;
+                    old_line += 1;
+                    new_line += 1;
+                }
+            }
+        }
+        if let Some(last) = file.as_mut() {
+            self.print_header(&mut out, last)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Prints the file's header once, before its first hunk (or on its own for
+    /// renames, mode changes, and binary files).
+    fn print_header(&self, out: &mut impl Write, file: &mut FileDiff) -> Result<()> {
+        if file.header_printed {
+            return Ok(());
+        }
+        file.header_printed = true;
+
+        let from = file.note("rename from ").or_else(|| file.old_path.clone());
+        let to = file.note("rename to ").or_else(|| file.new_path.clone());
+        let (title, what) = if file.note("new file mode").is_some() {
+            (to.unwrap_or_else(|| file.fallback_path.clone()), "added")
+        } else if file.note("deleted file mode").is_some() {
+            (
+                from.unwrap_or_else(|| file.fallback_path.clone()),
+                "deleted",
+            )
+        } else {
+            match (from, to) {
+                (Some(from), Some(to)) if from != to => (format!("{} → {}", from, to), "renamed"),
+                (from, to) => (
+                    to.or(from).unwrap_or_else(|| file.fallback_path.clone()),
+                    "modified",
+                ),
+            }
+        };
+        writeln!(out)?;
+        writeln!(out, "{}── {} {}({}){}", BOLD, title, DIM, what, RESET)?;
+        if let (Some(old), Some(new)) = (file.note("old mode "), file.note("new mode ")) {
+            writeln!(out, "   {}file mode {} → {}{}", DIM, old, new, RESET)?;
+        }
+        if file
+            .notes
+            .iter()
+            .any(|note| note.starts_with("Binary files"))
+        {
+            writeln!(out, "   {}binary file, contents not shown{}", DIM, RESET)?;
+        }
+        Ok(())
+    }
+
+    fn syntax_for(&self, path: &str) -> &SyntaxReference {
+        let path = Path::new(path);
+        let by_extension = path.extension().and_then(|ext| {
+            self.syntaxes
+                .find_syntax_by_extension(&ext.to_string_lossy())
+        });
+        // Files like `Makefile` are listed by their whole name.
+        let by_name = || {
+            path.file_name().and_then(|name| {
+                self.syntaxes
+                    .find_syntax_by_extension(&name.to_string_lossy())
+            })
+        };
+        by_extension
+            .or_else(by_name)
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text())
+    }
+
+    /// Colors `text` with 24-bit foreground escapes only, so the line's background
+    /// stays visible underneath.
+    fn highlight(&self, hl: &mut HighlightLines, text: &str) -> String {
+        let with_newline = format!("{}\n", text);
+        let Ok(ranges) = hl.highlight_line(&with_newline, &self.syntaxes) else {
+            return text.to_string();
+        };
+        let mut colored = String::new();
+        for (style, piece) in ranges {
+            let piece = piece.trim_end_matches('\n');
+            if piece.is_empty() {
+                continue;
+            }
+            let Style { foreground: c, .. } = style;
+            colored.push_str(&format!("\x1b[38;2;{};{};{}m{}", c.r, c.g, c.b, piece));
+        }
+        colored
+    }
+}
+
+/// `a/src/main.rs` → `src/main.rs`; `/dev/null` → `None`.
+fn strip_side(path: &str) -> Option<String> {
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// The start line of a hunk side, e.g. `-12,7` → 12.
+fn parse_start(range: &str) -> usize {
+    range[1.min(range.len())..]
+        .split(',')
+        .next()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
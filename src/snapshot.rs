@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::{git_cmd, git_command, run_git_silent};
+use crate::status::{get_git_dir, get_repo_root, resolve_commit};
+
+/// Records staged, unstaged, and untracked changes as a commit on top of HEAD
+/// without touching the current branch, index, or working tree.
+pub fn create_worktree_commit(message: &str) -> Result<String> {
+    let git_dir = get_git_dir()?;
+    let repo_root = get_repo_root()?;
+    let index_path = Path::new(&git_dir).join("sgit-snapshot-index");
+    let index = index_path.to_string_lossy().to_string();
+    let head = resolve_commit("HEAD").ok();
+
+    let result = (|| {
+        if let Some(ref head) = head {
+            git_with_index(&["read-tree", head], &index, &repo_root)?;
+        }
+        git_with_index(&["add", "-A"], &index, &repo_root)?;
+        let tree = git_with_index(&["write-tree"], &index, &repo_root)?;
+
+        let mut args = vec!["commit-tree", tree.as_str(), "-m", message];
+        if let Some(ref head) = head {
+            args.push("-p");
+            args.push(head);
+        }
+        git_with_index(&args, &index, &repo_root)
+    })();
+
+    let _ = fs::remove_file(&index_path);
+    result
+}
+
+/// The tree of the whole working tree, tracked changes and untracked files alike (ignored
+/// files and `.git` stay out), built in the scratch index at `index`. Keeping that index
+/// between calls lets git skip the files that did not change.
+pub fn working_tree_tree(index_path: &Path) -> Result<String> {
+    let repo_root = get_repo_root()?;
+    let index = index_path.to_string_lossy().to_string();
+    if !index_path.exists()
+        && let Ok(head) = resolve_commit("HEAD")
+    {
+        git_with_index(&["read-tree", &head], &index, &repo_root)?;
+    }
+    git_with_index(&["add", "-A"], &index, &repo_root)?;
+    git_with_index(&["write-tree"], &index, &repo_root)
+}
+
+/// Runs git against the scratch `index`, through [`git_command`] so the arguments suit
+/// the installed git.
+fn git_with_index(args: &[&str], index: &str, dir: &str) -> Result<String> {
+    let (mut command, _) = git_command(args, None);
+    let output = command
+        .current_dir(dir)
+        .env("GIT_INDEX_FILE", index)
+        .output()
+        .with_context(|| {
+            format!(
+                "failed to execute git {} - is git installed?",
+                args.join(" ")
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed:\n  {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/sgit/snapshots/";
+
+/// Saves the full current state under `refs/sgit/snapshots/` and returns the ref name.
+pub fn save_snapshot(reason: &str) -> Result<String> {
+    let commit = create_worktree_commit(&format!("sgit snapshot: {}", reason))?;
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let ref_name = format!("{}{}", SNAPSHOT_REF_PREFIX, stamp);
+    run_git_silent(&["update-ref", &ref_name, &commit])?;
+    Ok(ref_name)
+}
+
+/// Records staged, unstaged, and untracked changes as a commit shaped like the ones
+/// `git stash` makes (worktree on top of HEAD, with the index and untracked files as
+/// extra parents), so `git stash apply --index` can bring all three back. Returns `None`
+/// when there is nothing to record.
+pub fn create_stash_commit(message: &str) -> Result<Option<String>> {
+    let git_dir = get_git_dir()?;
+    let repo_root = get_repo_root()?;
+    let head = resolve_commit("HEAD")?;
+    let real_index = Path::new(&git_dir).join("index");
+    let index_path = Path::new(&git_dir).join("sgit-snapshot-index");
+    let pathspec_path = Path::new(&git_dir).join("sgit-snapshot-untracked");
+    let index = index_path.to_string_lossy().to_string();
+
+    let result = (|| {
+        let head_tree = resolve_tree(&head)?;
+        // Work on a copy of the index so the real one is never touched.
+        if real_index.exists() {
+            fs::copy(&real_index, &index_path).context("failed to copy the index")?;
+        } else {
+            git_with_index(&["read-tree", &head], &index, &repo_root)?;
+        }
+        let index_tree = git_with_index(&["write-tree"], &index, &repo_root)?;
+        git_with_index(&["add", "-u"], &index, &repo_root)?;
+        let worktree_tree = git_with_index(&["write-tree"], &index, &repo_root)?;
+
+        let untracked = git_with_index(
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+            &index,
+            &repo_root,
+        )?;
+        let untracked_tree = if untracked.is_empty() {
+            None
+        } else {
+            fs::remove_file(&index_path).context("failed to reset the scratch index")?;
+            fs::write(&pathspec_path, &untracked).context("failed to list untracked files")?;
+            let pathspec_arg = format!("--pathspec-from-file={}", pathspec_path.display());
+            git_with_index(
+                &["add", "--force", &pathspec_arg, "--pathspec-file-nul"],
+                &index,
+                &repo_root,
+            )?;
+            Some(git_with_index(&["write-tree"], &index, &repo_root)?)
+        };
+
+        if index_tree == head_tree && worktree_tree == head_tree && untracked_tree.is_none() {
+            return Ok(None);
+        }
+
+        let index_commit = git_with_index(
+            &["commit-tree", &index_tree, "-p", &head, "-m", "index"],
+            &index,
+            &repo_root,
+        )?;
+        let mut args = vec![
+            "commit-tree",
+            worktree_tree.as_str(),
+            "-p",
+            head.as_str(),
+            "-p",
+            index_commit.as_str(),
+            "-m",
+            message,
+        ];
+        let untracked_commit = match untracked_tree {
+            Some(ref tree) => Some(git_with_index(
+                &["commit-tree", tree, "-m", "untracked files"],
+                &index,
+                &repo_root,
+            )?),
+            None => None,
+        };
+        if let Some(ref commit) = untracked_commit {
+            args.push("-p");
+            args.push(commit);
+        }
+        git_with_index(&args, &index, &repo_root).map(Some)
+    })();
+
+    let _ = fs::remove_file(&index_path);
+    let _ = fs::remove_file(&pathspec_path);
+    result
+}
+
+fn resolve_tree(rev: &str) -> Result<String> {
+    let output = git_cmd()
+        .args(["rev-parse", &format!("{}^{{tree}}", rev)])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+    if !output.status.success() {
+        bail!("could not read the tree of {}", rev);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -1,11 +1,17 @@
-use std::process::Command as StdCommand;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
 use anyhow::{bail, Context, Result};
 
-use crate::git::NOT_IN_REPO_HINT;
+use crate::git::{git_cmd, git_command, NOT_IN_REPO_HINT};
+
+/// git's well-known hash of the empty tree, the base for a repository's first commit.
+pub const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
 
 pub fn get_repo_root() -> Result<String> {
-    let output = StdCommand::new("git")
+    let output = git_cmd()
         .args(["rev-parse", "--show-toplevel"])
         .output()
         .context("failed to execute git - is git installed?")?;
@@ -32,7 +38,7 @@ pub struct PorcelainStatus {
 
 impl PorcelainStatus {
     pub fn parse() -> Result<Self> {
-        let output = StdCommand::new("git")
+        let output = git_cmd()
             .args(["status", "--porcelain"])
             .output()
             .context("running git status --porcelain")?;
@@ -72,7 +78,7 @@ impl PorcelainStatus {
 }
 
 pub fn get_porcelain_lines() -> Result<Vec<(String, String)>> {
-    let output = StdCommand::new("git")
+    let output = git_cmd()
         .args(["status", "--porcelain"])
         .output()
         .context("running git status --porcelain")?;
@@ -123,6 +129,42 @@ pub fn get_staged_files() -> Result<Vec<String>> {
     Ok(files)
 }
 
+/// The path (from the repository root) and size of every added or modified file in the
+/// index, as git will store it.
+pub fn get_staged_blobs() -> Result<Vec<(String, u64)>> {
+    let output = git_cmd()
+        .args(["diff", "--cached", "--name-only", "--diff-filter=AM", "-z"])
+        .output()
+        .context("running git diff --cached")?;
+    let paths: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect();
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // `:<path>` names the file's blob in the index.
+    let mut child = git_cmd()
+        .args(["cat-file", "--batch-check=%(objectsize)"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("running git cat-file")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let input: String = paths.iter().map(|path| format!(":{}\n", path)).collect();
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output().context("running git cat-file")?;
+    let sizes = String::from_utf8_lossy(&output.stdout);
+    Ok(paths
+        .into_iter()
+        .zip(sizes.lines())
+        .filter_map(|(path, size)| Some((path, size.trim().parse().ok()?)))
+        .collect())
+}
+
 pub fn get_all_uncommitted_files() -> Result<Vec<String>> {
     let entries = get_porcelain_lines()?;
     let files: Vec<String> = entries.into_iter().map(|(_, path)| path).collect();
@@ -145,7 +187,7 @@ pub fn get_untracked_files() -> Result<Vec<String>> {
 }
 
 pub fn get_branches() -> Result<Vec<String>> {
-    let output = StdCommand::new("git")
+    let output = git_cmd()
         .args(["branch", "--format=%(refname:short)"])
         .output()
         .context("running git branch")?;
@@ -161,11 +203,375 @@ pub fn get_branches() -> Result<Vec<String>> {
 }
 
 pub fn get_current_branch() -> Result<String> {
-    let output = StdCommand::new("git")
-        .args(["branch", "--show-current"])
+    let output = git_command(&["branch", "--show-current"], None)
+        .0
         .output()
         .context("getting current branch")?;
 
     let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
     Ok(branch)
 }
+
+pub fn get_git_dir() -> Result<String> {
+    let output = git_cmd()
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+
+    if !output.status.success() {
+        bail!("{}", NOT_IN_REPO_HINT);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn get_config_value(key: &str) -> Option<String> {
+    let output = git_cmd().args(["config", "--get", key]).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+pub fn get_remotes() -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["remote"])
+        .output()
+        .context("running git remote")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let remotes: Vec<String> = stdout
+        .lines()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    Ok(remotes)
+}
+
+pub struct CommitSummary {
+    pub hash: String,
+    pub short_hash: String,
+    pub subject: String,
+    pub author: String,
+    pub relative_date: String,
+}
+
+impl CommitSummary {
+    pub fn label(&self) -> String {
+        format!(
+            "{} {} ({}, {})",
+            self.short_hash, self.subject, self.relative_date, self.author
+        )
+    }
+}
+
+pub fn get_recent_commits(limit: usize) -> Result<Vec<CommitSummary>> {
+    get_commits(&["-n", &limit.to_string()])
+}
+
+/// Runs `git log` with the given extra arguments and parses one summary per commit.
+pub fn get_commits(log_args: &[&str]) -> Result<Vec<CommitSummary>> {
+    let mut args = vec!["log", "--format=%H%x1f%h%x1f%s%x1f%an%x1f%ar"];
+    args.extend_from_slice(log_args);
+    let output = git_cmd().args(&args).output().context("running git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("does not have any commits") {
+            return Ok(Vec::new());
+        }
+        bail!("git log failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let commits = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(CommitSummary {
+                hash: fields.next()?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                relative_date: fields.next()?.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(commits)
+}
+
+pub fn resolve_commit(rev: &str) -> Result<String> {
+    let output = git_cmd()
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", rev),
+        ])
+        .output()
+        .context("failed to execute git - is git installed?")?;
+
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || hash.is_empty() {
+        bail!(
+            "'{}' is not a valid commit, branch, or tag - check 'sgit log' for valid refs",
+            rev
+        );
+    }
+    Ok(hash)
+}
+
+pub fn get_commit_parents(rev: &str) -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["rev-list", "--parents", "-n", "1", rev])
+        .output()
+        .context("running git rev-list")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .split_whitespace()
+        .skip(1)
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the commits reachable from HEAD that no remote-tracking branch contains.
+pub fn get_unpushed_commits() -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["rev-list", "HEAD", "--not", "--remotes"])
+        .output()
+        .context("running git rev-list")?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().map(str::to_string).collect())
+}
+
+pub fn get_commit_message(rev: &str) -> Result<String> {
+    let output = git_cmd()
+        .args(["log", "-n", "1", "--format=%B", rev])
+        .output()
+        .context("running git log")?;
+
+    if !output.status.success() {
+        bail!("could not read the message of {}", rev);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Returns the upstream of the current branch as `remote/branch`, if one is configured.
+pub fn get_upstream() -> Option<String> {
+    let output = git_cmd()
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let upstream = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!upstream.is_empty()).then_some(upstream)
+}
+
+pub fn is_ancestor(ancestor: &str, descendant: &str) -> bool {
+    is_ancestor_in_dir(Path::new("."), ancestor, descendant)
+}
+
+/// Like [`is_ancestor`], for the repository in `dir`.
+pub fn is_ancestor_in_dir(dir: &Path, ancestor: &str, descendant: &str) -> bool {
+    git_cmd()
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .current_dir(dir)
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+pub fn count_commits(range: &str) -> Result<usize> {
+    let output = git_cmd()
+        .args(["rev-list", "--count", range])
+        .output()
+        .context("running git rev-list --count")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git rev-list --count {} failed: {}", range, stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap_or(0))
+}
+
+/// A multi-step git operation that is paused, usually on conflicts.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RepoOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Am,
+    Bisect,
+}
+
+impl RepoOperation {
+    pub fn name(self) -> &'static str {
+        match self {
+            RepoOperation::Merge => "merge",
+            RepoOperation::Rebase => "rebase",
+            RepoOperation::CherryPick => "cherry-pick",
+            RepoOperation::Revert => "revert",
+            RepoOperation::Am => "patch application (git am)",
+            RepoOperation::Bisect => "bisect",
+        }
+    }
+
+    /// The git subcommand that owns `--continue` / `--abort` for this operation.
+    pub fn git_command(self) -> &'static str {
+        match self {
+            RepoOperation::Merge => "merge",
+            RepoOperation::Rebase => "rebase",
+            RepoOperation::CherryPick => "cherry-pick",
+            RepoOperation::Revert => "revert",
+            RepoOperation::Am => "am",
+            RepoOperation::Bisect => "bisect",
+        }
+    }
+
+    /// One line telling the user how to get out of this state.
+    pub fn next_steps(self) -> &'static str {
+        match self {
+            RepoOperation::Bisect => {
+                "test this commit, then run 'git bisect good' or 'git bisect bad' - or 'sgit abort' to stop bisecting"
+            }
+            RepoOperation::Merge => {
+                "resolve the conflicts with 'sgit conflicts', then 'sgit continue' - or 'sgit merge --abort' to go back to before the merge"
+            }
+            _ => "resolve any conflicts, run 'sgit stage .', then 'sgit continue' - or 'sgit abort' to go back",
+        }
+    }
+}
+
+/// Detects a paused operation from the state files git leaves in `.git`.
+pub fn get_operation_in_progress() -> Result<Option<RepoOperation>> {
+    let git_dir = PathBuf::from(get_git_dir()?);
+    Ok(get_operation_in(&git_dir))
+}
+
+/// Like [`get_operation_in_progress`], for callers that already know the git directory.
+pub fn get_operation_in(git_dir: &Path) -> Option<RepoOperation> {
+    if git_dir.join("rebase-merge").is_dir() {
+        Some(RepoOperation::Rebase)
+    } else if git_dir.join("rebase-apply").is_dir() {
+        if git_dir.join("rebase-apply").join("applying").exists() {
+            Some(RepoOperation::Am)
+        } else {
+            Some(RepoOperation::Rebase)
+        }
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Some(RepoOperation::Merge)
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        Some(RepoOperation::CherryPick)
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        Some(RepoOperation::Revert)
+    } else if git_dir.join("BISECT_LOG").exists() {
+        Some(RepoOperation::Bisect)
+    } else {
+        None
+    }
+}
+
+/// The branch (or commit) a paused merge is bringing in, as git names it in the message
+/// it prepared: `Merge branch 'feature'`, `Merge remote-tracking branch 'origin/x'`, ...
+pub fn get_merge_source() -> Option<String> {
+    let git_dir = get_git_dir().ok()?;
+    let message = fs::read_to_string(Path::new(&git_dir).join("MERGE_MSG")).ok()?;
+    let first = message.lines().next()?;
+    let start = first.find('\'')? + 1;
+    let end = start + first[start..].find('\'')?;
+    Some(first[start..end].to_string())
+}
+
+pub fn get_conflicted_files() -> Result<Vec<String>> {
+    let output = git_cmd()
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .output()
+        .context("running git diff --diff-filter=U")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the short hash HEAD points at when no branch is checked out.
+pub fn get_detached_head() -> Option<String> {
+    let on_branch = git_cmd()
+        .args(["symbolic-ref", "-q", "HEAD"])
+        .output()
+        .ok()?
+        .status
+        .success();
+    if on_branch {
+        return None;
+    }
+
+    let output = git_cmd()
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !hash.is_empty()).then_some(hash)
+}
+
+/// The remote's default branch name (e.g. `main`), from `<remote>/HEAD` or common names.
+pub fn get_default_branch(remote: &str) -> Option<String> {
+    let head_ref = format!("refs/remotes/{}/HEAD", remote);
+    let output = git_cmd()
+        .args(["symbolic-ref", "--short", head_ref.as_str()])
+        .output()
+        .ok()?;
+    if output.status.success() {
+        let full = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if let Some(name) = full.strip_prefix(&format!("{}/", remote)) {
+            return Some(name.to_string());
+        }
+    }
+
+    ["main", "master"]
+        .iter()
+        .find(|name| resolve_commit(&format!("{}/{}", remote, name)).is_ok())
+        .map(|name| name.to_string())
+}
+
+/// Commits on HEAD but not `upstream`, and on `upstream` but not HEAD.
+pub fn get_ahead_behind(upstream: &str) -> Option<(usize, usize)> {
+    let range = format!("HEAD...{}", upstream);
+    let output = git_cmd()
+        .args(["rev-list", "--left-right", "--count", range.as_str()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut counts = stdout.split_whitespace().map(|n| n.parse().unwrap_or(0));
+    Some((counts.next()?, counts.next()?))
+}
+
+pub fn get_stash_count() -> usize {
+    git_cmd()
+        .args(["stash", "list"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().count())
+        .unwrap_or(0)
+}
@@ -0,0 +1,169 @@
+//! Git submodule helpers: which submodules a repository has, and how each one stands
+//! against the commit the repository records for it, for `sgit status` and
+//! `sgit submodule`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::git::{git_cmd, git_output_in_dir};
+use crate::status::is_ancestor_in_dir;
+
+pub struct Submodule {
+    /// The name in `.gitmodules`, usually the same as the path.
+    pub name: String,
+    /// From the top of the repository it is in.
+    pub path: String,
+    /// The commit the repository records for it (in the index).
+    pub recorded: Option<String>,
+    /// `None` until it is initialized and checked out.
+    pub checked_out: Option<String>,
+    /// Changed or staged files inside it.
+    pub changes: usize,
+    pub untracked: usize,
+    pub sync: SubmoduleSync,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SubmoduleSync {
+    /// At the recorded commit.
+    InSync,
+    NotCheckedOut,
+    /// Has commits on top of the recorded one, not recorded yet.
+    Ahead,
+    /// At an older commit, typically after a pull brought a newer one.
+    Behind,
+    /// At a commit unrelated to the recorded one, or one it has not fetched.
+    Elsewhere,
+}
+
+impl Submodule {
+    /// What is wrong with it, in plain words; empty when it is in sync and clean.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match self.sync {
+            SubmoduleSync::InSync => {}
+            SubmoduleSync::NotCheckedOut => problems
+                .push("not checked out - 'sgit submodule update' clones it".to_string()),
+            SubmoduleSync::Ahead => problems.push(format!(
+                "has new commits - 'sgit stage {}' records them here",
+                self.path
+            )),
+            SubmoduleSync::Behind => problems.push(
+                "at an older commit than recorded - 'sgit submodule update' brings it up to date"
+                    .to_string(),
+            ),
+            SubmoduleSync::Elsewhere => problems.push(format!(
+                "at a different commit than recorded - 'sgit submodule update' checks out the recorded one, 'sgit stage {}' records this one",
+                self.path
+            )),
+        }
+        if self.changes > 0 {
+            problems.push(format!(
+                "{} uncommitted {} inside - commit {} in the submodule",
+                self.changes,
+                if self.changes == 1 {
+                    "change"
+                } else {
+                    "changes"
+                },
+                if self.changes == 1 { "it" } else { "them" }
+            ));
+        }
+        if self.untracked > 0 {
+            problems.push(format!(
+                "{} untracked {} inside",
+                self.untracked,
+                if self.untracked == 1 { "file" } else { "files" }
+            ));
+        }
+        problems
+    }
+}
+
+/// The submodules of the repository whose top is `dir`, in `.gitmodules` order. Nested
+/// submodules are not included; look inside each checked-out one for those.
+pub fn submodules_in(dir: &Path) -> Result<Vec<Submodule>> {
+    if !dir.join(".gitmodules").is_file() {
+        return Ok(Vec::new());
+    }
+    let output = git_cmd()
+        .args([
+            "config",
+            "--file",
+            ".gitmodules",
+            "--get-regexp",
+            r"^submodule\..*\.path$",
+        ])
+        .current_dir(dir)
+        .output()
+        .context("running git config")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut submodules = Vec::new();
+    for line in stdout.lines() {
+        let Some((key, path)) = line.split_once(' ') else {
+            continue;
+        };
+        let name = key
+            .trim_start_matches("submodule.")
+            .trim_end_matches(".path")
+            .to_string();
+        submodules.push(inspect(dir, name, path.to_string()));
+    }
+    Ok(submodules)
+}
+
+fn inspect(dir: &Path, name: String, path: String) -> Submodule {
+    let recorded = git_output_in_dir(&["ls-files", "--stage", "--", &path], dir)
+        .ok()
+        .and_then(|line| {
+            // `160000 <hash> <stage>\t<path>` for a submodule.
+            let mut fields = line.split_whitespace();
+            (fields.next() == Some("160000"))
+                .then(|| fields.next().map(str::to_string))
+                .flatten()
+        });
+    let inner = dir.join(&path);
+    // Without its own `.git`, git would answer for the outer repository instead.
+    let checked_out = inner
+        .join(".git")
+        .exists()
+        .then(|| git_output_in_dir(&["rev-parse", "--verify", "--quiet", "HEAD"], &inner).ok())
+        .flatten()
+        .map(|hash| hash.trim().to_string());
+
+    let (mut changes, mut untracked) = (0, 0);
+    if checked_out.is_some() {
+        for line in git_output_in_dir(&["status", "--porcelain"], &inner)
+            .unwrap_or_default()
+            .lines()
+        {
+            if line.starts_with("??") {
+                untracked += 1;
+            } else {
+                changes += 1;
+            }
+        }
+    }
+
+    let sync = match (&recorded, &checked_out) {
+        (_, None) => SubmoduleSync::NotCheckedOut,
+        (Some(recorded), Some(current)) if recorded == current => SubmoduleSync::InSync,
+        (Some(recorded), Some(current)) if is_ancestor_in_dir(&inner, recorded, current) => {
+            SubmoduleSync::Ahead
+        }
+        (Some(recorded), Some(current)) if is_ancestor_in_dir(&inner, current, recorded) => {
+            SubmoduleSync::Behind
+        }
+        (_, Some(_)) => SubmoduleSync::Elsewhere,
+    };
+    Submodule {
+        name,
+        path,
+        recorded,
+        checked_out,
+        changes,
+        untracked,
+        sync,
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+pub struct IgnoreTemplate {
+    pub name: &'static str,
+    /// Files whose presence at the project root suggests this template.
+    pub markers: &'static [&'static str],
+    pub patterns: &'static str,
+}
+
+pub const IGNORE_TEMPLATES: &[IgnoreTemplate] = &[
+    IgnoreTemplate {
+        name: "Rust",
+        markers: &["Cargo.toml"],
+        patterns: "/target/\n**/*.rs.bk\n",
+    },
+    IgnoreTemplate {
+        name: "Node",
+        markers: &["package.json"],
+        patterns: "node_modules/\nnpm-debug.log*\nyarn-error.log*\n.pnpm-debug.log*\ndist/\n.env\n",
+    },
+    IgnoreTemplate {
+        name: "Python",
+        markers: &["pyproject.toml", "requirements.txt", "setup.py"],
+        patterns:
+            "__pycache__/\n*.py[cod]\n.venv/\nvenv/\n.pytest_cache/\n*.egg-info/\nbuild/\ndist/\n",
+    },
+    IgnoreTemplate {
+        name: "Go",
+        markers: &["go.mod"],
+        patterns: "/bin/\n*.exe\n*.test\n*.out\nvendor/\n",
+    },
+    IgnoreTemplate {
+        name: "Java",
+        markers: &["pom.xml", "build.gradle", "build.gradle.kts"],
+        patterns: "target/\nbuild/\n.gradle/\n*.class\n*.jar\n",
+    },
+    IgnoreTemplate {
+        name: "C/C++",
+        markers: &["CMakeLists.txt", "Makefile"],
+        patterns: "build/\n*.o\n*.obj\n*.a\n*.so\n*.dylib\n*.dll\n*.exe\n",
+    },
+    IgnoreTemplate {
+        name: "OS and editors",
+        markers: &[],
+        patterns: ".DS_Store\nThumbs.db\n.idea/\n.vscode/\n*.swp\n*~\n",
+    },
+];
+
+/// Returns the templates whose marker files exist in `dir`, plus the OS/editor template.
+pub fn detect_ignore_templates(dir: &Path) -> Vec<&'static IgnoreTemplate> {
+    IGNORE_TEMPLATES
+        .iter()
+        .filter(|t| t.markers.is_empty() || t.markers.iter().any(|m| dir.join(m).exists()))
+        .collect()
+}
+
+/// The template called `name`, ignoring case; the parts of a name like "C/C++" and its
+/// first word ("os") work too.
+pub fn find_ignore_template(name: &str) -> Option<&'static IgnoreTemplate> {
+    let name = name.to_lowercase();
+    IGNORE_TEMPLATES.iter().find(|t| {
+        let full = t.name.to_lowercase();
+        full == name
+            || full.split('/').any(|part| part == name)
+            || full.split_whitespace().next() == Some(name.as_str())
+    })
+}
+
+/// Renders templates as one `.gitignore` body with a header per section.
+pub fn render_ignore_templates(templates: &[&IgnoreTemplate]) -> String {
+    let mut contents = String::new();
+    for template in templates {
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&format!("# {}\n", template.name));
+        contents.push_str(template.patterns);
+    }
+    contents
+}
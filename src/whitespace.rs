@@ -0,0 +1,355 @@
+//! Whitespace and line-ending problems in staged text files, found with
+//! `git diff --cached --check` plus checks git does not make (mixed CRLF/LF line endings,
+//! a missing final newline), and fixing them in the staged copies.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::git_cmd;
+use crate::status::{
+    get_config_value, get_repo_root, get_staged_blobs, resolve_commit, EMPTY_TREE,
+};
+
+/// How much of a blob is read to tell text from binary, as git does.
+/// How much of a file to look at when telling text from binary.
+pub(crate) const BINARY_SNIFF_BYTES: usize = 8000;
+
+pub enum Issue {
+    /// On these lines (1-based) of the staged file.
+    TrailingWhitespace(Vec<usize>),
+    SpaceBeforeTab(Vec<usize>),
+    BlankLinesAtEnd,
+    MixedLineEndings {
+        crlf: usize,
+        lf: usize,
+    },
+    NoFinalNewline,
+}
+
+impl Issue {
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::TrailingWhitespace(lines) => {
+                format!("trailing whitespace on {}", describe_lines(lines))
+            }
+            Issue::SpaceBeforeTab(lines) => format!(
+                "a space before a tab in the indentation on {}",
+                describe_lines(lines)
+            ),
+            Issue::BlankLinesAtEnd => "blank lines at the end of the file".to_string(),
+            Issue::MixedLineEndings { crlf, lf } => {
+                format!("mixed line endings ({} CRLF, {} LF)", crlf, lf)
+            }
+            Issue::NoFinalNewline => "no newline at the end of the file".to_string(),
+        }
+    }
+
+    /// Mixed indentation needs a human to decide which one is right.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self, Issue::SpaceBeforeTab(_))
+    }
+}
+
+fn describe_lines(lines: &[usize]) -> String {
+    let shown: Vec<String> = lines.iter().take(5).map(usize::to_string).collect();
+    let more = if lines.len() > 5 {
+        format!(" and {} more", lines.len() - 5)
+    } else {
+        String::new()
+    };
+    match lines.len() {
+        1 => format!("line {}", shown[0]),
+        _ => format!("lines {}{}", shown.join(", "), more),
+    }
+}
+
+pub struct FileIssues {
+    /// From the repository root.
+    pub path: String,
+    pub issues: Vec<Issue>,
+}
+
+/// The problems in the staged text files. Trailing whitespace and the like only count on
+/// the lines being committed, so old code does not get in the way; `.gitattributes`
+/// `whitespace` settings apply as they do for git.
+pub fn check_staged() -> Result<Vec<FileIssues>> {
+    let root = get_repo_root()?;
+    let mut found: BTreeMap<String, Vec<Issue>> = BTreeMap::new();
+    for (path, kind, lines) in diff_check(&root)? {
+        let issues = found.entry(path).or_default();
+        match kind.as_str() {
+            "trailing whitespace" => issues.push(Issue::TrailingWhitespace(lines)),
+            "space before tab in indent" => issues.push(Issue::SpaceBeforeTab(lines)),
+            "new blank line at EOF" => issues.push(Issue::BlankLinesAtEnd),
+            _ => {}
+        }
+    }
+
+    for (path, _) in get_staged_blobs()? {
+        let contents = staged_contents(&root, &path)?;
+        if is_binary(&contents) {
+            continue;
+        }
+        let (crlf, lf) = count_line_endings(&contents);
+        let mut issues = Vec::new();
+        if crlf > 0 && lf > 0 {
+            issues.push(Issue::MixedLineEndings { crlf, lf });
+        }
+        if !contents.is_empty() && !contents.ends_with(b"\n") {
+            issues.push(Issue::NoFinalNewline);
+        }
+        if !issues.is_empty() {
+            found.entry(path).or_default().extend(issues);
+        }
+    }
+
+    Ok(found
+        .into_iter()
+        .filter(|(_, issues)| !issues.is_empty())
+        .map(|(path, issues)| FileIssues { path, issues })
+        .collect())
+}
+
+/// `(path, problem, lines)` for each kind of problem `git diff --cached --check` reports
+/// in a file.
+fn diff_check(root: &str) -> Result<Vec<(String, String, Vec<usize>)>> {
+    let base = resolve_commit("HEAD").unwrap_or_else(|_| EMPTY_TREE.to_string());
+    // A CR before the newline is a line ending, not trailing whitespace; mixed endings are
+    // checked separately.
+    let whitespace = match get_config_value("core.whitespace") {
+        Some(value) => format!("core.whitespace={},cr-at-eol", value),
+        None => "core.whitespace=cr-at-eol".to_string(),
+    };
+    let output = git_cmd()
+        .args([
+            "-c",
+            &whitespace,
+            "diff",
+            "--cached",
+            "--check",
+            "--no-color",
+            &base,
+        ])
+        .current_dir(root)
+        .output()
+        .context("running git diff --check")?;
+    // Exit code 2 means problems were found.
+    if !output.status.success() && output.status.code() != Some(2) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git diff --check failed: {}", stderr.trim());
+    }
+
+    let mut problems: Vec<(String, String, Vec<usize>)> = Vec::new();
+    // Reports look like `path:12: trailing whitespace.`, each followed by the offending line.
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if line.starts_with('+') {
+            continue;
+        }
+        let Some((path, number, kind)) = parse_check_line(line) else {
+            continue;
+        };
+        match problems
+            .iter_mut()
+            .find(|(p, k, _)| *p == path && *k == kind)
+        {
+            Some((_, _, lines)) => lines.push(number),
+            None => problems.push((path, kind, vec![number])),
+        }
+    }
+    Ok(problems)
+}
+
+fn parse_check_line(line: &str) -> Option<(String, usize, String)> {
+    let line = line.strip_suffix('.')?;
+    // The path may itself contain `:`, so look for the first `:<number>: ` instead.
+    for (idx, _) in line.match_indices(':') {
+        let rest = &line[idx + 1..];
+        let Some((number, kind)) = rest.split_once(": ") else {
+            continue;
+        };
+        if let Ok(number) = number.parse() {
+            return Some((line[..idx].to_string(), number, kind.to_string()));
+        }
+    }
+    None
+}
+
+fn staged_contents(root: &str, path: &str) -> Result<Vec<u8>> {
+    let output = git_cmd()
+        .args(["cat-file", "blob", &format!(":{}", path)])
+        .current_dir(root)
+        .output()
+        .context("running git cat-file")?;
+    if !output.status.success() {
+        bail!("cannot read the staged copy of {}", path);
+    }
+    Ok(output.stdout)
+}
+
+/// Whether `contents` looks binary: a NUL byte near the start, as git decides.
+pub(crate) fn is_binary(contents: &[u8]) -> bool {
+    contents[..contents.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+fn count_line_endings(contents: &[u8]) -> (usize, usize) {
+    let mut crlf = 0;
+    let mut lf = 0;
+    for (idx, byte) in contents.iter().enumerate() {
+        if *byte == b'\n' {
+            if idx > 0 && contents[idx - 1] == b'\r' {
+                crlf += 1;
+            } else {
+                lf += 1;
+            }
+        }
+    }
+    (crlf, lf)
+}
+
+/// Fixes what can be fixed in the staged copy of each file, and in the working copy too
+/// when it matches the staged one. Returns the files whose working copy has other changes
+/// and was left alone.
+pub fn fix_staged(files: &[FileIssues]) -> Result<Vec<String>> {
+    let root = get_repo_root()?;
+    let mut untouched = Vec::new();
+    for file in files {
+        if !file.issues.iter().any(Issue::is_fixable) {
+            continue;
+        }
+        let original = staged_contents(&root, &file.path)?;
+        let fixed = fix_contents(&original, &file.issues);
+        if fixed == original {
+            continue;
+        }
+        stage_contents(&root, &file.path, &fixed)?;
+
+        let working = Path::new(&root).join(&file.path);
+        if fs::read(&working).is_ok_and(|current| current == original) {
+            fs::write(&working, &fixed)
+                .with_context(|| format!("failed to write {}", working.display()))?;
+        } else {
+            untouched.push(file.path.clone());
+        }
+    }
+    Ok(untouched)
+}
+
+fn fix_contents(contents: &[u8], issues: &[Issue]) -> Vec<u8> {
+    let mut trailing: &[usize] = &[];
+    let mut endings = false;
+    let mut blank_at_end = false;
+    let mut final_newline = false;
+    for issue in issues {
+        match issue {
+            Issue::TrailingWhitespace(lines) => trailing = lines,
+            Issue::MixedLineEndings { .. } => endings = true,
+            Issue::BlankLinesAtEnd => blank_at_end = true,
+            Issue::NoFinalNewline => final_newline = true,
+            Issue::SpaceBeforeTab(_) => {}
+        }
+    }
+    // Mixed endings become whichever the file uses most.
+    let (crlf, lf) = count_line_endings(contents);
+    let usual: &[u8] = if crlf > lf { b"\r\n" } else { b"\n" };
+
+    let mut lines: Vec<(Vec<u8>, &[u8])> = Vec::new();
+    let mut rest = contents;
+    while !rest.is_empty() {
+        let (line, ending, next) = match rest.iter().position(|byte| *byte == b'\n') {
+            Some(idx) if idx > 0 && rest[idx - 1] == b'\r' => {
+                (&rest[..idx - 1], &b"\r\n"[..], &rest[idx + 1..])
+            }
+            Some(idx) => (&rest[..idx], &b"\n"[..], &rest[idx + 1..]),
+            None => (rest, &b""[..], &rest[rest.len()..]),
+        };
+        let mut line = line.to_vec();
+        if trailing.contains(&(lines.len() + 1)) {
+            while line
+                .last()
+                .is_some_and(|byte| *byte == b' ' || *byte == b'\t')
+            {
+                line.pop();
+            }
+        }
+        let ending = if endings && !ending.is_empty() {
+            usual
+        } else {
+            ending
+        };
+        lines.push((line, ending));
+        rest = next;
+    }
+    if blank_at_end {
+        while lines.len() > 1
+            && lines
+                .last()
+                .is_some_and(|(line, _)| line.iter().all(u8::is_ascii_whitespace))
+        {
+            lines.pop();
+        }
+    }
+    if (final_newline || blank_at_end)
+        && let Some((_, ending)) = lines.last_mut()
+        && ending.is_empty()
+    {
+        *ending = usual;
+    }
+
+    let mut fixed = Vec::with_capacity(contents.len());
+    for (line, ending) in lines {
+        fixed.extend_from_slice(&line);
+        fixed.extend_from_slice(ending);
+    }
+    fixed
+}
+
+/// Replaces the staged copy of `path` with `contents`, keeping its file mode.
+fn stage_contents(root: &str, path: &str, contents: &[u8]) -> Result<()> {
+    let entry = git_cmd()
+        .args(["ls-files", "--stage", "--", path])
+        .current_dir(root)
+        .output()
+        .context("running git ls-files")?;
+    let entry = String::from_utf8_lossy(&entry.stdout);
+    let Some(mode) = entry.split_whitespace().next() else {
+        bail!("{} is not staged", path);
+    };
+
+    // The staged copy is already in the form git stores, so no filters run on it.
+    let mut child = git_cmd()
+        .args(["hash-object", "-w", "--no-filters", "--stdin"])
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("running git hash-object")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(contents)?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("running git hash-object")?;
+    if !output.status.success() {
+        bail!("failed to store the fixed copy of {}", path);
+    }
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let status = git_cmd()
+        .args([
+            "update-index",
+            "--cacheinfo",
+            &format!("{},{},{}", mode, hash, path),
+        ])
+        .current_dir(root)
+        .status()
+        .context("running git update-index")?;
+    if !status.success() {
+        bail!("failed to stage the fixed copy of {}", path);
+    }
+    Ok(())
+}
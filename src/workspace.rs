@@ -0,0 +1,176 @@
+//! The repositories `sgit workspace` works across, including the registry `sgit repos`
+//! keeps next to the user config, and the state of each one: its branch, how far it is
+//! from its upstream, and whether it has uncommitted changes.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::{user_config_path, UserConfig};
+use crate::git::git_cmd;
+use crate::paths::expand_home;
+use crate::status::get_repo_root;
+
+/// Directories a scan does not look inside: dependencies and build output, which can be
+/// huge and hold nobody's own repositories.
+const SCAN_SKIPPED: &[&str] = &["node_modules", "target", "vendor", "build", "dist"];
+
+pub struct RepoState {
+    /// `None` on a detached HEAD.
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    /// Changed, staged, and untracked files.
+    pub changes: usize,
+}
+
+/// The state of the repository in `dir`, as of its last fetch.
+pub fn repo_state(dir: &Path) -> Result<RepoState> {
+    let output = git_cmd()
+        .args(["status", "--porcelain=v2", "--branch"])
+        .current_dir(dir)
+        .output()
+        .context("running git status")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git status failed: {}", stderr.trim());
+    }
+    let mut state = RepoState {
+        branch: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        changes: 0,
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(name) = line.strip_prefix("# branch.head ") {
+            state.branch = (name != "(detached)").then(|| name.to_string());
+        } else if let Some(name) = line.strip_prefix("# branch.upstream ") {
+            state.upstream = Some(name.to_string());
+        } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            let mut parts = counts.split_whitespace();
+            state.ahead = parts
+                .next()
+                .and_then(|a| a.trim_start_matches('+').parse().ok())
+                .unwrap_or(0);
+            state.behind = parts
+                .next()
+                .and_then(|b| b.trim_start_matches('-').parse().ok())
+                .unwrap_or(0);
+        } else if !line.starts_with('#') {
+            state.changes += 1;
+        }
+    }
+    Ok(state)
+}
+
+/// The repositories in the workspace and where the list came from: `[workspace] repos`
+/// in the user config, then the `sgit repos` registry, or else the repositories next to
+/// this one (or inside the current directory, outside a repository).
+pub fn workspace_repos() -> Result<(Vec<PathBuf>, String)> {
+    let configured = UserConfig::load()?.workspace.repos;
+    if !configured.is_empty() {
+        let repos = configured.iter().map(|path| expand_home(path)).collect();
+        return Ok((repos, "[workspace] repos in your user config".to_string()));
+    }
+    let registered = load_registry()?;
+    if !registered.is_empty() {
+        return Ok((registered, "registered repositories".to_string()));
+    }
+    let (dir, source) = match get_repo_root() {
+        Ok(root) => {
+            let root = PathBuf::from(root);
+            let parent = root.parent().map(Path::to_path_buf).unwrap_or(root);
+            let source = format!("repositories next to this one, in {}", parent.display());
+            (parent, source)
+        }
+        Err(_) => {
+            let dir = env::current_dir().context("failed to read the current directory")?;
+            let source = format!("repositories in {}", dir.display());
+            (dir, source)
+        }
+    };
+    Ok((discover_repos(&dir), source))
+}
+
+/// The directories directly inside `dir` that are git repositories, sorted by name.
+pub fn discover_repos(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut repos: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        // `.git` is a directory in a clone and a file in a worktree or submodule.
+        .filter(|path| path.is_dir() && path.join(".git").exists())
+        .collect();
+    repos.sort();
+    repos
+}
+
+/// The repositories under `dir`, down to `depth` levels, without looking inside a
+/// repository once found or inside hidden and dependency directories.
+pub fn scan_repos(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if dir.join(".git").exists() {
+        found.push(dir.to_path_buf());
+        return found;
+    }
+    if depth == 0 {
+        return found;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return found;
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        // Symlinks are not followed, so a link back up the tree cannot loop.
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            !name.starts_with('.') && !SCAN_SKIPPED.contains(&name.as_ref())
+        })
+        .collect();
+    dirs.sort();
+    for dir in dirs {
+        found.extend(scan_repos(&dir, depth - 1));
+    }
+    found
+}
+
+/// `repos.json` in the sgit config directory, next to the user config.
+pub fn registry_path() -> Option<PathBuf> {
+    Some(user_config_path()?.parent()?.join("repos.json"))
+}
+
+/// The repositories registered with `sgit repos`, sorted.
+pub fn load_registry() -> Result<Vec<PathBuf>> {
+    let Some(path) = registry_path().filter(|path| path.exists()) else {
+        return Ok(Vec::new());
+    };
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn save_registry(repos: &[PathBuf]) -> Result<()> {
+    let path = registry_path()
+        .context("cannot locate the sgit config directory - set HOME or XDG_CONFIG_HOME")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let mut repos = repos.to_vec();
+    repos.sort();
+    repos.dedup();
+    let contents = serde_json::to_string_pretty(&repos)?;
+    fs::write(&path, contents + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn repo_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| dir.display().to_string())
+}